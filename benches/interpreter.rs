@@ -0,0 +1,64 @@
+//! Times whole scripts, each one leaning on a different kind of value.
+//!
+//! ```sh
+//! cargo bench --bench interpreter
+//! ```
+
+use std::io;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use typhoon::Lib;
+
+const SCRIPTS: &[(&str, &str)] = &[
+    (
+        "fib",
+        "fun fib(n) { if (n < 2) return n; return fib(n - 1) + fib(n - 2); }
+         fib(20);",
+    ),
+    (
+        "floats",
+        "var sum = 0.0;
+         for (var i = 0; i < 20000; i = i + 1) sum = sum + i * 0.5;",
+    ),
+    (
+        "strings",
+        r#"var words = "the quick brown fox jumps over the lazy dog".split(" ");
+           var joined = "";
+           for (var i = 0; i < 300; i = i + 1) {
+               for (var word in words) joined = "${word.toUpper()} " + word;
+           }"#,
+    ),
+    (
+        "arrays",
+        "var numbers = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9];
+         var total = 0;
+         for (var i = 0; i < 2000; i = i + 1) {
+             for (var n in numbers) total = total + numbers[n];
+         }",
+    ),
+    (
+        "instances",
+        "class Point {
+             init(x, y) { this.x = x; this.y = y; }
+             add(other) { return Point(this.x + other.x, this.y + other.y); }
+         }
+         var point = Point(0, 0);
+         for (var i = 0; i < 5000; i = i + 1) point = point.add(Point(1, 1));",
+    ),
+];
+
+fn scripts(c: &mut Criterion) {
+    for (name, source) in SCRIPTS {
+        c.bench_function(name, |b| {
+            b.iter(|| {
+                let mut lib = Lib::new();
+
+                lib.set_output(io::sink());
+                lib.run(String::from(*source)).unwrap();
+            })
+        });
+    }
+}
+
+criterion_group!(benches, scripts);
+criterion_main!(benches);
@@ -0,0 +1,410 @@
+use std::rc::Rc;
+
+use crate::{
+    expr::{self, Expr, ExprVisitor},
+    object::Object,
+    stmt::{self, Stmt, StmtVisitor},
+    token::{Span, Token},
+};
+
+/// Renders statements and expressions as indented s-expressions, nested
+/// statements go on their own lines one level deeper than their parent.
+#[derive(Default)]
+pub struct PrettyAstPrinter {
+    indent: usize,
+}
+
+impl PrettyAstPrinter {
+    pub fn new() -> Self {
+        Self { indent: 0 }
+    }
+
+    pub fn print(&mut self, stmts: &[Stmt]) -> String {
+        self.stmts(stmts).join("\n")
+    }
+
+    pub fn print_expression(&mut self, expr: &Expr) -> String {
+        expr.accept(self)
+    }
+
+    fn stmts(&mut self, stmts: &[Stmt]) -> Vec<String> {
+        stmts.iter().map(|stmt| stmt.accept(self)).collect()
+    }
+
+    fn parenthesize(&mut self, name: &str, exprs: &[&Expr]) -> String {
+        let mut string = format!("({name}");
+
+        for expr in exprs {
+            string.push(' ');
+            string.push_str(&expr.accept(self));
+        }
+
+        string.push(')');
+        string
+    }
+
+    /// Renders `(head` followed by the children built by `children`, each on
+    /// its own line one indentation level deeper.
+    fn nest(&mut self, head: &str, children: impl FnOnce(&mut Self) -> Vec<String>) -> String {
+        self.indent += 1;
+
+        let indent = "  ".repeat(self.indent);
+        let mut string = format!("({head}");
+
+        for child in children(self) {
+            string.push('\n');
+            string.push_str(&indent);
+            string.push_str(&child);
+        }
+
+        self.indent -= 1;
+        string.push(')');
+        string
+    }
+
+    fn params(params: &[Token], rest: &Option<Token>) -> String {
+        let mut params = params
+            .iter()
+            .map(|param| param.lexeme.to_string())
+            .collect::<Vec<_>>();
+
+        if let Some(rest) = rest {
+            params.push(format!("...{}", rest.lexeme));
+        }
+
+        params.join(" ")
+    }
+}
+
+impl ExprVisitor for PrettyAstPrinter {
+    type Item = String;
+
+    fn visit_comma(&mut self, expr: &expr::Comma) -> Self::Item {
+        self.parenthesize(",", &[&expr.left, &expr.right])
+    }
+
+    fn visit_lambda(&mut self, expr: &Rc<expr::Lambda>) -> Self::Item {
+        let head = format!(
+            "{}fun ({})",
+            if expr.is_async { "async " } else { "" },
+            PrettyAstPrinter::params(&expr.params, &expr.rest)
+        );
+
+        self.nest(&head, |printer| printer.stmts(&expr.body))
+    }
+
+    fn visit_assignment(&mut self, expr: &expr::Assignment) -> Self::Item {
+        format!("(= {} {})", expr.name.lexeme, expr.value.accept(self))
+    }
+
+    fn visit_set(&mut self, expr: &expr::Set) -> Self::Item {
+        format!(
+            "(= (. {} {}) {})",
+            expr.object.accept(self),
+            expr.name.lexeme,
+            expr.value.accept(self)
+        )
+    }
+
+    fn visit_index_set(&mut self, expr: &expr::IndexSet) -> Self::Item {
+        format!(
+            "(= ([] {} {}) {})",
+            expr.object.accept(self),
+            expr.index.accept(self),
+            expr.value.accept(self)
+        )
+    }
+
+    fn visit_ternary(&mut self, expr: &expr::Ternary) -> Self::Item {
+        self.parenthesize("?:", &[&expr.condition, &expr.truth, &expr.falsy])
+    }
+
+    fn visit_logical(&mut self, expr: &expr::Logical) -> Self::Item {
+        self.parenthesize(&expr.operator.lexeme, &[&expr.left, &expr.right])
+    }
+
+    fn visit_binary(&mut self, expr: &expr::Binary) -> Self::Item {
+        self.parenthesize(&expr.operator.lexeme, &[&expr.left, &expr.right])
+    }
+
+    fn visit_unary(&mut self, expr: &expr::Unary) -> Self::Item {
+        self.parenthesize(&expr.operator.lexeme, &[&expr.right])
+    }
+
+    fn visit_await(&mut self, expr: &expr::Await) -> Self::Item {
+        self.parenthesize("await", &[&expr.value])
+    }
+
+    fn visit_call(&mut self, expr: &expr::Call) -> Self::Item {
+        let mut exprs = vec![&expr.callee];
+
+        exprs.extend(&expr.arguments);
+        self.parenthesize("call", &exprs)
+    }
+
+    fn visit_get(&mut self, expr: &expr::Get) -> Self::Item {
+        let operator = if expr.optional { "?." } else { "." };
+
+        format!(
+            "({operator} {} {})",
+            expr.object.accept(self),
+            expr.name.lexeme
+        )
+    }
+
+    fn visit_index(&mut self, expr: &expr::Index) -> Self::Item {
+        self.parenthesize("[]", &[&expr.object, &expr.index])
+    }
+
+    fn visit_slice(&mut self, expr: &expr::Slice) -> Self::Item {
+        let bound = |bound: &Option<Expr>, printer: &mut Self| match bound {
+            Some(bound) => bound.accept(printer),
+            None => String::from("_"),
+        };
+
+        format!(
+            "([:] {} {} {})",
+            expr.object.accept(self),
+            bound(&expr.start, self),
+            bound(&expr.end, self)
+        )
+    }
+
+    fn visit_grouping(&mut self, expr: &Expr, _span: Span) -> Self::Item {
+        self.parenthesize("group", &[expr])
+    }
+
+    fn visit_variable(&mut self, expr: &Token) -> Self::Item {
+        expr.lexeme.to_string()
+    }
+
+    fn visit_this(&mut self, _: &Token) -> Self::Item {
+        String::from("this")
+    }
+
+    fn visit_super(&mut self, expr: &expr::Super) -> Self::Item {
+        format!("(super {})", expr.method.lexeme)
+    }
+
+    fn visit_array(&mut self, expr: &expr::Array) -> Self::Item {
+        self.parenthesize("array", &expr.elements.iter().collect::<Vec<_>>())
+    }
+
+    fn visit_map(&mut self, expr: &expr::Map) -> Self::Item {
+        let mut string = String::from("(map");
+
+        for (key, value) in &expr.entries {
+            string.push_str(&format!(" ({} {})", key.accept(self), value.accept(self)));
+        }
+
+        string.push(')');
+        string
+    }
+
+    fn visit_interpolation(&mut self, expr: &expr::Interpolation) -> Self::Item {
+        self.parenthesize("interpolate", &expr.parts.iter().collect::<Vec<_>>())
+    }
+
+    fn visit_spread(&mut self, expr: &expr::Spread) -> Self::Item {
+        self.parenthesize("...", &[&expr.value])
+    }
+
+    fn visit_literal(&mut self, expr: &Object, _span: Span) -> Self::Item {
+        match expr {
+            Object::String(string) => format!("\"{string}\""),
+            // Keeps integral floats apart from ints, `7.0` rather than `7`.
+            Object::Number(number) if number.fract() == 0.0 => format!("{number:.1}"),
+            _ => expr.to_string(),
+        }
+    }
+}
+
+impl StmtVisitor for PrettyAstPrinter {
+    type Item = String;
+
+    fn visit_empty_stmt(&mut self, _span: Span) -> Self::Item {
+        String::from("(empty)")
+    }
+
+    fn visit_expression_stmt(&mut self, stmt: &Expr, _span: Span) -> Self::Item {
+        self.parenthesize("expr", &[stmt])
+    }
+
+    fn visit_print_stmt(&mut self, stmt: &Expr, _span: Span) -> Self::Item {
+        self.parenthesize("print", &[stmt])
+    }
+
+    fn visit_variable_stmt(
+        &mut self,
+        stmt: &[stmt::VariableDeclaration],
+        _span: Span,
+    ) -> Self::Item {
+        let keyword = if stmt.iter().any(|variable| variable.is_const) {
+            "const"
+        } else {
+            "var"
+        };
+        let declarations = stmt
+            .iter()
+            .map(|variable| match &variable.initializer {
+                Some(initializer) => {
+                    format!("{} = {}", variable.name.lexeme, initializer.accept(self))
+                }
+                None => variable.name.lexeme.to_string(),
+            })
+            .collect::<Vec<_>>();
+
+        format!("({keyword} {})", declarations.join(", "))
+    }
+
+    fn visit_block_stmt(&mut self, stmt: &[Stmt], _span: Span) -> Self::Item {
+        self.nest("block", |printer| printer.stmts(stmt))
+    }
+
+    fn visit_if_stmt(&mut self, stmt: &stmt::If) -> Self::Item {
+        let head = format!("if {}", stmt.condition.accept(self));
+
+        self.nest(&head, |printer| {
+            let mut branches = vec![stmt.truth.accept(printer)];
+
+            if let Some(falsy) = &stmt.falsy {
+                branches.push(falsy.accept(printer));
+            }
+
+            branches
+        })
+    }
+
+    fn visit_while_stmt(&mut self, stmt: &stmt::While) -> Self::Item {
+        let head = format!("while {}", stmt.condition.accept(self));
+
+        self.nest(&head, |printer| vec![stmt.body.accept(printer)])
+    }
+
+    fn visit_for_stmt(&mut self, stmt: &stmt::For) -> Self::Item {
+        let clause = |printer: &mut Self, expr: &Option<Expr>| match expr {
+            Some(expr) => expr.accept(printer),
+            None => String::from("_"),
+        };
+        let initializer = match &stmt.initializer {
+            Some(initializer) => initializer.accept(self),
+            None => String::from("_"),
+        };
+        let head = format!(
+            "for {initializer} {} {}",
+            clause(self, &stmt.condition),
+            clause(self, &stmt.increment)
+        );
+
+        self.nest(&head, |printer| vec![stmt.body.accept(printer)])
+    }
+
+    fn visit_for_in_stmt(&mut self, stmt: &stmt::ForIn) -> Self::Item {
+        let head = format!("for {} in {}", stmt.name.lexeme, stmt.iterable.accept(self));
+
+        self.nest(&head, |printer| vec![stmt.body.accept(printer)])
+    }
+
+    fn visit_break_stmt(&mut self, _: &Token, _span: Span) -> Self::Item {
+        String::from("(break)")
+    }
+
+    fn visit_continue_stmt(&mut self, _: &Token, _span: Span) -> Self::Item {
+        String::from("(continue)")
+    }
+
+    fn visit_function_stmt(&mut self, stmt: &Rc<stmt::Function>) -> Self::Item {
+        let head = format!(
+            "{}fun {} ({})",
+            if stmt.is_async { "async " } else { "" },
+            stmt.name.lexeme,
+            PrettyAstPrinter::params(&stmt.params, &stmt.rest)
+        );
+
+        self.nest(&head, |printer| printer.stmts(&stmt.body))
+    }
+
+    fn visit_return_stmt(&mut self, stmt: &stmt::Return) -> Self::Item {
+        match &stmt.value {
+            Some(value) => self.parenthesize("return", &[value]),
+            None => String::from("(return)"),
+        }
+    }
+
+    fn visit_class_stmt(&mut self, stmt: &Rc<stmt::Class>) -> Self::Item {
+        let head = match &stmt.super_class {
+            Some(super_class) => {
+                format!("class {} < {}", stmt.name.lexeme, super_class.accept(self))
+            }
+            None => format!("class {}", stmt.name.lexeme),
+        };
+
+        self.nest(&head, |printer| {
+            let mut members = printer.stmts(&stmt.methods);
+
+            if !stmt.static_fields.is_empty() {
+                let fields = printer.visit_variable_stmt(&stmt.static_fields, stmt.span);
+
+                members.push(format!("(static {fields})"));
+            }
+
+            for method in &stmt.statics {
+                let method = printer.nest("static", |printer| vec![method.accept(printer)]);
+
+                members.push(method);
+            }
+
+            members
+        })
+    }
+
+    fn visit_try_stmt(&mut self, stmt: &stmt::Try) -> Self::Item {
+        self.nest("try", |printer| {
+            let mut children = printer.stmts(&stmt.body);
+
+            if let Some(catch) = &stmt.catch {
+                let head = match &catch.name {
+                    Some(name) => format!("catch {}", name.lexeme),
+                    None => String::from("catch"),
+                };
+
+                children.push(printer.nest(&head, |printer| printer.stmts(&catch.body)));
+            }
+
+            if let Some(finally) = &stmt.finally {
+                children.push(printer.nest("finally", |printer| printer.stmts(finally)));
+            }
+
+            children
+        })
+    }
+
+    fn visit_throw_stmt(&mut self, stmt: &stmt::Throw) -> Self::Item {
+        self.parenthesize("throw", &[&stmt.value])
+    }
+
+    fn visit_yield_stmt(&mut self, stmt: &stmt::Yield) -> Self::Item {
+        match &stmt.value {
+            Some(value) => self.parenthesize("yield", &[value]),
+            None => String::from("(yield)"),
+        }
+    }
+
+    fn visit_exit_stmt(&mut self, stmt: &stmt::Exit) -> Self::Item {
+        match &stmt.code {
+            Some(code) => self.parenthesize("exit", &[code]),
+            None => String::from("(exit)"),
+        }
+    }
+
+    fn visit_import_stmt(&mut self, stmt: &stmt::Import) -> Self::Item {
+        match stmt.native {
+            true => format!("(import native {})", stmt.path.lexeme),
+            false => format!("(import {})", stmt.path.lexeme),
+        }
+    }
+
+    fn visit_export_stmt(&mut self, stmt: &stmt::Export) -> Self::Item {
+        self.nest("export", |printer| vec![stmt.declaration.accept(printer)])
+    }
+}
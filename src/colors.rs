@@ -0,0 +1,59 @@
+//! Terminal colors for reports. Without the `cli` feature, e.g. when built for
+//! the browser, a stand-in with the same methods leaves the text as it is.
+
+#[cfg(feature = "cli")]
+pub use colored::{ColoredString, Colorize};
+
+#[cfg(not(feature = "cli"))]
+pub use plain::{ColoredString, Colorize};
+
+#[cfg(not(feature = "cli"))]
+mod plain {
+    use std::fmt::Display;
+
+    pub type ColoredString = String;
+
+    pub trait Colorize: Display + Sized {
+        fn normal(&self) -> ColoredString {
+            self.to_string()
+        }
+
+        fn bold(&self) -> ColoredString {
+            self.to_string()
+        }
+
+        fn dimmed(&self) -> ColoredString {
+            self.to_string()
+        }
+
+        fn red(&self) -> ColoredString {
+            self.to_string()
+        }
+
+        fn bright_red(&self) -> ColoredString {
+            self.to_string()
+        }
+
+        fn green(&self) -> ColoredString {
+            self.to_string()
+        }
+
+        fn yellow(&self) -> ColoredString {
+            self.to_string()
+        }
+
+        fn blue(&self) -> ColoredString {
+            self.to_string()
+        }
+
+        fn bright_white(&self) -> ColoredString {
+            self.to_string()
+        }
+
+        fn truecolor(&self, _r: u8, _g: u8, _b: u8) -> ColoredString {
+            self.to_string()
+        }
+    }
+
+    impl<T: Display> Colorize for T {}
+}
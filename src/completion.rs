@@ -0,0 +1,224 @@
+//! Tab completion for the REPL, of keywords and global variables, and of
+//! members after `name.`, and highlighting of the line being typed.
+
+use std::{
+    borrow::Cow::{self, Borrowed, Owned},
+    cell::Cell,
+};
+
+use colored::Colorize;
+use rustyline::{
+    completion::Completer,
+    highlight::{CmdKind, Highlighter},
+    hint::Hinter,
+    validate::Validator,
+    Context, Helper,
+};
+
+use crate::{
+    interpreter::Interpreter,
+    object::Object,
+    scanner::{Scanner, KEYWORDS},
+    token::Token,
+    token_type::TokenType,
+};
+
+/// Completes from the globals as they were when the line started, see
+/// [`ReplHelper::refresh`].
+#[derive(Default)]
+pub struct ReplHelper {
+    globals: Vec<(String, Object)>,
+    /// The cursor, while the bracket next to it should show its match.
+    cursor: Cell<Option<usize>>,
+}
+
+impl ReplHelper {
+    /// Takes the globals declared so far, the interpreter can't be borrowed
+    /// while a line is read.
+    pub fn refresh(&mut self, interpreter: &Interpreter) {
+        self.globals = interpreter
+            .global_names()
+            .into_iter()
+            .filter_map(|name| {
+                let value = interpreter.global(&name)?;
+
+                Some((name, value))
+            })
+            .collect();
+    }
+
+    /// The names `prefix` may be completed to, given what precedes it.
+    fn candidates(&self, before: &str, prefix: &str) -> Vec<String> {
+        let names = match before.strip_suffix('.') {
+            Some(receiver) => {
+                let receiver = &receiver[word_start(receiver)..];
+
+                match self.globals.iter().find(|(name, _)| name == receiver) {
+                    Some((_, value)) => Interpreter::property_names(value),
+                    None => vec![],
+                }
+            }
+            None => KEYWORDS
+                .keys()
+                .map(|keyword| String::from(*keyword))
+                .chain(self.globals.iter().map(|(name, _)| name.clone()))
+                .collect(),
+        };
+        let mut candidates = names
+            .into_iter()
+            .filter(|name| name.starts_with(prefix))
+            .collect::<Vec<_>>();
+
+        candidates.sort();
+        candidates.dedup();
+        candidates
+    }
+}
+
+/// Where the identifier at the end of `text` starts.
+fn word_start(text: &str) -> usize {
+    let word = text
+        .chars()
+        .rev()
+        .take_while(|c| c.is_alphanumeric() || *c == '_')
+        .map(char::len_utf8)
+        .sum::<usize>();
+
+    text.len() - word
+}
+
+impl Completer for ReplHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let line = &line[..pos];
+        let start = word_start(line);
+
+        Ok((start, self.candidates(&line[..start], &line[start..])))
+    }
+}
+
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+
+/// The index of the bracket matching the one under or just before `pos`.
+fn matching_bracket(tokens: &[Token], pos: usize) -> Option<usize> {
+    let is_open = |token: &Token| {
+        matches!(
+            token.token_type,
+            TokenType::LeftParenthesis | TokenType::LeftBraces | TokenType::LeftBracket
+        )
+    };
+    let is_close = |token: &Token| {
+        matches!(
+            token.token_type,
+            TokenType::RightParenthesis | TokenType::RightBraces | TokenType::RightBracket
+        )
+    };
+    let at = |offset: usize| {
+        tokens
+            .iter()
+            .position(|token| token.span.start as usize == offset)
+            .filter(|&index| is_open(&tokens[index]) || is_close(&tokens[index]))
+    };
+    let index = at(pos).or_else(|| at(pos.checked_sub(1)?))?;
+    let mut depth = 0usize;
+
+    // Brackets of different kinds can't interleave in valid code, so counting
+    // all of them together finds the same match.
+    if is_open(&tokens[index]) {
+        for (offset, token) in tokens[index + 1..].iter().enumerate() {
+            if is_open(token) {
+                depth += 1;
+            } else if is_close(token) {
+                if depth == 0 {
+                    return Some(index + 1 + offset);
+                }
+
+                depth -= 1;
+            }
+        }
+    } else {
+        for (offset, token) in tokens[..index].iter().enumerate().rev() {
+            if is_close(token) {
+                depth += 1;
+            } else if is_open(token) {
+                if depth == 0 {
+                    return Some(offset);
+                }
+
+                depth -= 1;
+            }
+        }
+    }
+
+    None
+}
+
+impl Highlighter for ReplHelper {
+    /// Colors the tokens the [`Scanner`] finds, leaving lines that don't scan,
+    /// like an unterminated string, as they are.
+    fn highlight<'l>(&self, line: &'l str, _: usize) -> Cow<'l, str> {
+        let Ok(tokens) = Scanner::new(String::from(line)).scan_tokens() else {
+            return Borrowed(line);
+        };
+        let matching = self
+            .cursor
+            .get()
+            .and_then(|pos| matching_bracket(&tokens, pos));
+        let mut highlighted = String::with_capacity(line.len());
+        let mut end = 0;
+
+        for (index, token) in tokens.iter().enumerate() {
+            let range = token.span.range();
+            let text = &line[range.clone()];
+
+            highlighted.push_str(&line[end..range.start]);
+            end = range.end;
+
+            if Some(index) == matching {
+                highlighted.push_str(&text.bold().blue().to_string());
+                continue;
+            }
+
+            match token.token_type {
+                TokenType::StringLiteral | TokenType::Interpolation => {
+                    highlighted.push_str(&text.green().to_string());
+                }
+                TokenType::NumberLiteral => highlighted.push_str(&text.yellow().to_string()),
+                _ if KEYWORDS.contains_key(text) => {
+                    highlighted.push_str(&text.magenta().to_string());
+                }
+                _ => highlighted.push_str(text),
+            }
+        }
+
+        highlighted.push_str(&line[end..]);
+
+        Owned(highlighted)
+    }
+
+    /// Every edit may change the colors, so the line is always redrawn but
+    /// once it's submitted, when the bracket is no longer highlighted.
+    fn highlight_char(&self, _: &str, pos: usize, kind: CmdKind) -> bool {
+        if kind == CmdKind::ForcedRefresh {
+            self.cursor.set(None);
+
+            return false;
+        }
+
+        self.cursor.set(Some(pos));
+
+        true
+    }
+}
+
+impl Validator for ReplHelper {}
+
+impl Helper for ReplHelper {}
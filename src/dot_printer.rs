@@ -0,0 +1,560 @@
+use std::rc::Rc;
+
+use crate::{
+    expr::{self, Expr, ExprVisitor},
+    object::Object,
+    stmt::{self, Stmt, StmtVisitor},
+    token::{Span, Token},
+};
+
+/// Renders a program as a Graphviz DOT graph, a node per statement and
+/// expression with edges to their parts labeled by the role they play.
+///
+/// ```
+/// use typhoon::dot_printer::DotPrinter;
+///
+/// let graph = DotPrinter::new().print(&[]);
+///
+/// assert!(graph.starts_with("digraph ast {"));
+/// ```
+#[derive(Default)]
+pub struct DotPrinter {
+    lines: Vec<String>,
+    nodes: usize,
+}
+
+impl DotPrinter {
+    pub fn new() -> Self {
+        Self {
+            lines: vec![],
+            nodes: 0,
+        }
+    }
+
+    pub fn print(&mut self, stmts: &[Stmt]) -> String {
+        let program = self.node("program");
+
+        self.stmts(program, stmts);
+
+        let mut graph = String::from("digraph ast {\n");
+
+        graph.push_str("  ordering=out;\n");
+        graph.push_str("  node [shape=box, fontname=monospace];\n");
+
+        for line in self.lines.drain(..) {
+            graph.push_str("  ");
+            graph.push_str(&line);
+            graph.push('\n');
+        }
+
+        graph.push_str("}\n");
+        graph
+    }
+
+    /// Adds a node labeled `label`, returning its id.
+    fn node(&mut self, label: &str) -> usize {
+        let id = self.nodes;
+
+        self.nodes += 1;
+        self.lines
+            .push(format!("n{id} [label=\"{}\"];", DotPrinter::escape(label)));
+
+        id
+    }
+
+    fn edge(&mut self, from: usize, to: usize, label: &str) {
+        if label.is_empty() {
+            self.lines.push(format!("n{from} -> n{to};"));
+        } else {
+            self.lines.push(format!(
+                "n{from} -> n{to} [label=\"{}\"];",
+                DotPrinter::escape(label)
+            ));
+        }
+    }
+
+    /// Adds the node of `expr` as a child of `parent`.
+    fn expr(&mut self, parent: usize, label: &str, expr: &Expr) {
+        let child = expr.accept(self);
+
+        self.edge(parent, child, label);
+    }
+
+    fn stmt(&mut self, parent: usize, label: &str, stmt: &Stmt) {
+        let child = stmt.accept(self);
+
+        self.edge(parent, child, label);
+    }
+
+    /// Adds `stmts` as children of `parent` in order, left to right.
+    fn stmts(&mut self, parent: usize, stmts: &[Stmt]) {
+        for stmt in stmts {
+            self.stmt(parent, "", stmt);
+        }
+    }
+
+    /// A node for a function's name and parameters with its body below.
+    fn function(
+        &mut self,
+        head: &str,
+        params: &[Token],
+        rest: &Option<Token>,
+        body: &[Stmt],
+    ) -> usize {
+        let mut params = params
+            .iter()
+            .map(|param| param.lexeme.to_string())
+            .collect::<Vec<_>>();
+
+        if let Some(rest) = rest {
+            params.push(format!("...{}", rest.lexeme));
+        }
+
+        let function = self.node(&format!("{head}({})", params.join(", ")));
+
+        self.stmts(function, body);
+
+        function
+    }
+
+    fn escape(label: &str) -> String {
+        label
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+    }
+}
+
+impl ExprVisitor for DotPrinter {
+    type Item = usize;
+
+    fn visit_comma(&mut self, expr: &expr::Comma) -> Self::Item {
+        let node = self.node(",");
+
+        self.expr(node, "", &expr.left);
+        self.expr(node, "", &expr.right);
+
+        node
+    }
+
+    fn visit_lambda(&mut self, expr: &Rc<expr::Lambda>) -> Self::Item {
+        let head = if expr.is_async { "async fun " } else { "fun " };
+
+        self.function(head, &expr.params, &expr.rest, &expr.body)
+    }
+
+    fn visit_assignment(&mut self, expr: &expr::Assignment) -> Self::Item {
+        let node = self.node(&format!("{} =", expr.name.lexeme));
+
+        self.expr(node, "value", &expr.value);
+
+        node
+    }
+
+    fn visit_set(&mut self, expr: &expr::Set) -> Self::Item {
+        let node = self.node(&format!(".{} =", expr.name.lexeme));
+
+        self.expr(node, "object", &expr.object);
+        self.expr(node, "value", &expr.value);
+
+        node
+    }
+
+    fn visit_index_set(&mut self, expr: &expr::IndexSet) -> Self::Item {
+        let node = self.node("[] =");
+
+        self.expr(node, "object", &expr.object);
+        self.expr(node, "index", &expr.index);
+        self.expr(node, "value", &expr.value);
+
+        node
+    }
+
+    fn visit_ternary(&mut self, expr: &expr::Ternary) -> Self::Item {
+        let node = self.node("?:");
+
+        self.expr(node, "condition", &expr.condition);
+        self.expr(node, "then", &expr.truth);
+        self.expr(node, "else", &expr.falsy);
+
+        node
+    }
+
+    fn visit_logical(&mut self, expr: &expr::Logical) -> Self::Item {
+        let node = self.node(&expr.operator.lexeme);
+
+        self.expr(node, "", &expr.left);
+        self.expr(node, "", &expr.right);
+
+        node
+    }
+
+    fn visit_binary(&mut self, expr: &expr::Binary) -> Self::Item {
+        let node = self.node(&expr.operator.lexeme);
+
+        self.expr(node, "", &expr.left);
+        self.expr(node, "", &expr.right);
+
+        node
+    }
+
+    fn visit_unary(&mut self, expr: &expr::Unary) -> Self::Item {
+        let node = self.node(&expr.operator.lexeme);
+
+        self.expr(node, "", &expr.right);
+
+        node
+    }
+
+    fn visit_await(&mut self, expr: &expr::Await) -> Self::Item {
+        let node = self.node("await");
+
+        self.expr(node, "", &expr.value);
+
+        node
+    }
+
+    fn visit_call(&mut self, expr: &expr::Call) -> Self::Item {
+        let node = self.node("call");
+
+        self.expr(node, "callee", &expr.callee);
+
+        for (i, argument) in expr.arguments.iter().enumerate() {
+            self.expr(node, &format!("arg {i}"), argument);
+        }
+
+        node
+    }
+
+    fn visit_get(&mut self, expr: &expr::Get) -> Self::Item {
+        let operator = if expr.optional { "?." } else { "." };
+        let node = self.node(&format!("{operator}{}", expr.name.lexeme));
+
+        self.expr(node, "object", &expr.object);
+
+        node
+    }
+
+    fn visit_index(&mut self, expr: &expr::Index) -> Self::Item {
+        let node = self.node("[]");
+
+        self.expr(node, "object", &expr.object);
+        self.expr(node, "index", &expr.index);
+
+        node
+    }
+
+    fn visit_slice(&mut self, expr: &expr::Slice) -> Self::Item {
+        let node = self.node("[:]");
+
+        self.expr(node, "object", &expr.object);
+
+        if let Some(start) = &expr.start {
+            self.expr(node, "start", start);
+        }
+
+        if let Some(end) = &expr.end {
+            self.expr(node, "end", end);
+        }
+
+        node
+    }
+
+    fn visit_grouping(&mut self, expr: &Expr, _span: Span) -> Self::Item {
+        let node = self.node("group");
+
+        self.expr(node, "", expr);
+
+        node
+    }
+
+    fn visit_variable(&mut self, expr: &Token) -> Self::Item {
+        self.node(&expr.lexeme)
+    }
+
+    fn visit_this(&mut self, _: &Token) -> Self::Item {
+        self.node("this")
+    }
+
+    fn visit_super(&mut self, expr: &expr::Super) -> Self::Item {
+        self.node(&format!("super.{}", expr.method.lexeme))
+    }
+
+    fn visit_array(&mut self, expr: &expr::Array) -> Self::Item {
+        let node = self.node("array");
+
+        for element in &expr.elements {
+            self.expr(node, "", element);
+        }
+
+        node
+    }
+
+    fn visit_map(&mut self, expr: &expr::Map) -> Self::Item {
+        let node = self.node("map");
+
+        for (key, value) in &expr.entries {
+            self.expr(node, "key", key);
+            self.expr(node, "value", value);
+        }
+
+        node
+    }
+
+    fn visit_interpolation(&mut self, expr: &expr::Interpolation) -> Self::Item {
+        let node = self.node("interpolate");
+
+        for part in &expr.parts {
+            self.expr(node, "", part);
+        }
+
+        node
+    }
+
+    fn visit_spread(&mut self, expr: &expr::Spread) -> Self::Item {
+        let node = self.node("...");
+
+        self.expr(node, "", &expr.value);
+
+        node
+    }
+
+    fn visit_literal(&mut self, expr: &Object, _span: Span) -> Self::Item {
+        match expr {
+            Object::String(string) => self.node(&format!("\"{string}\"")),
+            // Keeps integral floats apart from ints, `7.0` rather than `7`.
+            Object::Number(number) if number.fract() == 0.0 => self.node(&format!("{number:.1}")),
+            _ => self.node(&expr.to_string()),
+        }
+    }
+}
+
+impl StmtVisitor for DotPrinter {
+    type Item = usize;
+
+    fn visit_empty_stmt(&mut self, _span: Span) -> Self::Item {
+        self.node("empty")
+    }
+
+    fn visit_expression_stmt(&mut self, stmt: &Expr, _span: Span) -> Self::Item {
+        let node = self.node("expr");
+
+        self.expr(node, "", stmt);
+
+        node
+    }
+
+    fn visit_print_stmt(&mut self, stmt: &Expr, _span: Span) -> Self::Item {
+        let node = self.node("print");
+
+        self.expr(node, "", stmt);
+
+        node
+    }
+
+    fn visit_variable_stmt(
+        &mut self,
+        stmt: &[stmt::VariableDeclaration],
+        _span: Span,
+    ) -> Self::Item {
+        let keyword = if stmt.iter().any(|variable| variable.is_const) {
+            "const"
+        } else {
+            "var"
+        };
+        let node = self.node(keyword);
+
+        for variable in stmt {
+            let name = self.node(&variable.name.lexeme);
+
+            self.edge(node, name, "");
+
+            if let Some(initializer) = &variable.initializer {
+                self.expr(name, "value", initializer);
+            }
+        }
+
+        node
+    }
+
+    fn visit_block_stmt(&mut self, stmt: &[Stmt], _span: Span) -> Self::Item {
+        let node = self.node("block");
+
+        self.stmts(node, stmt);
+
+        node
+    }
+
+    fn visit_if_stmt(&mut self, stmt: &stmt::If) -> Self::Item {
+        let node = self.node("if");
+
+        self.expr(node, "condition", &stmt.condition);
+        self.stmt(node, "then", &stmt.truth);
+
+        if let Some(falsy) = &stmt.falsy {
+            self.stmt(node, "else", falsy);
+        }
+
+        node
+    }
+
+    fn visit_while_stmt(&mut self, stmt: &stmt::While) -> Self::Item {
+        let node = self.node("while");
+
+        self.expr(node, "condition", &stmt.condition);
+        self.stmt(node, "body", &stmt.body);
+
+        node
+    }
+
+    fn visit_for_stmt(&mut self, stmt: &stmt::For) -> Self::Item {
+        let node = self.node("for");
+
+        if let Some(initializer) = &stmt.initializer {
+            self.stmt(node, "initializer", initializer);
+        }
+
+        if let Some(condition) = &stmt.condition {
+            self.expr(node, "condition", condition);
+        }
+
+        if let Some(increment) = &stmt.increment {
+            self.expr(node, "increment", increment);
+        }
+
+        self.stmt(node, "body", &stmt.body);
+
+        node
+    }
+
+    fn visit_for_in_stmt(&mut self, stmt: &stmt::ForIn) -> Self::Item {
+        let node = self.node(&format!("for {} in", stmt.name.lexeme));
+
+        self.expr(node, "iterable", &stmt.iterable);
+        self.stmt(node, "body", &stmt.body);
+
+        node
+    }
+
+    fn visit_break_stmt(&mut self, _: &Token, _span: Span) -> Self::Item {
+        self.node("break")
+    }
+
+    fn visit_continue_stmt(&mut self, _: &Token, _span: Span) -> Self::Item {
+        self.node("continue")
+    }
+
+    fn visit_function_stmt(&mut self, stmt: &Rc<stmt::Function>) -> Self::Item {
+        let head = match stmt.is_async {
+            true => format!("async fun {}", stmt.name.lexeme),
+            false => format!("fun {}", stmt.name.lexeme),
+        };
+
+        self.function(&head, &stmt.params, &stmt.rest, &stmt.body)
+    }
+
+    fn visit_return_stmt(&mut self, stmt: &stmt::Return) -> Self::Item {
+        let node = self.node("return");
+
+        if let Some(value) = &stmt.value {
+            self.expr(node, "", value);
+        }
+
+        node
+    }
+
+    fn visit_class_stmt(&mut self, stmt: &Rc<stmt::Class>) -> Self::Item {
+        let node = self.node(&format!("class {}", stmt.name.lexeme));
+
+        if let Some(super_class) = &stmt.super_class {
+            self.expr(node, "super class", super_class);
+        }
+
+        for method in &stmt.methods {
+            self.stmt(node, "method", method);
+        }
+
+        if !stmt.static_fields.is_empty() {
+            let fields = self.visit_variable_stmt(&stmt.static_fields, stmt.span);
+
+            self.edge(node, fields, "static");
+        }
+
+        for method in &stmt.statics {
+            self.stmt(node, "static", method);
+        }
+
+        node
+    }
+
+    fn visit_try_stmt(&mut self, stmt: &stmt::Try) -> Self::Item {
+        let node = self.node("try");
+        let body = self.node("block");
+
+        self.edge(node, body, "body");
+        self.stmts(body, &stmt.body);
+
+        if let Some(catch) = &stmt.catch {
+            let label = match &catch.name {
+                Some(name) => format!("catch {}", name.lexeme),
+                None => String::from("catch"),
+            };
+            let handler = self.node(&label);
+
+            self.edge(node, handler, "catch");
+            self.stmts(handler, &catch.body);
+        }
+
+        if let Some(finally) = &stmt.finally {
+            let block = self.node("block");
+
+            self.edge(node, block, "finally");
+            self.stmts(block, finally);
+        }
+
+        node
+    }
+
+    fn visit_throw_stmt(&mut self, stmt: &stmt::Throw) -> Self::Item {
+        let node = self.node("throw");
+
+        self.expr(node, "", &stmt.value);
+
+        node
+    }
+
+    fn visit_yield_stmt(&mut self, stmt: &stmt::Yield) -> Self::Item {
+        let node = self.node("yield");
+
+        if let Some(value) = &stmt.value {
+            self.expr(node, "", value);
+        }
+
+        node
+    }
+
+    fn visit_exit_stmt(&mut self, stmt: &stmt::Exit) -> Self::Item {
+        let node = self.node("exit");
+
+        if let Some(code) = &stmt.code {
+            self.expr(node, "", code);
+        }
+
+        node
+    }
+
+    fn visit_import_stmt(&mut self, stmt: &stmt::Import) -> Self::Item {
+        match stmt.native {
+            true => self.node(&format!("import native {}", stmt.path.lexeme)),
+            false => self.node(&format!("import {}", stmt.path.lexeme)),
+        }
+    }
+
+    fn visit_export_stmt(&mut self, stmt: &stmt::Export) -> Self::Item {
+        let node = self.node("export");
+
+        self.stmt(node, "", &stmt.declaration);
+
+        node
+    }
+}
@@ -1,9 +1,34 @@
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
 
-use crate::{errors::RuntimeError, object::Object, token::Token};
+use crate::{
+    errors::RuntimeError,
+    object::Object,
+    symbol::{self, Symbol},
+    token::Token,
+};
 
+/// A variable's value, shared between the environment declaring it and the
+/// closures capturing it.
+pub type Cell = Rc<RefCell<Object>>;
+
+/// The slot of `this` in the environment a method is bound in.
+pub const THIS_SLOT: usize = 0;
+/// The slot of the class in the environment its methods close over.
+pub const CLASS_SLOT: usize = 0;
+/// The slot of the super class in the environment methods close over.
+pub const SUPER_SLOT: usize = 1;
+
+/// Global variables are looked up by name, as they can be declared after
+/// the code using them is resolved. Locals live in the slot the resolver
+/// assigned them in the environment of their scope.
 pub struct Environment {
-    values: HashMap<String, Object>,
+    values: HashMap<Symbol, Object>,
+    constants: HashSet<Symbol>,
+    slots: Vec<Cell>,
     pub enclosing: Option<Rc<RefCell<Environment>>>,
 }
 
@@ -11,10 +36,39 @@ impl Environment {
     pub fn new(enclosing: Option<Rc<RefCell<Environment>>>) -> Self {
         Self {
             values: HashMap::new(),
+            constants: HashSet::new(),
+            slots: vec![],
             enclosing,
         }
     }
 
+    /// Creates an environment holding `values` in its first slots.
+    pub fn with_slots(enclosing: Option<Rc<RefCell<Environment>>>, values: Vec<Object>) -> Self {
+        Self {
+            slots: values
+                .into_iter()
+                .map(|value| Rc::new(RefCell::new(value)))
+                .collect(),
+            ..Self::new(enclosing)
+        }
+    }
+
+    /// Whether no variable is declared in the environment itself, e.g. the
+    /// closure of a function that captures nothing.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty() && self.slots.is_empty()
+    }
+
+    /// Whether the global `name` was declared with `const`.
+    pub fn is_constant(&self, name: &str) -> bool {
+        self.constants.contains(name)
+    }
+
+    /// The names of the variables declared by name, the globals.
+    pub fn names(&self) -> impl Iterator<Item = &Symbol> {
+        self.values.keys()
+    }
+
     pub fn get(&self, name: &Token) -> Result<Object, RuntimeError> {
         if let Some(obj) = self.values.get(&name.lexeme) {
             Ok(obj.clone())
@@ -28,21 +82,25 @@ impl Environment {
         }
     }
 
-    pub fn get_at(&self, depth: usize, name: &str) -> Result<Object, RuntimeError> {
+    /// The local in `slot` of the environment `depth` levels up.
+    pub fn get_at(&self, depth: usize, slot: usize) -> Object {
         if depth == 0 {
-            Ok(self.values.get(name).unwrap().clone())
+            self.slots
+                .get(slot)
+                .map_or(Object::Undefined, |cell| cell.borrow().clone())
         } else {
             self.enclosing
                 .as_ref()
                 .unwrap()
                 .borrow()
-                .get_at(depth - 1, name)
+                .get_at(depth - 1, slot)
         }
     }
 
     pub fn assign(&mut self, name: &Token, value: Object) -> Result<(), RuntimeError> {
         if self.values.contains_key(&name.lexeme) {
-            self.values.insert(String::clone(&name.lexeme), value);
+            self.check_not_const(name)?;
+            self.values.insert(Rc::clone(&name.lexeme), value);
 
             Ok(())
         } else if let Some(env) = &mut self.enclosing {
@@ -55,27 +113,90 @@ impl Environment {
         }
     }
 
-    pub fn assign_at(
-        &mut self,
-        depth: usize,
-        name: &str,
-        value: Object,
-    ) -> Result<(), RuntimeError> {
+    /// Assigns the local in `slot` of the environment `depth` levels up.
+    pub fn assign_at(&mut self, depth: usize, slot: usize, value: Object) {
         if depth == 0 {
-            self.values.insert(String::from(name), value);
-
-            Ok(())
+            *self.slot(slot).borrow_mut() = value;
         } else {
             self.enclosing
                 .as_ref()
                 .unwrap()
                 .borrow_mut()
-                .assign_at(depth - 1, name, value)
+                .assign_at(depth - 1, slot, value)
         }
     }
 
     pub fn define(&mut self, name: &str, value: Object) -> &mut Self {
-        self.values.insert(String::from(name), value);
+        self.constants.remove(name);
+        self.values.insert(symbol::intern(name), value);
         self
     }
+
+    /// Defines a variable that can't be assigned to afterwards.
+    pub fn define_const(&mut self, name: &str, value: Object) -> &mut Self {
+        let name = symbol::intern(name);
+
+        self.constants.insert(Rc::clone(&name));
+        self.values.insert(name, value);
+        self
+    }
+
+    /// Removes the global `name`, if it's declared.
+    pub fn undefine(&mut self, name: &str) {
+        self.constants.remove(name);
+        self.values.remove(name);
+    }
+
+    /// Defines the local in `slot`, constants are enforced by the resolver.
+    pub fn define_at(&mut self, slot: usize, value: Object) {
+        *self.slot(slot).borrow_mut() = value;
+    }
+
+    /// Adds the local in `slot` of the environment `depth` levels up from
+    /// `from` to the next slot of this one, both then share its value.
+    ///
+    /// A function can capture a variable before it's defined, its own name
+    /// or the variable its declaration initializes, so the slot is filled
+    /// with `undefined` when it's missing.
+    pub fn capture(&mut self, from: &mut Environment, depth: usize, slot: usize) {
+        if depth > 0 {
+            let enclosing = Rc::clone(from.enclosing.as_ref().unwrap());
+
+            return self.capture(&mut enclosing.borrow_mut(), depth - 1, slot);
+        }
+
+        self.slots.push(Rc::clone(from.slot(slot)));
+    }
+
+    /// Moves the locals into new cells holding their current values, the
+    /// closures that captured the old ones keep them as they are.
+    pub fn renew_slots(&mut self) {
+        for cell in &mut self.slots {
+            let value = cell.borrow().clone();
+
+            *cell = Rc::new(RefCell::new(value));
+        }
+    }
+
+    /// The cell of `slot`, the slots up to it are created undefined if
+    /// they're missing.
+    fn slot(&mut self, slot: usize) -> &Cell {
+        if slot >= self.slots.len() {
+            self.slots
+                .resize_with(slot + 1, || Rc::new(RefCell::new(Object::Undefined)));
+        }
+
+        &self.slots[slot]
+    }
+
+    fn check_not_const(&self, name: &Token) -> Result<(), RuntimeError> {
+        if self.constants.contains(&name.lexeme) {
+            Err(RuntimeError {
+                token: name.clone(),
+                message: format!("Can't assign to constant '{}'", name.lexeme),
+            })
+        } else {
+            Ok(())
+        }
+    }
 }
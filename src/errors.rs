@@ -1,13 +1,279 @@
-use crate::{object::Object, token::Token};
+use std::{error, fmt, rc::Rc};
+
+use crate::{
+    object::{Callable, Object},
+    token::{Span, Token},
+    token_type::TokenType,
+};
 
 #[derive(Debug)]
 pub struct SyntaxError;
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A warning reported by the resolver that can be turned off on its own.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Lint {
+    UnusedVariable,
+    UnusedParameter,
+    UnusedFunction,
+    UnreachableCode,
+    ShadowedVariable,
+    EmptyBlock,
+    ConstantCondition,
+    LooseEquality,
+    MissingSuperInit,
+}
+
+impl Lint {
+    pub const ALL: [Lint; 9] = [
+        Lint::UnusedVariable,
+        Lint::UnusedParameter,
+        Lint::UnusedFunction,
+        Lint::UnreachableCode,
+        Lint::ShadowedVariable,
+        Lint::EmptyBlock,
+        Lint::ConstantCondition,
+        Lint::LooseEquality,
+        Lint::MissingSuperInit,
+    ];
+
+    /// The name used to refer to the lint on the command line.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Lint::UnusedVariable => "unused-variable",
+            Lint::UnusedParameter => "unused-parameter",
+            Lint::UnusedFunction => "unused-function",
+            Lint::UnreachableCode => "unreachable-code",
+            Lint::ShadowedVariable => "shadowed-variable",
+            Lint::EmptyBlock => "empty-block",
+            Lint::ConstantCondition => "constant-condition",
+            Lint::LooseEquality => "loose-equality",
+            Lint::MissingSuperInit => "missing-super-init",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Lint> {
+        Lint::ALL.into_iter().find(|lint| lint.name() == name)
+    }
+
+    /// Whether the lint is reported when running a script, the others are
+    /// only checked by `typhoon lint`.
+    pub fn is_default(&self) -> bool {
+        matches!(self, Lint::UnusedVariable)
+    }
+
+    /// The level of the lint when running a script unless configured.
+    pub fn default_level(&self) -> LintLevel {
+        if self.is_default() {
+            LintLevel::Warn
+        } else {
+            LintLevel::Allow
+        }
+    }
+}
+
+/// What's done with the warnings of a lint when running a script.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LintLevel {
+    /// The lint isn't checked.
+    Allow,
+    /// Its warnings are reported and the script runs regardless.
+    Warn,
+    /// Its warnings are reported as errors and the script doesn't run.
+    Deny,
+}
+
+/// How diagnostics and runtime errors are reported.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ErrorFormat {
+    /// Colored messages with the offending source line underlined.
+    #[default]
+    Human,
+    /// One JSON object per line, see [`Diagnostic::to_json`].
+    Json,
+}
+
+/// A problem found while scanning, parsing, resolving or running a program.
+#[derive(Clone, Debug)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// What kind of problem it is, for tools: `syntax-error`,
+    /// `resolve-error`, `runtime-error`, `limit-exceeded` or the name of the
+    /// lint.
+    pub code: &'static str,
+    pub line: usize,
+    pub span: Option<Span>,
+    pub wheres: String,
+    pub message: String,
+    /// The lint that produced this warning, if any.
+    pub lint: Option<Lint>,
+}
+
+impl Diagnostic {
+    pub fn at_line(severity: Severity, code: &'static str, line: usize, message: &str) -> Self {
+        Self {
+            severity,
+            code,
+            line,
+            span: None,
+            wheres: String::new(),
+            message: String::from(message),
+            lint: None,
+        }
+    }
+
+    pub fn with_span(mut self, span: Span) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    pub fn at_token(severity: Severity, code: &'static str, token: &Token, message: &str) -> Self {
+        let wheres = if token.token_type == TokenType::Eof {
+            String::from("at end")
+        } else {
+            format!("at '{}'", token.lexeme)
+        };
+
+        Self {
+            severity,
+            code,
+            line: token.line,
+            span: Some(token.span),
+            wheres,
+            message: String::from(message),
+            lint: None,
+        }
+    }
+
+    pub fn with_lint(mut self, lint: Lint) -> Self {
+        self.lint = Some(lint);
+        self
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    /// The diagnostic as a single line JSON object:
+    ///
+    /// ```json
+    /// {"severity": "error", "code": "syntax-error", "message": "Expect an expression",
+    ///  "line": 1, "column": 7, "start": 6, "end": 7}
+    /// ```
+    ///
+    /// `column`, `start` and `end` are `null` when the location is only known
+    /// by line, `start` and `end` are byte offsets into the source.
+    pub fn to_json(&self) -> String {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        let (column, start, end) = match self.span {
+            Some(span) => (
+                span.column.to_string(),
+                span.start.to_string(),
+                span.end.to_string(),
+            ),
+            None => (
+                String::from("null"),
+                String::from("null"),
+                String::from("null"),
+            ),
+        };
+
+        format!(
+            r#"{{"severity": "{severity}", "code": "{}", "message": {}, "line": {}, "column": {column}, "start": {start}, "end": {end}}}"#,
+            self.code,
+            json_string(&self.message),
+            self.line,
+        )
+    }
+}
+
+impl From<&RuntimeError> for Diagnostic {
+    fn from(runtime_error: &RuntimeError) -> Self {
+        Diagnostic::at_token(
+            Severity::Error,
+            "runtime-error",
+            &runtime_error.token,
+            &runtime_error.message,
+        )
+    }
+}
+
+/// Quotes `text` as a JSON string.
+fn json_string(text: &str) -> String {
+    let mut quoted = String::from('"');
+
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+
+    quoted.push('"');
+    quoted
+}
+
+impl error::Error for Diagnostic {}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "Error",
+            Severity::Warning => "Warning",
+        };
+
+        match self.span {
+            Some(span) => write!(f, "[{}:{}]", self.line, span.column)?,
+            None => write!(f, "[{}]", self.line)?,
+        }
+
+        write!(f, " {} {}: {}", severity, self.wheres, self.message)?;
+
+        match self.lint {
+            Some(lint) => write!(f, " [{}]", lint.name()),
+            None => Ok(()),
+        }
+    }
+}
+
 pub enum VMException {
     RuntimeError(RuntimeError),
+    ThrowException(Token, Object),
     ReturnException(Object),
+    /// A `return` of a call in tail position, the caller runs it in place of
+    /// the returning function.
+    TailCallException(Box<TailCall>),
     BreakException,
     ContinueException,
+    /// A `yield` suspending the generator running it with its value.
+    YieldException(Object),
+    /// An `exit` statement ending the run with its code.
+    ExitException(i32),
+    /// A sandbox limit was reached, it unwinds through `try` to the caller of
+    /// the interpreter.
+    LimitExceeded(RuntimeError),
+}
+
+/// A limit set with [`Interpreter::set_limits`](crate::interpreter::Interpreter::set_limits)
+/// that a script went over.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Limit {
+    Statements,
+    CallDepth,
+    Timeout,
 }
 
 #[derive(Debug)]
@@ -16,6 +282,201 @@ pub struct RuntimeError {
     pub message: String,
 }
 
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.token.span.column {
+            0 => write!(f, "[{}] {}", self.token.line, self.message),
+            column => write!(f, "[{}:{}] {}", self.token.line, column, self.message),
+        }
+    }
+}
+
+impl error::Error for RuntimeError {}
+
+/// Why a value couldn't be converted with
+/// [`FromObject`](crate::object::FromObject), along with where it was in
+/// the value converted, e.g. `.points[2].x`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConversionError {
+    message: String,
+    path: String,
+}
+
+impl ConversionError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            path: String::new(),
+        }
+    }
+
+    /// The error of a value whose type isn't the `expected` one.
+    pub fn expected(expected: &str, found: &Object) -> Self {
+        ConversionError::new(format!("Expected {expected}, found {}", found.type_name()))
+    }
+
+    /// Locates the error inside the element or field `segment` of the value
+    /// being converted, e.g. `[2]` or `.x`.
+    pub fn within(mut self, segment: &str) -> Self {
+        self.path.insert_str(0, segment);
+        self
+    }
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.path.as_str() {
+            "" => write!(f, "{}", self.message),
+            path => write!(f, "{} at {path}", self.message),
+        }
+    }
+}
+
+impl error::Error for ConversionError {}
+
+/// Why running a script failed. Diagnostics are already reported when it's
+/// returned, so embedders can simply propagate it with `?`.
+///
+/// ```
+/// use typhoon::{Lib, TyphoonError};
+///
+/// let mut lib = Lib::new();
+/// let error = lib.run(String::from("var x = ;")).unwrap_err();
+///
+/// assert!(matches!(error, TyphoonError::Syntax(_)));
+/// assert_eq!(error.to_string(), "[1:9] Error at ';': Expect an expression");
+/// assert_eq!(error.span().map(|span| span.start), Some(8));
+/// ```
+#[derive(Debug)]
+pub enum TyphoonError {
+    /// Scanning, parsing or resolving found errors. Holds every diagnostic
+    /// of the run, warnings included.
+    Syntax(Vec<Diagnostic>),
+    /// Execution raised an error nothing caught.
+    Runtime(RuntimeError),
+    /// Execution was stopped for going over one of its limits.
+    LimitExceeded(Limit, RuntimeError),
+}
+
+impl TyphoonError {
+    /// The line of the first error.
+    pub fn line(&self) -> usize {
+        match self {
+            TyphoonError::Syntax(diagnostics) => diagnostics
+                .iter()
+                .find(|diagnostic| diagnostic.is_error())
+                .map_or(0, |diagnostic| diagnostic.line),
+            TyphoonError::Runtime(runtime_error)
+            | TyphoonError::LimitExceeded(_, runtime_error) => runtime_error.token.line,
+        }
+    }
+
+    /// Where the first error is in the source, `None` when only its line is
+    /// known.
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            TyphoonError::Syntax(diagnostics) => diagnostics
+                .iter()
+                .find(|diagnostic| diagnostic.is_error())
+                .and_then(|diagnostic| diagnostic.span),
+            TyphoonError::Runtime(runtime_error)
+            | TyphoonError::LimitExceeded(_, runtime_error) => Some(runtime_error.token.span),
+        }
+    }
+}
+
+impl fmt::Display for TyphoonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TyphoonError::Syntax(diagnostics) => {
+                let errors = diagnostics
+                    .iter()
+                    .filter(|diagnostic| diagnostic.is_error())
+                    .map(|diagnostic| diagnostic.to_string())
+                    .collect::<Vec<_>>();
+
+                write!(f, "{}", errors.join("\n"))
+            }
+            TyphoonError::Runtime(runtime_error)
+            | TyphoonError::LimitExceeded(_, runtime_error) => {
+                write!(f, "{runtime_error}")
+            }
+        }
+    }
+}
+
+impl error::Error for TyphoonError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            TyphoonError::Syntax(_) => None,
+            TyphoonError::Runtime(runtime_error)
+            | TyphoonError::LimitExceeded(_, runtime_error) => Some(runtime_error),
+        }
+    }
+}
+
+impl From<RuntimeError> for TyphoonError {
+    fn from(runtime_error: RuntimeError) -> Self {
+        TyphoonError::Runtime(runtime_error)
+    }
+}
+
+pub struct TailCall {
+    pub callable: Rc<dyn Callable>,
+    pub arguments: Vec<Object>,
+    pub call_site: Token,
+}
+
+/// A function call in progress, the called function's name and the token of
+/// the call expression.
+#[derive(Debug, Clone)]
+pub struct CallFrame {
+    pub function: String,
+    pub call_site: Token,
+}
+
+impl CallFrame {
+    /// Groups the consecutive frames of `backtrace` calling the same function
+    /// from the same place, as recursion leaves them, with how many each
+    /// group has.
+    ///
+    /// ```
+    /// use typhoon::{
+    ///     errors::CallFrame,
+    ///     token::{Span, Token},
+    ///     token_type::TokenType,
+    /// };
+    ///
+    /// let frame = |function: &str, line| CallFrame {
+    ///     function: String::from(function),
+    ///     call_site: Token::new(TokenType::Identifier, function, None, line, Span::default()),
+    /// };
+    /// let backtrace = [frame("f", 1), frame("f", 1), frame("f", 1), frame("g", 2)];
+    /// let collapsed = CallFrame::collapse(&backtrace)
+    ///     .into_iter()
+    ///     .map(|(frame, count)| (frame.function.as_str(), count))
+    ///     .collect::<Vec<_>>();
+    ///
+    /// assert_eq!(collapsed, [("f", 3), ("g", 1)]);
+    /// ```
+    pub fn collapse(backtrace: &[CallFrame]) -> Vec<(&CallFrame, usize)> {
+        backtrace
+            .chunk_by(|frame, next| {
+                frame.function == next.function
+                    && frame.call_site.line == next.call_site.line
+                    && frame.call_site.span == next.call_site.span
+            })
+            .map(|frames| (&frames[0], frames.len()))
+            .collect()
+    }
+}
+
+impl From<RuntimeError> for VMException {
+    fn from(runtime_error: RuntimeError) -> Self {
+        VMException::RuntimeError(runtime_error)
+    }
+}
+
 pub struct BreakException;
 
 pub struct ContinueException;
@@ -1,66 +1,108 @@
+mod _await;
 mod _super;
+mod array;
 mod assignment;
 mod binary;
 mod call;
 mod comma;
 mod get;
+mod index;
+mod index_set;
+mod interpolation;
 mod lambda;
 mod logical;
+mod map;
 mod set;
+mod slice;
+mod spread;
 mod ternary;
 mod unary;
 
+pub use _await::Await;
 pub use _super::Super;
+pub use array::Array;
 pub use assignment::Assignment;
 pub use binary::Binary;
 pub use call::Call;
 pub use comma::Comma;
 pub use get::Get;
+pub use index::Index;
+pub use index_set::IndexSet;
+pub use interpolation::Interpolation;
 pub use lambda::Lambda;
 pub use logical::Logical;
+pub use map::Map;
 pub use set::Set;
+pub use slice::Slice;
+pub use spread::Spread;
 pub use ternary::Ternary;
 pub use unary::Unary;
 
-use crate::{object::Object, token::Token};
+use std::rc::Rc;
 
+use crate::{
+    object::Object,
+    token::{Span, Token},
+};
+
+/// Every expression knows its span in the source, see [`Expr::span`]. The
+/// variants whose tokens don't reach both of its ends keep it alongside.
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Expr {
     Comma(Box<Comma>),
-    Lambda(Box<Lambda>),
+    /// Shared with the functions created from it, evaluating one doesn't
+    /// copy its body.
+    Lambda(Rc<Lambda>),
     Assignment(Box<Assignment>),
     Set(Box<Set>),
+    IndexSet(Box<IndexSet>),
     Ternary(Box<Ternary>),
     Logical(Box<Logical>),
     Binary(Box<Binary>),
     Unary(Box<Unary>),
+    Await(Box<Await>),
     Call(Box<Call>),
     Get(Box<Get>),
-    Grouping(Box<Expr>),
+    Index(Box<Index>),
+    Slice(Box<Slice>),
+    Grouping(Box<Expr>, Span),
     Variable(Box<Token>),
     This(Box<Token>),
     Super(Box<Super>),
-    Literal(Box<Object>),
+    Array(Box<Array>),
+    Map(Box<Map>),
+    Interpolation(Box<Interpolation>),
+    Spread(Box<Spread>),
+    Literal(Box<Object>, Span),
 }
 
 pub trait ExprVisitor {
     type Item;
 
     fn visit_comma(&mut self, expr: &Comma) -> Self::Item;
-    fn visit_lambda(&mut self, expr: &Lambda) -> Self::Item;
+    fn visit_lambda(&mut self, expr: &Rc<Lambda>) -> Self::Item;
     fn visit_assignment(&mut self, expr: &Assignment) -> Self::Item;
     fn visit_set(&mut self, expr: &Set) -> Self::Item;
+    fn visit_index_set(&mut self, expr: &IndexSet) -> Self::Item;
     fn visit_ternary(&mut self, expr: &Ternary) -> Self::Item;
     fn visit_logical(&mut self, expr: &Logical) -> Self::Item;
     fn visit_binary(&mut self, expr: &Binary) -> Self::Item;
     fn visit_unary(&mut self, expr: &Unary) -> Self::Item;
+    fn visit_await(&mut self, expr: &Await) -> Self::Item;
     fn visit_call(&mut self, expr: &Call) -> Self::Item;
     fn visit_get(&mut self, expr: &Get) -> Self::Item;
-    fn visit_grouping(&mut self, expr: &Expr) -> Self::Item;
+    fn visit_index(&mut self, expr: &Index) -> Self::Item;
+    fn visit_slice(&mut self, expr: &Slice) -> Self::Item;
+    fn visit_grouping(&mut self, expr: &Expr, span: Span) -> Self::Item;
     fn visit_variable(&mut self, expr: &Token) -> Self::Item;
     fn visit_this(&mut self, expr: &Token) -> Self::Item;
     fn visit_super(&mut self, expr: &Super) -> Self::Item;
-    fn visit_literal(&mut self, expr: &Object) -> Self::Item;
+    fn visit_array(&mut self, expr: &Array) -> Self::Item;
+    fn visit_map(&mut self, expr: &Map) -> Self::Item;
+    fn visit_interpolation(&mut self, expr: &Interpolation) -> Self::Item;
+    fn visit_spread(&mut self, expr: &Spread) -> Self::Item;
+    fn visit_literal(&mut self, expr: &Object, span: Span) -> Self::Item;
 }
 
 impl Expr {
@@ -70,17 +112,81 @@ impl Expr {
             Expr::Lambda(expr) => visitor.visit_lambda(expr),
             Expr::Assignment(expr) => visitor.visit_assignment(expr),
             Expr::Set(expr) => visitor.visit_set(expr),
+            Expr::IndexSet(expr) => visitor.visit_index_set(expr),
             Expr::Ternary(expr) => visitor.visit_ternary(expr),
             Expr::Logical(expr) => visitor.visit_logical(expr),
             Expr::Binary(expr) => visitor.visit_binary(expr),
             Expr::Unary(expr) => visitor.visit_unary(expr),
+            Expr::Await(expr) => visitor.visit_await(expr),
             Expr::Call(expr) => visitor.visit_call(expr),
             Expr::Get(expr) => visitor.visit_get(expr),
-            Expr::Grouping(expr) => visitor.visit_grouping(expr),
+            Expr::Index(expr) => visitor.visit_index(expr),
+            Expr::Slice(expr) => visitor.visit_slice(expr),
+            Expr::Grouping(expr, span) => visitor.visit_grouping(expr, *span),
             Expr::Variable(expr) => visitor.visit_variable(expr),
             Expr::This(expr) => visitor.visit_this(expr),
             Expr::Super(expr) => visitor.visit_super(expr),
-            Expr::Literal(expr) => visitor.visit_literal(expr),
+            Expr::Array(expr) => visitor.visit_array(expr),
+            Expr::Map(expr) => visitor.visit_map(expr),
+            Expr::Interpolation(expr) => visitor.visit_interpolation(expr),
+            Expr::Spread(expr) => visitor.visit_spread(expr),
+            Expr::Literal(expr, span) => visitor.visit_literal(expr, *span),
+        }
+    }
+
+    /// The line the expression starts on, as far as its tokens tell.
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            Expr::Comma(expr) => expr.left.line(),
+            Expr::Lambda(expr) => Some(expr.name.line),
+            Expr::Assignment(expr) => Some(expr.name.line),
+            Expr::Set(expr) => Some(expr.name.line),
+            Expr::IndexSet(expr) => Some(expr.bracket.line),
+            Expr::Ternary(expr) => expr.condition.line(),
+            Expr::Logical(expr) => Some(expr.operator.line),
+            Expr::Binary(expr) => Some(expr.operator.line),
+            Expr::Unary(expr) => Some(expr.operator.line),
+            Expr::Await(expr) => Some(expr.keyword.line),
+            Expr::Call(expr) => Some(expr.paren.line),
+            Expr::Get(expr) => Some(expr.name.line),
+            Expr::Index(expr) => Some(expr.bracket.line),
+            Expr::Slice(expr) => Some(expr.bracket.line),
+            Expr::Grouping(expr, _) => expr.line(),
+            Expr::Variable(token) | Expr::This(token) => Some(token.line),
+            Expr::Super(expr) => Some(expr.keyword.line),
+            Expr::Array(expr) => Some(expr.bracket.line),
+            Expr::Map(expr) => Some(expr.brace.line),
+            Expr::Interpolation(expr) => expr.parts.first().and_then(Expr::line),
+            Expr::Spread(expr) => Some(expr.ellipsis.line),
+            Expr::Literal(..) => None,
+        }
+    }
+
+    /// Where the expression is in its source, from its first token to its
+    /// last. Parentheses are only part of it on a grouping.
+    pub fn span(&self) -> Span {
+        match self {
+            Expr::Comma(expr) => expr.left.span().to(expr.right.span()),
+            Expr::Lambda(expr) => expr.span,
+            Expr::Assignment(expr) => expr.name.span.to(expr.value.span()),
+            Expr::Set(expr) => expr.object.span().to(expr.value.span()),
+            Expr::IndexSet(expr) => expr.object.span().to(expr.value.span()),
+            Expr::Ternary(expr) => expr.condition.span().to(expr.falsy.span()),
+            Expr::Logical(expr) => expr.left.span().to(expr.right.span()),
+            Expr::Binary(expr) => expr.left.span().to(expr.right.span()),
+            Expr::Unary(expr) => expr.operator.span.to(expr.right.span()),
+            Expr::Await(expr) => expr.keyword.span.to(expr.value.span()),
+            Expr::Call(expr) => expr.callee.span().to(expr.paren.span),
+            Expr::Get(expr) => expr.object.span().to(expr.name.span),
+            Expr::Index(expr) => expr.span,
+            Expr::Slice(expr) => expr.span,
+            Expr::Grouping(_, span) | Expr::Literal(_, span) => *span,
+            Expr::Variable(token) | Expr::This(token) => token.span,
+            Expr::Super(expr) => expr.keyword.span.to(expr.method.span),
+            Expr::Array(expr) => expr.span,
+            Expr::Map(expr) => expr.span,
+            Expr::Interpolation(expr) => expr.span,
+            Expr::Spread(expr) => expr.ellipsis.span.to(expr.value.span()),
         }
     }
 }
@@ -0,0 +1,12 @@
+use crate::token::Token;
+
+use super::Expr;
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Await {
+    pub keyword: Token,
+    /// The promise waited for, any other value is waited for as a promise
+    /// fulfilled with it.
+    pub value: Expr,
+}
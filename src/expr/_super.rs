@@ -1,7 +1,16 @@
 use crate::token::Token;
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Super {
     pub keyword: Token,
     pub method: Token,
 }
+
+impl Super {
+    /// Whether it's written `super(...)`, calling the super class's `init`
+    /// through a method token that takes the place of the keyword.
+    pub fn is_shorthand(&self) -> bool {
+        self.method.span == self.keyword.span
+    }
+}
@@ -0,0 +1,11 @@
+use crate::token::{Span, Token};
+
+use super::Expr;
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Array {
+    pub bracket: Token,
+    pub elements: Vec<Expr>,
+    pub span: Span,
+}
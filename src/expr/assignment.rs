@@ -3,6 +3,7 @@ use crate::token::Token;
 use super::Expr;
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Assignment {
     pub name: Token,
     pub value: Expr,
@@ -3,6 +3,7 @@ use crate::token::Token;
 use super::Expr;
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Call {
     pub callee: Expr,
     pub arguments: Vec<Expr>,
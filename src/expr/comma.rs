@@ -1,6 +1,7 @@
 use super::Expr;
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Comma {
     pub left: Expr,
     pub right: Expr,
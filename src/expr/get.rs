@@ -3,7 +3,10 @@ use crate::token::Token;
 use super::Expr;
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Get {
     pub object: Expr,
     pub name: Token,
+    /// Written `object?.name`, yields `undefined` when the object is.
+    pub optional: bool,
 }
@@ -0,0 +1,12 @@
+use crate::token::{Span, Token};
+
+use super::Expr;
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Index {
+    pub object: Expr,
+    pub bracket: Token,
+    pub index: Expr,
+    pub span: Span,
+}
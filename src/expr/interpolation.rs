@@ -0,0 +1,10 @@
+use crate::token::Span;
+
+use super::Expr;
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Interpolation {
+    pub parts: Vec<Expr>,
+    pub span: Span,
+}
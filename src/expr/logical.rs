@@ -3,6 +3,7 @@ use crate::token::Token;
 use super::Expr;
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Logical {
     pub operator: Token,
     pub left: Expr,
@@ -0,0 +1,14 @@
+use crate::token::{Span, Token};
+
+use super::Expr;
+
+/// `object[start:end]`, either bound can be left out.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Slice {
+    pub object: Expr,
+    pub bracket: Token,
+    pub start: Option<Expr>,
+    pub end: Option<Expr>,
+    pub span: Span,
+}
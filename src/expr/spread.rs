@@ -0,0 +1,10 @@
+use crate::token::Token;
+
+use super::Expr;
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Spread {
+    pub ellipsis: Token,
+    pub value: Expr,
+}
@@ -1,6 +1,7 @@
 use super::Expr;
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Ternary {
     pub condition: Expr,
     pub truth: Expr,
@@ -0,0 +1,917 @@
+use std::{collections::VecDeque, rc::Rc};
+
+use crate::{
+    errors::Diagnostic,
+    expr::{self, Expr, ExprVisitor},
+    object::Object,
+    parser::Parser,
+    scanner::{Comment, Scanner, KEYWORDS},
+    stmt::{self, Stmt, StmtVisitor},
+    token::{Span, Token},
+    token_type::TokenType,
+};
+
+/// Reformats `source` into the canonical layout: four space indentation, one
+/// statement per line, braces on the same line and a blank line around
+/// function and class declarations. Blank lines separating statements in the
+/// source are kept, several in a row become one.
+///
+/// Comments aren't part of the syntax tree, they're kept on their own line
+/// before the statement following them, or at the end of the line for
+/// comments trailing a single line statement. Comments before a closing brace
+/// stay at the end of its block, and comments among the items of an array or
+/// map put the items one per line.
+///
+/// ```
+/// use typhoon::formatter;
+///
+/// let formatted = formatter::format("var a=1;if(a>0){print a;}").unwrap();
+///
+/// assert_eq!(formatted, "var a = 1;\nif (a > 0) {\n    print a;\n}\n");
+/// ```
+pub fn format(source: &str) -> Result<String, Vec<Diagnostic>> {
+    let (tokens, comments) = Scanner::new(String::from(source)).scan_with_comments()?;
+    let statements = Parser::new(tokens).parse()?;
+    let mut formatter = Formatter {
+        indent: 0,
+        comments: VecDeque::from(comments),
+        source_lines: source
+            .split_inclusive('\n')
+            .scan(0, |start, line| {
+                let line_start = *start;
+
+                *start += line.len();
+
+                Some((line_start, line.trim().is_empty()))
+            })
+            .collect(),
+    };
+    let mut formatted = formatter.lines(&statements);
+
+    formatter.leading_comments(&mut formatted, Some(usize::MAX));
+
+    Ok(formatted)
+}
+
+/// Formats a single statement in the canonical layout, without comments.
+pub(crate) fn format_stmt(stmt: &Stmt) -> String {
+    stmt.accept(&mut Formatter {
+        indent: 0,
+        comments: VecDeque::new(),
+        source_lines: vec![],
+    })
+}
+
+/// Formats a single expression in the canonical layout, without comments.
+pub(crate) fn format_expr(expr: &Expr) -> String {
+    expr.accept(&mut Formatter {
+        indent: 0,
+        comments: VecDeque::new(),
+        source_lines: vec![],
+    })
+}
+
+struct Formatter {
+    indent: usize,
+    comments: VecDeque<Comment>,
+    /// The offset each line of the source starts at and whether it's blank,
+    /// empty when formatting a tree without its source.
+    source_lines: Vec<(usize, bool)>,
+}
+
+impl Formatter {
+    fn pad(&self) -> String {
+        "    ".repeat(self.indent)
+    }
+
+    /// Formats `stmts` one per line at the current indentation, each line is
+    /// terminated by a newline.
+    fn lines(&mut self, stmts: &[Stmt]) -> String {
+        let mut lines = String::new();
+
+        for (i, stmt) in stmts.iter().enumerate() {
+            if i > 0
+                && (Formatter::is_declaration(&stmts[i - 1]) || Formatter::is_declaration(stmt))
+            {
+                lines.push('\n');
+            }
+
+            let line = self.start_line(stmt);
+
+            self.leading_comments(&mut lines, line);
+
+            let formatted = stmt.accept(self);
+
+            if let Some(line) = line {
+                self.blank_line(&mut lines, line);
+            }
+
+            lines.push_str(&self.pad());
+            lines.push_str(&formatted);
+
+            if !formatted.contains('\n') {
+                lines.push_str(&self.trailing_comment(line));
+            }
+
+            lines.push('\n');
+        }
+
+        lines
+    }
+
+    /// The line `stmt` starts on, its first token's rather than the one
+    /// [`Stmt::line`] reports for errors.
+    fn start_line(&self, stmt: &Stmt) -> Option<usize> {
+        self.line_at(stmt.span().start).or_else(|| stmt.line())
+    }
+
+    /// The line of the source `offset` is on, `None` without the source.
+    fn line_at(&self, offset: u32) -> Option<usize> {
+        if self.source_lines.is_empty() {
+            return None;
+        }
+
+        Some(
+            self.source_lines
+                .partition_point(|(line_start, _)| *line_start <= offset as usize),
+        )
+    }
+
+    /// The line of the bracket closing `span`.
+    fn end_line(&self, span: Span) -> Option<usize> {
+        self.line_at(span.end.saturating_sub(1))
+    }
+
+    /// Keeps the blank line the source has above `line` in `lines`, unless
+    /// it's the start of a block or there's one already.
+    fn blank_line(&self, lines: &mut String, line: usize) {
+        let blank_above = line >= 2
+            && self
+                .source_lines
+                .get(line - 2)
+                .is_some_and(|(_, blank)| *blank);
+
+        if blank_above && !lines.is_empty() && !lines.ends_with("\n\n") {
+            lines.push('\n');
+        }
+    }
+
+    /// Moves the comments found before `line` to `lines`, one per line.
+    fn leading_comments(&mut self, lines: &mut String, line: Option<usize>) {
+        let Some(line) = line else {
+            return;
+        };
+
+        while let Some(comment) = self.comments.front() {
+            if comment.line >= line {
+                break;
+            }
+
+            let comment = self.comments.pop_front().unwrap();
+
+            self.blank_line(lines, comment.line);
+            lines.push_str(&self.pad());
+            lines.push_str(&comment.text);
+            lines.push('\n');
+        }
+    }
+
+    fn trailing_comment(&mut self, line: Option<usize>) -> String {
+        match self.comments.front() {
+            Some(comment) if comment.trailing && Some(comment.line) == line => {
+                let comment = self.comments.pop_front().unwrap();
+
+                format!(" {}", comment.text)
+            }
+            _ => String::new(),
+        }
+    }
+
+    fn is_declaration(stmt: &Stmt) -> bool {
+        match stmt {
+            Stmt::Function(_) | Stmt::Class(_) => true,
+            Stmt::Export(export) => Formatter::is_declaration(&export.declaration),
+            _ => false,
+        }
+    }
+
+    /// Formats the block of `stmts` closed by the end of `span`, keeping the
+    /// comments written before its `}` inside it.
+    fn block(&mut self, stmts: &[Stmt], span: Span) -> String {
+        self.indent += 1;
+
+        let mut lines = self.lines(stmts);
+
+        self.leading_comments(&mut lines, self.end_line(span));
+        self.indent -= 1;
+
+        if lines.is_empty() {
+            return String::from("{}");
+        }
+
+        format!("{{\n{lines}{}}}", self.pad())
+    }
+
+    /// Lays out `items` one per line when comments are written between them
+    /// and the end of `span`, so they stay in place, returning the lines
+    /// without their brackets. `None` when there are no such comments.
+    fn commented_items<T>(
+        &mut self,
+        items: &[T],
+        span: Span,
+        item_span: impl Fn(&T) -> Span,
+        format: impl Fn(&mut Formatter, &T) -> String,
+    ) -> Option<String> {
+        let end = self.end_line(span)?;
+
+        if self
+            .comments
+            .front()
+            .is_none_or(|comment| comment.line >= end)
+        {
+            return None;
+        }
+
+        self.indent += 1;
+
+        let mut lines = String::new();
+
+        for (i, item) in items.iter().enumerate() {
+            let line = self.line_at(item_span(item).start);
+
+            self.leading_comments(&mut lines, line);
+
+            let formatted = format(self, item);
+
+            if let Some(line) = line {
+                self.blank_line(&mut lines, line);
+            }
+
+            lines.push_str(&self.pad());
+            lines.push_str(&formatted);
+
+            if i + 1 < items.len() {
+                lines.push(',');
+            }
+
+            lines.push_str(&self.trailing_comment(line));
+            lines.push('\n');
+        }
+
+        self.leading_comments(&mut lines, Some(end));
+        self.indent -= 1;
+
+        Some(lines)
+    }
+
+    /// Formats the body of a control flow statement, including the space
+    /// separating it from the header.
+    fn body(&mut self, stmt: &Stmt) -> String {
+        match stmt {
+            Stmt::Empty(_) => String::from(";"),
+            Stmt::Block(stmts, span) if !Formatter::is_destructuring_assignment(stmts) => {
+                format!(" {}", self.block(stmts, *span))
+            }
+            _ => format!(" {}", stmt.accept(self)),
+        }
+    }
+
+    /// Whether `name` is the hidden variable the parser stores the value of a
+    /// destructuring pattern in, named after its opening bracket.
+    fn is_pattern(name: &Token) -> bool {
+        matches!(
+            name.token_type,
+            TokenType::LeftBracket | TokenType::LeftBraces
+        )
+    }
+
+    /// Whether `value` reads an element or property of the pattern `hidden`.
+    fn reads_pattern(value: &Expr, hidden: &Token) -> bool {
+        let object = match value {
+            Expr::Index(index) => &index.object,
+            Expr::Slice(slice) => &slice.object,
+            Expr::Get(get) => &get.object,
+            _ => return false,
+        };
+
+        matches!(object, Expr::Variable(variable) if variable.lexeme == hidden.lexeme)
+    }
+
+    /// Whether `stmts` is the block a destructuring assignment is parsed
+    /// into, it starts with the hidden variable declared on its own.
+    fn is_destructuring_assignment(stmts: &[Stmt]) -> bool {
+        match stmts.first() {
+            Some(Stmt::Variable(declarations, _)) => {
+                declarations.len() == 1 && Formatter::is_pattern(&declarations[0].name)
+            }
+            _ => false,
+        }
+    }
+
+    /// Prints back `[targets] = value;` or `({targets} = value);` from the
+    /// block it was parsed into.
+    fn destructuring_assignment(&mut self, stmts: &[Stmt]) -> String {
+        let (Stmt::Variable(declarations, _), assignments) = stmts.split_first().unwrap() else {
+            unreachable!()
+        };
+        let value = declarations[0].initializer.as_ref().unwrap().accept(self);
+        let targets = assignments
+            .iter()
+            .map(|stmt| {
+                let Stmt::Expression(assignment, _) = stmt else {
+                    unreachable!()
+                };
+                let (target, value) = match assignment.as_ref() {
+                    Expr::Assignment(assignment) => {
+                        (assignment.name.lexeme.to_string(), &assignment.value)
+                    }
+                    Expr::Set(set) => (
+                        format!("{}.{}", set.object.accept(self), set.name.lexeme),
+                        &set.value,
+                    ),
+                    Expr::IndexSet(index_set) => (
+                        format!(
+                            "{}[{}]",
+                            index_set.object.accept(self),
+                            index_set.index.accept(self)
+                        ),
+                        &index_set.value,
+                    ),
+                    _ => unreachable!(),
+                };
+
+                match value {
+                    Expr::Slice(_) => format!("...{target}"),
+                    _ => target,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        match declarations[0].name.token_type {
+            TokenType::LeftBracket => format!("[{}] = {value};", targets.join(", ")),
+            _ => format!("({{{}}} = {value});", targets.join(", ")),
+        }
+    }
+
+    fn function(
+        &mut self,
+        name: &str,
+        params: &[Token],
+        rest: &Option<Token>,
+        body: &[Stmt],
+        span: Span,
+    ) -> String {
+        format!(
+            "{name}({}) {}",
+            Self::params(params, rest),
+            self.block(body, span)
+        )
+    }
+
+    /// Formats an arrow function, keeping the expression body shorthand when
+    /// it was written with one.
+    fn arrow_function(&mut self, lambda: &expr::Lambda) -> String {
+        let params = Self::params(&lambda.params, &lambda.rest);
+
+        let body = match lambda.body.as_slice() {
+            [Stmt::Return(ret)] if ret.keyword.token_type == TokenType::Arrow => match &ret.value {
+                Some(value) => value.accept(self),
+                None => String::from("undefined"),
+            },
+            body => self.block(body, lambda.span),
+        };
+
+        let prefix = if lambda.is_async { "async " } else { "" };
+
+        format!("{prefix}({params}) => {body}")
+    }
+
+    fn params(params: &[Token], rest: &Option<Token>) -> String {
+        let mut params = params
+            .iter()
+            .map(|param| param.lexeme.to_string())
+            .collect::<Vec<_>>();
+
+        if let Some(rest) = rest {
+            params.push(format!("...{}", rest.lexeme));
+        }
+
+        params.join(", ")
+    }
+
+    fn list(&mut self, exprs: &[Expr]) -> String {
+        exprs
+            .iter()
+            .map(|expr| expr.accept(self))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn entry(&mut self, (key, value): &(Expr, Expr)) -> String {
+        let key = match key {
+            Expr::Literal(literal, _) => match literal.as_ref() {
+                Object::String(key) if Formatter::is_identifier(key) => key.to_string(),
+                _ => key.accept(self),
+            },
+            _ => key.accept(self),
+        };
+
+        format!("{key}: {}", value.accept(self))
+    }
+
+    fn is_identifier(key: &str) -> bool {
+        let mut chars = key.chars();
+
+        chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+            && !KEYWORDS.contains_key(key)
+    }
+}
+
+impl ExprVisitor for Formatter {
+    type Item = String;
+
+    fn visit_comma(&mut self, expr: &expr::Comma) -> Self::Item {
+        format!("{}, {}", expr.left.accept(self), expr.right.accept(self))
+    }
+
+    fn visit_lambda(&mut self, expr: &Rc<expr::Lambda>) -> Self::Item {
+        if expr.name.token_type == TokenType::Arrow {
+            return self.arrow_function(expr);
+        }
+
+        let head = if expr.is_async { "async fun " } else { "fun " };
+
+        self.function(head, &expr.params, &expr.rest, &expr.body, expr.span)
+    }
+
+    fn visit_assignment(&mut self, expr: &expr::Assignment) -> Self::Item {
+        format!("{} = {}", expr.name.lexeme, expr.value.accept(self))
+    }
+
+    fn visit_set(&mut self, expr: &expr::Set) -> Self::Item {
+        format!(
+            "{}.{} = {}",
+            expr.object.accept(self),
+            expr.name.lexeme,
+            expr.value.accept(self)
+        )
+    }
+
+    fn visit_index_set(&mut self, expr: &expr::IndexSet) -> Self::Item {
+        format!(
+            "{}[{}] = {}",
+            expr.object.accept(self),
+            expr.index.accept(self),
+            expr.value.accept(self)
+        )
+    }
+
+    fn visit_ternary(&mut self, expr: &expr::Ternary) -> Self::Item {
+        format!(
+            "{} ? {} : {}",
+            expr.condition.accept(self),
+            expr.truth.accept(self),
+            expr.falsy.accept(self)
+        )
+    }
+
+    fn visit_logical(&mut self, expr: &expr::Logical) -> Self::Item {
+        format!(
+            "{} {} {}",
+            expr.left.accept(self),
+            expr.operator.lexeme,
+            expr.right.accept(self)
+        )
+    }
+
+    fn visit_binary(&mut self, expr: &expr::Binary) -> Self::Item {
+        format!(
+            "{} {} {}",
+            expr.left.accept(self),
+            expr.operator.lexeme,
+            expr.right.accept(self)
+        )
+    }
+
+    fn visit_unary(&mut self, expr: &expr::Unary) -> Self::Item {
+        format!("{}{}", expr.operator.lexeme, expr.right.accept(self))
+    }
+
+    fn visit_await(&mut self, expr: &expr::Await) -> Self::Item {
+        format!("await {}", expr.value.accept(self))
+    }
+
+    fn visit_call(&mut self, expr: &expr::Call) -> Self::Item {
+        format!(
+            "{}({})",
+            expr.callee.accept(self),
+            self.list(&expr.arguments)
+        )
+    }
+
+    fn visit_get(&mut self, expr: &expr::Get) -> Self::Item {
+        let operator = if expr.optional { "?." } else { "." };
+
+        format!("{}{operator}{}", expr.object.accept(self), expr.name.lexeme)
+    }
+
+    fn visit_index(&mut self, expr: &expr::Index) -> Self::Item {
+        format!("{}[{}]", expr.object.accept(self), expr.index.accept(self))
+    }
+
+    fn visit_slice(&mut self, expr: &expr::Slice) -> Self::Item {
+        let start = expr.start.as_ref().map(|start| start.accept(self));
+        let end = expr.end.as_ref().map(|end| end.accept(self));
+
+        format!(
+            "{}[{}:{}]",
+            expr.object.accept(self),
+            start.unwrap_or_default(),
+            end.unwrap_or_default()
+        )
+    }
+
+    fn visit_grouping(&mut self, expr: &Expr, _span: Span) -> Self::Item {
+        format!("({})", expr.accept(self))
+    }
+
+    fn visit_variable(&mut self, expr: &Token) -> Self::Item {
+        expr.lexeme.to_string()
+    }
+
+    fn visit_this(&mut self, _: &Token) -> Self::Item {
+        String::from("this")
+    }
+
+    fn visit_super(&mut self, expr: &expr::Super) -> Self::Item {
+        if expr.is_shorthand() {
+            return String::from("super");
+        }
+
+        format!("super.{}", expr.method.lexeme)
+    }
+
+    fn visit_array(&mut self, expr: &expr::Array) -> Self::Item {
+        let commented = self.commented_items(
+            &expr.elements,
+            expr.span,
+            Expr::span,
+            |formatter, element| element.accept(formatter),
+        );
+
+        match commented {
+            Some(lines) => format!("[\n{lines}{}]", self.pad()),
+            None => format!("[{}]", self.list(&expr.elements)),
+        }
+    }
+
+    fn visit_map(&mut self, expr: &expr::Map) -> Self::Item {
+        let commented = self.commented_items(
+            &expr.entries,
+            expr.span,
+            |(key, _)| key.span(),
+            Formatter::entry,
+        );
+
+        if let Some(lines) = commented {
+            return format!("{{\n{lines}{}}}", self.pad());
+        }
+
+        let entries = expr
+            .entries
+            .iter()
+            .map(|entry| self.entry(entry))
+            .collect::<Vec<_>>();
+
+        format!("{{{}}}", entries.join(", "))
+    }
+
+    fn visit_interpolation(&mut self, expr: &expr::Interpolation) -> Self::Item {
+        let mut string = String::from("\"");
+
+        for part in &expr.parts {
+            match part {
+                Expr::Literal(literal, _) => match literal.as_ref() {
+                    Object::String(text) => string.push_str(text),
+                    _ => string.push_str(&format!("${{{}}}", part.accept(self))),
+                },
+                _ => string.push_str(&format!("${{{}}}", part.accept(self))),
+            }
+        }
+
+        string.push('"');
+        string
+    }
+
+    fn visit_spread(&mut self, expr: &expr::Spread) -> Self::Item {
+        format!("...{}", expr.value.accept(self))
+    }
+
+    fn visit_literal(&mut self, expr: &Object, _span: Span) -> Self::Item {
+        match expr {
+            Object::String(string) => format!("\"{string}\""),
+            // Keeps integral floats apart from ints, `7.0` rather than `7`.
+            Object::Number(number) if number.fract() == 0.0 => format!("{number:.1}"),
+            _ => expr.to_string(),
+        }
+    }
+}
+
+impl StmtVisitor for Formatter {
+    type Item = String;
+
+    fn visit_empty_stmt(&mut self, _span: Span) -> Self::Item {
+        String::from(";")
+    }
+
+    fn visit_expression_stmt(&mut self, stmt: &Expr, _span: Span) -> Self::Item {
+        format!("{};", stmt.accept(self))
+    }
+
+    fn visit_print_stmt(&mut self, stmt: &Expr, _span: Span) -> Self::Item {
+        format!("print {};", stmt.accept(self))
+    }
+
+    fn visit_variable_stmt(
+        &mut self,
+        stmt: &[stmt::VariableDeclaration],
+        _span: Span,
+    ) -> Self::Item {
+        let keyword = if stmt.iter().any(|variable| variable.is_const) {
+            "const"
+        } else {
+            "var"
+        };
+        let mut declarations = vec![];
+        let mut variables = stmt.iter().peekable();
+
+        while let Some(variable) = variables.next() {
+            if !Formatter::is_pattern(&variable.name) {
+                declarations.push(match &variable.initializer {
+                    Some(initializer) => {
+                        format!("{} = {}", variable.name.lexeme, initializer.accept(self))
+                    }
+                    None => variable.name.lexeme.to_string(),
+                });
+                continue;
+            }
+
+            // The variables following the hidden one read the names of the
+            // pattern out of it.
+            let mut names = vec![];
+
+            while let Some(name) = variables.next_if(|name| {
+                name.initializer
+                    .as_ref()
+                    .is_some_and(|value| Formatter::reads_pattern(value, &variable.name))
+            }) {
+                names.push(match name.initializer {
+                    Some(Expr::Slice(_)) => format!("...{}", name.name.lexeme),
+                    _ => name.name.lexeme.to_string(),
+                });
+            }
+
+            let value = variable.initializer.as_ref().unwrap().accept(self);
+
+            declarations.push(match variable.name.token_type {
+                TokenType::LeftBracket => format!("[{}] = {value}", names.join(", ")),
+                _ => format!("{{{}}} = {value}", names.join(", ")),
+            });
+        }
+
+        format!("{keyword} {};", declarations.join(", "))
+    }
+
+    fn visit_block_stmt(&mut self, stmt: &[Stmt], span: Span) -> Self::Item {
+        if Formatter::is_destructuring_assignment(stmt) {
+            return self.destructuring_assignment(stmt);
+        }
+
+        self.block(stmt, span)
+    }
+
+    fn visit_if_stmt(&mut self, stmt: &stmt::If) -> Self::Item {
+        let mut string = format!(
+            "if ({}){}",
+            stmt.condition.accept(self),
+            self.body(&stmt.truth)
+        );
+
+        if let Some(falsy) = &stmt.falsy {
+            if matches!(stmt.truth, Stmt::Block(..)) {
+                string.push(' ');
+            } else {
+                string.push('\n');
+                string.push_str(&self.pad());
+            }
+
+            string.push_str("else");
+            string.push_str(&self.body(falsy));
+        }
+
+        string
+    }
+
+    fn visit_while_stmt(&mut self, stmt: &stmt::While) -> Self::Item {
+        format!(
+            "while ({}){}",
+            stmt.condition.accept(self),
+            self.body(&stmt.body)
+        )
+    }
+
+    fn visit_for_stmt(&mut self, stmt: &stmt::For) -> Self::Item {
+        let initializer = match &stmt.initializer {
+            Some(initializer) => initializer.accept(self),
+            None => String::from(";"),
+        };
+        let condition = match &stmt.condition {
+            Some(condition) => format!(" {};", condition.accept(self)),
+            None => String::from(";"),
+        };
+        let increment = match &stmt.increment {
+            Some(increment) => format!(" {}", increment.accept(self)),
+            None => String::new(),
+        };
+
+        format!(
+            "for ({initializer}{condition}{increment}){}",
+            self.body(&stmt.body)
+        )
+    }
+
+    fn visit_for_in_stmt(&mut self, stmt: &stmt::ForIn) -> Self::Item {
+        format!(
+            "for (var {} in {}){}",
+            stmt.name.lexeme,
+            stmt.iterable.accept(self),
+            self.body(&stmt.body)
+        )
+    }
+
+    fn visit_break_stmt(&mut self, _: &Token, _span: Span) -> Self::Item {
+        String::from("break;")
+    }
+
+    fn visit_continue_stmt(&mut self, _: &Token, _span: Span) -> Self::Item {
+        String::from("continue;")
+    }
+
+    fn visit_function_stmt(&mut self, stmt: &Rc<stmt::Function>) -> Self::Item {
+        let name = match stmt.is_async {
+            true => format!("async fun {}", stmt.name.lexeme),
+            false => format!("fun {}", stmt.name.lexeme),
+        };
+
+        self.function(&name, &stmt.params, &stmt.rest, &stmt.body, stmt.span)
+    }
+
+    fn visit_return_stmt(&mut self, stmt: &stmt::Return) -> Self::Item {
+        match &stmt.value {
+            Some(value) => format!("return {};", value.accept(self)),
+            None => String::from("return;"),
+        }
+    }
+
+    fn visit_class_stmt(&mut self, stmt: &Rc<stmt::Class>) -> Self::Item {
+        let mut string = format!("class {}", stmt.name.lexeme);
+
+        if let Some(super_class) = &stmt.super_class {
+            string.push_str(&format!(" < {}", super_class.accept(self)));
+        }
+
+        self.indent += 1;
+
+        let mut members = vec![];
+
+        if !stmt.static_fields.is_empty() {
+            let mut fields = String::new();
+
+            for field in &stmt.static_fields {
+                self.leading_comments(&mut fields, Some(field.name.line));
+                self.blank_line(&mut fields, field.name.line);
+                fields.push_str(&self.pad());
+                fields.push_str(&match &field.initializer {
+                    Some(initializer) => {
+                        format!(
+                            "class {} = {};",
+                            field.name.lexeme,
+                            initializer.accept(self)
+                        )
+                    }
+                    None => format!("class {};", field.name.lexeme),
+                });
+                fields.push_str(&self.trailing_comment(Some(field.name.line)));
+                fields.push('\n');
+            }
+
+            members.push(fields);
+        }
+
+        let methods = stmt
+            .statics
+            .iter()
+            .map(|method| (method, "class "))
+            .chain(stmt.methods.iter().map(|method| (method, "")));
+
+        for (method, prefix) in methods {
+            if let Stmt::Function(function) = method {
+                let mut member = String::new();
+
+                self.leading_comments(&mut member, Some(function.name.line));
+                let is_async = if function.is_async { "async " } else { "" };
+                let name = format!(
+                    "{}{}{}{}",
+                    self.pad(),
+                    prefix,
+                    is_async,
+                    function.name.lexeme
+                );
+
+                member.push_str(&self.function(
+                    &name,
+                    &function.params,
+                    &function.rest,
+                    &function.body,
+                    function.span,
+                ));
+                member.push('\n');
+                members.push(member);
+            }
+        }
+
+        let end = self.end_line(stmt.span);
+
+        match members.last_mut() {
+            Some(last) => self.leading_comments(last, end),
+            None => {
+                let mut comments = String::new();
+
+                self.leading_comments(&mut comments, end);
+
+                if !comments.is_empty() {
+                    members.push(comments);
+                }
+            }
+        }
+
+        self.indent -= 1;
+
+        if members.is_empty() {
+            return format!("{string} {{}}");
+        }
+
+        format!("{string} {{\n{}{}}}", members.join("\n"), self.pad())
+    }
+
+    fn visit_try_stmt(&mut self, stmt: &stmt::Try) -> Self::Item {
+        let mut string = format!("try {}", self.block(&stmt.body, stmt.body_span));
+
+        if let Some(catch) = &stmt.catch {
+            match &catch.name {
+                Some(name) => string.push_str(&format!(" catch ({}) ", name.lexeme)),
+                None => string.push_str(" catch "),
+            }
+
+            string.push_str(&self.block(&catch.body, catch.span));
+        }
+
+        if let Some(finally) = &stmt.finally {
+            string.push_str(&format!(" finally {}", self.block(finally, stmt.span)));
+        }
+
+        string
+    }
+
+    fn visit_throw_stmt(&mut self, stmt: &stmt::Throw) -> Self::Item {
+        format!("throw {};", stmt.value.accept(self))
+    }
+
+    fn visit_yield_stmt(&mut self, stmt: &stmt::Yield) -> Self::Item {
+        match &stmt.value {
+            Some(value) => format!("yield {};", value.accept(self)),
+            None => String::from("yield;"),
+        }
+    }
+
+    fn visit_exit_stmt(&mut self, stmt: &stmt::Exit) -> Self::Item {
+        match &stmt.code {
+            Some(code) => format!("exit({});", code.accept(self)),
+            None => String::from("exit;"),
+        }
+    }
+
+    fn visit_import_stmt(&mut self, stmt: &stmt::Import) -> Self::Item {
+        match stmt.native {
+            true => format!("import native {};", stmt.path.lexeme),
+            false => format!("import {};", stmt.path.lexeme),
+        }
+    }
+
+    fn visit_export_stmt(&mut self, stmt: &stmt::Export) -> Self::Item {
+        format!("export {}", stmt.declaration.accept(self))
+    }
+}
@@ -1,24 +1,127 @@
+mod arrays;
+mod coverage;
+mod dates;
+mod event_loop;
+mod functions;
+pub(crate) mod generators;
 mod globals;
-mod operations;
-
-use std::{cell::RefCell, collections::HashMap, rc::Rc};
+#[cfg(feature = "http")]
+mod http;
+mod journal;
+mod limits;
+mod numbers;
+pub(crate) mod operations;
+mod output;
+mod profiler;
+pub(crate) mod promises;
+mod random;
+mod reflection;
+mod regexes;
+#[cfg(feature = "serde")]
+mod snapshot;
+#[cfg(feature = "net")]
+mod sockets;
+mod strings;
+mod trace;
+
+pub use coverage::Coverage;
+pub use journal::Journal;
+pub use limits::Limits;
+pub use output::CapturedOutput;
+pub use profiler::Profiler;
+#[cfg(feature = "serde")]
+pub use snapshot::Snapshot;
+
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    rc::Rc,
+    thread,
+    time::{Duration, Instant},
+};
 
 use crate::{
-    environment::Environment,
-    errors::{RuntimeError, VMException},
+    environment::{Environment, CLASS_SLOT, SUPER_SLOT},
+    errors::{CallFrame, Diagnostic, Limit, Lint, LintLevel, RuntimeError, TailCall, VMException},
     expr::{self, Expr, ExprVisitor},
-    object::{Callable, Class, Function, Instance, Object},
+    literal_type::LiteralType,
+    object::{
+        Callable, CallableInstance, Class, FromObject, Function, Instance, NativeFunction,
+        NativeModule, Object, ObjectIter, ResolvableFunction,
+    },
     stmt::{self, Stmt, StmtVisitor},
-    token::Token,
+    token::{Span, Token},
     token_type::TokenType,
-    utils::{bool_to_number, is_truthy},
+    utils::is_truthy,
     Lib,
 };
 
+use random::Rng;
+
+/// What a `for-in` loop walks: a built-in collection, the `__next` method
+/// of an instance following the iterator protocol, or the native iterator
+/// of an instance such as a generator.
+enum Iteration {
+    Builtin(ObjectIter),
+    Protocol(Rc<dyn Callable>),
+    Steps(Rc<dyn Callable>),
+}
+
 pub struct Interpreter {
+    root: Rc<RefCell<Environment>>,
     globals: Rc<RefCell<Environment>>,
     environment: Rc<RefCell<Environment>>,
-    locals: HashMap<String, usize>,
+    modules: HashMap<PathBuf, HashMap<String, Object>>,
+    /// The modules of natives registered by the host, by their name.
+    native_modules: HashMap<String, Rc<dyn NativeModule>>,
+    module_stack: Vec<PathBuf>,
+    call_stack: Vec<CallFrame>,
+    backtrace: Vec<CallFrame>,
+    /// `try` statements enclosing the code running in the current call, a
+    /// `return` inside one can't be turned into a tail call.
+    try_depth: usize,
+    /// Logs every statement and expression to stderr when set.
+    trace: bool,
+    /// Whether scripts are run with their constant expressions folded.
+    fold_constants: bool,
+    /// Whether dividing by zero gives infinity or NaN instead of an error.
+    ieee_division: bool,
+    /// The lints whose level differs from their default one.
+    lint_levels: HashMap<Lint, LintLevel>,
+    /// Whether the warnings of every lint are errors.
+    deny_warnings: bool,
+    profiler: Option<Profiler>,
+    coverage: Option<Coverage>,
+    limits: Limits,
+    usage: limits::Usage,
+    /// The limit the last run went over.
+    exceeded_limit: Option<Limit>,
+    /// The code the last run was ended with by `exit`.
+    exit_code: Option<i32>,
+    /// The generator of random numbers, seeded with `Random.seed`.
+    rng: Rng,
+    /// The environment variables set with `Env.set`, read before the ones
+    /// of the process, which are left untouched as other threads may be
+    /// reading them.
+    env_vars: HashMap<String, String>,
+    /// The results of nondeterministic natives being recorded or replayed.
+    journal: Option<Journal>,
+    event_loop: event_loop::EventLoop,
+    /// The outcome of the promise an async function is resumed with, what
+    /// the `await` it was suspended on evaluates to.
+    awaited: Option<Result<Object, Object>>,
+    /// Where `print` writes to, stdout unless redirected.
+    output: Box<dyn Write>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Interpreter {
@@ -27,92 +130,1065 @@ impl Interpreter {
 
         globals
             .borrow_mut()
-            .define("clock", Object::Callable(Rc::new(globals::Clock)));
+            .define("clock", Object::Callable(Rc::new(globals::Clock)))
+            .define("now", globals::now())
+            .define("sleep", Object::Callable(Rc::new(globals::Sleep)))
+            .define("repeat", Object::Callable(Rc::new(globals::Repeat)))
+            .define("Date", Object::Instance(Rc::new(globals::date())))
+            .define("Math", Object::Instance(Rc::new(globals::math())))
+            .define("Number", Object::Instance(Rc::new(globals::number())))
+            .define("File", Object::Instance(Rc::new(globals::file())))
+            .define("Regex", Object::Instance(Rc::new(regexes::regex())))
+            .define(
+                "Random",
+                Object::Instance(Rc::new(globals::random_namespace())),
+            )
+            .define("random", globals::random())
+            .define("randomInt", globals::random_int())
+            .define("Env", Object::Instance(Rc::new(globals::env())))
+            .define("ARGS", Object::Array(Rc::default()))
+            .define("type", globals::type_of())
+            .define("keys", reflection::keys())
+            .define("hasField", reflection::has_field())
+            .define("getField", reflection::get_field())
+            .define("setField", reflection::set_field())
+            .define("methods", reflection::methods())
+            .define("seal", reflection::seal())
+            .define("freeze", reflection::freeze())
+            .define("isNaN", globals::is_nan())
+            .define("assert", globals::assert())
+            .define("input", globals::input())
+            .define("readLine", globals::read_line())
+            .define("eprint", globals::eprint())
+            .define(
+                "Promise",
+                Object::Instance(Rc::new(promises::promise_namespace())),
+            )
+            .define("delay", promises::delay());
+
+        #[cfg(feature = "http")]
+        globals
+            .borrow_mut()
+            .define("Http", Object::Instance(Rc::new(http::http())));
+
+        #[cfg(feature = "net")]
+        globals
+            .borrow_mut()
+            .define("Socket", Object::Instance(Rc::new(sockets::socket())));
 
         Self {
+            root: Rc::clone(&globals),
             environment: Rc::clone(&globals),
             globals,
-            locals: HashMap::new(),
+            modules: HashMap::new(),
+            native_modules: HashMap::new(),
+            module_stack: vec![],
+            call_stack: vec![],
+            backtrace: vec![],
+            try_depth: 0,
+            trace: false,
+            fold_constants: true,
+            ieee_division: false,
+            lint_levels: HashMap::new(),
+            deny_warnings: false,
+            profiler: None,
+            coverage: None,
+            limits: Limits::default(),
+            usage: limits::Usage::default(),
+            exceeded_limit: None,
+            exit_code: None,
+            rng: Rng::from_entropy(),
+            env_vars: HashMap::new(),
+            journal: None,
+            event_loop: event_loop::EventLoop::default(),
+            awaited: None,
+            output: Box::new(io::stdout()),
+            diagnostics: vec![],
         }
     }
 
-    pub fn interpret(&mut self, stmts: &Vec<Stmt>) {
-        for stmt in stmts {
-            if let Err(e) = self.execute(stmt) {
-                match e {
-                    VMException::RuntimeError(runtime_error) => Lib::runtime_error(&runtime_error),
-                    _ => unreachable!(),
-                };
+    /// Redirects what `print` and the prompt of `input` write, e.g. to a
+    /// [`CapturedOutput`].
+    pub fn set_output(&mut self, output: Box<dyn Write>) {
+        self.output = output;
+    }
+
+    /// Writes `text` to the program's output, see [`Interpreter::set_output`].
+    pub fn write_output(&mut self, text: &str) -> Result<(), RuntimeError> {
+        self.output
+            .write_all(text.as_bytes())
+            .and_then(|()| self.output.flush())
+            .map_err(|err| self.native_error(&format!("Can't write output: {err}")))
+    }
+
+    pub fn interpret(&mut self, stmts: &[Stmt]) -> Result<(), RuntimeError> {
+        self.start_run();
+
+        let result = self
+            .execute_top_level(stmts)
+            .and_then(|_| self.run_event_loop());
+
+        self.end_run(result, ())
+    }
+
+    /// Evaluates a single top-level expression, used by the REPL to show the
+    /// value of what was typed.
+    pub fn interpret_expression(&mut self, expr: &Expr) -> Result<Object, RuntimeError> {
+        self.start_run();
+
+        let result = self
+            .evaluate(expr)
+            .and_then(|value| self.run_event_loop().map(|()| value));
+
+        self.end_run(result, Object::Undefined)
+    }
+
+    /// Calls `callable` from the host as a run of its own, see
+    /// [`Callback`](crate::object::Callback). Natives calling back into
+    /// scripts during a run use [`Interpreter::call`] instead.
+    pub fn interpret_call(
+        &mut self,
+        callable: Rc<dyn Callable>,
+        arguments: Vec<Object>,
+    ) -> Result<Object, RuntimeError> {
+        self.start_run();
+
+        let call_site = Token::new(TokenType::Eof, "", None, 0, Span::default());
+        let result = self
+            .call(callable, arguments, &call_site)
+            .and_then(|value| self.run_event_loop().map(|()| value));
+
+        self.end_run(result, Object::Undefined)
+    }
+
+    /// Runs `stmts` like [`Interpreter::interpret`], returning the value of
+    /// a top-level `return` that ended them, or else of the last one if it's
+    /// an expression statement.
+    pub fn interpret_last(&mut self, stmts: &[Stmt]) -> Result<Option<Object>, RuntimeError> {
+        self.start_run();
+
+        let (last, rest) = match stmts.split_last() {
+            Some((Stmt::Expression(expr, _), rest)) => (Some(expr), rest),
+            _ => (None, stmts),
+        };
+
+        let result = self
+            .execute_top_level(rest)
+            .and_then(|returned| match (returned, last) {
+                (Some(value), _) => Ok(Some(value)),
+                (None, Some(expr)) => self.evaluate(expr).map(Some),
+                (None, None) => Ok(None),
+            })
+            .and_then(|value| self.run_event_loop().map(|()| value));
+
+        self.end_run(result, None)
+    }
+
+    /// Runs the top-level statements of a script or module, a `return` among
+    /// them ends the run early with its value.
+    fn execute_top_level(&mut self, stmts: &[Stmt]) -> Result<Option<Object>, VMException> {
+        match stmts.iter().try_for_each(|stmt| self.execute(stmt)) {
+            Ok(()) => Ok(None),
+            Err(VMException::ReturnException(value)) => Ok(Some(value)),
+            // There's no call to replace at the top level, the tail call is
+            // made like any other.
+            Err(VMException::TailCallException(tail_call)) => {
+                let TailCall {
+                    callable,
+                    arguments,
+                    call_site,
+                } = *tail_call;
+
+                self.call(callable, arguments, &call_site).map(Some)
             }
+            Err(err) => Err(err),
         }
     }
 
-    fn evaluate(&mut self, expr: &Expr) -> Result<Object, RuntimeError> {
-        expr.accept(self)
+    fn start_run(&mut self) {
+        // Whatever a run that failed left waiting is dropped.
+        self.event_loop = event_loop::EventLoop::default();
+        self.backtrace.clear();
+        self.exceeded_limit = None;
+        self.exit_code = None;
+        self.usage = limits::Usage::start(&self.limits);
     }
 
-    fn evaluate_and_map_error(&mut self, expr: &Expr) -> Result<Object, VMException> {
-        self.evaluate(expr)
-            .map_err(|e| VMException::RuntimeError(e))
+    /// Turns what unwound the run into its result, `exited` is what's
+    /// returned when an `exit` ended it.
+    fn end_run<T>(&mut self, result: Result<T, VMException>, exited: T) -> Result<T, RuntimeError> {
+        match result {
+            Err(VMException::ExitException(code)) => {
+                self.exit_code = Some(code);
+                Ok(exited)
+            }
+            result => result.map_err(Interpreter::uncaught),
+        }
+    }
+
+    /// The code passed to the `exit` that ended the last run, `None` if it
+    /// ran to the end.
+    pub fn exit_code(&self) -> Option<i32> {
+        self.exit_code
+    }
+
+    fn uncaught(exception: VMException) -> RuntimeError {
+        match exception {
+            VMException::RuntimeError(runtime_error)
+            | VMException::LimitExceeded(runtime_error) => runtime_error,
+            VMException::ThrowException(token, value) => RuntimeError {
+                token,
+                message: format!("Uncaught exception: {value}"),
+            },
+            _ => unreachable!(),
+        }
+    }
+
+    /// Forgets what an error `try` just caught left for reporting it.
+    fn caught(&mut self) {
+        self.backtrace.clear();
+        self.exceeded_limit = None;
+    }
+
+    /// Takes the calls that were active when the last uncaught error was
+    /// raised, innermost first.
+    pub fn take_backtrace(&mut self) -> Vec<CallFrame> {
+        let mut backtrace = std::mem::take(&mut self.backtrace);
+
+        backtrace.reverse();
+        backtrace
+    }
+
+    /// Takes the limit the last run was stopped for going over, if any.
+    pub fn take_exceeded_limit(&mut self) -> Option<Limit> {
+        self.exceeded_limit.take()
+    }
+
+    /// Drains the warnings collected while compiling imported modules.
+    pub fn take_diagnostics(&mut self) -> Vec<Diagnostic> {
+        std::mem::take(&mut self.diagnostics)
+    }
+
+    fn evaluate(&mut self, expr: &Expr) -> Result<Object, VMException> {
+        let value = expr.accept(self);
+
+        if self.trace {
+            if let Ok(value) = &value {
+                trace::expression(expr, value, self.call_stack.len());
+            }
+        }
+
+        value
     }
 
     fn execute(&mut self, stmt: &Stmt) -> Result<(), VMException> {
+        self.track(stmt)?;
+
         stmt.accept(self)
     }
 
-    pub fn execute_block(
-        &mut self,
-        stmts: &Vec<Stmt>,
-        env: Environment,
-    ) -> Result<(), VMException> {
+    /// Traces, counts and covers `stmt` before it runs.
+    fn track(&mut self, stmt: &Stmt) -> Result<(), VMException> {
+        if self.trace {
+            trace::statement(stmt, self.call_stack.len());
+        }
+
+        if let Some(limit) = self.usage.step(&self.limits) {
+            let line = stmt.line().unwrap_or(0);
+            let token = Token::new(TokenType::Eof, "", None, line, Span::default());
+
+            return Err(self.limit_exceeded(limit, token));
+        }
+
+        if let Some(coverage) = &mut self.coverage {
+            coverage.hit(&self.globals, stmt.span());
+        }
+
+        Ok(())
+    }
+
+    /// Starts collecting per function call counts and timings, replacing any
+    /// profile collected so far.
+    pub fn start_profiling(&mut self) {
+        self.profiler = Some(Profiler::new());
+    }
+
+    /// Stops profiling and returns what was collected since
+    /// [`Interpreter::start_profiling`].
+    pub fn take_profile(&mut self) -> Option<Profiler> {
+        self.profiler.take()
+    }
+
+    /// Starts counting the statements run in the scripts and modules
+    /// compiled from now on, replacing any coverage collected so far. With
+    /// `listing` the report shows the source of each file with its counts.
+    pub fn start_coverage(&mut self, listing: bool) {
+        self.coverage = Some(Coverage::new(listing));
+    }
+
+    /// Stops collecting coverage and returns what was collected since
+    /// [`Interpreter::start_coverage`].
+    pub fn take_coverage(&mut self) -> Option<Coverage> {
+        self.coverage.take()
+    }
+
+    /// Registers `statements`, compiled from `source`, for coverage as the
+    /// file of the module running, if it's being collected.
+    pub(crate) fn cover(&mut self, source: &str, statements: &[Stmt]) {
+        if let Some(coverage) = &mut self.coverage {
+            let path = self.module_stack.last().map(PathBuf::as_path);
+
+            coverage.add_file(path, source, statements, &self.globals);
+        }
+    }
+
+    /// Enables or disables logging each executed statement and evaluated
+    /// expression to stderr.
+    pub fn set_trace(&mut self, trace: bool) {
+        self.trace = trace;
+    }
+
+    /// Enables or disables folding constant expressions and dropping the
+    /// branches constant conditions never take before running code, it's on
+    /// by default.
+    pub fn set_constant_folding(&mut self, fold: bool) {
+        self.fold_constants = fold;
+    }
+
+    pub(crate) fn folds_constants(&self) -> bool {
+        self.fold_constants
+    }
+
+    /// Sets whether the warnings of `lint` are ignored, reported, or keep
+    /// scripts and the modules they import from running.
+    pub fn set_lint_level(&mut self, lint: Lint, level: LintLevel) {
+        self.lint_levels.insert(lint, level);
+    }
+
+    /// Makes every lint that would warn deny instead, see
+    /// [`Interpreter::set_lint_level`].
+    pub fn set_deny_warnings(&mut self, deny: bool) {
+        self.deny_warnings = deny;
+    }
+
+    pub(crate) fn lint_level(&self, lint: Lint) -> LintLevel {
+        self.lint_level_or(lint, lint.default_level())
+    }
+
+    /// The level of `lint`, `default` unless it was set.
+    pub(crate) fn lint_level_or(&self, lint: Lint, default: LintLevel) -> LintLevel {
+        let level = self.lint_levels.get(&lint).copied().unwrap_or(default);
+
+        if self.deny_warnings && level == LintLevel::Warn {
+            LintLevel::Deny
+        } else {
+            level
+        }
+    }
+
+    /// Sets the `ARGS` global, the arguments the script was run with.
+    pub fn set_args(&mut self, args: Vec<String>) {
+        let args = args
+            .into_iter()
+            .map(|arg| Object::String(Rc::from(arg)))
+            .collect();
+
+        self.globals
+            .borrow_mut()
+            .define("ARGS", Object::Array(Rc::new(RefCell::new(args))));
+    }
+
+    /// Forgets every variable and loaded module, starting over with only the
+    /// builtin globals. The settings, the output and `ARGS` are kept.
+    pub fn reset(&mut self) {
+        let args = self.global("ARGS");
+        let fresh = Interpreter {
+            trace: self.trace,
+            fold_constants: self.fold_constants,
+            ieee_division: self.ieee_division,
+            lint_levels: std::mem::take(&mut self.lint_levels),
+            deny_warnings: self.deny_warnings,
+            profiler: self.profiler.take(),
+            coverage: self.coverage.take(),
+            limits: self.limits,
+            output: std::mem::replace(&mut self.output, Box::new(io::sink())),
+            native_modules: std::mem::take(&mut self.native_modules),
+            ..Interpreter::new()
+        };
+
+        if let Some(args) = args {
+            fresh.globals.borrow_mut().define("ARGS", args);
+        }
+
+        *self = fresh;
+    }
+
+    /// Makes `/` and `%` by zero follow IEEE 754 like floats do, giving
+    /// infinity or NaN instead of failing, integers included. It's off by
+    /// default.
+    pub fn set_ieee_division(&mut self, ieee: bool) {
+        self.ieee_division = ieee;
+    }
+
+    /// Sets the limits every following run is held to, a run going over one
+    /// fails with a [`RuntimeError`] and [`Interpreter::take_exceeded_limit`]
+    /// tells which.
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    /// The names of the global variables, builtins included.
+    pub fn global_names(&self) -> Vec<String> {
+        self.globals
+            .borrow()
+            .names()
+            .map(|name| name.to_string())
+            .collect()
+    }
+
+    /// The value of the global variable `name`, if declared.
+    pub fn global(&self, name: &str) -> Option<Object> {
+        let token = Token::new(TokenType::Identifier, name, None, 0, Span::default());
+
+        self.globals.borrow().get(&token).ok()
+    }
+
+    /// The names of the properties `value.name` can read.
+    pub fn property_names(value: &Object) -> Vec<String> {
+        let builtin: &[&str] = match value {
+            Object::String(_) => &strings::PROPERTIES,
+            Object::Array(_) => &arrays::PROPERTIES,
+            Object::Int(_) | Object::Number(_) => &numbers::PROPERTIES,
+            Object::Callable(_) => &functions::PROPERTIES,
+            Object::Map(map) => return map.borrow().keys().cloned().collect(),
+            Object::Instance(instance) => return instance.property_names(),
+            Object::CallableInstance(class) => return class.property_names(),
+            Object::Undefined | Object::Boolean(_) => &[],
+        };
+
+        builtin.iter().map(|name| String::from(*name)).collect()
+    }
+
+    pub(crate) fn rng(&mut self) -> &mut Rng {
+        &mut self.rng
+    }
+
+    pub(crate) fn env_vars(&mut self) -> &mut HashMap<String, String> {
+        &mut self.env_vars
+    }
+
+    /// Fails natives relying on `feature` on `wasm32`, which has no clock,
+    /// sleep or threads and panics, taking the module down, when asked for
+    /// them.
+    pub(crate) fn check_supported(&self, feature: &str) -> Result<(), RuntimeError> {
+        if cfg!(target_arch = "wasm32") {
+            return Err(self.native_error(&format!("{feature} isn't supported on wasm32")));
+        }
+
+        Ok(())
+    }
+
+    /// Blocks the script for `duration`. A sleep past the run's timeout only
+    /// lasts until it, then stops the run.
+    pub(crate) fn sleep(&mut self, duration: Duration) -> Result<(), VMException> {
+        self.check_supported("Sleeping")?;
+
+        match self.usage.deadline() {
+            Some(deadline) if Instant::now() + duration >= deadline => {
+                thread::sleep(deadline.saturating_duration_since(Instant::now()));
+
+                Err(self.limit_exceeded(Limit::Timeout, self.call_site()))
+            }
+            _ => {
+                thread::sleep(duration);
+
+                Ok(())
+            }
+        }
+    }
+
+    fn limit_exceeded(&mut self, limit: Limit, token: Token) -> VMException {
+        let message = match limit {
+            Limit::Statements => format!(
+                "Exceeded the limit of {} statements",
+                self.limits.max_statements.unwrap_or_default()
+            ),
+            Limit::CallDepth => format!(
+                "Exceeded the maximum call depth of {}",
+                self.limits.max_call_depth.unwrap_or_default()
+            ),
+            Limit::Timeout => format!(
+                "Exceeded the time limit of {:?}",
+                self.limits.timeout.unwrap_or_default()
+            ),
+        };
+
+        self.exceeded_limit = Some(limit);
+
+        match limit {
+            // Unwinding the calls frees the stack, so scripts may catch it.
+            Limit::CallDepth => VMException::RuntimeError(RuntimeError { token, message }),
+            Limit::Statements | Limit::Timeout => {
+                VMException::LimitExceeded(RuntimeError { token, message })
+            }
+        }
+    }
+
+    pub fn execute_block(&mut self, stmts: &[Stmt], env: Environment) -> Result<(), VMException> {
         let mut env_ref = Rc::new(RefCell::new(env));
 
         std::mem::swap(&mut self.environment, &mut env_ref);
 
-        let result = stmts.into_iter().try_for_each(|stmt| self.execute(stmt));
+        let result = stmts.iter().try_for_each(|stmt| self.execute(stmt));
 
         std::mem::swap(&mut self.environment, &mut env_ref);
 
         result
     }
 
-    pub fn resolve(&mut self, hash: &str, depth: usize) {
-        self.locals.insert(String::from(hash), depth);
+    fn execute_catch(&mut self, catch: &stmt::Catch, value: Object) -> Result<(), VMException> {
+        let enclosing = Some(Rc::clone(&self.environment));
+        let env = match &catch.name {
+            Some(_) => Environment::with_slots(enclosing, vec![value]),
+            None => Environment::new(enclosing),
+        };
+
+        self.execute_block(&catch.body, env)
+    }
+
+    /// Registers a host function as a global callable from scripts.
+    ///
+    /// `arity` is the exact number of arguments the function receives,
+    /// errors should be built with [`Interpreter::native_error`].
+    pub fn register_native(
+        &mut self,
+        name: &str,
+        arity: usize,
+        function: impl Fn(&mut Interpreter, Vec<Object>) -> Result<Object, RuntimeError> + 'static,
+    ) {
+        self.register_native_function(NativeFunction::new(name, arity, function));
+    }
+
+    /// Registers `function` as a global, use this over
+    /// [`Interpreter::register_native`] for functions built with
+    /// [`NativeFunction::optional`] or [`NativeFunction::variadic`].
+    pub fn register_native_function(&mut self, function: NativeFunction) {
+        let name = function.name();
+
+        self.root
+            .borrow_mut()
+            .define(&name, Object::Callable(Rc::new(function)));
+    }
+
+    /// Registers `module` so scripts can declare its natives with
+    /// `import native "name";`, replacing a module of the same name.
+    pub fn register_module(&mut self, module: impl NativeModule + 'static) {
+        self.native_modules
+            .insert(String::from(module.name()), Rc::new(module));
+    }
+
+    /// Builds an error located at the innermost call expression, used by
+    /// native functions which have no token of their own.
+    pub fn native_error(&self, message: &str) -> RuntimeError {
+        RuntimeError {
+            token: self.call_site(),
+            message: String::from(message),
+        }
+    }
+
+    /// Converts an argument of a native, failing with a
+    /// [`native_error`](Interpreter::native_error) when it has another type.
+    ///
+    /// ```
+    /// use typhoon::{interpreter::CapturedOutput, object::IntoObject, Lib};
+    ///
+    /// let output = CapturedOutput::new();
+    /// let mut lib = Lib::new();
+    ///
+    /// lib.set_output(output.clone());
+    /// lib.register_native("repeat", 2, |interpreter, args| {
+    ///     let text: String = interpreter.convert(&args[0])?;
+    ///     let times: usize = interpreter.convert(&args[1])?;
+    ///
+    ///     Ok(vec![text; times].into_object())
+    /// });
+    /// lib.run(String::from(r#"print repeat("ab", 2);"#)).unwrap();
+    ///
+    /// assert!(lib.run(String::from(r#"repeat("ab", -1);"#)).is_err());
+    /// assert_eq!(output.contents(), "[\"ab\", \"ab\"]\n");
+    /// ```
+    pub fn convert<T: FromObject>(&self, value: &Object) -> Result<T, RuntimeError> {
+        T::from_object(value).map_err(|err| self.native_error(&err.to_string()))
+    }
+
+    /// The token of the innermost call expression.
+    pub fn call_site(&self) -> Token {
+        self.call_stack
+            .last()
+            .map(|frame| frame.call_site.clone())
+            .unwrap_or_else(|| Token::new(TokenType::Eof, "", None, 0, Span::default()))
+    }
+
+    /// Calls `callable` on behalf of the call expression at `call_site`.
+    ///
+    /// Tail calls returned by script functions are run in a loop here, in
+    /// place of the frame that returned them, so recursion in tail position
+    /// doesn't grow the stack. Natives calling back into scripts should go
+    /// through this rather than [`Callable::call`].
+    pub fn call(
+        &mut self,
+        callable: Rc<dyn Callable>,
+        arguments: Vec<Object>,
+        call_site: &Token,
+    ) -> Result<Object, VMException> {
+        Interpreter::check_arity(callable.as_ref(), &arguments, call_site)?;
+
+        if self
+            .limits
+            .max_call_depth
+            .is_some_and(|max_call_depth| self.call_stack.len() >= max_call_depth)
+        {
+            return Err(self.limit_exceeded(Limit::CallDepth, call_site.clone()));
+        }
+
+        self.call_stack.push(CallFrame {
+            function: callable.name(),
+            call_site: call_site.clone(),
+        });
+
+        let try_depth = std::mem::take(&mut self.try_depth);
+        let mut result = self.invoke(callable.as_ref(), arguments);
+
+        while let Err(VMException::TailCallException(tail_call)) = result {
+            let TailCall {
+                callable,
+                arguments,
+                call_site,
+            } = *tail_call;
+
+            result = Interpreter::check_arity(callable.as_ref(), &arguments, &call_site)
+                .map_err(VMException::from)
+                .and_then(|()| {
+                    *self.call_stack.last_mut().unwrap() = CallFrame {
+                        function: callable.name(),
+                        call_site,
+                    };
+
+                    self.invoke(callable.as_ref(), arguments)
+                });
+        }
+
+        if let Err(
+            VMException::RuntimeError(_)
+            | VMException::ThrowException(..)
+            | VMException::LimitExceeded(_),
+        ) = result
+        {
+            if self.backtrace.is_empty() {
+                self.backtrace = self.call_stack.clone();
+            }
+        }
+
+        self.try_depth = try_depth;
+        self.call_stack.pop();
+
+        result
+    }
+
+    /// Turns an instance whose class defines `toString` into what that
+    /// returns, other values are returned as they are. `expr` is what the
+    /// value was evaluated from, errors are reported at it.
+    fn call_to_string(&mut self, value: Object, expr: &Expr) -> Result<Object, VMException> {
+        let Some(method) = Interpreter::method(&value, "toString") else {
+            return Ok(value);
+        };
+        let call_site = Token::new(
+            TokenType::Identifier,
+            "toString",
+            None,
+            expr.line().unwrap_or(0),
+            expr.span(),
+        );
+
+        match self.call(method, vec![], &call_site)? {
+            string @ Object::String(_) => Ok(string),
+            other => Err(RuntimeError {
+                token: call_site,
+                message: format!("toString must return a string, not {}", other.type_name()),
+            })?,
+        }
+    }
+
+    /// The method `name` of an instance bound to it, if its class has one.
+    fn method(value: &Object, name: &str) -> Option<Rc<dyn Callable>> {
+        let Object::Instance(instance) = value else {
+            return None;
+        };
+
+        match instance.class()?.find_method(name)? {
+            Object::Callable(method) => Some(method.bind(value.clone()).unwrap_or(method)),
+            _ => None,
+        }
+    }
+
+    /// Starts iterating over `iterable`. Besides the built-in collections,
+    /// an instance whose class has an `__iter()` method is iterated over
+    /// through what it returns, and one with a `__next()` method is its own
+    /// iterator.
+    fn iteration(&mut self, iterable: Object, token: &Token) -> Result<Iteration, VMException> {
+        let iterator = match Interpreter::method(&iterable, "__iter") {
+            Some(method) => self.call(method, vec![], token)?,
+            None => iterable,
+        };
+
+        if let Some(iter) = iterator.iter() {
+            return Ok(Iteration::Builtin(iter));
+        }
+
+        if let Some(next) = match &iterator {
+            Object::Instance(instance) => instance.iterator(),
+            _ => None,
+        } {
+            return Ok(Iteration::Steps(next));
+        }
+
+        match Interpreter::method(&iterator, "__next") {
+            Some(next) => Ok(Iteration::Protocol(next)),
+            None => Err(RuntimeError {
+                token: token.clone(),
+                message: String::from(
+                    "Can only iterate over arrays, maps, strings and instances with an '__iter' or '__next' method",
+                ),
+            })?,
+        }
+    }
+
+    /// The next item of `iteration`, `__next()` returning `undefined` or a
+    /// native iterator's step being `done` ends it.
+    fn next_item(
+        &mut self,
+        iteration: &mut Iteration,
+        token: &Token,
+    ) -> Result<Option<Object>, VMException> {
+        match iteration {
+            Iteration::Builtin(iter) => Ok(iter.next()),
+            Iteration::Protocol(next) => match self.call(Rc::clone(next), vec![], token)? {
+                Object::Undefined => Ok(None),
+                item => Ok(Some(item)),
+            },
+            Iteration::Steps(next) => {
+                let step = self.call(Rc::clone(next), vec![], token)?;
+
+                Ok(generators::step_value(&step))
+            }
+        }
+    }
+
+    /// Runs a `for` loop inside the environment holding its initializer, the
+    /// increment runs after `continue` too.
+    fn for_loop(&mut self, stmt: &stmt::For) -> Result<(), VMException> {
+        if let Some(initializer) = &stmt.initializer {
+            self.execute(initializer)?;
+        }
+
+        loop {
+            if let Some(condition) = &stmt.condition {
+                if !is_truthy(&self.evaluate(condition)?) {
+                    break;
+                }
+            }
+
+            match self.execute(&stmt.body) {
+                Ok(()) | Err(VMException::ContinueException) => {}
+                Err(VMException::BreakException) => break,
+                Err(err) => return Err(err),
+            }
+
+            // Each iteration gets its own copy of the loop variables, so
+            // closures created in the body see the values of their iteration.
+            self.environment.borrow_mut().renew_slots();
+
+            if let Some(increment) = &stmt.increment {
+                self.evaluate(increment)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn invoke(
+        &mut self,
+        callable: &dyn Callable,
+        arguments: Vec<Object>,
+    ) -> Result<Object, VMException> {
+        if let Some(profiler) = &mut self.profiler {
+            profiler.enter(callable.name());
+        }
+
+        let result = callable.call(self, arguments);
+
+        if let Some(profiler) = &mut self.profiler {
+            profiler.exit();
+        }
+
+        result
+    }
+
+    fn check_arity(
+        callable: &dyn Callable,
+        arguments: &[Object],
+        call_site: &Token,
+    ) -> Result<(), RuntimeError> {
+        let arity = callable.arity();
+        let expected = match callable.max_arity() {
+            Some(max_arity) if max_arity == arity => format!("{arity}"),
+            Some(max_arity) => format!("{arity} to {max_arity}"),
+            None => format!("at least {arity}"),
+        };
+
+        if arguments.len() < arity
+            || callable
+                .max_arity()
+                .is_some_and(|max_arity| arguments.len() > max_arity)
+        {
+            Err(RuntimeError {
+                token: call_site.clone(),
+                message: format!(
+                    "'{}' expected [{expected}] arguments got [{}]",
+                    callable.name(),
+                    arguments.len()
+                ),
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    fn evaluate_call(
+        &mut self,
+        expr: &expr::Call,
+    ) -> Result<(Rc<dyn Callable>, Vec<Object>), VMException> {
+        let callee = self.evaluate(&expr.callee)?;
+        let mut arguments = Vec::with_capacity(expr.arguments.len());
+
+        for argument in &expr.arguments {
+            match argument {
+                Expr::Spread(spread) => match self.evaluate(&spread.value)? {
+                    Object::Array(array) => arguments.extend(array.borrow().iter().cloned()),
+                    _ => Err(RuntimeError {
+                        token: spread.ellipsis.clone(),
+                        message: String::from("Can only spread arrays"),
+                    })?,
+                },
+                _ => arguments.push(self.evaluate(argument)?),
+            }
+        }
+        let callable: Rc<dyn Callable> = match callee {
+            Object::Callable(callable) => callable,
+            Object::CallableInstance(callable) => callable,
+            _ => Err(RuntimeError {
+                token: expr.paren.clone(),
+                message: "Can only call functions and classes".to_string(),
+            })?,
+        };
+
+        Ok((callable, arguments))
+    }
+
+    pub fn with_globals<R>(
+        &mut self,
+        mut globals: Rc<RefCell<Environment>>,
+        f: impl FnOnce(&mut Self) -> R,
+    ) -> R {
+        std::mem::swap(&mut self.globals, &mut globals);
+
+        let result = f(self);
+
+        std::mem::swap(&mut self.globals, &mut globals);
+
+        result
+    }
+
+    pub fn enter_main_module(&mut self, path: &str) {
+        if let Ok(path) = fs::canonicalize(path) {
+            self.module_stack.push(path);
+        }
+    }
+
+    /// Forgets the modules imported so far, returning their paths. Importing
+    /// one of them again runs it anew.
+    pub fn forget_modules(&mut self) -> Vec<PathBuf> {
+        self.modules.drain().map(|(path, _)| path).collect()
+    }
+
+    /// Undoes [`Interpreter::enter_main_module`] once the script has run, so
+    /// imports are resolved from the working directory again.
+    pub fn leave_main_module(&mut self) {
+        self.module_stack.pop();
+    }
+
+    fn load_module(
+        &mut self,
+        stmt: &stmt::Import,
+        path: &Path,
+    ) -> Result<HashMap<String, Object>, VMException> {
+        let source = fs::read_to_string(path).map_err(|err| RuntimeError {
+            token: stmt.path.clone(),
+            message: format!("Can't read module '{}': {err}", path.display()),
+        })?;
+        let mut diagnostics = vec![];
+        let statements = Lib::compile(self, String::clone(&source), &mut diagnostics);
+        let (errors, warnings): (Vec<_>, Vec<_>) =
+            diagnostics.into_iter().partition(Diagnostic::is_error);
+
+        self.diagnostics.extend(warnings);
+
+        let statements = statements.ok_or_else(|| RuntimeError {
+            token: stmt.path.clone(),
+            message: format!(
+                "Module '{}' has errors:\n{}",
+                path.display(),
+                errors
+                    .iter()
+                    .map(|error| error.to_string())
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            ),
+        })?;
+        let module = Rc::new(RefCell::new(Environment::new(Some(Rc::clone(&self.root)))));
+        let previous = std::mem::replace(&mut self.environment, Rc::clone(&module));
+
+        self.module_stack.push(path.to_path_buf());
+
+        let result = self.with_globals(Rc::clone(&module), |interpreter| {
+            interpreter.cover(&source, &statements);
+            interpreter.execute_top_level(&statements)
+        });
+
+        self.module_stack.pop();
+        self.environment = previous;
+        result?;
+
+        let mut exports = HashMap::new();
+
+        for stmt in &statements {
+            if let Stmt::Export(export) = stmt {
+                for name in export.names() {
+                    exports.insert(name.lexeme.to_string(), module.borrow().get(name)?);
+                }
+            }
+        }
+
+        Ok(exports)
+    }
+
+    /// Builds the closure of `function` out of the variables it captures,
+    /// rather than keeping every enclosing scope alive.
+    fn closure(&self, function: &dyn ResolvableFunction) -> Rc<RefCell<Environment>> {
+        let mut closure = Environment::new(None);
+
+        for &(distance, slot) in function.captures().borrow().iter() {
+            closure.capture(&mut self.environment.borrow_mut(), distance, slot);
+        }
+
+        Rc::new(RefCell::new(closure))
+    }
+
+    /// Fails when the private member `name` of an instance is accessed
+    /// through anything but `this`. The resolver can't tell instances from
+    /// maps, whose keys may start with an underscore.
+    fn check_private(object: &Object, target: &Expr, name: &Token) -> Result<(), RuntimeError> {
+        match object {
+            Object::Instance(instance)
+                if name.is_private()
+                    && instance.class().is_some()
+                    && !matches!(target, Expr::This(_)) =>
+            {
+                Err(RuntimeError {
+                    token: name.clone(),
+                    message: String::from("Can't access a private member outside of its class"),
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// The class declaring the method the private member `name` is accessed
+    /// from.
+    fn enclosing_class(&self, name: &Token) -> Class {
+        let (distance, slot) = name.location.get().unwrap();
+
+        match self.environment.borrow().get_at(distance, slot) {
+            Object::CallableInstance(class) => class
+                .as_any()
+                .downcast_ref::<Class>()
+                .expect("the class environment holds a class")
+                .clone(),
+            _ => unreachable!(),
+        }
     }
 
     fn look_up_variable(&mut self, name: &Token) -> Result<Object, RuntimeError> {
-        let distance = self.locals.get(name.identifier_hash.as_ref().unwrap());
+        match name.location.get() {
+            Some((depth, slot)) => Ok(self.environment.borrow().get_at(depth, slot)),
+            None => self.globals.borrow().get(name),
+        }
+    }
+
+    /// Defines the variable declared by `name`, in the slot the resolver
+    /// assigned it when it's local.
+    fn define_variable(&mut self, name: &Token, value: Object, is_const: bool) {
+        let mut environment = self.environment.borrow_mut();
 
-        match distance {
-            Some(depth) => self.environment.borrow().get_at(*depth, &name.lexeme),
-            None => self.globals.borrow().get(&name),
+        match name.location.get() {
+            Some((_, slot)) => environment.define_at(slot, value),
+            None if is_const => {
+                environment.define_const(&name.lexeme, value);
+            }
+            None => {
+                environment.define(&name.lexeme, value);
+            }
+        }
+    }
+
+    /// Drops the value of the variable declared by `name`, a global is
+    /// removed altogether.
+    fn undefine_variable(&mut self, name: &Token) {
+        let mut environment = self.environment.borrow_mut();
+
+        match name.location.get() {
+            Some((_, slot)) => environment.define_at(slot, Object::Undefined),
+            None => environment.undefine(&name.lexeme),
         }
     }
 }
 
 impl ExprVisitor for Interpreter {
-    type Item = Result<Object, RuntimeError>;
+    type Item = Result<Object, VMException>;
 
     fn visit_comma(&mut self, expr: &expr::Comma) -> Self::Item {
         self.evaluate(&expr.left)?;
         self.evaluate(&expr.right)
     }
 
-    fn visit_lambda(&mut self, expr: &expr::Lambda) -> Self::Item {
-        let function = Function::new(Rc::new(expr.clone()), Rc::clone(&self.environment), false);
+    fn visit_lambda(&mut self, expr: &Rc<expr::Lambda>) -> Self::Item {
+        let function = Function::new(
+            Rc::clone(expr),
+            self.closure(expr.as_ref()),
+            Rc::clone(&self.globals),
+            false,
+        );
 
         Ok(Object::Callable(Rc::new(function)))
     }
 
     fn visit_assignment(&mut self, expr: &expr::Assignment) -> Self::Item {
         let value = self.evaluate(&expr.value)?;
-        let distance = self.locals.get(expr.name.identifier_hash.as_ref().unwrap());
-
-        match distance {
-            Some(depth) => {
+        match expr.name.location.get() {
+            Some((depth, slot)) => {
                 self.environment
                     .borrow_mut()
-                    .assign_at(*depth, &expr.name.lexeme, value.clone())?
+                    .assign_at(depth, slot, value.clone())
             }
             None => self
                 .globals
@@ -126,11 +1202,13 @@ impl ExprVisitor for Interpreter {
     fn visit_set(&mut self, expr: &expr::Set) -> Self::Item {
         let object = self.evaluate(&expr.object)?;
 
+        Interpreter::check_private(&object, &expr.object, &expr.name)?;
+
         fn set_field<T: Instance + ?Sized>(
             instance: Rc<T>,
             expr: &expr::Set,
             interpreter: &mut Interpreter,
-        ) -> Result<Object, RuntimeError> {
+        ) -> Result<Object, VMException> {
             let value = interpreter.evaluate(&expr.value)?;
 
             instance.set(&expr.name, value.clone())?;
@@ -144,10 +1222,23 @@ impl ExprVisitor for Interpreter {
             _ => Err(RuntimeError {
                 token: expr.name.clone(),
                 message: "Only class instances have fields".to_string(),
-            }),
+            })?,
         }
     }
 
+    fn visit_index_set(&mut self, expr: &expr::IndexSet) -> Self::Item {
+        let object = self.evaluate(&expr.object)?;
+        let index = self.evaluate(&expr.index)?;
+        let value = self.evaluate(&expr.value)?;
+
+        Ok(operations::handle_index_set(
+            &object,
+            &index,
+            value,
+            &expr.bracket,
+        )?)
+    }
+
     fn visit_ternary(&mut self, expr: &expr::Ternary) -> Self::Item {
         let condition = self.evaluate(&expr.condition)?;
 
@@ -176,6 +1267,10 @@ impl ExprVisitor for Interpreter {
                     self.evaluate(&expr.right)?
                 }
             }
+            TokenType::QuestionQuestion => match left {
+                Object::Undefined => self.evaluate(&expr.right)?,
+                _ => left,
+            },
             _ => unreachable!(),
         };
 
@@ -183,130 +1278,151 @@ impl ExprVisitor for Interpreter {
     }
 
     fn visit_binary(&mut self, expr: &expr::Binary) -> Self::Item {
-        let left = self.evaluate(&expr.left)?;
-        let right = self.evaluate(&expr.right)?;
+        let mut left = self.evaluate(&expr.left)?;
+        let mut right = self.evaluate(&expr.right)?;
 
-        match expr.operator.token_type {
-            TokenType::Plus => operations::handle_addition(&left, &right, &expr.operator),
-            TokenType::Minus => operations::handle_subtraction(&left, &right, &expr.operator),
-            TokenType::Star => operations::handle_multiplication(&left, &right, &expr.operator),
-            TokenType::Slash => operations::handle_division(&left, &right, &expr.operator),
-            TokenType::Percentage => operations::handle_modulus(&left, &right, &expr.operator),
-            TokenType::Greater => operations::handle_greater_than(&left, &right, &expr.operator),
-            TokenType::GreaterEqual => {
-                operations::handle_greater_than_equal(&left, &right, &expr.operator)
-            }
-            TokenType::Less => operations::handle_less_than(&left, &right, &expr.operator),
-            TokenType::LessEqual => {
-                operations::handle_less_than_equal(&left, &right, &expr.operator)
-            }
-            TokenType::BangEqual => Ok(Object::Boolean(left != right)),
-            TokenType::EqualEqual => Ok(Object::Boolean(left == right)),
-            _ => unreachable!(),
+        if expr.operator.token_type == TokenType::Plus {
+            match (&left, &right) {
+                (Object::String(_), Object::Instance(_)) => {
+                    right = self.call_to_string(right, &expr.right)?;
+                }
+                (Object::Instance(_), Object::String(_)) => {
+                    left = self.call_to_string(left, &expr.left)?;
+                }
+                _ => {}
+            }
         }
-    }
 
-    fn visit_unary(&mut self, expr: &expr::Unary) -> Self::Item {
-        let literal = self.evaluate(&expr.right)?;
-        let literal = match expr.operator.token_type {
-            TokenType::Bang => Object::Boolean(!is_truthy(&literal)),
-            TokenType::Minus => {
-                let literal = match literal {
-                    Object::Number(number) => number,
-                    Object::Boolean(boolean) => bool_to_number(boolean),
-                    _ => {
-                        return Err(RuntimeError {
-                            token: expr.operator.clone(),
-                            message: String::from("Unary minus requires number or boolean operand"),
-                        })
-                    }
-                };
-
-                Object::Number(-literal)
+        if self.ieee_division {
+            if let Some(value) = operations::ieee_division(&left, &right, &expr.operator) {
+                return Ok(value);
             }
-            _ => unreachable!(),
-        };
+        }
 
-        Ok(literal)
+        Ok(operations::handle_binary(&left, &right, &expr.operator)?)
     }
 
-    fn visit_call(&mut self, expr: &expr::Call) -> Self::Item {
-        let callee = self.evaluate(&expr.callee)?;
-        let arguments = expr
-            .arguments
-            .iter()
-            .map(|f| self.evaluate(f))
-            .collect::<Result<Vec<_>, _>>()?;
+    fn visit_unary(&mut self, expr: &expr::Unary) -> Self::Item {
+        let right = self.evaluate(&expr.right)?;
 
-        fn check_and_call<T: Callable + ?Sized>(
-            callable: Rc<T>,
-            expr: &expr::Call,
-            interpreter: &mut Interpreter,
-            arguments: Vec<Object>,
-        ) -> Result<Object, RuntimeError> {
-            let arity = callable.arity();
+        Ok(operations::handle_unary(&right, &expr.operator)?)
+    }
 
-            if arguments.len() < arity {
-                Err(RuntimeError {
-                    token: expr.paren.clone(),
-                    message: format!("Expected [{arity}] arguments got [{}]", arguments.len()),
-                })
-            } else {
-                callable.call(interpreter, arguments)
-            }
+    /// Suspends the async function running it on the value, evaluating to
+    /// the outcome of the promise once resumed, see [`Stmt::awaited`].
+    fn visit_await(&mut self, expr: &expr::Await) -> Self::Item {
+        match self.awaited.take() {
+            Some(Ok(value)) => Ok(value),
+            Some(Err(reason)) => Err(VMException::ThrowException(expr.keyword.clone(), reason)),
+            None => Err(VMException::YieldException(self.evaluate(&expr.value)?)),
         }
+    }
 
-        match callee {
-            Object::Callable(c) => check_and_call(c, expr, self, arguments),
-            Object::CallableInstance(c) => check_and_call(c, expr, self, arguments),
-            _ => Err(RuntimeError {
-                token: expr.paren.clone(),
-                message: "Can only call functions and classes".to_string(),
-            }),
-        }
+    fn visit_call(&mut self, expr: &expr::Call) -> Self::Item {
+        let (callable, arguments) = self.evaluate_call(expr)?;
+
+        self.call(callable, arguments, &expr.paren)
     }
 
     fn visit_get(&mut self, expr: &expr::Get) -> Self::Item {
         let object = self.evaluate(&expr.object)?;
 
-        match &object {
+        if expr.optional && matches!(object, Object::Undefined) {
+            return Ok(Object::Undefined);
+        }
+
+        if let (Expr::This(_), Object::Instance(instance)) = (&expr.object, &object) {
+            if expr.name.is_private() {
+                let class = self.enclosing_class(&expr.name);
+
+                return Ok(instance.get_private(object.clone(), &expr.name, &class)?);
+            }
+        }
+
+        Interpreter::check_private(&object, &expr.object, &expr.name)?;
+
+        let value = match &object {
+            Object::String(string) => strings::get_property(string, &expr.name),
+            Object::Array(array) => arrays::get_property(array, &expr.name),
+            Object::Map(map) => Ok(map
+                .borrow()
+                .get(&*expr.name.lexeme)
+                .cloned()
+                .unwrap_or(Object::Undefined)),
+            Object::Callable(callable) => functions::get_property(callable, &expr.name),
+            Object::Int(int) => numbers::get_property(*int as f64, &expr.name),
+            Object::Number(number) => numbers::get_property(*number, &expr.name),
             Object::Instance(class_instance) => class_instance.get(object.clone(), &expr.name),
             Object::CallableInstance(class_instance) => {
                 class_instance.get(object.clone(), &expr.name)
             }
             _ => Err(RuntimeError {
                 token: expr.name.clone(),
-                message: String::from("Only class instance have known properties"),
+                message: String::from(
+                    "Only class instances, functions, arrays, maps, strings and numbers have known properties",
+                ),
             }),
-        }
+        }?;
+
+        Ok(value)
+    }
+
+    fn visit_index(&mut self, expr: &expr::Index) -> Self::Item {
+        let object = self.evaluate(&expr.object)?;
+        let index = self.evaluate(&expr.index)?;
+
+        Ok(operations::handle_index(&object, &index, &expr.bracket)?)
     }
 
-    fn visit_grouping(&mut self, expr: &Expr) -> Self::Item {
+    fn visit_slice(&mut self, expr: &expr::Slice) -> Self::Item {
+        let object = self.evaluate(&expr.object)?;
+        let start = expr
+            .start
+            .as_ref()
+            .map(|start| self.evaluate(start))
+            .transpose()?;
+        let end = expr
+            .end
+            .as_ref()
+            .map(|end| self.evaluate(end))
+            .transpose()?;
+
+        Ok(operations::handle_slice(
+            &object,
+            start.as_ref(),
+            end.as_ref(),
+            &expr.bracket,
+        )?)
+    }
+
+    fn visit_grouping(&mut self, expr: &Expr, _span: Span) -> Self::Item {
         self.evaluate(expr)
     }
 
     fn visit_variable(&mut self, expr: &Token) -> Self::Item {
-        self.look_up_variable(expr)
+        Ok(self.look_up_variable(expr)?)
     }
 
     fn visit_this(&mut self, expr: &Token) -> Self::Item {
-        self.look_up_variable(expr)
+        Ok(self.look_up_variable(expr)?)
     }
 
     fn visit_super(&mut self, expr: &expr::Super) -> Self::Item {
-        let distance = self
-            .locals
-            .get(expr.keyword.identifier_hash.as_ref().unwrap())
-            .unwrap();
-        let super_class = self.environment.borrow().get_at(*distance, "super")?;
-        let object = self.environment.borrow().get_at(distance - 1, "this")?;
+        let super_class = self.look_up_variable(&expr.keyword)?;
+        // The resolver resolves `this` against the method name, or the class
+        // inside statics.
+        let object = self.look_up_variable(&expr.method)?;
 
         if let Object::CallableInstance(super_class) = super_class {
             if let Some(class) = super_class.as_any().downcast_ref::<Class>() {
+                if let Object::CallableInstance(_) = object {
+                    return Ok(class.get(object, &expr.method)?);
+                }
+
                 match class.find_method(&expr.method.lexeme) {
                     Some(method) => {
                         if let Object::Callable(method) = method {
-                            return Ok(method.bind(object));
+                            return Ok(Object::Callable(method.bind(object).unwrap_or(method)));
                         }
                     }
                     None => Err(RuntimeError {
@@ -320,7 +1436,54 @@ impl ExprVisitor for Interpreter {
         unreachable!();
     }
 
-    fn visit_literal(&mut self, expr: &Object) -> Self::Item {
+    fn visit_array(&mut self, expr: &expr::Array) -> Self::Item {
+        let elements = expr
+            .elements
+            .iter()
+            .map(|element| self.evaluate(element))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Object::Array(Rc::new(RefCell::new(elements))))
+    }
+
+    fn visit_map(&mut self, expr: &expr::Map) -> Self::Item {
+        let mut map = BTreeMap::new();
+
+        for (key, value) in &expr.entries {
+            let key = match self.evaluate(key)? {
+                Object::String(key) => key.to_string(),
+                _ => Err(RuntimeError {
+                    token: expr.brace.clone(),
+                    message: String::from("Map keys must be strings"),
+                })?,
+            };
+
+            map.insert(key, self.evaluate(value)?);
+        }
+
+        Ok(Object::Map(Rc::new(RefCell::new(map))))
+    }
+
+    fn visit_spread(&mut self, expr: &expr::Spread) -> Self::Item {
+        Err(RuntimeError {
+            token: expr.ellipsis.clone(),
+            message: String::from("Spread is only allowed in call arguments"),
+        })?
+    }
+
+    fn visit_interpolation(&mut self, expr: &expr::Interpolation) -> Self::Item {
+        let mut string = String::new();
+
+        for part in &expr.parts {
+            let value = self.evaluate(part)?;
+
+            string.push_str(&self.call_to_string(value, part)?.to_string());
+        }
+
+        Ok(Object::String(Rc::from(string)))
+    }
+
+    fn visit_literal(&mut self, expr: &Object, _span: Span) -> Self::Item {
         Ok(expr.clone())
     }
 }
@@ -328,48 +1491,69 @@ impl ExprVisitor for Interpreter {
 impl StmtVisitor for Interpreter {
     type Item = Result<(), VMException>;
 
-    fn visit_empty_stmt(&mut self) -> Self::Item {
+    fn visit_empty_stmt(&mut self, _span: Span) -> Self::Item {
         Ok(())
     }
 
-    fn visit_expression_stmt(&mut self, stmt: &Expr) -> Self::Item {
-        self.evaluate_and_map_error(stmt)?;
+    fn visit_expression_stmt(&mut self, stmt: &Expr, _span: Span) -> Self::Item {
+        self.evaluate(stmt)?;
 
         Ok(())
     }
 
-    fn visit_print_stmt(&mut self, stmt: &Expr) -> Self::Item {
-        let value = self.evaluate_and_map_error(stmt)?;
+    fn visit_print_stmt(&mut self, stmt: &Expr, _span: Span) -> Self::Item {
+        let value = self.evaluate(stmt)?;
+        let value = self.call_to_string(value, stmt)?;
 
-        println!("{}", value);
+        self.write_output(&format!("{value}\n"))?;
 
         Ok(())
     }
 
-    fn visit_variable_stmt(&mut self, stmt: &Vec<stmt::VariableDeclaration>) -> Self::Item {
-        for var in stmt {
+    fn visit_variable_stmt(
+        &mut self,
+        stmt: &[stmt::VariableDeclaration],
+        _span: Span,
+    ) -> Self::Item {
+        let defined = stmt.iter().try_for_each(|var| {
             let value = if let Some(expr) = &var.initializer {
-                self.evaluate_and_map_error(expr)?
+                self.evaluate(expr)?
             } else {
                 Object::Undefined
             };
 
-            self.environment
-                .borrow_mut()
-                .define(&var.name.lexeme, value);
+            self.define_variable(&var.name, value, var.is_const);
+
+            Ok(())
+        });
+
+        // The hidden variables of destructuring patterns are only read by
+        // the declarations following them. One declared on its own starts
+        // the block of a destructuring assignment, which ends with it.
+        let hidden = stmt.iter().filter(|var| {
+            matches!(
+                var.name.token_type,
+                TokenType::LeftBracket | TokenType::LeftBraces
+            )
+        });
+
+        if stmt.len() > 1 {
+            for var in hidden {
+                self.undefine_variable(&var.name);
+            }
         }
 
-        Ok(())
+        defined
     }
 
-    fn visit_block_stmt(&mut self, stmt: &Vec<Stmt>) -> Self::Item {
+    fn visit_block_stmt(&mut self, stmt: &[Stmt], _span: Span) -> Self::Item {
         self.execute_block(stmt, Environment::new(Some(Rc::clone(&self.environment))))?;
 
         Ok(())
     }
 
     fn visit_if_stmt(&mut self, stmt: &stmt::If) -> Self::Item {
-        let condition = self.evaluate_and_map_error(&stmt.condition)?;
+        let condition = self.evaluate(&stmt.condition)?;
 
         if is_truthy(&condition) {
             self.execute(&stmt.truth)?;
@@ -381,7 +1565,7 @@ impl StmtVisitor for Interpreter {
     }
 
     fn visit_while_stmt(&mut self, stmt: &stmt::While) -> Self::Item {
-        while is_truthy(&self.evaluate_and_map_error(&stmt.condition)?) {
+        while is_truthy(&self.evaluate(&stmt.condition)?) {
             let result = self.execute(&stmt.body);
 
             if let Err(e) = &result {
@@ -396,27 +1580,71 @@ impl StmtVisitor for Interpreter {
         Ok(())
     }
 
-    fn visit_break_stmt(&mut self, _: &Token) -> Self::Item {
+    fn visit_for_stmt(&mut self, stmt: &stmt::For) -> Self::Item {
+        let mut env_ref = Rc::new(RefCell::new(Environment::new(Some(Rc::clone(
+            &self.environment,
+        )))));
+
+        std::mem::swap(&mut self.environment, &mut env_ref);
+
+        let result = self.for_loop(stmt);
+
+        std::mem::swap(&mut self.environment, &mut env_ref);
+
+        result
+    }
+
+    fn visit_for_in_stmt(&mut self, stmt: &stmt::ForIn) -> Self::Item {
+        let iterable = self.evaluate(&stmt.iterable)?;
+        let mut iteration = self.iteration(iterable, &stmt.keyword)?;
+
+        while let Some(item) = self.next_item(&mut iteration, &stmt.keyword)? {
+            let env = Environment::with_slots(Some(Rc::clone(&self.environment)), vec![item]);
+
+            match self.execute_block(std::slice::from_ref(&stmt.body), env) {
+                Err(VMException::BreakException) => break,
+                Err(VMException::ContinueException) => continue,
+                result => result?,
+            }
+        }
+
+        Ok(())
+    }
+
+    fn visit_break_stmt(&mut self, _: &Token, _span: Span) -> Self::Item {
         Err(VMException::BreakException)
     }
 
-    fn visit_continue_stmt(&mut self, _: &Token) -> Self::Item {
+    fn visit_continue_stmt(&mut self, _: &Token, _span: Span) -> Self::Item {
         Err(VMException::ContinueException)
     }
 
-    fn visit_function_stmt(&mut self, stmt: &stmt::Function) -> Self::Item {
-        let function = Function::new(Rc::new(stmt.clone()), Rc::clone(&self.environment), false);
+    fn visit_function_stmt(&mut self, stmt: &Rc<stmt::Function>) -> Self::Item {
+        let function = Function::new(
+            Rc::clone(stmt),
+            self.closure(stmt.as_ref()),
+            Rc::clone(&self.globals),
+            false,
+        );
 
-        self.environment
-            .borrow_mut()
-            .define(&stmt.name.lexeme, Object::Callable(Rc::new(function)));
+        self.define_variable(&stmt.name, Object::Callable(Rc::new(function)), false);
 
         Ok(())
     }
 
     fn visit_return_stmt(&mut self, stmt: &stmt::Return) -> Self::Item {
+        if let (Some(Expr::Call(call)), 0) = (&stmt.value, self.try_depth) {
+            let (callable, arguments) = self.evaluate_call(call)?;
+
+            return Err(VMException::TailCallException(Box::new(TailCall {
+                callable,
+                arguments,
+                call_site: call.paren.clone(),
+            })));
+        }
+
         let value = if let Some(value) = &stmt.value {
-            self.evaluate_and_map_error(value)?
+            self.evaluate(value)?
         } else {
             Object::Undefined
         };
@@ -424,10 +1652,9 @@ impl StmtVisitor for Interpreter {
         Err(VMException::ReturnException(value))
     }
 
-    fn visit_class_stmt(&mut self, stmt: &stmt::Class) -> Self::Item {
+    fn visit_class_stmt(&mut self, stmt: &Rc<stmt::Class>) -> Self::Item {
         let super_class = if let Some(Expr::Variable(super_class)) = &stmt.super_class {
-            let super_class_object =
-                self.evaluate_and_map_error(stmt.super_class.as_ref().unwrap())?;
+            let super_class_object = self.evaluate(stmt.super_class.as_ref().unwrap())?;
 
             match super_class_object {
                 Object::CallableInstance(callable_instance) => Some(callable_instance),
@@ -440,32 +1667,43 @@ impl StmtVisitor for Interpreter {
             None
         };
 
-        self.environment
-            .borrow_mut()
-            .define(&stmt.name.lexeme, Object::Undefined);
+        let top_level = Rc::ptr_eq(&self.environment, &self.globals)
+            .then(|| (Rc::clone(stmt), Rc::clone(&self.globals)));
 
-        if let Some(super_class) = &super_class {
-            self.environment = Rc::new(RefCell::new(Environment::new(Some(Rc::clone(
-                &self.environment,
-            )))));
+        self.define_variable(&stmt.name, Object::Undefined, false);
+
+        let mut statics = HashMap::new();
+
+        for field in &stmt.static_fields {
+            let value = match &field.initializer {
+                Some(initializer) => self.evaluate(initializer)?,
+                None => Object::Undefined,
+            };
+
+            statics.insert(Rc::clone(&field.name.lexeme), value);
+        }
 
+        self.environment = Rc::new(RefCell::new(Environment::new(Some(Rc::clone(
+            &self.environment,
+        )))));
+
+        if let Some(super_class) = &super_class {
             self.environment
                 .borrow_mut()
-                .define("super", Object::CallableInstance(Rc::clone(super_class)));
+                .define_at(SUPER_SLOT, Object::CallableInstance(Rc::clone(super_class)));
         }
 
-        let mut statics = HashMap::new();
-
         for method in &stmt.statics {
             if let Stmt::Function(function_stmt) = method {
                 let function = Function::new(
-                    Rc::new(*function_stmt.clone()),
-                    Rc::clone(&self.environment),
+                    Rc::clone(function_stmt),
+                    self.closure(function_stmt.as_ref()),
+                    Rc::clone(&self.globals),
                     false,
                 );
 
                 statics.insert(
-                    String::clone(&function_stmt.name.lexeme),
+                    Rc::clone(&function_stmt.name.lexeme),
                     Object::Callable(Rc::new(function)),
                 );
             }
@@ -475,31 +1713,167 @@ impl StmtVisitor for Interpreter {
 
         for method in &stmt.methods {
             if let Stmt::Function(function_stmt) = method {
-                let function = Function::new(
-                    Rc::new(*function_stmt.clone()),
-                    Rc::clone(&self.environment),
-                    function_stmt.name.lexeme.eq("init"),
+                let function = Function::method(
+                    Rc::clone(function_stmt),
+                    self.closure(function_stmt.as_ref()),
+                    Rc::clone(&self.globals),
+                    &*function_stmt.name.lexeme == "init",
                 );
 
                 methods.insert(
-                    String::clone(&function_stmt.name.lexeme),
+                    Rc::clone(&function_stmt.name.lexeme),
                     Object::Callable(Rc::new(function)),
                 );
             }
         }
 
-        let class = Class::new(&stmt.name.lexeme, super_class, statics, methods);
-
-        if let Some(_) = &stmt.super_class {
-            let previous = Rc::clone(self.environment.borrow().enclosing.as_ref().unwrap());
-            self.environment = previous;
-        }
+        let class: Rc<dyn CallableInstance> = Rc::new(Class::new(
+            &stmt.name.lexeme,
+            super_class,
+            statics,
+            methods,
+            top_level,
+        ));
 
+        // `class` is a keyword so it can't clash with a user variable, private
+        // members are looked up through it.
         self.environment
             .borrow_mut()
-            .assign(&stmt.name, Object::CallableInstance(Rc::new(class)))
-            .unwrap();
+            .define_at(CLASS_SLOT, Object::CallableInstance(Rc::clone(&class)));
+
+        let previous = Rc::clone(self.environment.borrow().enclosing.as_ref().unwrap());
+        self.environment = previous;
+
+        self.define_variable(&stmt.name, Object::CallableInstance(class), false);
 
         Ok(())
     }
+
+    fn visit_try_stmt(&mut self, stmt: &stmt::Try) -> Self::Item {
+        self.try_depth += 1;
+
+        let mut result = self.execute_block(
+            &stmt.body,
+            Environment::new(Some(Rc::clone(&self.environment))),
+        );
+
+        if let Some(catch) = &stmt.catch {
+            result = match result {
+                Err(VMException::RuntimeError(runtime_error)) => {
+                    self.caught();
+                    self.execute_catch(catch, Object::String(Rc::from(runtime_error.message)))
+                }
+                Err(VMException::ThrowException(_, value)) => {
+                    self.caught();
+                    self.execute_catch(catch, value)
+                }
+                result => result,
+            };
+        }
+
+        self.try_depth -= 1;
+
+        if let Some(finally) = &stmt.finally {
+            self.execute_block(
+                finally,
+                Environment::new(Some(Rc::clone(&self.environment))),
+            )?;
+        }
+
+        result
+    }
+
+    fn visit_throw_stmt(&mut self, stmt: &stmt::Throw) -> Self::Item {
+        let value = self.evaluate(&stmt.value)?;
+
+        Err(VMException::ThrowException(stmt.keyword.clone(), value))
+    }
+
+    fn visit_yield_stmt(&mut self, stmt: &stmt::Yield) -> Self::Item {
+        let value = match &stmt.value {
+            Some(value) => self.evaluate(value)?,
+            None => Object::Undefined,
+        };
+
+        Err(VMException::YieldException(value))
+    }
+
+    fn visit_exit_stmt(&mut self, stmt: &stmt::Exit) -> Self::Item {
+        let code = match &stmt.code {
+            Some(code) => match self.evaluate(code)? {
+                Object::Int(code) => i32::try_from(code).map_err(|_| RuntimeError {
+                    token: stmt.keyword.clone(),
+                    message: format!("Exit code {code} is out of range"),
+                })?,
+                _ => Err(RuntimeError {
+                    token: stmt.keyword.clone(),
+                    message: String::from("Exit code must be an integer"),
+                })?,
+            },
+            None => 0,
+        };
+
+        Err(VMException::ExitException(code))
+    }
+
+    fn visit_import_stmt(&mut self, stmt: &stmt::Import) -> Self::Item {
+        let Some(LiteralType::String(relative)) = &stmt.path.literal else {
+            unreachable!()
+        };
+
+        if stmt.native {
+            let module = self
+                .native_modules
+                .get(relative)
+                .ok_or_else(|| RuntimeError {
+                    token: stmt.path.clone(),
+                    message: format!("Unknown native module '{relative}'"),
+                })?;
+
+            for native in module.natives() {
+                self.environment
+                    .borrow_mut()
+                    .define(&native.name(), Object::Callable(Rc::new(native)));
+            }
+
+            return Ok(());
+        }
+
+        let base = match self.module_stack.last() {
+            Some(importer) => importer.parent().map(Path::to_path_buf).unwrap_or_default(),
+            None => PathBuf::new(),
+        };
+        let path = fs::canonicalize(base.join(relative)).map_err(|err| RuntimeError {
+            token: stmt.path.clone(),
+            message: format!("Can't find module '{relative}': {err}"),
+        })?;
+
+        if self.module_stack.contains(&path) {
+            Err(RuntimeError {
+                token: stmt.path.clone(),
+                message: format!("Circular import of module '{relative}'"),
+            })?;
+        }
+
+        let exports = match self.modules.get(&path) {
+            Some(exports) => exports.clone(),
+            None => {
+                let exports = self.load_module(stmt, &path)?;
+
+                self.modules.insert(path, exports.clone());
+
+                exports
+            }
+        };
+
+        for (name, value) in exports {
+            self.environment.borrow_mut().define(&name, value);
+        }
+
+        Ok(())
+    }
+
+    fn visit_export_stmt(&mut self, stmt: &stmt::Export) -> Self::Item {
+        self.execute(&stmt.declaration)
+    }
 }
@@ -0,0 +1,239 @@
+use std::{cell::RefCell, cmp::Ordering, rc::Rc};
+
+use crate::{
+    errors::{RuntimeError, VMException},
+    object::{Callable, Object},
+    token::Token,
+    utils::is_truthy,
+};
+
+use super::{globals::expect_callback, operations, Interpreter};
+
+type Elements = Rc<RefCell<Vec<Object>>>;
+
+/// The properties [`get_property`] knows, for completion.
+pub const PROPERTIES: [&str; 8] = [
+    "length", "push", "pop", "map", "filter", "reduce", "forEach", "sort",
+];
+
+pub fn get_property(array: &Elements, name: &Token) -> Result<Object, RuntimeError> {
+    let kind = match &*name.lexeme {
+        "length" => return Ok(Object::Int(array.borrow().len() as i64)),
+        "push" => Kind::Push,
+        "pop" => Kind::Pop,
+        "map" => Kind::Map,
+        "filter" => Kind::Filter,
+        "reduce" => Kind::Reduce,
+        "forEach" => Kind::ForEach,
+        "sort" => Kind::Sort,
+        _ => Err(RuntimeError {
+            token: name.clone(),
+            message: format!("Undefined array method '{}'", name.lexeme),
+        })?,
+    };
+
+    Ok(Object::Callable(Rc::new(ArrayMethod {
+        array: Rc::clone(array),
+        kind,
+    })))
+}
+
+#[derive(Clone, Copy)]
+enum Kind {
+    Push,
+    Pop,
+    Map,
+    Filter,
+    Reduce,
+    ForEach,
+    Sort,
+}
+
+/// A method of an array. The ones taking a callback call it through
+/// [`Interpreter::call`], so exceptions it throws propagate, and iterate over
+/// the elements the array held when the method was called.
+struct ArrayMethod {
+    array: Elements,
+    kind: Kind,
+}
+
+impl ArrayMethod {
+    fn elements(&self) -> Vec<Object> {
+        self.array.borrow().clone()
+    }
+}
+
+impl Callable for ArrayMethod {
+    fn arity(&self) -> usize {
+        match self.kind {
+            Kind::Pop | Kind::Sort => 0,
+            Kind::Push | Kind::Map | Kind::Filter | Kind::Reduce | Kind::ForEach => 1,
+        }
+    }
+
+    fn max_arity(&self) -> Option<usize> {
+        match self.kind {
+            Kind::Push => None,
+            Kind::Reduce => Some(2),
+            Kind::Sort => Some(1),
+            _ => Some(self.arity()),
+        }
+    }
+
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        arguments: Vec<Object>,
+    ) -> Result<Object, VMException> {
+        let call_site = interpreter.call_site();
+        let mut arguments = arguments.into_iter();
+
+        match self.kind {
+            Kind::Push => {
+                let mut array = self.array.borrow_mut();
+
+                array.extend(arguments);
+
+                Ok(Object::Int(array.len() as i64))
+            }
+            Kind::Pop => Ok(self.array.borrow_mut().pop().unwrap_or(Object::Undefined)),
+            Kind::Map => {
+                let callback = expect_callback(interpreter, arguments.next())?;
+                let mut mapped = vec![];
+
+                for element in self.elements() {
+                    mapped.push(interpreter.call(
+                        Rc::clone(&callback),
+                        vec![element],
+                        &call_site,
+                    )?);
+                }
+
+                Ok(Object::Array(Rc::new(RefCell::new(mapped))))
+            }
+            Kind::Filter => {
+                let callback = expect_callback(interpreter, arguments.next())?;
+                let mut kept = vec![];
+
+                for element in self.elements() {
+                    let keep = interpreter.call(
+                        Rc::clone(&callback),
+                        vec![element.clone()],
+                        &call_site,
+                    )?;
+
+                    if is_truthy(&keep) {
+                        kept.push(element);
+                    }
+                }
+
+                Ok(Object::Array(Rc::new(RefCell::new(kept))))
+            }
+            Kind::Reduce => {
+                let callback = expect_callback(interpreter, arguments.next())?;
+                let mut elements = self.elements().into_iter();
+                let Some(mut accumulator) = arguments.next().or_else(|| elements.next()) else {
+                    Err(interpreter
+                        .native_error("reduce() of an empty array needs an initial value"))?
+                };
+
+                for element in elements {
+                    accumulator = interpreter.call(
+                        Rc::clone(&callback),
+                        vec![accumulator, element],
+                        &call_site,
+                    )?;
+                }
+
+                Ok(accumulator)
+            }
+            Kind::ForEach => {
+                let callback = expect_callback(interpreter, arguments.next())?;
+
+                for element in self.elements() {
+                    interpreter.call(Rc::clone(&callback), vec![element], &call_site)?;
+                }
+
+                Ok(Object::Undefined)
+            }
+            Kind::Sort => {
+                let comparator = match arguments.next() {
+                    Some(comparator) => Some(expect_callback(interpreter, Some(comparator))?),
+                    None => None,
+                };
+                let sorted =
+                    merge_sort(self.elements(), &mut |left, right| match &comparator {
+                        Some(comparator) => {
+                            let order = interpreter.call(
+                                Rc::clone(comparator),
+                                vec![left.clone(), right.clone()],
+                                &call_site,
+                            )?;
+
+                            match order {
+                                Object::Int(order) => Ok(order.cmp(&0)),
+                                Object::Number(order) => {
+                                    Ok(order.partial_cmp(&0.0).unwrap_or(Ordering::Equal))
+                                }
+                                _ => Err(interpreter
+                                    .native_error("sort() comparator must return a number"))?,
+                            }
+                        }
+                        None => Ok(operations::compare(left, right, &call_site)?
+                            .unwrap_or(Ordering::Equal)),
+                    })?;
+
+                *self.array.borrow_mut() = sorted;
+
+                Ok(Object::Array(Rc::clone(&self.array)))
+            }
+        }
+    }
+
+    fn name(&self) -> String {
+        String::from(match self.kind {
+            Kind::Push => "push",
+            Kind::Pop => "pop",
+            Kind::Map => "map",
+            Kind::Filter => "filter",
+            Kind::Reduce => "reduce",
+            Kind::ForEach => "forEach",
+            Kind::Sort => "sort",
+        })
+    }
+
+    fn to_string(&self) -> String {
+        format!("Native Function: ({})", self.name())
+    }
+}
+
+/// A stable sort that stops at the first error of `compare`. Unlike the
+/// standard library's it can't panic when a script comparator isn't a
+/// consistent order.
+fn merge_sort(
+    mut elements: Vec<Object>,
+    compare: &mut impl FnMut(&Object, &Object) -> Result<Ordering, VMException>,
+) -> Result<Vec<Object>, VMException> {
+    if elements.len() < 2 {
+        return Ok(elements);
+    }
+
+    let right = merge_sort(elements.split_off(elements.len() / 2), compare)?;
+    let left = merge_sort(elements, compare)?;
+    let mut merged = Vec::with_capacity(left.len() + right.len());
+    let mut left = left.into_iter().peekable();
+    let mut right = right.into_iter().peekable();
+
+    while let (Some(l), Some(r)) = (left.peek(), right.peek()) {
+        if compare(l, r)?.is_gt() {
+            merged.extend(right.next());
+        } else {
+            merged.extend(left.next());
+        }
+    }
+
+    merged.extend(left);
+    merged.extend(right);
+
+    Ok(merged)
+}
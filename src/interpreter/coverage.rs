@@ -0,0 +1,302 @@
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+    fmt::{self, Display},
+    path::Path,
+};
+
+use crate::{
+    environment::Environment,
+    expr::Expr,
+    stmt::{Stmt, VariableDeclaration},
+    token::Span,
+};
+
+/// Counts how many times each statement of the scripts and modules run ran.
+///
+/// Statements are told apart by the start of their span, and the file they're
+/// from by the globals they run with, which are the module's own ones. The
+/// report is per line, a line is covered once a statement starting on it ran.
+#[derive(Default)]
+pub struct Coverage {
+    files: Vec<File>,
+    modules: HashMap<*const RefCell<Environment>, usize>,
+    /// Whether the report lists the source of each file with its counts.
+    listing: bool,
+}
+
+struct File {
+    name: String,
+    source: String,
+    /// How many times each statement ran, by the start of its span.
+    hits: BTreeMap<u32, usize>,
+}
+
+impl Coverage {
+    pub fn new(listing: bool) -> Self {
+        Self {
+            listing,
+            ..Self::default()
+        }
+    }
+
+    /// Registers the statements of the file at `path`, run with `globals`.
+    pub fn add_file(
+        &mut self,
+        path: Option<&Path>,
+        source: &str,
+        statements: &[Stmt],
+        globals: &RefCell<Environment>,
+    ) {
+        let mut spans = vec![];
+
+        Coverage::statements(statements, &mut spans);
+
+        let name = match path {
+            Some(path) => Coverage::display_name(path),
+            None => String::from("<script>"),
+        };
+        let file = File {
+            name,
+            source: String::from(source),
+            hits: spans.into_iter().map(|span| (span.start, 0)).collect(),
+        };
+
+        self.modules.insert(globals, self.files.len());
+        self.files.push(file);
+    }
+
+    /// Counts a run of the statement at `span` of the file run with `globals`.
+    pub fn hit(&mut self, globals: &RefCell<Environment>, span: Span) {
+        let Some(&file) = self.modules.get(&(globals as *const _)) else {
+            return;
+        };
+
+        if let Some(hits) = self.files[file].hits.get_mut(&span.start) {
+            *hits += 1;
+        }
+    }
+
+    /// The path relative to the working directory if it's inside it.
+    fn display_name(path: &Path) -> String {
+        std::env::current_dir()
+            .ok()
+            .and_then(|cwd| path.strip_prefix(cwd).ok())
+            .unwrap_or(path)
+            .display()
+            .to_string()
+    }
+
+    /// Collects the spans of the statements in `stmts` that are executed on
+    /// their own, blocks only run what's in them and methods are declared
+    /// along with their class.
+    fn statements(stmts: &[Stmt], spans: &mut Vec<Span>) {
+        for stmt in stmts {
+            Coverage::statement(stmt, spans);
+        }
+    }
+
+    fn statement(stmt: &Stmt, spans: &mut Vec<Span>) {
+        if !matches!(stmt, Stmt::Block(..) | Stmt::Empty(_)) {
+            spans.push(stmt.span());
+        }
+
+        match stmt {
+            Stmt::Expression(expr, _) | Stmt::Print(expr, _) => Coverage::expression(expr, spans),
+            Stmt::Variable(declarations, _) => Coverage::declarations(declarations, spans),
+            Stmt::Block(stmts, _) => Coverage::statements(stmts, spans),
+            Stmt::If(stmt) => {
+                Coverage::expression(&stmt.condition, spans);
+                Coverage::statement(&stmt.truth, spans);
+
+                if let Some(falsy) = &stmt.falsy {
+                    Coverage::statement(falsy, spans);
+                }
+            }
+            Stmt::While(stmt) => {
+                Coverage::expression(&stmt.condition, spans);
+                Coverage::statement(&stmt.body, spans);
+            }
+            Stmt::For(stmt) => {
+                if let Some(initializer) = &stmt.initializer {
+                    Coverage::statement(initializer, spans);
+                }
+
+                for expr in stmt.condition.iter().chain(&stmt.increment) {
+                    Coverage::expression(expr, spans);
+                }
+
+                Coverage::statement(&stmt.body, spans);
+            }
+            Stmt::ForIn(stmt) => {
+                Coverage::expression(&stmt.iterable, spans);
+                Coverage::statement(&stmt.body, spans);
+            }
+            Stmt::Function(function) => Coverage::statements(&function.body, spans),
+            Stmt::Return(stmt) => {
+                if let Some(value) = &stmt.value {
+                    Coverage::expression(value, spans);
+                }
+            }
+            Stmt::Class(class) => {
+                if let Some(super_class) = &class.super_class {
+                    Coverage::expression(super_class, spans);
+                }
+
+                for method in class.methods.iter().chain(&class.statics) {
+                    if let Stmt::Function(function) = method {
+                        Coverage::statements(&function.body, spans);
+                    }
+                }
+
+                Coverage::declarations(&class.static_fields, spans);
+            }
+            Stmt::Try(stmt) => {
+                Coverage::statements(&stmt.body, spans);
+
+                if let Some(catch) = &stmt.catch {
+                    Coverage::statements(&catch.body, spans);
+                }
+
+                if let Some(finally) = &stmt.finally {
+                    Coverage::statements(finally, spans);
+                }
+            }
+            Stmt::Throw(stmt) => Coverage::expression(&stmt.value, spans),
+            Stmt::Yield(stmt) => {
+                if let Some(value) = &stmt.value {
+                    Coverage::expression(value, spans);
+                }
+            }
+            Stmt::Exit(stmt) => {
+                if let Some(code) = &stmt.code {
+                    Coverage::expression(code, spans);
+                }
+            }
+            Stmt::Export(stmt) => Coverage::statement(&stmt.declaration, spans),
+            Stmt::Empty(_) | Stmt::Break(..) | Stmt::Continue(..) | Stmt::Import(_) => {}
+        }
+    }
+
+    fn declarations(declarations: &[VariableDeclaration], spans: &mut Vec<Span>) {
+        for initializer in declarations
+            .iter()
+            .filter_map(|var| var.initializer.as_ref())
+        {
+            Coverage::expression(initializer, spans);
+        }
+    }
+
+    /// Collects the statements of the anonymous functions in `expr`.
+    fn expression(expr: &Expr, spans: &mut Vec<Span>) {
+        let children = match expr {
+            Expr::Lambda(lambda) => return Coverage::statements(&lambda.body, spans),
+            Expr::Comma(expr) => vec![&expr.left, &expr.right],
+            Expr::Assignment(expr) => vec![&expr.value],
+            Expr::Set(expr) => vec![&expr.object, &expr.value],
+            Expr::IndexSet(expr) => vec![&expr.object, &expr.index, &expr.value],
+            Expr::Ternary(expr) => vec![&expr.condition, &expr.truth, &expr.falsy],
+            Expr::Logical(expr) => vec![&expr.left, &expr.right],
+            Expr::Binary(expr) => vec![&expr.left, &expr.right],
+            Expr::Unary(expr) => vec![&expr.right],
+            Expr::Await(expr) => vec![&expr.value],
+            Expr::Call(expr) => std::iter::once(&expr.callee)
+                .chain(&expr.arguments)
+                .collect(),
+            Expr::Get(expr) => vec![&expr.object],
+            Expr::Index(expr) => vec![&expr.object, &expr.index],
+            Expr::Slice(expr) => std::iter::once(&expr.object)
+                .chain(&expr.start)
+                .chain(&expr.end)
+                .collect(),
+            Expr::Grouping(expr, _) => vec![&**expr],
+            Expr::Array(expr) => expr.elements.iter().collect(),
+            Expr::Map(expr) => expr
+                .entries
+                .iter()
+                .flat_map(|(key, value)| [key, value])
+                .collect(),
+            Expr::Interpolation(expr) => expr.parts.iter().collect(),
+            Expr::Spread(expr) => vec![&expr.value],
+            Expr::Variable(_) | Expr::This(_) | Expr::Super(_) | Expr::Literal(..) => vec![],
+        };
+
+        for child in children {
+            Coverage::expression(child, spans);
+        }
+    }
+}
+
+impl File {
+    /// The most times a statement starting on each line ran, by line number.
+    fn lines(&self) -> BTreeMap<usize, usize> {
+        let mut lines = BTreeMap::new();
+        let mut line = 1;
+        let mut offset = 0;
+
+        for (&start, &hits) in &self.hits {
+            let start = start as usize;
+
+            line += self.source[offset..start].matches('\n').count();
+            offset = start;
+
+            let count = lines.entry(line).or_insert(0);
+
+            *count = hits.max(*count);
+        }
+
+        lines
+    }
+}
+
+impl Display for Coverage {
+    /// Renders the covered lines of each file, followed by their listing when
+    /// it was asked for.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:<40} {:>10} {:>10} {:>10}",
+            "file", "lines", "covered", "percent"
+        )?;
+
+        for file in &self.files {
+            let lines = file.lines();
+            let covered = lines.values().filter(|&&hits| hits > 0).count();
+            let percent = match lines.len() {
+                0 => 100.0,
+                total => covered as f64 * 100.0 / total as f64,
+            };
+
+            writeln!(
+                f,
+                "{:<40} {:>10} {:>10} {:>9.1}%",
+                file.name,
+                lines.len(),
+                covered,
+                percent
+            )?;
+        }
+
+        if !self.listing {
+            return Ok(());
+        }
+
+        for file in &self.files {
+            let lines = file.lines();
+
+            writeln!(f, "\n{}", file.name)?;
+
+            for (i, text) in file.source.lines().enumerate() {
+                let count = match lines.get(&(i + 1)) {
+                    Some(0) => String::from("#####"),
+                    Some(hits) => hits.to_string(),
+                    None => String::new(),
+                };
+
+                writeln!(f, "{count:>8} | {:>4} | {text}", i + 1)?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,176 @@
+//! Calendar arithmetic for the `Date` global. Dates are milliseconds since
+//! the Unix epoch and always in UTC, there's no time zone database to consult.
+
+const MILLIS_PER_DAY: i64 = 86_400_000;
+
+/// The fields of a timestamp in the proleptic Gregorian calendar.
+pub struct DateTime {
+    pub year: i64,
+    /// From 1 for January to 12.
+    pub month: i64,
+    pub day: i64,
+    pub hour: i64,
+    pub minute: i64,
+    pub second: i64,
+    pub millisecond: i64,
+    /// From 0 for Sunday to 6.
+    pub weekday: i64,
+}
+
+impl DateTime {
+    pub fn from_timestamp(timestamp: i64) -> Self {
+        let days = timestamp.div_euclid(MILLIS_PER_DAY);
+        let time = timestamp.rem_euclid(MILLIS_PER_DAY);
+        let (year, month, day) = civil_from_days(days);
+
+        Self {
+            year,
+            month,
+            day,
+            hour: time / 3_600_000,
+            minute: time / 60_000 % 60,
+            second: time / 1000 % 60,
+            millisecond: time % 1000,
+            // The epoch was a Thursday.
+            weekday: (days + 4).rem_euclid(7),
+        }
+    }
+}
+
+/// Formats `timestamp` replacing `YYYY`, `MM`, `DD`, `HH`, `mm`, `ss` and
+/// `SSS` in `pattern` with the zero padded year, month, day, hours, minutes,
+/// seconds and milliseconds, anything else is copied as is.
+pub fn format(timestamp: i64, pattern: &str) -> String {
+    let date = DateTime::from_timestamp(timestamp);
+    let mut formatted = String::new();
+    let mut rest = pattern;
+
+    while let Some(c) = rest.chars().next() {
+        let field = [
+            ("YYYY", date.year, 4),
+            ("SSS", date.millisecond, 3),
+            ("MM", date.month, 2),
+            ("DD", date.day, 2),
+            ("HH", date.hour, 2),
+            ("mm", date.minute, 2),
+            ("ss", date.second, 2),
+        ]
+        .into_iter()
+        .find(|(token, ..)| rest.starts_with(token));
+
+        match field {
+            Some((token, value, width)) => {
+                formatted.push_str(&format!("{value:0width$}"));
+                rest = &rest[token.len()..];
+            }
+            None => {
+                formatted.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+
+    formatted
+}
+
+/// Parses an ISO 8601 date, `YYYY-MM-DD` optionally followed by a `T` or a
+/// space and `HH:mm`, `HH:mm:ss` or `HH:mm:ss.SSS`, with an optional `Z`.
+/// Returns its timestamp, `None` when it isn't a valid date.
+pub fn parse(text: &str) -> Option<i64> {
+    let text = text.strip_suffix('Z').unwrap_or(text);
+    let (date, time) = match text.split_once(['T', ' ']) {
+        Some((date, time)) => (date, Some(time)),
+        None => (text, None),
+    };
+
+    let mut date = date.split('-');
+    let year = number(date.next()?, 4)?;
+    let month = number(date.next()?, 2)?;
+    let day = number(date.next()?, 2)?;
+
+    if date.next().is_some()
+        || !(1..=12).contains(&month)
+        || !(1..=days_in_month(year, month)).contains(&day)
+    {
+        return None;
+    }
+
+    let mut millis = days_from_civil(year, month, day) * MILLIS_PER_DAY;
+
+    if let Some(time) = time {
+        let (time, fraction) = match time.split_once('.') {
+            Some((time, fraction)) => (time, Some(number(fraction, 3)?)),
+            None => (time, None),
+        };
+        let mut fields = time.split(':');
+        let hour = number(fields.next()?, 2)?;
+        let minute = number(fields.next()?, 2)?;
+        let second = match fields.next() {
+            Some(second) => number(second, 2)?,
+            None if fraction.is_none() => 0,
+            None => return None,
+        };
+
+        if fields.next().is_some() || hour > 23 || minute > 59 || second > 59 {
+            return None;
+        }
+
+        millis += ((hour * 60 + minute) * 60 + second) * 1000 + fraction.unwrap_or(0);
+    }
+
+    Some(millis)
+}
+
+/// Reads exactly `digits` decimal digits.
+fn number(text: &str, digits: usize) -> Option<i64> {
+    if text.len() != digits || !text.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+
+    text.parse().ok()
+}
+
+fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// The conversions between days since the epoch and dates are Howard
+// Hinnant's, counting in 400 year eras that start on the 1st of March.
+
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    (year, month, day)
+}
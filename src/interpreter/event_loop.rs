@@ -0,0 +1,195 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    rc::Rc,
+    sync::mpsc::{self, Receiver, RecvTimeoutError, Sender},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    errors::{Limit, RuntimeError, VMException},
+    object::{Object, PlainValue},
+};
+
+use super::{
+    promises::{self, Outcome, Promise, Reaction},
+    Interpreter,
+};
+
+/// What native I/O running on another thread ends with, the message of its
+/// error rejects the promise waiting on it.
+type JobResult = Result<PlainValue, String>;
+
+/// The work left once the script itself is done: reactions to settled
+/// promises, and the promises still waiting on a timer or on native I/O.
+pub(super) struct EventLoop {
+    tasks: VecDeque<(Reaction, Outcome)>,
+    /// The promises of `delay` and what they're fulfilled with, the earliest
+    /// first.
+    timers: VecDeque<(Instant, Rc<Promise>, Object)>,
+    /// The promises waiting on threads, by the number of their job.
+    jobs: HashMap<u64, Rc<Promise>>,
+    next_job: u64,
+    sender: Sender<(u64, JobResult)>,
+    receiver: Receiver<(u64, JobResult)>,
+    /// The promises rejected while nothing reacted to them, anything can
+    /// still react before the loop is done.
+    pub(super) unhandled: Vec<Rc<Promise>>,
+}
+
+impl Default for EventLoop {
+    fn default() -> Self {
+        let (sender, receiver) = mpsc::channel();
+
+        Self {
+            tasks: VecDeque::new(),
+            timers: VecDeque::new(),
+            jobs: HashMap::new(),
+            next_job: 0,
+            sender,
+            receiver,
+            unhandled: vec![],
+        }
+    }
+}
+
+impl EventLoop {
+    pub(super) fn queue(&mut self, reaction: Reaction, outcome: Outcome) {
+        self.tasks.push_back((reaction, outcome));
+    }
+
+    fn is_idle(&self) -> bool {
+        self.tasks.is_empty() && self.timers.is_empty() && self.jobs.is_empty()
+    }
+}
+
+impl Interpreter {
+    /// Runs the reactions to settled promises, waiting for timers and native
+    /// I/O to settle more, until there's nothing left to wait for. A promise
+    /// rejected with nothing reacting to it is then reported as uncaught.
+    pub(super) fn run_event_loop(&mut self) -> Result<(), VMException> {
+        while !self.event_loop.is_idle() {
+            while let Some((reaction, outcome)) = self.event_loop.tasks.pop_front() {
+                self.react(reaction, outcome)?;
+            }
+
+            if self.event_loop.timers.is_empty() && self.event_loop.jobs.is_empty() {
+                break;
+            }
+
+            self.wait()?;
+        }
+
+        let unhandled = std::mem::take(&mut self.event_loop.unhandled);
+
+        match unhandled.iter().find_map(|promise| promise.unhandled()) {
+            Some(runtime_error) => Err(runtime_error)?,
+            None => Ok(()),
+        }
+    }
+
+    /// Blocks until the next timer is due or a job is done, settling their
+    /// promises. Waiting past the run's timeout stops it.
+    fn wait(&mut self) -> Result<(), VMException> {
+        let timer = self
+            .event_loop
+            .timers
+            .front()
+            .map(|(deadline, ..)| *deadline);
+        let deadline = match (timer, self.usage.deadline()) {
+            (Some(timer), Some(timeout)) => Some(timer.min(timeout)),
+            (timer, timeout) => timer.or(timeout),
+        };
+        let received = match deadline {
+            Some(deadline) => {
+                let timeout = deadline.saturating_duration_since(Instant::now());
+
+                self.event_loop.receiver.recv_timeout(timeout)
+            }
+            // Holding a sender, the channel never disconnects.
+            None => Ok(self.event_loop.receiver.recv().unwrap()),
+        };
+
+        match received {
+            Ok((job, result)) => {
+                if let Some(promise) = self.event_loop.jobs.remove(&job) {
+                    let outcome = result
+                        .map(Object::from)
+                        .map_err(|message| Object::String(Rc::from(message)));
+
+                    self.settle(&promise, outcome);
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => unreachable!(),
+        }
+
+        let now = Instant::now();
+
+        while let Some((deadline, ..)) = self.event_loop.timers.front() {
+            if *deadline > now {
+                break;
+            }
+
+            let (_, promise, value) = self.event_loop.timers.pop_front().unwrap();
+
+            self.settle(&promise, Ok(value));
+        }
+
+        match self.usage.deadline() {
+            Some(timeout) if now >= timeout => {
+                Err(self.limit_exceeded(Limit::Timeout, self.call_site()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// A promise fulfilled with `value` once `duration` has passed.
+    pub(super) fn timer(
+        &mut self,
+        duration: Duration,
+        value: Object,
+    ) -> Result<Rc<Promise>, RuntimeError> {
+        self.check_supported("Setting a timer")?;
+
+        let promise = Promise::pending(self.call_site());
+        let deadline = Instant::now() + duration;
+        let timers = &mut self.event_loop.timers;
+        let index = timers.partition_point(|(other, ..)| *other <= deadline);
+
+        timers.insert(index, (deadline, Rc::clone(&promise), value));
+
+        Ok(promise)
+    }
+
+    /// Runs `job` on another thread, returning a promise of what it returns.
+    /// Natives doing blocking I/O use this for their async variants.
+    pub(super) fn spawn(
+        &mut self,
+        job: impl FnOnce() -> JobResult + Send + 'static,
+    ) -> Result<Object, RuntimeError> {
+        self.check_supported("Running I/O in the background")?;
+
+        let promise = Promise::pending(self.call_site());
+        let id = self.event_loop.next_job;
+        let sender = self.event_loop.sender.clone();
+        let spawned = thread::Builder::new().spawn(move || {
+            // The interpreter may be gone by then, nothing waits anymore.
+            let _ = sender.send((id, job()));
+        });
+
+        match spawned {
+            Ok(_) => {
+                self.event_loop.next_job += 1;
+                self.event_loop.jobs.insert(id, Rc::clone(&promise));
+            }
+            Err(err) => {
+                let reason = Object::String(Rc::from(format!("Can't start a thread: {err}")));
+
+                self.settle(&promise, Err(reason));
+            }
+        }
+
+        Ok(promises::object(&promise))
+    }
+}
@@ -0,0 +1,104 @@
+use std::rc::Rc;
+
+use crate::{
+    errors::{RuntimeError, TailCall, VMException},
+    object::{Callable, Object},
+    token::Token,
+};
+
+use super::Interpreter;
+
+/// The properties [`get_property`] knows, for completion.
+pub const PROPERTIES: [&str; 3] = ["bind", "call", "apply"];
+
+pub fn get_property(callable: &Rc<dyn Callable>, name: &Token) -> Result<Object, RuntimeError> {
+    let kind = match &*name.lexeme {
+        "bind" => Kind::Bind,
+        "call" => Kind::Call,
+        "apply" => Kind::Apply,
+        _ => Err(RuntimeError {
+            token: name.clone(),
+            message: format!("Undefined function method '{}'", name.lexeme),
+        })?,
+    };
+
+    Ok(Object::Callable(Rc::new(FunctionMethod {
+        callable: Rc::clone(callable),
+        kind,
+    })))
+}
+
+#[derive(Clone, Copy)]
+enum Kind {
+    Bind,
+    Call,
+    Apply,
+}
+
+/// `bind(this)`, `call(this, ...arguments)` or `apply(this, arguments)` of a
+/// callable. `this` only replaces the instance of methods, other callables
+/// ignore it.
+///
+/// Calls are handed back to [`Interpreter::call`] as tail calls, so they're
+/// checked and traced like any other call and exceptions thrown by the
+/// callable propagate.
+struct FunctionMethod {
+    callable: Rc<dyn Callable>,
+    kind: Kind,
+}
+
+impl Callable for FunctionMethod {
+    fn arity(&self) -> usize {
+        match self.kind {
+            Kind::Bind | Kind::Call => 1,
+            Kind::Apply => 2,
+        }
+    }
+
+    fn max_arity(&self) -> Option<usize> {
+        match self.kind {
+            Kind::Call => None,
+            Kind::Bind | Kind::Apply => Some(self.arity()),
+        }
+    }
+
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        arguments: Vec<Object>,
+    ) -> Result<Object, VMException> {
+        let mut arguments = arguments.into_iter();
+        let this = arguments.next().unwrap_or(Object::Undefined);
+        let callable = self
+            .callable
+            .bind(this)
+            .unwrap_or_else(|| Rc::clone(&self.callable));
+
+        let arguments = match (self.kind, arguments.next()) {
+            (Kind::Bind, _) => return Ok(Object::Callable(callable)),
+            (Kind::Call, first) => first.into_iter().chain(arguments).collect(),
+            (Kind::Apply, Some(Object::Array(array))) => array.borrow().clone(),
+            (Kind::Apply, _) => {
+                Err(interpreter.native_error("apply() expects an array of arguments"))?
+            }
+        };
+
+        Err(VMException::TailCallException(Box::new(TailCall {
+            callable,
+            arguments,
+            call_site: interpreter.call_site(),
+        })))
+    }
+
+    fn name(&self) -> String {
+        String::from(match self.kind {
+            Kind::Bind => "bind",
+            Kind::Call => "call",
+            Kind::Apply => "apply",
+        })
+    }
+
+    fn to_string(&self) -> String {
+        format!("Native Function: ({})", self.name())
+    }
+}
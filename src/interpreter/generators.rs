@@ -0,0 +1,633 @@
+use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+
+use crate::{
+    environment::Environment,
+    errors::{RuntimeError, TailCall, VMException},
+    object::{Callable, Instance, Object, ResolvableFunction},
+    stmt::{self, Stmt},
+    token::Token,
+    utils::is_truthy,
+};
+
+use super::{Interpreter, Iteration};
+
+type Env = Rc<RefCell<Environment>>;
+
+/// What calling a generator function returns, `next()` runs its body up to
+/// the next `yield` and returns `{value, done}`: the yielded value, or what
+/// the body returned with `done` true once it's finished.
+pub fn generator(
+    function: Rc<dyn ResolvableFunction>,
+    environment: Environment,
+    globals: Env,
+) -> Object {
+    Object::Instance(Rc::new(GeneratorInstance(coroutine(
+        function,
+        environment,
+        globals,
+    ))))
+}
+
+/// A call of `function` whose body runs in steps, see
+/// [`Interpreter::resume`]. Async functions run on one too.
+pub(super) fn coroutine(
+    function: Rc<dyn ResolvableFunction>,
+    environment: Environment,
+    globals: Env,
+) -> Rc<Generator> {
+    Rc::new(Generator {
+        function,
+        environment: Rc::new(RefCell::new(environment)),
+        globals,
+        state: RefCell::new(State::Suspended(vec![])),
+    })
+}
+
+pub(super) struct Generator {
+    function: Rc<dyn ResolvableFunction>,
+    /// The environment of the call, holding the arguments.
+    environment: Env,
+    globals: Env,
+    state: RefCell<State>,
+}
+
+/// Where a step of a [`Generator`] ended.
+pub(super) enum Step {
+    /// At a `yield` of the value, or an `await` of it.
+    Suspended(Object),
+    /// At the end of the body, with what it returned.
+    Returned(Object),
+}
+
+enum State {
+    /// Waiting for `next()`, at the start of the body when there are no
+    /// frames.
+    Suspended(Vec<Frame>),
+    Running,
+    Done,
+}
+
+/// Where a suspended generator resumes inside one of the statements around
+/// its `yield`. The `yield` unwinds through them, each leaving a frame on
+/// the way out, so the outermost one is last and resuming pops them back
+/// from there.
+enum Frame {
+    /// Resumes the statement at `index`, the block's later statements run
+    /// after it.
+    Block {
+        index: usize,
+        environment: Env,
+    },
+    /// Resumes the branch of an `if` that was taken.
+    If {
+        truth: bool,
+    },
+    While,
+    /// Resumes the body of a `for` loop in the environment of its variables.
+    For {
+        environment: Env,
+    },
+    /// Resumes the body of a `for-in` loop in the environment of its item.
+    ForIn {
+        iteration: Iteration,
+        environment: Env,
+    },
+    /// Resumes the `try` block, the `catch` block or the `finally` block of
+    /// a `try` statement.
+    Try(TryStage),
+    /// The `yield` itself, which is done once resumed.
+    Yield,
+    /// A statement starting with an `await`, run again with the outcome of
+    /// the promise once it's settled, see [`Stmt::awaited`].
+    Await(Option<Result<Object, Object>>),
+}
+
+enum TryStage {
+    Body,
+    Catch,
+    /// With what the statement ends once the `finally` block is done.
+    Finally(Result<(), VMException>),
+}
+
+struct GeneratorInstance(Rc<Generator>);
+
+impl Instance for GeneratorInstance {
+    fn get(&self, _: Object, name: &Token) -> Result<Object, RuntimeError> {
+        match &*name.lexeme {
+            "next" => Ok(Object::Callable(Rc::new(Next(Rc::clone(&self.0))))),
+            _ => Err(RuntimeError {
+                token: name.clone(),
+                message: format!("Undefined property '{}' on generator", name.lexeme),
+            }),
+        }
+    }
+
+    fn set(&self, name: &Token, _: Object) -> Result<(), RuntimeError> {
+        Err(RuntimeError {
+            token: name.clone(),
+            message: String::from("Can't modify a generator"),
+        })
+    }
+
+    fn property_names(&self) -> Vec<String> {
+        vec![String::from("next")]
+    }
+
+    fn iterator(&self) -> Option<Rc<dyn Callable>> {
+        Some(Rc::new(Next(Rc::clone(&self.0))))
+    }
+
+    fn to_string(&self) -> String {
+        format!("[Generator: ({})]", self.0.function.name())
+    }
+}
+
+/// What `next()` returns, the `value` of a step and whether it's the last.
+fn step(value: Object, done: bool) -> Object {
+    let entries = BTreeMap::from([
+        (String::from("value"), value),
+        (String::from("done"), Object::Boolean(done)),
+    ]);
+
+    Object::Map(Rc::new(RefCell::new(entries)))
+}
+
+/// The value of a step returned by `next()`, `None` once it's `done`.
+pub(super) fn step_value(step: &Object) -> Option<Object> {
+    let Object::Map(entries) = step else {
+        return None;
+    };
+    let entries = entries.borrow();
+
+    match entries.get("done").is_some_and(is_truthy) {
+        true => None,
+        false => Some(entries.get("value").cloned().unwrap_or(Object::Undefined)),
+    }
+}
+
+/// The `next()` method of a generator.
+struct Next(Rc<Generator>);
+
+impl Callable for Next {
+    fn arity(&self) -> usize {
+        0
+    }
+
+    fn call(&self, interpreter: &mut Interpreter, _: Vec<Object>) -> Result<Object, VMException> {
+        match interpreter.resume(&self.0, None)? {
+            Step::Suspended(value) => Ok(step(value, false)),
+            Step::Returned(value) => Ok(step(value, true)),
+        }
+    }
+
+    fn name(&self) -> String {
+        String::from("next")
+    }
+
+    fn to_string(&self) -> String {
+        String::from("Native Function: (next)")
+    }
+}
+
+impl Interpreter {
+    /// Runs `generator` until its next `yield` or `await`, or to the end of
+    /// its body. `outcome` is what the promise it's awaiting was settled
+    /// with.
+    pub(super) fn resume(
+        &mut self,
+        generator: &Generator,
+        outcome: Option<Result<Object, Object>>,
+    ) -> Result<Step, VMException> {
+        let state = std::mem::replace(&mut *generator.state.borrow_mut(), State::Running);
+        let mut frames = match state {
+            State::Suspended(frames) => frames,
+            State::Running => Err(self.native_error("Generator is already running"))?,
+            State::Done => {
+                *generator.state.borrow_mut() = State::Done;
+
+                return Ok(Step::Returned(Object::Undefined));
+            }
+        };
+
+        // The innermost frame is the first one left.
+        if let (Some(outcome), Some(Frame::Await(awaited))) = (outcome, frames.first_mut()) {
+            *awaited = Some(outcome);
+        }
+
+        let result = self.with_globals(Rc::clone(&generator.globals), |interpreter| {
+            interpreter.resume_block(
+                generator.function.body(),
+                Rc::clone(&generator.environment),
+                &mut frames,
+            )
+        });
+
+        let (state, result) = match result {
+            Err(VMException::YieldException(value)) => {
+                (State::Suspended(frames), Ok(Step::Suspended(value)))
+            }
+            Ok(()) => (State::Done, Ok(Step::Returned(Object::Undefined))),
+            Err(VMException::ReturnException(value)) => (State::Done, Ok(Step::Returned(value))),
+            Err(VMException::TailCallException(tail_call)) => {
+                let TailCall {
+                    callable,
+                    arguments,
+                    call_site,
+                } = *tail_call;
+                let result = self.call(callable, arguments, &call_site);
+
+                (State::Done, result.map(Step::Returned))
+            }
+            Err(err) => (State::Done, Err(err)),
+        };
+
+        *generator.state.borrow_mut() = state;
+
+        result
+    }
+
+    /// Runs the block `stmts` in `environment`, or resumes it where its frame
+    /// says.
+    fn resume_block(
+        &mut self,
+        stmts: &[Stmt],
+        environment: Env,
+        frames: &mut Vec<Frame>,
+    ) -> Result<(), VMException> {
+        let (start, environment) = match frames.pop() {
+            Some(Frame::Block { index, environment }) => (index, environment),
+            None => (0, environment),
+            Some(_) => unreachable!("a block resumed from another statement's frame"),
+        };
+        let enclosing = std::mem::replace(&mut self.environment, Rc::clone(&environment));
+        let mut result = Ok(());
+
+        for (index, stmt) in stmts.iter().enumerate().skip(start) {
+            result = self.resume_stmt(stmt, frames);
+
+            if let Err(VMException::YieldException(_)) = result {
+                frames.push(Frame::Block {
+                    index,
+                    environment: Rc::clone(&environment),
+                });
+            }
+
+            if result.is_err() {
+                break;
+            }
+        }
+
+        self.environment = enclosing;
+
+        result
+    }
+
+    /// Runs `stmt` in a generator, resuming it instead when there are frames
+    /// left. Only the statements a `yield` can be nested in leave frames, the
+    /// others run as usual.
+    fn resume_stmt(&mut self, stmt: &Stmt, frames: &mut Vec<Frame>) -> Result<(), VMException> {
+        match stmt {
+            Stmt::Block(stmts, _) => {
+                if frames.is_empty() {
+                    self.track(stmt)?;
+                }
+
+                let environment = Environment::new(Some(Rc::clone(&self.environment)));
+
+                self.resume_block(stmts, Rc::new(RefCell::new(environment)), frames)
+            }
+            Stmt::If(if_stmt) => self.resume_if(stmt, if_stmt, frames),
+            Stmt::While(while_stmt) => self.resume_while(stmt, while_stmt, frames),
+            Stmt::For(for_stmt) => {
+                let (environment, resuming) = match frames.pop() {
+                    Some(Frame::For { environment }) => (environment, true),
+                    None => {
+                        self.track(stmt)?;
+
+                        let environment = Environment::new(Some(Rc::clone(&self.environment)));
+
+                        (Rc::new(RefCell::new(environment)), false)
+                    }
+                    Some(_) => unreachable!("a for loop resumed from another statement's frame"),
+                };
+                let enclosing = std::mem::replace(&mut self.environment, Rc::clone(&environment));
+                let result = self.resume_for(for_stmt, resuming, frames);
+
+                self.environment = enclosing;
+
+                if let Err(VMException::YieldException(_)) = result {
+                    frames.push(Frame::For { environment });
+                }
+
+                result
+            }
+            Stmt::ForIn(for_in) => self.resume_for_in(stmt, for_in, frames),
+            Stmt::Try(try_stmt) => self.resume_try(stmt, try_stmt, frames),
+            Stmt::Yield(_) => {
+                if let Some(frame) = frames.pop() {
+                    debug_assert!(matches!(frame, Frame::Yield));
+
+                    return Ok(());
+                }
+
+                let result = self.execute(stmt);
+
+                if let Err(VMException::YieldException(_)) = result {
+                    frames.push(Frame::Yield);
+                }
+
+                result
+            }
+            _ if stmt.awaited().is_some() => {
+                if let Some(frame) = frames.pop() {
+                    let Frame::Await(outcome) = frame else {
+                        unreachable!("an await resumed from another statement's frame")
+                    };
+
+                    self.awaited = outcome;
+
+                    return stmt.accept(self);
+                }
+
+                let result = self.execute(stmt);
+
+                if let Err(VMException::YieldException(_)) = result {
+                    frames.push(Frame::Await(None));
+                }
+
+                result
+            }
+            _ => self.execute(stmt),
+        }
+    }
+
+    fn resume_if(
+        &mut self,
+        stmt: &Stmt,
+        if_stmt: &stmt::If,
+        frames: &mut Vec<Frame>,
+    ) -> Result<(), VMException> {
+        let truth = match frames.pop() {
+            Some(Frame::If { truth }) => truth,
+            None => {
+                self.track(stmt)?;
+                is_truthy(&self.evaluate(&if_stmt.condition)?)
+            }
+            Some(_) => unreachable!("an if resumed from another statement's frame"),
+        };
+        let branch = match truth {
+            true => &if_stmt.truth,
+            false => match &if_stmt.falsy {
+                Some(falsy) => falsy,
+                None => return Ok(()),
+            },
+        };
+        let result = self.resume_stmt(branch, frames);
+
+        if let Err(VMException::YieldException(_)) = result {
+            frames.push(Frame::If { truth });
+        }
+
+        result
+    }
+
+    fn resume_while(
+        &mut self,
+        stmt: &Stmt,
+        while_stmt: &stmt::While,
+        frames: &mut Vec<Frame>,
+    ) -> Result<(), VMException> {
+        let mut resuming = match frames.pop() {
+            Some(Frame::While) => true,
+            None => {
+                self.track(stmt)?;
+                false
+            }
+            Some(_) => unreachable!("a while loop resumed from another statement's frame"),
+        };
+
+        loop {
+            if !resuming && !is_truthy(&self.evaluate(&while_stmt.condition)?) {
+                break;
+            }
+
+            resuming = false;
+
+            match self.resume_stmt(&while_stmt.body, frames) {
+                Ok(()) | Err(VMException::ContinueException) => {}
+                Err(VMException::BreakException) => break,
+                Err(err) => {
+                    if let VMException::YieldException(_) = err {
+                        frames.push(Frame::While);
+                    }
+
+                    return Err(err);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Runs or resumes a `for` loop inside the environment of its variables,
+    /// like [`Interpreter::for_loop`].
+    fn resume_for(
+        &mut self,
+        stmt: &stmt::For,
+        mut resuming: bool,
+        frames: &mut Vec<Frame>,
+    ) -> Result<(), VMException> {
+        if let (Some(initializer), false) = (&stmt.initializer, resuming) {
+            self.execute(initializer)?;
+        }
+
+        loop {
+            if let (Some(condition), false) = (&stmt.condition, resuming) {
+                if !is_truthy(&self.evaluate(condition)?) {
+                    break;
+                }
+            }
+
+            resuming = false;
+
+            match self.resume_stmt(&stmt.body, frames) {
+                Ok(()) | Err(VMException::ContinueException) => {}
+                Err(VMException::BreakException) => break,
+                Err(err) => return Err(err),
+            }
+
+            self.environment.borrow_mut().renew_slots();
+
+            if let Some(increment) = &stmt.increment {
+                self.evaluate(increment)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn resume_for_in(
+        &mut self,
+        stmt: &Stmt,
+        for_in: &stmt::ForIn,
+        frames: &mut Vec<Frame>,
+    ) -> Result<(), VMException> {
+        let (mut iteration, mut resumed) = match frames.pop() {
+            Some(Frame::ForIn {
+                iteration,
+                environment,
+            }) => (iteration, Some(environment)),
+            None => {
+                self.track(stmt)?;
+
+                let iterable = self.evaluate(&for_in.iterable)?;
+
+                (self.iteration(iterable, &for_in.keyword)?, None)
+            }
+            Some(_) => unreachable!("a for-in loop resumed from another statement's frame"),
+        };
+
+        loop {
+            let environment = match resumed.take() {
+                Some(environment) => environment,
+                None => match self.next_item(&mut iteration, &for_in.keyword)? {
+                    Some(item) => {
+                        let enclosing = Some(Rc::clone(&self.environment));
+
+                        Rc::new(RefCell::new(Environment::with_slots(enclosing, vec![item])))
+                    }
+                    None => break,
+                },
+            };
+            let enclosing = std::mem::replace(&mut self.environment, Rc::clone(&environment));
+            let result = self.resume_stmt(&for_in.body, frames);
+
+            self.environment = enclosing;
+
+            match result {
+                Ok(()) | Err(VMException::ContinueException) => {}
+                Err(VMException::BreakException) => break,
+                Err(err) => {
+                    if let VMException::YieldException(_) = err {
+                        frames.push(Frame::ForIn {
+                            iteration,
+                            environment,
+                        });
+                    }
+
+                    return Err(err);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Runs or resumes a `try` statement like [`Interpreter::visit_try_stmt`],
+    /// its `finally` block doesn't run when the function is suspended.
+    fn resume_try(
+        &mut self,
+        stmt: &Stmt,
+        try_stmt: &stmt::Try,
+        frames: &mut Vec<Frame>,
+    ) -> Result<(), VMException> {
+        let stage = match frames.pop() {
+            Some(Frame::Try(stage)) => stage,
+            None => {
+                self.track(stmt)?;
+                TryStage::Body
+            }
+            Some(_) => unreachable!("a try resumed from another statement's frame"),
+        };
+        let result = match (stage, &try_stmt.catch) {
+            (TryStage::Body, catch) => {
+                let environment = Environment::new(Some(Rc::clone(&self.environment)));
+
+                self.try_depth += 1;
+
+                let result =
+                    self.resume_block(&try_stmt.body, Rc::new(RefCell::new(environment)), frames);
+
+                self.try_depth -= 1;
+
+                match (result, catch) {
+                    (Err(VMException::YieldException(value)), _) => {
+                        frames.push(Frame::Try(TryStage::Body));
+
+                        return Err(VMException::YieldException(value));
+                    }
+                    (Err(VMException::RuntimeError(runtime_error)), Some(catch)) => {
+                        self.caught();
+
+                        let message = Object::String(Rc::from(runtime_error.message));
+
+                        self.resume_catch(catch, Some(message), frames)
+                    }
+                    (Err(VMException::ThrowException(_, value)), Some(catch)) => {
+                        self.caught();
+                        self.resume_catch(catch, Some(value), frames)
+                    }
+                    (result, _) => result,
+                }
+            }
+            (TryStage::Catch, Some(catch)) => self.resume_catch(catch, None, frames),
+            (TryStage::Catch, None) => unreachable!("a try without a catch resumed in it"),
+            (TryStage::Finally(result), _) => return self.resume_finally(try_stmt, result, frames),
+        };
+
+        if let Err(VMException::YieldException(_)) = result {
+            frames.push(Frame::Try(TryStage::Catch));
+
+            return result;
+        }
+
+        self.resume_finally(try_stmt, result, frames)
+    }
+
+    /// Runs or resumes a `catch` block, `value` is what was caught when it
+    /// starts.
+    fn resume_catch(
+        &mut self,
+        catch: &stmt::Catch,
+        value: Option<Object>,
+        frames: &mut Vec<Frame>,
+    ) -> Result<(), VMException> {
+        let enclosing = Some(Rc::clone(&self.environment));
+        let environment = match (&catch.name, value) {
+            (Some(_), Some(value)) => Environment::with_slots(enclosing, vec![value]),
+            _ => Environment::new(enclosing),
+        };
+
+        self.try_depth += 1;
+
+        let result = self.resume_block(&catch.body, Rc::new(RefCell::new(environment)), frames);
+
+        self.try_depth -= 1;
+
+        result
+    }
+
+    /// Runs or resumes the `finally` block of `try_stmt`, if any, ending
+    /// with `result` unless the block itself doesn't finish.
+    fn resume_finally(
+        &mut self,
+        try_stmt: &stmt::Try,
+        result: Result<(), VMException>,
+        frames: &mut Vec<Frame>,
+    ) -> Result<(), VMException> {
+        let Some(finally) = &try_stmt.finally else {
+            return result;
+        };
+        let environment = Environment::new(Some(Rc::clone(&self.environment)));
+
+        match self.resume_block(finally, Rc::new(RefCell::new(environment)), frames) {
+            Ok(()) => result,
+            Err(VMException::YieldException(value)) => {
+                frames.push(Frame::Try(TryStage::Finally(result)));
+
+                Err(VMException::YieldException(value))
+            }
+            Err(err) => Err(err),
+        }
+    }
+}
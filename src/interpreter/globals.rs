@@ -1,11 +1,26 @@
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::{
+    cell::RefCell,
+    env,
+    f64::consts,
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::Path,
+    rc::Rc,
+    sync::OnceLock,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
 
 use crate::{
-    errors::RuntimeError,
-    object::{Callable, Object},
+    errors::{RuntimeError, VMException},
+    object::{Callable, Namespace, NativeFunction, Object, PlainValue},
+    utils::is_truthy,
 };
 
-use super::Interpreter;
+use super::{
+    dates::{self, DateTime},
+    random::Rng,
+    Interpreter,
+};
 
 pub struct Clock;
 
@@ -14,21 +29,669 @@ impl Callable for Clock {
         0
     }
 
-    fn call(&self, _: &mut Interpreter, _: Vec<Object>) -> Result<Object, RuntimeError> {
-        let now = SystemTime::now();
-        let millis = now
+    fn call(&self, interpreter: &mut Interpreter, _: Vec<Object>) -> Result<Object, VMException> {
+        let now = interpreter.nondeterministic("clock", |interpreter| {
+            interpreter.check_supported("Reading the clock")?;
+
+            let millis = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("Time went backwards")
+                .as_millis() as f64;
+
+            Ok(Object::Number(millis))
+        })?;
+
+        Ok(now)
+    }
+
+    fn name(&self) -> String {
+        String::from("clock")
+    }
+
+    fn to_string(&self) -> String {
+        String::from("Native Function: (clock)")
+    }
+}
+
+/// The current time in milliseconds since the Unix epoch, as recorded or
+/// replayed for `native`.
+fn now_millis(interpreter: &mut Interpreter, native: &str) -> Result<i64, RuntimeError> {
+    let now = interpreter.nondeterministic(native, |interpreter| {
+        interpreter.check_supported("Reading the clock")?;
+
+        let millis = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .expect("Time went backwards")
-            .as_millis() as f64;
+            .as_millis() as i64;
+
+        Ok(Object::Int(millis))
+    })?;
+
+    match now {
+        Object::Int(millis) => Ok(millis),
+        _ => Err(interpreter.native_error("Replay diverged: expected a timestamp")),
+    }
+}
 
-        Ok(Object::Number(millis))
+pub fn now() -> Object {
+    Object::Callable(Rc::new(NativeFunction::new("now", 0, |interpreter, _| {
+        Ok(Object::Int(now_millis(interpreter, "now")?))
+    })))
+}
+
+/// Blocks for a number of milliseconds, see [`Interpreter::sleep`].
+pub struct Sleep;
+
+impl Callable for Sleep {
+    fn arity(&self) -> usize {
+        1
+    }
+
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        arguments: Vec<Object>,
+    ) -> Result<Object, VMException> {
+        let duration = expect_duration(interpreter, &arguments[0])?;
+
+        interpreter.sleep(duration)?;
+
+        Ok(Object::Undefined)
+    }
+
+    fn name(&self) -> String {
+        String::from("sleep")
     }
 
     fn to_string(&self) -> String {
-        String::from("Native Function: (clock)")
+        String::from("Native Function: (sleep)")
+    }
+}
+
+/// Calls a function a number of times with the count of previous calls,
+/// sleeping the optional interval in between like `setInterval`.
+pub struct Repeat;
+
+impl Callable for Repeat {
+    fn arity(&self) -> usize {
+        2
+    }
+
+    fn max_arity(&self) -> Option<usize> {
+        Some(3)
+    }
+
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        arguments: Vec<Object>,
+    ) -> Result<Object, VMException> {
+        let call_site = interpreter.call_site();
+        let mut arguments = arguments.into_iter();
+        let times = arguments.next().unwrap_or(Object::Undefined);
+        let times = expect_index(interpreter, &times, usize::MAX)?;
+        let callback = expect_callback(interpreter, arguments.next())?;
+        let interval = match arguments.next() {
+            Some(interval) => expect_duration(interpreter, &interval)?,
+            None => Duration::ZERO,
+        };
+
+        for i in 0..times {
+            if i > 0 && !interval.is_zero() {
+                interpreter.sleep(interval)?;
+            }
+
+            interpreter.call(
+                Rc::clone(&callback),
+                vec![Object::Int(i as i64)],
+                &call_site,
+            )?;
+        }
+
+        Ok(Object::Undefined)
+    }
+
+    fn name(&self) -> String {
+        String::from("repeat")
+    }
+
+    fn to_string(&self) -> String {
+        String::from("Native Function: (repeat)")
+    }
+}
+
+pub fn expect_duration(
+    interpreter: &Interpreter,
+    value: &Object,
+) -> Result<Duration, RuntimeError> {
+    match expect_number(interpreter, value)? {
+        millis if millis >= 0.0 && millis.is_finite() => {
+            Ok(Duration::from_secs_f64(millis / 1000.0))
+        }
+        _ => Err(interpreter.native_error("Expected a non-negative number of milliseconds")),
+    }
+}
+
+pub fn date() -> Namespace {
+    fn expect_timestamp(interpreter: &Interpreter, value: &Object) -> Result<i64, RuntimeError> {
+        match value {
+            Object::Int(int) => Ok(*int),
+            Object::Number(number) if number.is_finite() => Ok(number.floor() as i64),
+            _ => Err(interpreter.native_error("Expected a timestamp in milliseconds")),
+        }
+    }
+
+    let mut date = Namespace::new("Date");
+
+    date.define(
+        "now",
+        Object::Callable(Rc::new(NativeFunction::new("now", 0, |interpreter, _| {
+            Ok(Object::Int(now_millis(interpreter, "Date.now")?))
+        }))),
+    )
+    .define(
+        "monotonic",
+        Object::Callable(Rc::new(NativeFunction::new(
+            "monotonic",
+            0,
+            |interpreter, _| {
+                interpreter.nondeterministic("Date.monotonic", |interpreter| {
+                    interpreter.check_supported("Reading the clock")?;
+
+                    static START: OnceLock<Instant> = OnceLock::new();
+
+                    let elapsed = START.get_or_init(Instant::now).elapsed();
+
+                    Ok(Object::Number(elapsed.as_secs_f64() * 1000.0))
+                })
+            },
+        ))),
+    )
+    .define(
+        "parts",
+        Object::Callable(Rc::new(
+            NativeFunction::new("parts", 0, |interpreter, args| {
+                let timestamp = match args.first() {
+                    Some(timestamp) => expect_timestamp(interpreter, timestamp)?,
+                    None => now_millis(interpreter, "Date.parts")?,
+                };
+                let date = DateTime::from_timestamp(timestamp);
+                let parts = [
+                    ("year", date.year),
+                    ("month", date.month),
+                    ("day", date.day),
+                    ("hour", date.hour),
+                    ("minute", date.minute),
+                    ("second", date.second),
+                    ("millisecond", date.millisecond),
+                    ("weekday", date.weekday),
+                ]
+                .into_iter()
+                .map(|(name, value)| (String::from(name), Object::Int(value)))
+                .collect();
+
+                Ok(Object::Map(Rc::new(RefCell::new(parts))))
+            })
+            .optional(1),
+        )),
+    )
+    .define(
+        "format",
+        Object::Callable(Rc::new(NativeFunction::new(
+            "format",
+            2,
+            |interpreter, args| {
+                let timestamp = expect_timestamp(interpreter, &args[0])?;
+                let pattern = expect_string(interpreter, &args[1])?;
+
+                Ok(Object::String(Rc::from(dates::format(timestamp, &pattern))))
+            },
+        ))),
+    )
+    .define(
+        "parse",
+        Object::Callable(Rc::new(NativeFunction::new(
+            "parse",
+            1,
+            |interpreter, args| {
+                let text = expect_string(interpreter, &args[0])?;
+
+                match dates::parse(&text) {
+                    Some(timestamp) => Ok(Object::Int(timestamp)),
+                    None => Err(interpreter.native_error(&format!("Invalid date '{text}'"))),
+                }
+            },
+        ))),
+    );
+
+    date
+}
+
+pub fn math() -> Namespace {
+    fn unary(name: &str, operation: fn(f64) -> f64) -> Object {
+        Object::Callable(Rc::new(NativeFunction::new(
+            name,
+            1,
+            move |interpreter, args| {
+                Ok(Object::Number(operation(expect_number(
+                    interpreter,
+                    &args[0],
+                )?)))
+            },
+        )))
     }
 
-    fn bind(&self, _: Object) -> Object {
-        unreachable!()
+    fn fold(name: &str, operation: fn(f64, f64) -> f64) -> Object {
+        Object::Callable(Rc::new(
+            NativeFunction::new(name, 2, move |interpreter, args| {
+                let mut result = expect_number(interpreter, &args[0])?;
+
+                for arg in &args[1..] {
+                    result = operation(result, expect_number(interpreter, arg)?);
+                }
+
+                Ok(Object::Number(result))
+            })
+            .variadic(),
+        ))
+    }
+
+    let mut math = Namespace::new("Math");
+
+    math.define("PI", Object::Number(consts::PI))
+        .define("E", Object::Number(consts::E))
+        .define("abs", unary("abs", f64::abs))
+        .define("floor", unary("floor", f64::floor))
+        .define("ceil", unary("ceil", f64::ceil))
+        .define("sqrt", unary("sqrt", f64::sqrt))
+        .define("sin", unary("sin", f64::sin))
+        .define("cos", unary("cos", f64::cos))
+        .define("min", fold("min", f64::min))
+        .define("max", fold("max", f64::max))
+        .define(
+            "pow",
+            Object::Callable(Rc::new(NativeFunction::new(
+                "pow",
+                2,
+                |interpreter, args| {
+                    let base = expect_number(interpreter, &args[0])?;
+                    let exponent = expect_number(interpreter, &args[1])?;
+
+                    Ok(Object::Number(base.powf(exponent)))
+                },
+            ))),
+        )
+        .define(
+            "random",
+            Object::Callable(Rc::new(NativeFunction::new(
+                "random",
+                0,
+                |interpreter, _| Ok(Object::Number(interpreter.rng().next_f64())),
+            ))),
+        );
+
+    math
+}
+
+pub fn number() -> Namespace {
+    let mut number = Namespace::new("Number");
+
+    number
+        .define("INFINITY", Object::Number(f64::INFINITY))
+        .define("NAN", Object::Number(f64::NAN));
+
+    number
+}
+
+pub fn env() -> Namespace {
+    let mut env = Namespace::new("Env");
+
+    env.define(
+        "get",
+        Object::Callable(Rc::new(NativeFunction::new(
+            "get",
+            1,
+            |interpreter, args| {
+                let name = expect_string(interpreter, &args[0])?;
+
+                if let Some(value) = interpreter.env_vars().get(&name) {
+                    return Ok(Object::String(Rc::from(value.as_str())));
+                }
+
+                interpreter.nondeterministic("Env.get", |_| {
+                    Ok(match env::var(name) {
+                        Ok(value) => Object::String(Rc::from(value)),
+                        Err(_) => Object::Undefined,
+                    })
+                })
+            },
+        ))),
+    )
+    .define(
+        "set",
+        Object::Callable(Rc::new(NativeFunction::new(
+            "set",
+            2,
+            |interpreter, args| {
+                let name = expect_string(interpreter, &args[0])?;
+
+                if name.is_empty() || name.contains(['=', '\0']) {
+                    return Err(interpreter
+                        .native_error(&format!("Invalid environment variable name '{name}'")));
+                }
+
+                let value = args[1].to_string();
+
+                if value.contains('\0') {
+                    return Err(interpreter.native_error("Environment values can't contain NUL"));
+                }
+
+                interpreter.env_vars().insert(name, value);
+
+                Ok(Object::Undefined)
+            },
+        ))),
+    );
+
+    env
+}
+
+pub fn random() -> Object {
+    Object::Callable(Rc::new(NativeFunction::new(
+        "random",
+        0,
+        |interpreter, _| Ok(Object::Number(interpreter.rng().next_f64())),
+    )))
+}
+
+pub fn random_int() -> Object {
+    Object::Callable(Rc::new(NativeFunction::new(
+        "randomInt",
+        2,
+        |interpreter, args| {
+            let (Object::Int(low), Object::Int(high)) = (&args[0], &args[1]) else {
+                return Err(interpreter.native_error("Expected integer bounds"));
+            };
+
+            if low > high {
+                return Err(interpreter.native_error(&format!(
+                    "randomInt() low bound {low} is greater than high bound {high}"
+                )));
+            }
+
+            Ok(Object::Int(interpreter.rng().range(*low, *high)))
+        },
+    )))
+}
+
+pub fn random_namespace() -> Namespace {
+    let mut random = Namespace::new("Random");
+
+    random.define(
+        "seed",
+        Object::Callable(Rc::new(NativeFunction::new(
+            "seed",
+            1,
+            |interpreter, args| {
+                let Object::Int(seed) = args[0] else {
+                    return Err(interpreter.native_error("Expected an integer seed"));
+                };
+
+                *interpreter.rng() = Rng::with_seed(seed as u64);
+
+                Ok(Object::Undefined)
+            },
+        ))),
+    );
+
+    random
+}
+
+pub fn file() -> Namespace {
+    fn io_message(action: &str, path: &str, err: io::Error) -> String {
+        format!("Can't {action} '{path}': {err}")
+    }
+
+    fn io_error(
+        interpreter: &Interpreter,
+        action: &str,
+        path: &str,
+        err: io::Error,
+    ) -> RuntimeError {
+        interpreter.native_error(&io_message(action, path, err))
+    }
+
+    let mut file = Namespace::new("File");
+
+    file.define(
+        "read",
+        Object::Callable(Rc::new(NativeFunction::new(
+            "read",
+            1,
+            |interpreter, args| {
+                let path = expect_string(interpreter, &args[0])?;
+                let text = fs::read_to_string(&path)
+                    .map_err(|err| io_error(interpreter, "read", &path, err))?;
+
+                Ok(Object::String(Rc::from(text)))
+            },
+        ))),
+    )
+    .define(
+        "write",
+        Object::Callable(Rc::new(NativeFunction::new(
+            "write",
+            2,
+            |interpreter, args| {
+                let path = expect_string(interpreter, &args[0])?;
+                let text = expect_string(interpreter, &args[1])?;
+
+                fs::write(&path, text).map_err(|err| io_error(interpreter, "write", &path, err))?;
+
+                Ok(Object::Undefined)
+            },
+        ))),
+    )
+    .define(
+        "append",
+        Object::Callable(Rc::new(NativeFunction::new(
+            "append",
+            2,
+            |interpreter, args| {
+                let path = expect_string(interpreter, &args[0])?;
+                let text = expect_string(interpreter, &args[1])?;
+
+                OpenOptions::new()
+                    .append(true)
+                    .create(true)
+                    .open(&path)
+                    .and_then(|mut file| file.write_all(text.as_bytes()))
+                    .map_err(|err| io_error(interpreter, "append to", &path, err))?;
+
+                Ok(Object::Undefined)
+            },
+        ))),
+    )
+    .define(
+        "exists",
+        Object::Callable(Rc::new(NativeFunction::new(
+            "exists",
+            1,
+            |interpreter, args| {
+                let path = expect_string(interpreter, &args[0])?;
+
+                Ok(Object::Boolean(Path::new(&path).exists()))
+            },
+        ))),
+    )
+    .define(
+        "delete",
+        Object::Callable(Rc::new(NativeFunction::new(
+            "delete",
+            1,
+            |interpreter, args| {
+                let path = expect_string(interpreter, &args[0])?;
+
+                fs::remove_file(&path)
+                    .map_err(|err| io_error(interpreter, "delete", &path, err))?;
+
+                Ok(Object::Undefined)
+            },
+        ))),
+    )
+    .define(
+        "readAsync",
+        Object::Callable(Rc::new(NativeFunction::new(
+            "readAsync",
+            1,
+            |interpreter, args| {
+                let path = expect_string(interpreter, &args[0])?;
+
+                interpreter.spawn(move || {
+                    fs::read_to_string(&path)
+                        .map(PlainValue::String)
+                        .map_err(|err| io_message("read", &path, err))
+                })
+            },
+        ))),
+    )
+    .define(
+        "writeAsync",
+        Object::Callable(Rc::new(NativeFunction::new(
+            "writeAsync",
+            2,
+            |interpreter, args| {
+                let path = expect_string(interpreter, &args[0])?;
+                let text = expect_string(interpreter, &args[1])?;
+
+                interpreter.spawn(move || {
+                    fs::write(&path, text)
+                        .map(|()| PlainValue::Undefined)
+                        .map_err(|err| io_message("write", &path, err))
+                })
+            },
+        ))),
+    );
+
+    file
+}
+
+pub fn type_of() -> Object {
+    Object::Callable(Rc::new(NativeFunction::new("type", 1, |_, args| {
+        Ok(Object::String(Rc::from(args[0].type_name())))
+    })))
+}
+
+pub fn assert() -> Object {
+    Object::Callable(Rc::new(
+        NativeFunction::new("assert", 1, |interpreter, args| {
+            if is_truthy(&args[0]) {
+                return Ok(Object::Undefined);
+            }
+
+            Err(match args.get(1) {
+                Some(message) => interpreter.native_error(&format!("Assertion failed: {message}")),
+                None => interpreter.native_error("Assertion failed"),
+            })
+        })
+        .optional(1),
+    ))
+}
+
+pub fn is_nan() -> Object {
+    Object::Callable(Rc::new(NativeFunction::new(
+        "isNaN",
+        1,
+        |interpreter, args| {
+            let number = expect_number(interpreter, &args[0])?;
+
+            Ok(Object::Boolean(number.is_nan()))
+        },
+    )))
+}
+
+pub fn input() -> Object {
+    Object::Callable(Rc::new(
+        NativeFunction::new("input", 0, |interpreter, args| {
+            if let Some(prompt) = args.first() {
+                interpreter.write_output(&prompt.to_string())?;
+            }
+
+            interpreter.nondeterministic("input", read_stdin_line)
+        })
+        .optional(1),
+    ))
+}
+
+pub fn read_line() -> Object {
+    Object::Callable(Rc::new(NativeFunction::new(
+        "readLine",
+        0,
+        |interpreter, _| interpreter.nondeterministic("readLine", read_stdin_line),
+    )))
+}
+
+pub fn eprint() -> Object {
+    Object::Callable(Rc::new(NativeFunction::new("eprint", 1, |_, args| {
+        eprintln!("{}", args[0]);
+
+        Ok(Object::Undefined)
+    })))
+}
+
+/// Reads a line from stdin without its line ending, `undefined` once stdin
+/// is exhausted.
+fn read_stdin_line(interpreter: &mut Interpreter) -> Result<Object, RuntimeError> {
+    let mut line = String::new();
+    let read = io::stdin()
+        .read_line(&mut line)
+        .map_err(|err| interpreter.native_error(&err.to_string()))?;
+
+    if read == 0 {
+        return Ok(Object::Undefined);
+    }
+
+    let trimmed = line.trim_end_matches(['\n', '\r']).len();
+
+    line.truncate(trimmed);
+
+    Ok(Object::String(Rc::from(line)))
+}
+
+pub fn expect_number(interpreter: &Interpreter, value: &Object) -> Result<f64, RuntimeError> {
+    match value {
+        Object::Int(int) => Ok(*int as f64),
+        Object::Number(number) => Ok(*number),
+        _ => Err(interpreter.native_error("Expected a number argument")),
+    }
+}
+
+pub fn expect_callback(
+    interpreter: &Interpreter,
+    value: Option<Object>,
+) -> Result<Rc<dyn Callable>, RuntimeError> {
+    match value {
+        Some(Object::Callable(callable)) => Ok(callable),
+        Some(Object::CallableInstance(callable)) => Ok(callable),
+        _ => Err(interpreter.native_error("Expected a function argument")),
+    }
+}
+
+pub fn expect_string(interpreter: &Interpreter, value: &Object) -> Result<String, RuntimeError> {
+    match value {
+        Object::String(string) => Ok(string.to_string()),
+        _ => Err(interpreter.native_error("Expected a string argument")),
+    }
+}
+
+pub fn expect_index(
+    interpreter: &Interpreter,
+    value: &Object,
+    len: usize,
+) -> Result<usize, RuntimeError> {
+    match value {
+        Object::Int(int) if *int >= 0 => Ok((*int as usize).min(len)),
+        Object::Number(n) if n.fract() == 0.0 && *n >= 0.0 => Ok((*n as usize).min(len)),
+        _ => Err(interpreter.native_error("Expected a non-negative integer argument")),
     }
 }
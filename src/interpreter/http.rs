@@ -0,0 +1,112 @@
+use std::{collections::BTreeMap, rc::Rc};
+
+use ureq::{http::Response, Agent, Body};
+
+use crate::object::{Namespace, NativeFunction, Object, PlainValue};
+
+use super::globals::expect_string;
+
+pub fn http() -> Namespace {
+    let mut http = Namespace::new("Http");
+
+    http.define(
+        "get",
+        Object::Callable(Rc::new(NativeFunction::new(
+            "get",
+            1,
+            |interpreter, args| {
+                let url = expect_string(interpreter, &args[0])?;
+
+                get(&url)
+                    .map(Object::from)
+                    .map_err(|message| interpreter.native_error(&message))
+            },
+        ))),
+    )
+    .define(
+        "post",
+        Object::Callable(Rc::new(NativeFunction::new(
+            "post",
+            2,
+            |interpreter, args| {
+                let url = expect_string(interpreter, &args[0])?;
+
+                // Anything but a string is sent as it would be printed.
+                post(&url, args[1].to_string())
+                    .map(Object::from)
+                    .map_err(|message| interpreter.native_error(&message))
+            },
+        ))),
+    )
+    .define(
+        "getAsync",
+        Object::Callable(Rc::new(NativeFunction::new(
+            "getAsync",
+            1,
+            |interpreter, args| {
+                let url = expect_string(interpreter, &args[0])?;
+
+                interpreter.spawn(move || get(&url))
+            },
+        ))),
+    )
+    .define(
+        "postAsync",
+        Object::Callable(Rc::new(NativeFunction::new(
+            "postAsync",
+            2,
+            |interpreter, args| {
+                let url = expect_string(interpreter, &args[0])?;
+                let body = args[1].to_string();
+
+                interpreter.spawn(move || post(&url, body))
+            },
+        ))),
+    );
+
+    http
+}
+
+fn get(url: &str) -> Result<PlainValue, String> {
+    into_plain(url, agent().get(url).call())
+}
+
+fn post(url: &str, body: String) -> Result<PlainValue, String> {
+    into_plain(url, agent().post(url).send(body))
+}
+
+/// Error statuses are responses like any other, scripts check `status`.
+fn agent() -> Agent {
+    Agent::config_builder()
+        .http_status_as_error(false)
+        .build()
+        .into()
+}
+
+/// The response as a map of its `status`, `headers` and `body`. Being
+/// plain, it can be sent back from the thread of an async request.
+fn into_plain(
+    url: &str,
+    response: Result<Response<Body>, ureq::Error>,
+) -> Result<PlainValue, String> {
+    let failed = |err: ureq::Error| format!("Request to '{url}' failed: {err}");
+    let mut response = response.map_err(failed)?;
+    let headers = response
+        .headers()
+        .iter()
+        .map(|(name, value)| {
+            let value = String::from_utf8_lossy(value.as_bytes());
+
+            (name.to_string(), PlainValue::String(value.into_owned()))
+        })
+        .collect::<BTreeMap<_, _>>();
+    let status = i64::from(response.status().as_u16());
+    let body = response.body_mut().read_to_string().map_err(failed)?;
+    let fields = BTreeMap::from([
+        (String::from("status"), PlainValue::Int(status)),
+        (String::from("headers"), PlainValue::Map(headers)),
+        (String::from("body"), PlainValue::String(body)),
+    ]);
+
+    Ok(PlainValue::Map(fields))
+}
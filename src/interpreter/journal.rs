@@ -0,0 +1,273 @@
+use std::{
+    collections::VecDeque,
+    fmt::{self, Display},
+    rc::Rc,
+    str::FromStr,
+};
+
+use crate::{errors::RuntimeError, object::Object};
+
+use super::{random::Rng, Interpreter};
+
+/// The first line of every journal, followed by the version of its format.
+const HEADER: &str = "typhoon-journal 1";
+
+/// The results of the natives a script can't know in advance, the clock,
+/// `input`, `readLine` and `Env.get`, along with the seed of its random
+/// numbers, in the order they were called.
+///
+/// A journal recorded while running a script makes it run the same way
+/// again: replaying it, those natives return what they returned then
+/// instead of asking the system.
+///
+/// ```
+/// use typhoon::{
+///     interpreter::{CapturedOutput, Journal},
+///     Lib,
+/// };
+///
+/// let script = "print now() + random();";
+/// let run = |lib: &mut Lib| {
+///     let output = CapturedOutput::new();
+///
+///     lib.set_output(output.clone());
+///     lib.run(String::from(script)).unwrap();
+///
+///     output.contents()
+/// };
+///
+/// let mut recording = Lib::new();
+/// recording.start_recording();
+///
+/// let recorded = run(&mut recording);
+/// let journal = recording.take_journal().unwrap().to_string();
+///
+/// let mut replaying = Lib::new();
+/// replaying.replay(journal.parse::<Journal>().unwrap());
+///
+/// assert_eq!(run(&mut replaying), recorded);
+/// ```
+pub struct Journal {
+    seed: u64,
+    entries: VecDeque<Entry>,
+    replaying: bool,
+}
+
+struct Entry {
+    /// The name of the native, e.g. `Date.now`.
+    native: String,
+    /// What it returned, or the message of its error.
+    result: Result<Recorded, String>,
+}
+
+/// The values natives return that journals keep.
+enum Recorded {
+    Undefined,
+    Int(i64),
+    Number(f64),
+    String(String),
+}
+
+impl Journal {
+    fn recording(seed: u64) -> Self {
+        Self {
+            seed,
+            entries: VecDeque::new(),
+            replaying: false,
+        }
+    }
+
+    fn escape(text: &str) -> String {
+        let mut escaped = String::with_capacity(text.len());
+
+        for char in text.chars() {
+            match char {
+                '\\' => escaped.push_str("\\\\"),
+                '\n' => escaped.push_str("\\n"),
+                '\r' => escaped.push_str("\\r"),
+                '\t' => escaped.push_str("\\t"),
+                char => escaped.push(char),
+            }
+        }
+
+        escaped
+    }
+
+    fn unescape(text: &str) -> Result<String, String> {
+        let mut unescaped = String::with_capacity(text.len());
+        let mut chars = text.chars();
+
+        while let Some(char) = chars.next() {
+            if char != '\\' {
+                unescaped.push(char);
+                continue;
+            }
+
+            match chars.next() {
+                Some('\\') => unescaped.push('\\'),
+                Some('n') => unescaped.push('\n'),
+                Some('r') => unescaped.push('\r'),
+                Some('t') => unescaped.push('\t'),
+                Some(other) => return Err(format!("unknown escape '\\{other}'")),
+                None => return Err(String::from("unterminated escape")),
+            }
+        }
+
+        Ok(unescaped)
+    }
+
+    fn parse_entry(line: &str) -> Result<Entry, String> {
+        let mut fields = line.splitn(3, ' ');
+        let native = fields.next().unwrap_or_default();
+        let kind = fields.next().ok_or("expected the kind of the result")?;
+        let value = fields.next().unwrap_or_default();
+        let number = |value: &str| format!("invalid {kind} '{value}'");
+        let result = match kind {
+            "undefined" => Ok(Recorded::Undefined),
+            "int" => Ok(Recorded::Int(value.parse().map_err(|_| number(value))?)),
+            "number" => Ok(Recorded::Number(value.parse().map_err(|_| number(value))?)),
+            "string" => Ok(Recorded::String(Journal::unescape(value)?)),
+            "error" => Err(Journal::unescape(value)?),
+            _ => return Err(format!("unknown kind of result '{kind}'")),
+        };
+
+        Ok(Entry {
+            native: String::from(native),
+            result,
+        })
+    }
+}
+
+impl Display for Journal {
+    /// Renders the journal one entry per line, after the header and the seed.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{HEADER}")?;
+        writeln!(f, "seed {}", self.seed)?;
+
+        for Entry { native, result } in &self.entries {
+            match result {
+                Ok(Recorded::Undefined) => writeln!(f, "{native} undefined")?,
+                Ok(Recorded::Int(int)) => writeln!(f, "{native} int {int}")?,
+                Ok(Recorded::Number(number)) => writeln!(f, "{native} number {number}")?,
+                Ok(Recorded::String(string)) => {
+                    writeln!(f, "{native} string {}", Journal::escape(string))?
+                }
+                Err(message) => writeln!(f, "{native} error {}", Journal::escape(message))?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Journal {
+    type Err = String;
+
+    /// Parses a journal rendered by [`Journal`]'s `Display`, to be replayed.
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        let mut lines = source.lines().enumerate();
+
+        if lines.next().map(|(_, line)| line) != Some(HEADER) {
+            return Err(String::from(
+                "Not a journal, expected it to start with the header",
+            ));
+        }
+
+        let seed = lines
+            .next()
+            .and_then(|(_, line)| line.strip_prefix("seed "))
+            .and_then(|seed| seed.parse().ok())
+            .ok_or("Expected the seed on line 2")?;
+        let entries = lines
+            .map(|(index, line)| {
+                Journal::parse_entry(line).map_err(|err| format!("Line {}: {err}", index + 1))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            seed,
+            entries,
+            replaying: true,
+        })
+    }
+}
+
+impl Interpreter {
+    /// Starts recording the results of nondeterministic natives into a
+    /// [`Journal`], reseeding the random numbers so it can hold their seed.
+    pub fn start_recording(&mut self) {
+        let seed = Rng::entropy();
+
+        self.rng = Rng::with_seed(seed);
+        self.journal = Some(Journal::recording(seed));
+    }
+
+    /// Makes nondeterministic natives return the results recorded in
+    /// `journal` instead, a native called out of order is an error.
+    pub fn replay(&mut self, journal: Journal) {
+        self.rng = Rng::with_seed(journal.seed);
+        self.journal = Some(journal);
+    }
+
+    /// Stops recording or replaying, returning the journal recorded since
+    /// [`Interpreter::start_recording`], or what's left to replay.
+    pub fn take_journal(&mut self) -> Option<Journal> {
+        self.journal.take()
+    }
+
+    /// Runs `produce` for the result of the nondeterministic `native`,
+    /// recording it when a journal is, or returns the one recorded instead
+    /// when replaying. `produce` may only return undefined, an integer, a
+    /// number or a string.
+    pub(super) fn nondeterministic(
+        &mut self,
+        native: &str,
+        produce: impl FnOnce(&mut Interpreter) -> Result<Object, RuntimeError>,
+    ) -> Result<Object, RuntimeError> {
+        let Some(journal) = &mut self.journal else {
+            return produce(self);
+        };
+
+        if journal.replaying {
+            let Some(entry) = journal.entries.pop_front() else {
+                return Err(self.native_error(&format!(
+                    "Replay diverged: the journal has no result left for '{native}'"
+                )));
+            };
+
+            if entry.native != native {
+                return Err(self.native_error(&format!(
+                    "Replay diverged: the journal has a result of '{}' next, not of '{native}'",
+                    entry.native
+                )));
+            }
+
+            return match entry.result {
+                Ok(Recorded::Undefined) => Ok(Object::Undefined),
+                Ok(Recorded::Int(int)) => Ok(Object::Int(int)),
+                Ok(Recorded::Number(number)) => Ok(Object::Number(number)),
+                Ok(Recorded::String(string)) => Ok(Object::String(Rc::from(string))),
+                Err(message) => Err(self.native_error(&message)),
+            };
+        }
+
+        let result = produce(self);
+        let recorded = match &result {
+            Ok(Object::Undefined) => Ok(Recorded::Undefined),
+            Ok(Object::Int(int)) => Ok(Recorded::Int(*int)),
+            Ok(Object::Number(number)) => Ok(Recorded::Number(*number)),
+            Ok(Object::String(string)) => Ok(Recorded::String(string.to_string())),
+            Ok(other) => unreachable!("Can't record {other} in a journal"),
+            Err(runtime_error) => Err(runtime_error.message.clone()),
+        };
+
+        if let Some(journal) = &mut self.journal {
+            journal.entries.push_back(Entry {
+                native: String::from(native),
+                result: recorded,
+            });
+        }
+
+        result
+    }
+}
@@ -0,0 +1,127 @@
+use std::time::{Duration, Instant};
+
+use crate::errors::Limit;
+
+/// Bounds on the work a single run may do, so embedders can run untrusted
+/// scripts without them looping or recursing forever. Only the call depth is
+/// limited by default, to what fits the stack of a spawned thread.
+///
+/// Going over the call depth is an error `try` can catch, the other limits
+/// stop the script with one it can't.
+///
+/// ```
+/// use std::thread;
+///
+/// use typhoon::{errors::Limit, Lib, TyphoonError};
+///
+/// thread::spawn(|| {
+///     let mut lib = Lib::new();
+///
+///     assert!(lib.run(String::from("fun depth(n) { return 1 + depth(n + 1); }")).is_ok());
+///     assert!(matches!(
+///         lib.run(String::from("depth(0);")),
+///         Err(TyphoonError::LimitExceeded(Limit::CallDepth, _))
+///     ));
+///     assert!(lib.run(String::from("try { depth(0); } catch (error) {}")).is_ok());
+/// })
+/// .join()
+/// .unwrap();
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Limits {
+    /// How many statements may be executed.
+    pub max_statements: Option<u64>,
+    /// How deeply calls may nest, running a script on a thread with a
+    /// bigger stack than [`Limits::DEFAULT_STACK_SIZE`] allows raising it
+    /// with [`Limits::call_depth_for_stack`].
+    pub max_call_depth: Option<usize>,
+    /// How long the run may take, ignored on `wasm32` which has no clock.
+    pub timeout: Option<Duration>,
+}
+
+impl Limits {
+    /// The stack the default call depth is fitted to: the 2 MiB of a thread
+    /// started with `std::thread::spawn`, the smallest an embedder gets
+    /// without asking for a size, or the 1 MiB `wasm32` gets.
+    pub const DEFAULT_STACK_SIZE: usize = if cfg!(target_arch = "wasm32") {
+        1 << 20
+    } else {
+        2 << 20
+    };
+
+    /// A generous bound on the stack a nested call takes, the interpreter
+    /// recursing through the AST uses much more of it without optimizations.
+    const STACK_PER_CALL: usize = if cfg!(debug_assertions) {
+        32 << 10
+    } else {
+        8 << 10
+    };
+
+    /// The call depth a thread with `stack_size` bytes of stack runs to
+    /// without overflowing it, leaving an eighth to the host.
+    pub const fn call_depth_for_stack(stack_size: usize) -> usize {
+        stack_size / 8 * 7 / Limits::STACK_PER_CALL
+    }
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_statements: None,
+            max_call_depth: Some(Limits::call_depth_for_stack(Limits::DEFAULT_STACK_SIZE)),
+            timeout: None,
+        }
+    }
+}
+
+/// What the current run has used of its [`Limits`].
+#[derive(Default)]
+pub(super) struct Usage {
+    statements: u64,
+    deadline: Option<Instant>,
+}
+
+impl Usage {
+    /// Reading the clock costs more than most statements, the deadline is
+    /// only checked this often.
+    const CLOCK_INTERVAL: u64 = 1024;
+
+    pub fn start(limits: &Limits) -> Self {
+        Self {
+            statements: 0,
+            // There's no clock to time the run with on `wasm32`.
+            deadline: limits
+                .timeout
+                .filter(|_| !cfg!(target_arch = "wasm32"))
+                .map(|timeout| Instant::now() + timeout),
+        }
+    }
+
+    /// When the run times out, if it has a timeout.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Counts a statement about to be executed, returning the limit it goes
+    /// over if any.
+    pub fn step(&mut self, limits: &Limits) -> Option<Limit> {
+        self.statements += 1;
+
+        if limits
+            .max_statements
+            .is_some_and(|max_statements| self.statements > max_statements)
+        {
+            return Some(Limit::Statements);
+        }
+
+        match self.deadline {
+            Some(deadline)
+                if self.statements.is_multiple_of(Usage::CLOCK_INTERVAL)
+                    && Instant::now() >= deadline =>
+            {
+                Some(Limit::Timeout)
+            }
+            _ => None,
+        }
+    }
+}
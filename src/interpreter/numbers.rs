@@ -0,0 +1,60 @@
+use std::rc::Rc;
+
+use crate::{
+    errors::RuntimeError,
+    object::{NativeFunction, Object},
+    token::Token,
+    utils::format_number,
+};
+
+use super::globals::expect_index;
+
+/// Most digits `toFixed` accepts after the decimal point.
+const MAX_FRACTION_DIGITS: usize = 100;
+
+/// The properties [`get_property`] knows, for completion.
+pub const PROPERTIES: [&str; 1] = ["toFixed"];
+
+pub fn get_property(number: f64, name: &Token) -> Result<Object, RuntimeError> {
+    let method = match &*name.lexeme {
+        "toFixed" => NativeFunction::new("toFixed", 1, move |interpreter, args| {
+            let digits = expect_index(interpreter, &args[0], usize::MAX)?;
+
+            if digits > MAX_FRACTION_DIGITS {
+                return Err(interpreter.native_error(&format!(
+                    "toFixed() digits must be between 0 and {MAX_FRACTION_DIGITS}"
+                )));
+            }
+
+            Ok(Object::String(Rc::from(to_fixed(number, digits))))
+        }),
+        _ => Err(RuntimeError {
+            token: name.clone(),
+            message: format!("Undefined number method '{}'", name.lexeme),
+        })?,
+    };
+
+    Ok(Object::Callable(Rc::new(method)))
+}
+
+/// Formats `number` with `digits` after the decimal point. Rust rounds exact
+/// ties to even, they're rounded away from zero here instead.
+fn to_fixed(number: f64, digits: usize) -> String {
+    if !number.is_finite() {
+        return format_number(number);
+    }
+
+    // Every finite f64 has an exact decimal expansion within 1074 digits.
+    let exact = format!("{number:.1074}");
+    let fraction = exact.split_once('.').map_or("", |(_, fraction)| fraction);
+    let rest = &fraction[digits..];
+    let is_tie = rest.starts_with('5') && rest[1..].bytes().all(|digit| digit == b'0');
+
+    if !is_tie {
+        format!("{number:.digits$}")
+    } else if number > 0.0 {
+        format!("{:.digits$}", number.next_up())
+    } else {
+        format!("{:.digits$}", number.next_down())
+    }
+}
@@ -1,137 +1,224 @@
-use crate::{errors::RuntimeError, object::Object, token::Token, utils::bool_to_number};
+use std::{cell::RefCell, cmp::Ordering, rc::Rc};
 
-pub fn handle_addition(
+use crate::{
+    errors::RuntimeError,
+    literal_type::LiteralType,
+    object::{Class, Object},
+    token::Token,
+    token_type::TokenType,
+    utils::is_truthy,
+};
+
+/// A numeric operand, booleans take part in arithmetic as the integers 0 and 1.
+#[derive(Clone, Copy)]
+enum Numeric {
+    Int(i64),
+    Float(f64),
+}
+
+impl Numeric {
+    fn from_object(value: &Object) -> Option<Self> {
+        match value {
+            Object::Int(int) => Some(Numeric::Int(*int)),
+            Object::Number(number) => Some(Numeric::Float(*number)),
+            Object::Boolean(boolean) => Some(Numeric::Int(i64::from(*boolean))),
+            _ => None,
+        }
+    }
+
+    fn to_f64(self) -> f64 {
+        match self {
+            Numeric::Int(int) => int as f64,
+            Numeric::Float(number) => number,
+        }
+    }
+
+    fn is_zero(self) -> bool {
+        self.to_f64() == 0.0
+    }
+}
+
+/// Applies an arithmetic operator, two integers give an integer and anything
+/// else is promoted to a float. `int` returns `None` when the result
+/// overflows.
+fn arithmetic(
     left: &Object,
     right: &Object,
     operator: &Token,
+    int: fn(i64, i64) -> Option<i64>,
+    float: fn(f64, f64) -> f64,
 ) -> Result<Object, RuntimeError> {
-    let value = match (left, right) {
-        (Object::Number(l), Object::Number(r)) => Object::Number(l + r),
-        (Object::Number(l), Object::String(r)) => Object::String(format!("{l}{r}")),
-        (Object::Number(l), Object::Boolean(r)) => Object::Number(l + bool_to_number(*r)),
-        (Object::String(l), Object::Number(r)) => Object::String(format!("{l}{r}")),
-        (Object::String(l), Object::String(r)) => Object::String(format!("{l}{r}")),
-        (Object::Boolean(l), Object::Number(r)) => Object::Number(bool_to_number(*l) + r),
-        (Object::Boolean(l), Object::Boolean(r)) => {
-            Object::Number(bool_to_number(*l) + bool_to_number(*r))
-        }
-        _ => Err(RuntimeError {
+    let (Some(l), Some(r)) = (Numeric::from_object(left), Numeric::from_object(right)) else {
+        return Err(RuntimeError {
             token: operator.clone(),
-            message: String::from("Operands must be (numbers or booleans) or two strings"),
-        })?,
+            message: String::from("Operands must be numbers or booleans"),
+        });
     };
 
-    Ok(value)
+    match (l, r) {
+        (Numeric::Int(l), Numeric::Int(r)) => {
+            int(l, r).map(Object::Int).ok_or_else(|| RuntimeError {
+                token: operator.clone(),
+                message: String::from("Integer overflow"),
+            })
+        }
+        _ => Ok(Object::Number(float(l.to_f64(), r.to_f64()))),
+    }
 }
 
-pub fn handle_subtraction(
+/// Applies the binary `operator` to its evaluated operands.
+pub fn handle_binary(
     left: &Object,
     right: &Object,
     operator: &Token,
 ) -> Result<Object, RuntimeError> {
-    let value = match (left, right) {
-        (Object::Number(l), Object::Number(r)) => Object::Number(l - r),
-        (Object::Number(l), Object::Boolean(r)) => Object::Number(l - bool_to_number(*r)),
-        (Object::Boolean(l), Object::Number(r)) => Object::Number(bool_to_number(*l) - r),
-        (Object::Boolean(l), Object::Boolean(r)) => {
-            Object::Number(bool_to_number(*l) - bool_to_number(*r))
-        }
-        _ => Err(RuntimeError {
-            token: operator.clone(),
-            message: String::from("Operands must be numbers or booleans"),
-        })?,
-    };
+    match operator.token_type {
+        TokenType::Plus => handle_addition(left, right, operator),
+        TokenType::Minus => handle_subtraction(left, right, operator),
+        TokenType::Star => handle_multiplication(left, right, operator),
+        TokenType::Slash => handle_division(left, right, operator),
+        TokenType::Percentage => handle_modulus(left, right, operator),
+        TokenType::Greater => handle_greater_than(left, right, operator),
+        TokenType::GreaterEqual => handle_greater_than_equal(left, right, operator),
+        TokenType::Less => handle_less_than(left, right, operator),
+        TokenType::LessEqual => handle_less_than_equal(left, right, operator),
+        TokenType::InstanceOf => handle_instance_of(left, right, operator),
+        TokenType::BangEqual => Ok(Object::Boolean(left != right)),
+        TokenType::EqualEqual => Ok(Object::Boolean(left == right)),
+        TokenType::BangEqualEqual => Ok(Object::Boolean(!left.strict_eq(right))),
+        TokenType::EqualEqualEqual => Ok(Object::Boolean(left.strict_eq(right))),
+        _ => unreachable!(),
+    }
+}
 
-    Ok(value)
+/// Applies the unary `operator` to its evaluated operand.
+pub fn handle_unary(right: &Object, operator: &Token) -> Result<Object, RuntimeError> {
+    match operator.token_type {
+        TokenType::Bang => Ok(Object::Boolean(!is_truthy(right))),
+        TokenType::Minus => match right {
+            Object::Int(int) => Ok(Object::Int(int.checked_neg().ok_or_else(|| {
+                RuntimeError {
+                    token: operator.clone(),
+                    message: String::from("Integer overflow"),
+                }
+            })?)),
+            Object::Number(number) => Ok(Object::Number(-number)),
+            Object::Boolean(boolean) => Ok(Object::Int(-i64::from(*boolean))),
+            _ => Err(RuntimeError {
+                token: operator.clone(),
+                message: String::from("Unary minus requires number or boolean operand"),
+            }),
+        },
+        _ => unreachable!(),
+    }
 }
 
-pub fn handle_multiplication(
+pub fn handle_addition(
     left: &Object,
     right: &Object,
     operator: &Token,
 ) -> Result<Object, RuntimeError> {
-    let value = match (left, right) {
-        (Object::Number(l), Object::Number(r)) => Object::Number(l * r),
-        (Object::Number(l), Object::Boolean(r)) => Object::Number(l * bool_to_number(*r)),
-        (Object::Boolean(l), Object::Number(r)) => Object::Number(bool_to_number(*l) * r),
-        (Object::Boolean(l), Object::Boolean(r)) => {
-            Object::Number(bool_to_number(*l) * bool_to_number(*r))
+    match (left, right) {
+        (Object::String(l), Object::String(r)) => Ok(Object::String(Rc::from(format!("{l}{r}")))),
+        (Object::String(l), Object::Number(_) | Object::Int(_)) => {
+            Ok(Object::String(Rc::from(format!("{l}{right}"))))
+        }
+        (Object::Number(_) | Object::Int(_), Object::String(r)) => {
+            Ok(Object::String(Rc::from(format!("{left}{r}"))))
+        }
+        _ if Numeric::from_object(left).is_some() && Numeric::from_object(right).is_some() => {
+            arithmetic(left, right, operator, i64::checked_add, |l, r| l + r)
         }
         _ => Err(RuntimeError {
             token: operator.clone(),
-            message: String::from("Operands must be numbers or booleans"),
-        })?,
-    };
+            message: String::from("Operands must be (numbers or booleans) or two strings"),
+        }),
+    }
+}
 
-    Ok(value)
+pub fn handle_subtraction(
+    left: &Object,
+    right: &Object,
+    operator: &Token,
+) -> Result<Object, RuntimeError> {
+    arithmetic(left, right, operator, i64::checked_sub, |l, r| l - r)
 }
 
-pub fn handle_division(
+pub fn handle_multiplication(
     left: &Object,
     right: &Object,
     operator: &Token,
 ) -> Result<Object, RuntimeError> {
-    let divide = |l, r| {
-        if r == 0.0 {
-            Err(RuntimeError {
-                token: operator.clone(),
-                message: String::from("Divide by zero"),
-            })
-        } else {
-            Ok(l / r)
-        }
-    };
+    arithmetic(left, right, operator, i64::checked_mul, |l, r| l * r)
+}
 
-    let value = match (left, right) {
-        (Object::Number(l), Object::Number(r)) => Object::Number(divide(*l, *r)?),
-        (Object::Number(l), Object::Boolean(r)) => Object::Number(divide(*l, bool_to_number(*r))?),
-        (Object::Boolean(l), Object::Number(r)) => Object::Number(divide(bool_to_number(*l), *r)?),
-        (Object::Boolean(l), Object::Boolean(r)) => {
-            Object::Number(divide(bool_to_number(*l), bool_to_number(*r))?)
-        }
-        _ => Err(RuntimeError {
+/// Integers divide to an integer, truncating towards zero.
+pub fn handle_division(
+    left: &Object,
+    right: &Object,
+    operator: &Token,
+) -> Result<Object, RuntimeError> {
+    if Numeric::from_object(right).is_some_and(Numeric::is_zero) {
+        return Err(RuntimeError {
             token: operator.clone(),
-            message: String::from("Operands must be numbers or booleans"),
-        })?,
-    };
+            message: String::from("Divide by zero"),
+        });
+    }
 
-    Ok(value)
+    arithmetic(left, right, operator, i64::checked_div, |l, r| l / r)
 }
 
+/// The remainder takes the sign of the dividend. Unlike floats, integers
+/// can't take the remainder of a division by zero.
 pub fn handle_modulus(
     left: &Object,
     right: &Object,
     operator: &Token,
 ) -> Result<Object, RuntimeError> {
-    let value = match (left, right) {
-        (Object::Number(l), Object::Number(r)) => Object::Number(l % r),
-        (Object::Number(l), Object::Boolean(r)) => Object::Number(l % bool_to_number(*r)),
-        (Object::Boolean(l), Object::Number(r)) => Object::Number(bool_to_number(*l) % r),
-        (Object::Boolean(l), Object::Boolean(r)) => {
-            Object::Number(bool_to_number(*l) % bool_to_number(*r))
-        }
-        _ => Err(RuntimeError {
+    if let (Some(Numeric::Int(_)), Some(Numeric::Int(0))) =
+        (Numeric::from_object(left), Numeric::from_object(right))
+    {
+        return Err(RuntimeError {
             token: operator.clone(),
-            message: String::from("Operands must be numbers or booleans"),
-        })?,
+            message: String::from("Divide by zero"),
+        });
+    }
+
+    arithmetic(left, right, operator, i64::checked_rem, |l, r| l % r)
+}
+
+/// A division or remainder by zero computed with floats, `None` for any other
+/// operation, which is handled as usual.
+pub fn ieee_division(left: &Object, right: &Object, operator: &Token) -> Option<Object> {
+    let (Some(l), Some(r)) = (Numeric::from_object(left), Numeric::from_object(right)) else {
+        return None;
     };
 
-    Ok(value)
+    if !r.is_zero() {
+        return None;
+    }
+
+    match operator.token_type {
+        TokenType::Slash => Some(Object::Number(l.to_f64() / r.to_f64())),
+        TokenType::Percentage => Some(Object::Number(l.to_f64() % r.to_f64())),
+        _ => None,
+    }
 }
 
-pub fn handle_less_than(
+/// Orders numbers, booleans and strings. `None` means the operands can't be
+/// ordered, like NaN.
+pub fn compare(
     left: &Object,
     right: &Object,
     operator: &Token,
-) -> Result<Object, RuntimeError> {
-    match (left, right) {
-        (Object::Number(l), Object::Number(r)) => Ok(Object::Boolean(l < r)),
-        (Object::Number(l), Object::Boolean(r)) => Ok(Object::Boolean(*l < bool_to_number(*r))),
-        (Object::Boolean(l), Object::Number(r)) => Ok(Object::Boolean(bool_to_number(*l) < *r)),
-        (Object::Boolean(l), Object::Boolean(r)) => {
-            Ok(Object::Boolean(bool_to_number(*l) < bool_to_number(*r)))
-        }
-        (Object::String(l), Object::String(r)) => Ok(Object::Boolean(l < r)),
+) -> Result<Option<Ordering>, RuntimeError> {
+    if let (Object::String(l), Object::String(r)) = (left, right) {
+        return Ok(Some(l.cmp(r)));
+    }
+
+    match (Numeric::from_object(left), Numeric::from_object(right)) {
+        (Some(Numeric::Int(l)), Some(Numeric::Int(r))) => Ok(Some(l.cmp(&r))),
+        (Some(l), Some(r)) => Ok(l.to_f64().partial_cmp(&r.to_f64())),
         _ => Err(RuntimeError {
             token: operator.clone(),
             message: String::from("Operands must be numbers, booleans, or strings"),
@@ -139,24 +226,53 @@ pub fn handle_less_than(
     }
 }
 
-pub fn handle_greater_than(
+pub fn handle_less_than(
     left: &Object,
     right: &Object,
     operator: &Token,
 ) -> Result<Object, RuntimeError> {
-    match (left, right) {
-        (Object::Number(l), Object::Number(r)) => Ok(Object::Boolean(l > r)),
-        (Object::Number(l), Object::Boolean(r)) => Ok(Object::Boolean(*l > bool_to_number(*r))),
-        (Object::Boolean(l), Object::Number(r)) => Ok(Object::Boolean(bool_to_number(*l) > *r)),
-        (Object::Boolean(l), Object::Boolean(r)) => {
-            Ok(Object::Boolean(bool_to_number(*l) > bool_to_number(*r)))
-        }
-        (Object::String(l), Object::String(r)) => Ok(Object::Boolean(l > r)),
-        _ => Err(RuntimeError {
+    let ordering = compare(left, right, operator)?;
+
+    Ok(Object::Boolean(
+        ordering.is_some_and(|ordering| ordering.is_lt()),
+    ))
+}
+
+pub fn handle_instance_of(
+    left: &Object,
+    right: &Object,
+    operator: &Token,
+) -> Result<Object, RuntimeError> {
+    let class = match right {
+        Object::CallableInstance(class) => class.as_any().downcast_ref::<Class>(),
+        _ => None,
+    };
+    let Some(class) = class else {
+        return Err(RuntimeError {
             token: operator.clone(),
-            message: String::from("Operands must be numbers, booleans, or strings"),
-        }),
-    }
+            message: String::from("Right operand of 'instanceof' must be a class"),
+        });
+    };
+    let is_instance = match left {
+        Object::Instance(instance) => instance
+            .class()
+            .is_some_and(|instance_class| instance_class.is_subclass_of(class)),
+        _ => false,
+    };
+
+    Ok(Object::Boolean(is_instance))
+}
+
+pub fn handle_greater_than(
+    left: &Object,
+    right: &Object,
+    operator: &Token,
+) -> Result<Object, RuntimeError> {
+    let ordering = compare(left, right, operator)?;
+
+    Ok(Object::Boolean(
+        ordering.is_some_and(|ordering| ordering.is_gt()),
+    ))
 }
 
 pub fn handle_less_than_equal(
@@ -164,19 +280,11 @@ pub fn handle_less_than_equal(
     right: &Object,
     operator: &Token,
 ) -> Result<Object, RuntimeError> {
-    match (left, right) {
-        (Object::Number(l), Object::Number(r)) => Ok(Object::Boolean(l <= r)),
-        (Object::Number(l), Object::Boolean(r)) => Ok(Object::Boolean(*l <= bool_to_number(*r))),
-        (Object::Boolean(l), Object::Number(r)) => Ok(Object::Boolean(bool_to_number(*l) <= *r)),
-        (Object::Boolean(l), Object::Boolean(r)) => {
-            Ok(Object::Boolean(bool_to_number(*l) <= bool_to_number(*r)))
-        }
-        (Object::String(l), Object::String(r)) => Ok(Object::Boolean(l <= r)),
-        _ => Err(RuntimeError {
-            token: operator.clone(),
-            message: String::from("Operands must be numbers, booleans, or strings"),
-        }),
-    }
+    let ordering = compare(left, right, operator)?;
+
+    Ok(Object::Boolean(
+        ordering.is_some_and(|ordering| ordering.is_le()),
+    ))
 }
 
 pub fn handle_greater_than_equal(
@@ -184,17 +292,169 @@ pub fn handle_greater_than_equal(
     right: &Object,
     operator: &Token,
 ) -> Result<Object, RuntimeError> {
-    match (left, right) {
-        (Object::Number(l), Object::Number(r)) => Ok(Object::Boolean(l >= r)),
-        (Object::Number(l), Object::Boolean(r)) => Ok(Object::Boolean(*l >= bool_to_number(*r))),
-        (Object::Boolean(l), Object::Number(r)) => Ok(Object::Boolean(bool_to_number(*l) >= *r)),
-        (Object::Boolean(l), Object::Boolean(r)) => {
-            Ok(Object::Boolean(bool_to_number(*l) >= bool_to_number(*r)))
+    let ordering = compare(left, right, operator)?;
+
+    Ok(Object::Boolean(
+        ordering.is_some_and(|ordering| ordering.is_ge()),
+    ))
+}
+
+fn integer_index(index: &Object, bracket: &Token) -> Result<i64, RuntimeError> {
+    match index {
+        Object::Int(int) => Ok(*int),
+        Object::Number(n) if n.fract() == 0.0 => Ok(*n as i64),
+        _ => Err(RuntimeError {
+            token: bracket.clone(),
+            message: String::from("Index must be an integer"),
+        }),
+    }
+}
+
+/// The position of `index` in an array or string of length `len`, negative
+/// indices count from the end.
+fn sequence_index(index: &Object, len: usize, bracket: &Token) -> Result<usize, RuntimeError> {
+    let index = integer_index(index, bracket)?;
+    let position = if index < 0 {
+        len.checked_sub(index.unsigned_abs() as usize)
+    } else {
+        Some(index as usize)
+    };
+
+    if let Some(position) = position.filter(|&position| position < len) {
+        return Ok(position);
+    }
+
+    // The bracket of an array pattern holds how many elements it names.
+    let message = match bracket.literal {
+        Some(LiteralType::Int(count)) => format!("Expected at least {count} elements, got {len}"),
+        _ => format!("Index [{index}] out of bounds for length [{len}]"),
+    };
+
+    Err(RuntimeError {
+        token: bracket.clone(),
+        message,
+    })
+}
+
+/// The position of a slice bound, negative ones count from the end and
+/// out of range ones are clamped to the sequence.
+fn slice_bound(bound: &Object, len: usize, bracket: &Token) -> Result<usize, RuntimeError> {
+    let bound = integer_index(bound, bracket)?;
+
+    if bound < 0 {
+        Ok(len.saturating_sub(bound.unsigned_abs() as usize))
+    } else {
+        Ok((bound as usize).min(len))
+    }
+}
+
+fn map_key(key: &Object, bracket: &Token) -> Result<String, RuntimeError> {
+    match key {
+        Object::String(key) => Ok(key.to_string()),
+        _ => Err(RuntimeError {
+            token: bracket.clone(),
+            message: String::from("Map keys must be strings"),
+        }),
+    }
+}
+
+pub fn handle_index(
+    object: &Object,
+    index: &Object,
+    bracket: &Token,
+) -> Result<Object, RuntimeError> {
+    match object {
+        Object::Array(array) => {
+            let array = array.borrow();
+            let index = sequence_index(index, array.len(), bracket)?;
+
+            Ok(array[index].clone())
+        }
+        Object::String(string) => {
+            let index = sequence_index(index, string.chars().count(), bracket)?;
+            let char = string.chars().nth(index).unwrap();
+
+            Ok(Object::String(Rc::from(char.to_string())))
+        }
+        Object::Map(map) => {
+            let key = map_key(index, bracket)?;
+
+            Ok(map.borrow().get(&key).cloned().unwrap_or(Object::Undefined))
         }
-        (Object::String(l), Object::String(r)) => Ok(Object::Boolean(l >= r)),
         _ => Err(RuntimeError {
-            token: operator.clone(),
-            message: String::from("Operands must be numbers, booleans, or strings"),
+            token: bracket.clone(),
+            message: String::from("Only arrays, strings and maps can be indexed"),
+        }),
+    }
+}
+
+/// `object[start:end]` of an array or string, a copy of the elements or
+/// characters from `start` up to but not including `end`. Bounds default to
+/// the whole sequence, see [`slice_bound`].
+pub fn handle_slice(
+    object: &Object,
+    start: Option<&Object>,
+    end: Option<&Object>,
+    bracket: &Token,
+) -> Result<Object, RuntimeError> {
+    let len = match object {
+        Object::Array(array) => array.borrow().len(),
+        Object::String(string) => string.chars().count(),
+        _ => Err(RuntimeError {
+            token: bracket.clone(),
+            message: String::from("Only arrays and strings can be sliced"),
+        })?,
+    };
+    let start = match start {
+        Some(start) => slice_bound(start, len, bracket)?,
+        None => 0,
+    };
+    let end = match end {
+        Some(end) => slice_bound(end, len, bracket)?,
+        None => len,
+    }
+    .max(start);
+
+    Ok(match object {
+        Object::Array(array) => {
+            Object::Array(Rc::new(RefCell::new(array.borrow()[start..end].to_vec())))
+        }
+        Object::String(string) => Object::String(Rc::from(
+            string
+                .chars()
+                .skip(start)
+                .take(end - start)
+                .collect::<String>(),
+        )),
+        _ => unreachable!(),
+    })
+}
+
+pub fn handle_index_set(
+    object: &Object,
+    index: &Object,
+    value: Object,
+    bracket: &Token,
+) -> Result<Object, RuntimeError> {
+    match object {
+        Object::Array(array) => {
+            let mut array = array.borrow_mut();
+            let index = sequence_index(index, array.len(), bracket)?;
+
+            array[index] = value.clone();
+
+            Ok(value)
+        }
+        Object::Map(map) => {
+            let key = map_key(index, bracket)?;
+
+            map.borrow_mut().insert(key, value.clone());
+
+            Ok(value)
+        }
+        _ => Err(RuntimeError {
+            token: bracket.clone(),
+            message: format!("Can't assign to an index of {}", object.type_name()),
         }),
     }
 }
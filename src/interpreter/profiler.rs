@@ -0,0 +1,93 @@
+use std::{
+    cmp::Reverse,
+    collections::HashMap,
+    fmt::{self, Display},
+    time::{Duration, Instant},
+};
+
+/// Accumulates call counts and timings per function name.
+///
+/// Inclusive time covers everything that happened during a call, exclusive
+/// time leaves out the calls it made. Recursive calls only count towards the
+/// inclusive time of the outermost one.
+#[derive(Default)]
+pub struct Profiler {
+    entries: HashMap<String, Entry>,
+    stack: Vec<Frame>,
+}
+
+#[derive(Default)]
+struct Entry {
+    calls: usize,
+    inclusive: Duration,
+    exclusive: Duration,
+}
+
+struct Frame {
+    name: String,
+    start: Instant,
+    children: Duration,
+}
+
+impl Profiler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn enter(&mut self, name: String) {
+        self.stack.push(Frame {
+            name,
+            start: Instant::now(),
+            children: Duration::ZERO,
+        });
+    }
+
+    pub fn exit(&mut self) {
+        let Some(frame) = self.stack.pop() else {
+            return;
+        };
+        let elapsed = frame.start.elapsed();
+        let is_recursive = self.stack.iter().any(|parent| parent.name == frame.name);
+
+        if let Some(parent) = self.stack.last_mut() {
+            parent.children += elapsed;
+        }
+
+        let entry = self.entries.entry(frame.name).or_default();
+
+        entry.calls += 1;
+        entry.exclusive += elapsed.saturating_sub(frame.children);
+
+        if !is_recursive {
+            entry.inclusive += elapsed;
+        }
+    }
+}
+
+impl Display for Profiler {
+    /// Renders the report sorted by exclusive time, slowest first.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut entries = self.entries.iter().collect::<Vec<_>>();
+
+        entries.sort_by_key(|(_, entry)| Reverse(entry.exclusive));
+
+        writeln!(
+            f,
+            "{:<24} {:>10} {:>16} {:>16}",
+            "function", "calls", "inclusive (ms)", "exclusive (ms)"
+        )?;
+
+        for (name, entry) in entries {
+            writeln!(
+                f,
+                "{:<24} {:>10} {:>16.3} {:>16.3}",
+                name,
+                entry.calls,
+                entry.inclusive.as_secs_f64() * 1000.0,
+                entry.exclusive.as_secs_f64() * 1000.0
+            )?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,420 @@
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+use crate::{
+    environment::Environment,
+    errors::{RuntimeError, VMException},
+    object::{Callable, Instance, Namespace, NativeFunction, Object, ResolvableFunction},
+    token::Token,
+};
+
+use super::{
+    generators::{self, Generator, Step},
+    globals::{expect_callback, expect_duration},
+    Interpreter,
+};
+
+type Env = Rc<RefCell<Environment>>;
+
+/// What a promise is settled with, its value or the reason it was rejected.
+pub(super) type Outcome = Result<Object, Object>;
+
+/// A value that may not be there yet, settled by the event loop once what
+/// it's waiting on is done, see [`Interpreter::run_event_loop`].
+pub struct Promise {
+    state: RefCell<State>,
+    /// What runs once it's settled, in the order they were added.
+    reactions: RefCell<Vec<Reaction>>,
+    /// Whether anything reacted to it, a rejection nothing reacts to is
+    /// reported when the event loop is done.
+    handled: Cell<bool>,
+    /// The call it was made by, where an unhandled rejection is reported.
+    origin: Token,
+}
+
+enum State {
+    Pending,
+    Settled(Outcome),
+}
+
+pub(super) enum Reaction {
+    /// Resumes an async function suspended on an `await`, settling its own
+    /// promise once it's done.
+    Resume(Rc<Generator>, Rc<Promise>),
+    /// Calls the callback of `then` or `catchError` matching the outcome and
+    /// settles `promise`, which it returned, with what the callback returns.
+    /// Without one the outcome is passed on as it is.
+    Then {
+        on_fulfilled: Option<Rc<dyn Callable>>,
+        on_rejected: Option<Rc<dyn Callable>>,
+        promise: Rc<Promise>,
+    },
+    /// Settles a promise resolved with this one the same way.
+    Adopt(Rc<Promise>),
+    /// Fills in the value at `index` of `Promise.all`.
+    All { index: usize, all: Rc<All> },
+}
+
+/// The promise of `Promise.all`, fulfilled once every value is there.
+pub(super) struct All {
+    values: RefCell<Vec<Object>>,
+    remaining: Cell<usize>,
+    promise: Rc<Promise>,
+}
+
+impl Promise {
+    pub(super) fn pending(origin: Token) -> Rc<Promise> {
+        Rc::new(Promise {
+            state: RefCell::new(State::Pending),
+            reactions: RefCell::new(vec![]),
+            handled: Cell::new(false),
+            origin,
+        })
+    }
+
+    /// The promise `value` stands for, if it's one.
+    fn of(value: &Object) -> Option<Rc<Promise>> {
+        match value {
+            Object::Instance(instance) => instance.promise(),
+            _ => None,
+        }
+    }
+
+    /// The error for a rejection nothing reacted to, if it's one.
+    pub(super) fn unhandled(&self) -> Option<RuntimeError> {
+        match &*self.state.borrow() {
+            State::Settled(Err(reason)) if !self.handled.get() => Some(RuntimeError {
+                token: self.origin.clone(),
+                message: format!("Uncaught (in promise): {reason}"),
+            }),
+            _ => None,
+        }
+    }
+}
+
+pub(super) fn object(promise: &Rc<Promise>) -> Object {
+    Object::Instance(Rc::new(PromiseInstance(Rc::clone(promise))))
+}
+
+struct PromiseInstance(Rc<Promise>);
+
+impl Instance for PromiseInstance {
+    fn get(&self, _: Object, name: &Token) -> Result<Object, RuntimeError> {
+        let promise = Rc::clone(&self.0);
+        let method = match &*name.lexeme {
+            "then" => NativeFunction::new("then", 1, move |interpreter, args| {
+                let on_fulfilled = expect_callback(interpreter, args.first().cloned())?;
+                let on_rejected = match args.get(1) {
+                    Some(callback) => Some(expect_callback(interpreter, Some(callback.clone()))?),
+                    None => None,
+                };
+
+                Ok(interpreter.then(&promise, Some(on_fulfilled), on_rejected))
+            })
+            .optional(1),
+            "catchError" => NativeFunction::new("catchError", 1, move |interpreter, args| {
+                let on_rejected = expect_callback(interpreter, args.first().cloned())?;
+
+                Ok(interpreter.then(&promise, None, Some(on_rejected)))
+            }),
+            _ => Err(RuntimeError {
+                token: name.clone(),
+                message: format!("Undefined property '{}' on promise", name.lexeme),
+            })?,
+        };
+
+        Ok(Object::Callable(Rc::new(method)))
+    }
+
+    fn set(&self, name: &Token, _: Object) -> Result<(), RuntimeError> {
+        Err(RuntimeError {
+            token: name.clone(),
+            message: String::from("Can't modify a promise"),
+        })
+    }
+
+    fn property_names(&self) -> Vec<String> {
+        vec![String::from("catchError"), String::from("then")]
+    }
+
+    fn promise(&self) -> Option<Rc<Promise>> {
+        Some(Rc::clone(&self.0))
+    }
+
+    fn to_string(&self) -> String {
+        match &*self.0.state.borrow() {
+            State::Pending => String::from("[Promise: pending]"),
+            State::Settled(Ok(value)) => format!("[Promise: fulfilled {value}]"),
+            State::Settled(Err(reason)) => format!("[Promise: rejected {reason}]"),
+        }
+    }
+}
+
+/// What calling an async function returns, a promise of what its body
+/// returns. The body runs right away up to its first `await`, the event loop
+/// resumes it from there.
+pub fn call_async(
+    interpreter: &mut Interpreter,
+    function: Rc<dyn ResolvableFunction>,
+    environment: Environment,
+    globals: Env,
+) -> Result<Object, VMException> {
+    let coroutine = generators::coroutine(function, environment, globals);
+    let promise = Promise::pending(interpreter.call_site());
+
+    interpreter.step(&coroutine, &promise, None)?;
+
+    Ok(object(&promise))
+}
+
+pub fn promise_namespace() -> Namespace {
+    let mut promise = Namespace::new("Promise");
+
+    promise
+        .define(
+            "resolve",
+            Object::Callable(Rc::new(NativeFunction::new(
+                "resolve",
+                1,
+                |interpreter, args| Ok(object(&interpreter.promise_of(args[0].clone()))),
+            ))),
+        )
+        .define(
+            "reject",
+            Object::Callable(Rc::new(NativeFunction::new(
+                "reject",
+                1,
+                |interpreter, args| {
+                    let promise = Promise::pending(interpreter.call_site());
+
+                    interpreter.settle(&promise, Err(args[0].clone()));
+
+                    Ok(object(&promise))
+                },
+            ))),
+        )
+        .define(
+            "all",
+            Object::Callable(Rc::new(NativeFunction::new(
+                "all",
+                1,
+                |interpreter, args| {
+                    let Object::Array(values) = &args[0] else {
+                        return Err(interpreter.native_error("Expected an array of promises"));
+                    };
+                    let values = values.borrow().clone();
+                    let all = Rc::new(All {
+                        values: RefCell::new(vec![Object::Undefined; values.len()]),
+                        remaining: Cell::new(values.len()),
+                        promise: Promise::pending(interpreter.call_site()),
+                    });
+
+                    if values.is_empty() {
+                        interpreter.settle(&all.promise, Ok(Object::Array(Rc::default())));
+                    }
+
+                    for (index, value) in values.into_iter().enumerate() {
+                        let promise = interpreter.promise_of(value);
+                        let all = Rc::clone(&all);
+
+                        interpreter.subscribe(&promise, Reaction::All { index, all });
+                    }
+
+                    Ok(object(&all.promise))
+                },
+            ))),
+        );
+
+    promise
+}
+
+/// `delay(ms, value)` returns a promise fulfilled with `value` after `ms`
+/// milliseconds, `undefined` when left out.
+pub fn delay() -> Object {
+    Object::Callable(Rc::new(
+        NativeFunction::new("delay", 1, |interpreter, args| {
+            let duration = expect_duration(interpreter, &args[0])?;
+            let value = args.get(1).cloned().unwrap_or(Object::Undefined);
+
+            Ok(object(&interpreter.timer(duration, value)?))
+        })
+        .optional(1),
+    ))
+}
+
+impl Interpreter {
+    /// Runs the async function `coroutine` up to its next `await`, waiting
+    /// there for the awaited promise, or settles `promise` once it's done.
+    fn step(
+        &mut self,
+        coroutine: &Rc<Generator>,
+        promise: &Rc<Promise>,
+        outcome: Option<Outcome>,
+    ) -> Result<(), VMException> {
+        match self.resume(coroutine, outcome) {
+            Ok(Step::Suspended(awaited)) => {
+                let awaited = self.promise_of(awaited);
+
+                self.subscribe(
+                    &awaited,
+                    Reaction::Resume(Rc::clone(coroutine), Rc::clone(promise)),
+                );
+            }
+            Ok(Step::Returned(value)) => self.settle(promise, Ok(value)),
+            Err(err) => {
+                let reason = self.rejection(err)?;
+
+                self.settle(promise, Err(reason));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// What an error that `try` could catch rejects a promise with, the
+    /// others are passed on.
+    fn rejection(&mut self, err: VMException) -> Result<Object, VMException> {
+        match err {
+            VMException::RuntimeError(runtime_error) => {
+                self.caught();
+
+                Ok(Object::String(Rc::from(runtime_error.message)))
+            }
+            VMException::ThrowException(_, value) => {
+                self.caught();
+
+                Ok(value)
+            }
+            err => Err(err),
+        }
+    }
+
+    /// The promise `value` is, or else one fulfilled with it.
+    fn promise_of(&mut self, value: Object) -> Rc<Promise> {
+        Promise::of(&value).unwrap_or_else(|| {
+            let promise = Promise::pending(self.call_site());
+
+            self.settle(&promise, Ok(value));
+
+            promise
+        })
+    }
+
+    /// The promise returned by `then` and `catchError`.
+    fn then(
+        &mut self,
+        promise: &Rc<Promise>,
+        on_fulfilled: Option<Rc<dyn Callable>>,
+        on_rejected: Option<Rc<dyn Callable>>,
+    ) -> Object {
+        let then = Promise::pending(self.call_site());
+        let reaction = Reaction::Then {
+            on_fulfilled,
+            on_rejected,
+            promise: Rc::clone(&then),
+        };
+
+        self.subscribe(promise, reaction);
+
+        object(&then)
+    }
+
+    /// Settles a pending `promise`, queueing its reactions. Fulfilling it
+    /// with another promise makes it wait for that one instead.
+    pub(super) fn settle(&mut self, promise: &Rc<Promise>, outcome: Outcome) {
+        if !matches!(*promise.state.borrow(), State::Pending) {
+            return;
+        }
+
+        if let Ok(value) = &outcome {
+            if let Some(other) = Promise::of(value) {
+                if Rc::ptr_eq(&other, promise) {
+                    let reason =
+                        Object::String(Rc::from("A promise can't be resolved with itself"));
+
+                    return self.settle(promise, Err(reason));
+                }
+
+                return self.subscribe(&other, Reaction::Adopt(Rc::clone(promise)));
+            }
+        }
+
+        let reactions = promise.reactions.take();
+
+        if outcome.is_err() && reactions.is_empty() {
+            self.event_loop.unhandled.push(Rc::clone(promise));
+        }
+
+        for reaction in reactions {
+            self.event_loop.queue(reaction, outcome.clone());
+        }
+
+        *promise.state.borrow_mut() = State::Settled(outcome);
+    }
+
+    /// Runs `reaction` once `promise` is settled, queueing it right away if
+    /// it already is.
+    fn subscribe(&mut self, promise: &Rc<Promise>, reaction: Reaction) {
+        promise.handled.set(true);
+
+        match &*promise.state.borrow() {
+            State::Pending => promise.reactions.borrow_mut().push(reaction),
+            State::Settled(outcome) => self.event_loop.queue(reaction, outcome.clone()),
+        }
+    }
+
+    /// Runs a reaction to a promise settled with `outcome`.
+    pub(super) fn react(
+        &mut self,
+        reaction: Reaction,
+        outcome: Outcome,
+    ) -> Result<(), VMException> {
+        match reaction {
+            Reaction::Resume(coroutine, promise) => {
+                self.step(&coroutine, &promise, Some(outcome))?
+            }
+            Reaction::Then {
+                on_fulfilled,
+                on_rejected,
+                promise,
+            } => {
+                let callback = match &outcome {
+                    Ok(_) => on_fulfilled,
+                    Err(_) => on_rejected,
+                };
+                let outcome = match (callback, outcome) {
+                    (Some(callback), Ok(value) | Err(value)) => {
+                        match self.call(callback, vec![value], &promise.origin) {
+                            Ok(value) => Ok(value),
+                            Err(err) => Err(self.rejection(err)?),
+                        }
+                    }
+                    (None, outcome) => outcome,
+                };
+
+                self.settle(&promise, outcome);
+            }
+            Reaction::Adopt(promise) => self.settle(&promise, outcome),
+            Reaction::All { index, all } => match outcome {
+                Ok(value) => {
+                    all.values.borrow_mut()[index] = value;
+                    all.remaining.set(all.remaining.get() - 1);
+
+                    if all.remaining.get() == 0 {
+                        let values = all.values.take();
+
+                        self.settle(
+                            &all.promise,
+                            Ok(Object::Array(Rc::new(RefCell::new(values)))),
+                        );
+                    }
+                }
+                Err(reason) => self.settle(&all.promise, Err(reason)),
+            },
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,51 @@
+use uuid::Uuid;
+
+/// The generator behind `random`, `randomInt` and `Math.random`, SplitMix64.
+/// It isn't fit for cryptography, but a seed always gives the same numbers.
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// A generator seeded from the system's randomness.
+    pub fn from_entropy() -> Self {
+        Rng::with_seed(Rng::entropy())
+    }
+
+    /// A seed taken from the system's randomness.
+    pub fn entropy() -> u64 {
+        Uuid::new_v4().as_u64_pair().0
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A float in `[0, 1)`.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// An integer in `[low, high]`, `low` must not be greater than `high`.
+    pub fn range(&mut self, low: i64, high: i64) -> i64 {
+        let span = high.wrapping_sub(low) as u64;
+
+        if span == u64::MAX {
+            return self.next_u64() as i64;
+        }
+
+        // Scaling the 64 random bits keeps the bias below 2^-64 per value.
+        let offset = (u128::from(self.next_u64()) * u128::from(span + 1)) >> 64;
+
+        low.wrapping_add(offset as i64)
+    }
+}
@@ -0,0 +1,159 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    errors::RuntimeError,
+    object::{Class, Instance, Mutability, NativeFunction, Object},
+    token::Token,
+    token_type::TokenType,
+};
+
+use super::{globals::expect_string, Interpreter};
+
+/// Lists the public fields of an instance, or the static members of a class,
+/// in alphabetical order.
+pub fn keys() -> Object {
+    Object::Callable(Rc::new(NativeFunction::new(
+        "keys",
+        1,
+        |interpreter, args| {
+            let mut names = expect_instance(interpreter, &args[0])?.field_names();
+
+            names.sort();
+
+            Ok(strings(names))
+        },
+    )))
+}
+
+pub fn has_field() -> Object {
+    Object::Callable(Rc::new(NativeFunction::new(
+        "hasField",
+        2,
+        |interpreter, args| {
+            let instance = expect_instance(interpreter, &args[0])?;
+            let name = expect_string(interpreter, &args[1])?;
+
+            Ok(Object::Boolean(instance.field_names().contains(&name)))
+        },
+    )))
+}
+
+/// Reads a property like `instance.name` would, methods come out bound.
+pub fn get_field() -> Object {
+    Object::Callable(Rc::new(NativeFunction::new(
+        "getField",
+        2,
+        |interpreter, args| {
+            let instance = expect_instance(interpreter, &args[0])?;
+            let name = property(interpreter, &args[1])?;
+
+            instance.get(args[0].clone(), &name)
+        },
+    )))
+}
+
+pub fn set_field() -> Object {
+    Object::Callable(Rc::new(NativeFunction::new(
+        "setField",
+        3,
+        |interpreter, args| {
+            let instance = expect_instance(interpreter, &args[0])?;
+            let name = property(interpreter, &args[1])?;
+
+            instance.set(&name, args[2].clone())?;
+
+            Ok(args[2].clone())
+        },
+    )))
+}
+
+/// Lists the public methods of a class or the class of an instance, the
+/// inherited ones included, in alphabetical order.
+pub fn methods() -> Object {
+    Object::Callable(Rc::new(NativeFunction::new(
+        "methods",
+        1,
+        |interpreter, args| {
+            let class = match &args[0] {
+                Object::CallableInstance(class) => class.as_any().downcast_ref::<Class>().cloned(),
+                Object::Instance(instance) => instance.class(),
+                _ => None,
+            };
+            let Some(class) = class else {
+                return Err(interpreter.native_error("Expected a class or an instance of one"));
+            };
+            let mut names: Vec<_> = class
+                .method_names()
+                .into_iter()
+                .filter(|name| !name.starts_with('_') && name != "init")
+                .collect();
+
+            names.sort();
+            names.dedup();
+
+            Ok(strings(names))
+        },
+    )))
+}
+
+/// Stops fields from being added to an instance, the ones it has can still be
+/// assigned.
+pub fn seal() -> Object {
+    restrict("seal", Mutability::Sealed)
+}
+
+/// Stops the fields of an instance from being added or assigned.
+pub fn freeze() -> Object {
+    restrict("freeze", Mutability::Frozen)
+}
+
+fn restrict(name: &'static str, mutability: Mutability) -> Object {
+    Object::Callable(Rc::new(NativeFunction::new(
+        name,
+        1,
+        move |interpreter, args| match &args[0] {
+            Object::Instance(instance) if instance.restrict(mutability) => Ok(args[0].clone()),
+            _ => Err(interpreter.native_error(&format!("Can only {name} class instances"))),
+        },
+    )))
+}
+
+fn expect_instance<'a>(
+    interpreter: &Interpreter,
+    value: &'a Object,
+) -> Result<&'a dyn Instance, RuntimeError> {
+    match value {
+        Object::Instance(instance) => Ok(&**instance),
+        Object::CallableInstance(instance) => Ok(&**instance),
+        _ => Err(interpreter.native_error("Expected an instance argument")),
+    }
+}
+
+/// The token a property named by a string argument is accessed with, private
+/// members stay out of reach like they are outside their class.
+fn property(interpreter: &Interpreter, name: &Object) -> Result<Token, RuntimeError> {
+    let name = expect_string(interpreter, name)?;
+
+    if name.starts_with('_') {
+        return Err(interpreter.native_error(&format!("Can't access private member '{name}'")));
+    }
+
+    let call_site = interpreter.call_site();
+
+    Ok(Token::new(
+        TokenType::Identifier,
+        &name,
+        None,
+        call_site.line,
+        call_site.span,
+    ))
+}
+
+fn strings(names: Vec<String>) -> Object {
+    let names = names
+        .into_iter()
+        .map(|name| Object::String(Rc::from(name)))
+        .collect();
+
+    Object::Array(Rc::new(RefCell::new(names)))
+}
@@ -0,0 +1,127 @@
+use std::{cell::RefCell, rc::Rc};
+
+use regex::Regex;
+
+use crate::{
+    errors::RuntimeError,
+    object::{Instance, Namespace, NativeFunction, Object},
+    token::Token,
+};
+
+use super::globals::expect_string;
+
+pub fn regex() -> Namespace {
+    let mut regex = Namespace::new("Regex");
+
+    regex.define(
+        "new",
+        Object::Callable(Rc::new(NativeFunction::new(
+            "new",
+            1,
+            |interpreter, args| {
+                let pattern = expect_string(interpreter, &args[0])?;
+
+                match Regex::new(&pattern) {
+                    Ok(regex) => Ok(Object::Instance(Rc::new(RegexInstance {
+                        regex: Rc::new(regex),
+                    }))),
+                    // The syntax errors of the regex crate point at the pattern
+                    // on the lines above, the last one says what's wrong.
+                    Err(err) => {
+                        let err = err.to_string();
+                        let reason = err.lines().last().unwrap_or_default();
+
+                        Err(interpreter.native_error(&format!(
+                            "Invalid regex '{pattern}': {}",
+                            reason.trim_start_matches("error: ")
+                        )))
+                    }
+                }
+            },
+        ))),
+    );
+
+    regex
+}
+
+/// A compiled regular expression returned by `Regex.new`.
+struct RegexInstance {
+    regex: Rc<Regex>,
+}
+
+impl Instance for RegexInstance {
+    fn get(&self, _: Object, name: &Token) -> Result<Object, RuntimeError> {
+        let regex = Rc::clone(&self.regex);
+        let method = match &*name.lexeme {
+            "source" => return Ok(Object::String(Rc::from(self.regex.as_str()))),
+            "test" => NativeFunction::new("test", 1, move |interpreter, args| {
+                let text = expect_string(interpreter, &args[0])?;
+
+                Ok(Object::Boolean(regex.is_match(&text)))
+            }),
+            "match" => NativeFunction::new("match", 1, move |interpreter, args| {
+                let text = expect_string(interpreter, &args[0])?;
+
+                Ok(match regex.find(&text) {
+                    Some(found) => Object::String(Rc::from(found.as_str())),
+                    None => Object::Undefined,
+                })
+            }),
+            "matchAll" => NativeFunction::new("matchAll", 1, move |interpreter, args| {
+                let text = expect_string(interpreter, &args[0])?;
+                let found = regex
+                    .find_iter(&text)
+                    .map(|found| Object::String(Rc::from(found.as_str())))
+                    .collect();
+
+                Ok(Object::Array(Rc::new(RefCell::new(found))))
+            }),
+            "captures" => NativeFunction::new("captures", 1, move |interpreter, args| {
+                let text = expect_string(interpreter, &args[0])?;
+                let Some(captures) = regex.captures(&text) else {
+                    return Ok(Object::Undefined);
+                };
+                let groups = captures
+                    .iter()
+                    .map(|group| match group {
+                        Some(group) => Object::String(Rc::from(group.as_str())),
+                        None => Object::Undefined,
+                    })
+                    .collect();
+
+                Ok(Object::Array(Rc::new(RefCell::new(groups))))
+            }),
+            "replace" => NativeFunction::new("replace", 2, move |interpreter, args| {
+                let text = expect_string(interpreter, &args[0])?;
+                let replacement = expect_string(interpreter, &args[1])?;
+
+                Ok(Object::String(Rc::from(
+                    regex.replace_all(&text, replacement.as_str()),
+                )))
+            }),
+            _ => Err(RuntimeError {
+                token: name.clone(),
+                message: format!("Undefined regex method '{}'", name.lexeme),
+            })?,
+        };
+
+        Ok(Object::Callable(Rc::new(method)))
+    }
+
+    fn set(&self, name: &Token, _: Object) -> Result<(), RuntimeError> {
+        Err(RuntimeError {
+            token: name.clone(),
+            message: String::from("Can't modify a regex"),
+        })
+    }
+
+    fn property_names(&self) -> Vec<String> {
+        ["source", "test", "match", "matchAll", "captures", "replace"]
+            .map(String::from)
+            .to_vec()
+    }
+
+    fn to_string(&self) -> String {
+        format!("[Regex: ({})]", self.regex.as_str())
+    }
+}
@@ -0,0 +1,212 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs, io,
+    path::Path,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    errors::TyphoonError,
+    object::{Class, Object},
+    token::{Span, Token},
+    token_type::TokenType,
+    Lib,
+};
+
+use super::Interpreter;
+
+/// The version of the snapshot format, restoring a snapshot of another one
+/// fails.
+const VERSION: u32 = 1;
+
+/// The globals declared by scripts, saved by [`Interpreter::snapshot`] to
+/// be restored into another interpreter by [`Interpreter::restore`].
+///
+/// Values made of booleans, numbers, strings, arrays and maps are kept as
+/// they are, functions and classes as the source of their declaration along
+/// with the values of the static fields of classes. What can't be declared
+/// again is left out, see [`Snapshot::skipped`]: natives, instances, and the
+/// functions and classes using the variables of a function they were
+/// declared in.
+///
+/// ```
+/// use typhoon::{interpreter::CapturedOutput, Lib};
+///
+/// let mut lib = Lib::new();
+///
+/// lib.run(String::from(
+///     "var count = 2; fun twice(x) { return x * count; } class Point { class origin = [0, 0]; }",
+/// ))
+/// .unwrap();
+///
+/// let snapshot = lib.snapshot();
+/// let output = CapturedOutput::new();
+/// let mut restored = Lib::new();
+///
+/// restored.set_output(output.clone());
+/// restored.restore(&snapshot).unwrap();
+/// restored.run(String::from("print twice(21); print Point.origin;")).unwrap();
+///
+/// assert!(snapshot.skipped().is_empty());
+/// assert_eq!(output.contents(), "42\n[0, 0]\n");
+/// ```
+#[derive(Serialize, Deserialize)]
+pub struct Snapshot {
+    version: u32,
+    values: BTreeMap<String, Object>,
+    /// The values declared with `const`.
+    constants: BTreeSet<String>,
+    /// The declarations of the functions and classes, superclasses first.
+    source: String,
+    /// The static fields of the classes, by the name of their class.
+    statics: BTreeMap<String, BTreeMap<String, Object>>,
+    #[serde(skip)]
+    skipped: Vec<String>,
+}
+
+impl Snapshot {
+    /// The globals left out of the snapshot, and the static fields as
+    /// `Class.field`, whose values can't be saved.
+    pub fn skipped(&self) -> &[String] {
+        &self.skipped
+    }
+
+    /// Writes the snapshot to `path` as JSON.
+    pub fn write(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+
+        fs::write(path, json)
+    }
+
+    /// Reads a snapshot written by [`Snapshot::write`].
+    pub fn read(path: impl AsRef<Path>) -> io::Result<Snapshot> {
+        let snapshot: Snapshot = serde_json::from_str(&fs::read_to_string(path)?)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+
+        match snapshot.version {
+            VERSION => Ok(snapshot),
+            version => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Unsupported snapshot version {version}, expected {VERSION}"),
+            )),
+        }
+    }
+}
+
+impl Interpreter {
+    /// Saves the globals declared by the scripts run so far, the builtins
+    /// aren't part of the snapshot.
+    pub fn snapshot(&self) -> Snapshot {
+        let builtins = Interpreter::new().global_names();
+        let mut names = self
+            .global_names()
+            .into_iter()
+            .filter(|name| !builtins.contains(name))
+            .collect::<Vec<_>>();
+        let mut snapshot = Snapshot {
+            version: VERSION,
+            values: BTreeMap::new(),
+            constants: BTreeSet::new(),
+            source: String::new(),
+            statics: BTreeMap::new(),
+            skipped: vec![],
+        };
+        let mut functions = vec![];
+        let mut classes = vec![];
+
+        names.sort();
+
+        for name in names {
+            let Some(value) = self.global(&name) else {
+                continue;
+            };
+
+            match &value {
+                Object::Callable(callable) => match callable.source(&name, &self.globals) {
+                    Some(source) => functions.push(source),
+                    None => snapshot.skipped.push(name),
+                },
+                Object::CallableInstance(class) => {
+                    match (
+                        class.source(&name, &self.globals),
+                        class.as_any().downcast_ref::<Class>(),
+                    ) {
+                        (Some(source), Some(class)) => classes.push((name, source, class.clone())),
+                        _ => snapshot.skipped.push(name),
+                    }
+                }
+                value if value.deep_clone_to_plain().is_some() => {
+                    if self.globals.borrow().is_constant(&name) {
+                        snapshot.constants.insert(String::clone(&name));
+                    }
+
+                    snapshot.values.insert(name, value.clone());
+                }
+                _ => snapshot.skipped.push(name),
+            }
+        }
+
+        // A class is declared after the one it inherits from.
+        classes.sort_by_key(|(_, _, class)| {
+            std::iter::successors(Some(class), |class| class.super_class()).count()
+        });
+
+        for (name, source, class) in classes {
+            let mut statics = BTreeMap::new();
+
+            for (field, value) in class.static_fields() {
+                match value.deep_clone_to_plain() {
+                    Some(_) => {
+                        statics.insert(field, value);
+                    }
+                    None => snapshot.skipped.push(format!("{name}.{field}")),
+                }
+            }
+
+            functions.push(source);
+
+            if !statics.is_empty() {
+                snapshot.statics.insert(name, statics);
+            }
+        }
+
+        snapshot.source = functions.join("\n\n");
+        snapshot
+    }
+
+    /// Declares the globals saved in `snapshot`, replacing the ones with the
+    /// same names.
+    pub fn restore(&mut self, snapshot: &Snapshot) -> Result<(), TyphoonError> {
+        for (name, value) in &snapshot.values {
+            let mut globals = self.globals.borrow_mut();
+
+            match snapshot.constants.contains(name) {
+                true => globals.define_const(name, value.clone()),
+                false => globals.define(name, value.clone()),
+            };
+        }
+
+        let mut diagnostics = vec![];
+        let statements = Lib::compile(self, String::clone(&snapshot.source), &mut diagnostics)
+            .ok_or(TyphoonError::Syntax(diagnostics))?;
+
+        self.interpret(&statements).map_err(TyphoonError::Runtime)?;
+
+        for (name, statics) in &snapshot.statics {
+            let Some(Object::CallableInstance(class)) = self.global(name) else {
+                continue;
+            };
+
+            for (field, value) in statics {
+                let field = Token::new(TokenType::Identifier, field, None, 0, Span::default());
+
+                class
+                    .set(&field, value.clone())
+                    .map_err(TyphoonError::Runtime)?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,202 @@
+use std::{
+    cell::RefCell,
+    io::{self, Read, Write},
+    net::{Shutdown, TcpStream},
+    rc::Rc,
+};
+
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+
+use crate::{
+    errors::RuntimeError,
+    object::{Instance, Namespace, NativeFunction, Object},
+    token::Token,
+};
+
+use super::{
+    globals::{expect_index, expect_string},
+    Interpreter,
+};
+
+/// How many bytes `read` waits for at most when not given a size.
+const READ_SIZE: usize = 4096;
+
+pub fn socket() -> Namespace {
+    let mut socket = Namespace::new("Socket");
+
+    socket.define(
+        "connect",
+        Object::Callable(Rc::new(NativeFunction::new(
+            "connect",
+            2,
+            |interpreter, args| {
+                let host = expect_string(interpreter, &args[0])?;
+                let port = match args[1] {
+                    Object::Int(port) => u16::try_from(port).ok(),
+                    _ => None,
+                }
+                .ok_or_else(|| interpreter.native_error("Expected a port between 0 and 65535"))?;
+                let stream = TcpStream::connect((host.as_str(), port)).map_err(|err| {
+                    interpreter.native_error(&format!("Can't connect to {host}:{port}: {err}"))
+                })?;
+
+                Ok(SocketInstance::object(Stream::Tcp(stream)))
+            },
+        ))),
+    );
+
+    #[cfg(unix)]
+    socket.define(
+        "connectUnix",
+        Object::Callable(Rc::new(NativeFunction::new(
+            "connectUnix",
+            1,
+            |interpreter, args| {
+                let path = expect_string(interpreter, &args[0])?;
+                let stream = UnixStream::connect(&path).map_err(|err| {
+                    interpreter.native_error(&format!("Can't connect to '{path}': {err}"))
+                })?;
+
+                Ok(SocketInstance::object(Stream::Unix(stream)))
+            },
+        ))),
+    );
+
+    socket
+}
+
+enum Stream {
+    Tcp(TcpStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+impl Stream {
+    fn read(&mut self, buffer: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Stream::Tcp(stream) => stream.read(buffer),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.read(buffer),
+        }
+    }
+
+    fn write_all(&mut self, bytes: &[u8]) -> io::Result<()> {
+        match self {
+            Stream::Tcp(stream) => stream.write_all(bytes),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.write_all(bytes),
+        }
+    }
+
+    fn shutdown(&self) -> io::Result<()> {
+        match self {
+            Stream::Tcp(stream) => stream.shutdown(Shutdown::Both),
+            #[cfg(unix)]
+            Stream::Unix(stream) => stream.shutdown(Shutdown::Both),
+        }
+    }
+}
+
+/// A connection returned by `Socket.connect`, `None` once closed.
+struct SocketInstance {
+    stream: Rc<RefCell<Option<Stream>>>,
+}
+
+impl SocketInstance {
+    fn object(stream: Stream) -> Object {
+        Object::Instance(Rc::new(SocketInstance {
+            stream: Rc::new(RefCell::new(Some(stream))),
+        }))
+    }
+}
+
+/// Runs `action` on the open stream, failing with the error of the
+/// `operation` otherwise.
+fn with_stream<T>(
+    interpreter: &Interpreter,
+    stream: &RefCell<Option<Stream>>,
+    operation: &str,
+    action: impl FnOnce(&mut Stream) -> io::Result<T>,
+) -> Result<T, RuntimeError> {
+    match &mut *stream.borrow_mut() {
+        Some(stream) => action(stream)
+            .map_err(|err| interpreter.native_error(&format!("Can't {operation} socket: {err}"))),
+        None => Err(interpreter.native_error("Socket is closed")),
+    }
+}
+
+impl Instance for SocketInstance {
+    fn get(&self, _: Object, name: &Token) -> Result<Object, RuntimeError> {
+        let stream = Rc::clone(&self.stream);
+        let method = match &*name.lexeme {
+            "read" => NativeFunction::new("read", 0, move |interpreter, args| {
+                let size = match args.first() {
+                    Some(size) => expect_index(interpreter, size, usize::MAX)?,
+                    None => READ_SIZE,
+                };
+                let mut buffer = vec![0; size];
+                let read = with_stream(interpreter, &stream, "read from", |stream| {
+                    stream.read(&mut buffer)
+                })?;
+
+                if read == 0 && size > 0 {
+                    return Ok(Object::Undefined);
+                }
+
+                Ok(Object::String(Rc::from(String::from_utf8_lossy(
+                    &buffer[..read],
+                ))))
+            })
+            .optional(1),
+            "write" => NativeFunction::new("write", 1, move |interpreter, args| {
+                let text = args[0].to_string();
+
+                with_stream(interpreter, &stream, "write to", |stream| {
+                    stream.write_all(text.as_bytes())
+                })?;
+
+                Ok(Object::Int(text.len() as i64))
+            }),
+            "close" => NativeFunction::new("close", 0, move |_, _| {
+                if let Some(stream) = stream.borrow_mut().take() {
+                    // The peer may have closed it first, it's closed either way.
+                    let _ = stream.shutdown();
+                }
+
+                Ok(Object::Undefined)
+            }),
+            _ => Err(RuntimeError {
+                token: name.clone(),
+                message: format!("Undefined socket method '{}'", name.lexeme),
+            })?,
+        };
+
+        Ok(Object::Callable(Rc::new(method)))
+    }
+
+    fn set(&self, name: &Token, _: Object) -> Result<(), RuntimeError> {
+        Err(RuntimeError {
+            token: name.clone(),
+            message: String::from("Can't modify a socket"),
+        })
+    }
+
+    fn property_names(&self) -> Vec<String> {
+        ["read", "write", "close"].map(String::from).to_vec()
+    }
+
+    fn to_string(&self) -> String {
+        let state = match &*self.stream.borrow() {
+            Some(Stream::Tcp(stream)) => match stream.peer_addr() {
+                Ok(address) => address.to_string(),
+                Err(_) => String::from("tcp"),
+            },
+            #[cfg(unix)]
+            Some(Stream::Unix(_)) => String::from("unix"),
+            None => String::from("closed"),
+        };
+
+        format!("[Socket: ({state})]")
+    }
+}
@@ -0,0 +1,87 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{
+    errors::RuntimeError,
+    object::{NativeFunction, Object},
+    token::Token,
+};
+
+use super::globals::{expect_index, expect_string};
+
+/// The properties [`get_property`] knows, for completion.
+pub const PROPERTIES: [&str; 8] = [
+    "length",
+    "substring",
+    "indexOf",
+    "split",
+    "toUpper",
+    "toLower",
+    "trim",
+    "replace",
+];
+
+pub fn get_property(string: &Rc<str>, name: &Token) -> Result<Object, RuntimeError> {
+    let string = Rc::clone(string);
+    let method = match &*name.lexeme {
+        "length" => return Ok(Object::Int(string.chars().count() as i64)),
+        "substring" => NativeFunction::new("substring", 1, move |interpreter, args| {
+            let chars = string.chars().collect::<Vec<_>>();
+            let start = expect_index(interpreter, &args[0], chars.len())?;
+            let end = match args.get(1) {
+                Some(end) => expect_index(interpreter, end, chars.len())?,
+                None => chars.len(),
+            };
+
+            Ok(Object::String(Rc::from(
+                chars[start.min(end)..end].iter().collect::<String>(),
+            )))
+        })
+        .optional(1),
+        "indexOf" => NativeFunction::new("indexOf", 1, move |interpreter, args| {
+            let needle = expect_string(interpreter, &args[0])?;
+            let index = match string.find(&needle) {
+                Some(byte) => string[..byte].chars().count() as i64,
+                None => -1,
+            };
+
+            Ok(Object::Int(index))
+        }),
+        "split" => NativeFunction::new("split", 1, move |interpreter, args| {
+            let separator = expect_string(interpreter, &args[0])?;
+            let parts = if separator.is_empty() {
+                string
+                    .chars()
+                    .map(|c| Object::String(Rc::from(c.to_string())))
+                    .collect()
+            } else {
+                string
+                    .split(&separator)
+                    .map(|part| Object::String(Rc::from(part)))
+                    .collect()
+            };
+
+            Ok(Object::Array(Rc::new(RefCell::new(parts))))
+        }),
+        "toUpper" => NativeFunction::new("toUpper", 0, move |_, _| {
+            Ok(Object::String(Rc::from(string.to_uppercase())))
+        }),
+        "toLower" => NativeFunction::new("toLower", 0, move |_, _| {
+            Ok(Object::String(Rc::from(string.to_lowercase())))
+        }),
+        "trim" => NativeFunction::new("trim", 0, move |_, _| {
+            Ok(Object::String(Rc::from(string.trim())))
+        }),
+        "replace" => NativeFunction::new("replace", 2, move |interpreter, args| {
+            let from = expect_string(interpreter, &args[0])?;
+            let to = expect_string(interpreter, &args[1])?;
+
+            Ok(Object::String(Rc::from(string.replace(&from, &to))))
+        }),
+        _ => Err(RuntimeError {
+            token: name.clone(),
+            message: format!("Undefined string method '{}'", name.lexeme),
+        })?,
+    };
+
+    Ok(Object::Callable(Rc::new(method)))
+}
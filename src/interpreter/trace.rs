@@ -0,0 +1,29 @@
+use crate::{ast_printer::PrettyAstPrinter, expr::Expr, object::Object, stmt::Stmt};
+
+/// Logs a statement about to be executed, statements spanning several lines
+/// are cut down to their first one.
+pub fn statement(stmt: &Stmt, depth: usize) {
+    let printed = PrettyAstPrinter::new().print(std::slice::from_ref(stmt));
+    let head = printed.lines().next().unwrap_or_default();
+
+    eprintln!("{}{} {head}", "  ".repeat(depth), location(stmt.line()));
+}
+
+/// Logs an evaluated expression along with the value it produced.
+pub fn expression(expr: &Expr, value: &Object, depth: usize) {
+    let printed = PrettyAstPrinter::new().print_expression(expr);
+    let head = printed.lines().next().unwrap_or_default();
+
+    eprintln!(
+        "{}{} {head} => {value}",
+        "  ".repeat(depth),
+        location(expr.line())
+    );
+}
+
+fn location(line: Option<usize>) -> String {
+    match line {
+        Some(line) => format!("[{line}]"),
+        None => String::from("[-]"),
+    }
+}
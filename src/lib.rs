@@ -1,145 +1,1249 @@
+pub mod ast_printer;
+pub mod dot_printer;
 pub mod environment;
 pub mod errors;
 pub mod expr;
+pub mod formatter;
 pub mod interpreter;
 pub mod literal_type;
 pub mod object;
 pub mod stmt;
+pub mod symbol;
 pub mod token;
 pub mod token_type;
 pub mod utils;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
+mod colors;
+#[cfg(feature = "cli")]
+mod completion;
+mod optimizer;
 mod parser;
 mod resolver;
 mod scanner;
 
-use colored::Colorize;
-use errors::RuntimeError;
-use interpreter::Interpreter;
+use std::{
+    fs,
+    io::{self, Write},
+    ops::Range,
+    path::{Path, PathBuf},
+    process,
+};
+#[cfg(feature = "cli")]
+use std::{thread, time::Duration};
+
+use ast_printer::PrettyAstPrinter;
+use colors::{ColoredString, Colorize};
+#[cfg(feature = "cli")]
+use completion::ReplHelper;
+use dot_printer::DotPrinter;
+use errors::{CallFrame, Diagnostic, ErrorFormat, Lint, LintLevel, RuntimeError, Severity};
+#[cfg(feature = "serde")]
+use interpreter::Snapshot;
+use interpreter::{Interpreter, Journal, Limits};
+use object::Object;
+use optimizer::Optimizer;
 use parser::Parser;
 use resolver::Resolver;
-use rustyline::DefaultEditor;
+#[cfg(feature = "cli")]
+use rustyline::{error::ReadlineError, history::DefaultHistory, Editor};
 use scanner::Scanner;
-use token::Token;
-use token_type::TokenType;
+use stmt::Stmt;
+use token::Span;
+
+pub use errors::TyphoonError;
 
+/// Runs scripts. Every instance has its own globals, modules and output, so
+/// several can run at once, each on a thread of its own.
+///
+/// ```
+/// use std::thread;
+///
+/// use typhoon::{interpreter::CapturedOutput, Lib};
+///
+/// let workers = (1..=4).map(|n| {
+///     thread::spawn(move || {
+///         let output = CapturedOutput::new();
+///         let mut lib = Lib::new();
+///
+///         lib.set_output(output.clone());
+///         lib.run(format!("var n = {n}; print n * n;")).unwrap();
+///
+///         output.contents()
+///     })
+/// });
+/// let squares = workers
+///     .collect::<Vec<_>>()
+///     .into_iter()
+///     .map(|worker| worker.join().unwrap())
+///     .collect::<String>();
+///
+/// assert_eq!(squares, "1\n4\n9\n16\n");
+/// ```
 pub struct Lib {
     interpreter: Interpreter,
+    error_format: ErrorFormat,
+    /// Everything reported by the last run.
+    diagnostics: Vec<Diagnostic>,
+    /// Where the journal recorded while running a file is written.
+    journal_path: Option<PathBuf>,
 }
 
-static mut HAD_ERROR: bool = false;
-static mut HAD_RUNTIME_ERROR: bool = false;
-static VERSION: &'static str = "Beta 0.0.1";
+#[cfg(feature = "cli")]
+static VERSION: &str = "Beta 0.0.1";
+
+/// Exit code used when the script file can't be read.
+pub const EXIT_IO_ERROR: i32 = 66;
+/// Exit code used when scanning, parsing or resolving reported an error.
+pub const EXIT_SYNTAX_ERROR: i32 = 65;
+/// Exit code used when the interpreter raised a runtime error.
+pub const EXIT_RUNTIME_ERROR: i32 = 70;
+
+impl Default for Lib {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl Lib {
     pub fn new() -> Self {
         Self {
             interpreter: Interpreter::new(),
+            error_format: ErrorFormat::Human,
+            diagnostics: vec![],
+            journal_path: None,
+        }
+    }
+
+    pub fn run_file(&mut self, path: &str) {
+        let source = Lib::read_script(path);
+
+        if path != "-" {
+            self.interpreter.enter_main_module(path);
         }
+
+        let result = self.run(source);
+
+        self.exit_on_error(result);
     }
 
-    pub fn run_file(&mut self) {
-        todo!()
+    /// Evaluates `source` given on the command line with [`Lib::eval`] and
+    /// prints the value of its last expression, exiting like
+    /// [`Lib::run_file`].
+    pub fn run_eval(&mut self, source: &str) {
+        let result = self.eval(source).map(|value| match value {
+            Object::Undefined => {}
+            value => println!("{value}"),
+        });
+
+        self.exit_on_error(result);
     }
 
+    fn exit_on_error(&mut self, result: Result<(), TyphoonError>) {
+        if let Some(profile) = self.interpreter.take_profile() {
+            eprint!("{profile}");
+        }
+
+        if let Some(coverage) = self.interpreter.take_coverage() {
+            eprint!("{coverage}");
+        }
+
+        if let (Some(path), Some(journal)) = (&self.journal_path, self.interpreter.take_journal()) {
+            if let Err(err) = fs::write(path, journal.to_string()) {
+                eprintln!("{} {}: {}", "Error:".bold().red(), path.display(), err);
+            }
+        }
+
+        match result {
+            Ok(()) => {
+                if let Some(code) = self.exit_code() {
+                    process::exit(code);
+                }
+            }
+            Err(TyphoonError::Syntax(_)) => process::exit(EXIT_SYNTAX_ERROR),
+            Err(TyphoonError::Runtime(_) | TyphoonError::LimitExceeded(..)) => {
+                process::exit(EXIT_RUNTIME_ERROR)
+            }
+        }
+    }
+
+    /// Prints the tokens scanned from the script at `path` without running it.
+    pub fn print_tokens(&self, path: &str) {
+        let source = Lib::read_script(path);
+        let tokens = Scanner::new(String::clone(&source))
+            .scan_tokens()
+            .unwrap_or_else(|diagnostics| self.exit_with(&diagnostics, &source));
+
+        for token in tokens {
+            println!(
+                "{:>9} {:<18} {}",
+                format!("{}:{}", token.line, token.span.column),
+                format!("{:?}", token.token_type),
+                token.lexeme
+            );
+        }
+    }
+
+    /// Prints the statements parsed from the script at `path` without
+    /// resolving or running them.
+    pub fn print_ast(&self, path: &str) {
+        let source = Lib::read_script(path);
+        let statements = Scanner::new(String::clone(&source))
+            .scan_tokens()
+            .and_then(|tokens| Parser::new(tokens).parse())
+            .unwrap_or_else(|diagnostics| self.exit_with(&diagnostics, &source));
+
+        println!("{}", PrettyAstPrinter::new().print(&statements));
+    }
+
+    /// Prints the statements parsed from the script at `path` as a Graphviz
+    /// DOT graph, see [`DotPrinter`].
+    pub fn print_ast_dot(&self, path: &str) {
+        let source = Lib::read_script(path);
+        let statements = Scanner::new(String::clone(&source))
+            .scan_tokens()
+            .and_then(|tokens| Parser::new(tokens).parse())
+            .unwrap_or_else(|diagnostics| self.exit_with(&diagnostics, &source));
+
+        print!("{}", DotPrinter::new().print(&statements));
+    }
+
+    /// Prints the statements parsed from the script at `path` as JSON.
+    #[cfg(feature = "serde")]
+    pub fn print_ast_json(&self, path: &str) {
+        let source = Lib::read_script(path);
+        let statements = Scanner::new(String::clone(&source))
+            .scan_tokens()
+            .and_then(|tokens| Parser::new(tokens).parse())
+            .unwrap_or_else(|diagnostics| self.exit_with(&diagnostics, &source));
+
+        match serde_json::to_string_pretty(&statements) {
+            Ok(json) => println!("{json}"),
+            Err(err) => {
+                eprintln!("{} {}", "Error:".bold().red(), err);
+                process::exit(EXIT_SYNTAX_ERROR);
+            }
+        }
+    }
+
+    /// Rewrites the script at `path` in its canonical format, see
+    /// [`formatter::format`]. With `check` the file is left untouched and only
+    /// reported if it needs formatting. Returns whether it was already
+    /// formatted.
+    pub fn format_file(&self, path: &str, check: bool) -> bool {
+        let source = Lib::read_script(path);
+        let formatted = formatter::format(&source)
+            .unwrap_or_else(|diagnostics| self.exit_with(&diagnostics, &source));
+
+        if formatted == source {
+            return true;
+        }
+
+        if check {
+            println!("{} {}", "Would reformat".yellow(), path);
+        } else if let Err(err) = fs::write(path, formatted) {
+            eprintln!("{} {}: {}", "Error:".bold().red(), path.yellow(), err);
+            process::exit(EXIT_IO_ERROR);
+        }
+
+        false
+    }
+
+    /// Resolves the script at `path` without running it and reports the
+    /// warnings of every lint, unless its level was set with
+    /// [`Lib::set_lint_level`] or [`Lib::set_deny_warnings`]. Returns whether
+    /// none was found.
+    pub fn lint_file(&mut self, path: &str) -> bool {
+        let source = Lib::read_script(path);
+        let statements = Scanner::new(String::clone(&source))
+            .scan_tokens()
+            .and_then(|tokens| Parser::new(tokens).parse())
+            .unwrap_or_else(|diagnostics| self.exit_with(&diagnostics, &source));
+        let diagnostics = Resolver::new()
+            .resolve(&statements)
+            .into_iter()
+            .filter_map(|diagnostic| {
+                let level = diagnostic
+                    .lint
+                    .map(|lint| self.interpreter.lint_level_or(lint, LintLevel::Warn));
+
+                Lib::leveled(diagnostic, level)
+            })
+            .collect::<Vec<_>>();
+
+        if diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.is_error() && diagnostic.lint.is_none())
+        {
+            self.exit_with(&diagnostics, &source);
+        }
+
+        if !diagnostics.is_empty() && self.error_format == ErrorFormat::Human {
+            println!("{}", path.bold());
+        }
+
+        diagnostics
+            .iter()
+            .for_each(|diagnostic| self.emit(diagnostic, Some(&source)));
+
+        diagnostics.is_empty()
+    }
+
+    /// Scans, parses and resolves the script at `path` without running it,
+    /// reporting what running it would before it starts. Returns whether it
+    /// has no errors.
+    pub fn check_file(&mut self, path: &str) -> bool {
+        let source = Lib::read_script(path);
+        let mut diagnostics = vec![];
+        let compiled = Lib::compile(
+            &mut self.interpreter,
+            String::clone(&source),
+            &mut diagnostics,
+        );
+
+        if !diagnostics.is_empty() && self.error_format == ErrorFormat::Human {
+            println!("{}", path.bold());
+        }
+
+        diagnostics
+            .iter()
+            .for_each(|diagnostic| self.emit(diagnostic, Some(&source)));
+
+        compiled.is_some()
+    }
+
+    /// Runs every `*_test.ty` script in `paths`, searching directories
+    /// recursively, each with globals of its own. A script passes when it
+    /// runs to the end without an uncaught error, such as a failed `assert`.
+    /// Returns whether all of them passed.
+    pub fn run_tests(&self, paths: &[&str]) -> bool {
+        let mut scripts = vec![];
+
+        for path in paths {
+            Lib::find_tests(Path::new(path), &mut scripts);
+        }
+
+        scripts.sort();
+
+        let mut failed = 0;
+
+        for script in &scripts {
+            let path = script.to_string_lossy();
+            let mut lib = Lib::new();
+
+            lib.set_error_format(self.error_format);
+            lib.interpreter.enter_main_module(&path);
+
+            let passed = lib.run(Lib::read_script(&path)).is_ok()
+                && lib.exit_code().is_none_or(|code| code == 0);
+
+            if passed {
+                println!("{} {}", "PASS".bold().green(), path);
+            } else {
+                println!("{} {}", "FAIL".bold().red(), path);
+                failed += 1;
+            }
+        }
+
+        println!("\n{} passed, {} failed", scripts.len() - failed, failed);
+
+        failed == 0
+    }
+
+    fn find_tests(path: &Path, scripts: &mut Vec<PathBuf>) {
+        if path.is_file() {
+            scripts.push(path.to_path_buf());
+            return;
+        }
+
+        let entries = match fs::read_dir(path) {
+            Ok(entries) => entries,
+            Err(err) => {
+                eprintln!("{} {}: {}", "Error:".bold().red(), path.display(), err);
+                process::exit(EXIT_IO_ERROR);
+            }
+        };
+
+        for entry in entries.filter_map(Result::ok) {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().into_owned();
+
+            if path.is_dir() && !name.starts_with('.') {
+                Lib::find_tests(&path, scripts);
+            } else if name.ends_with("_test.ty") {
+                scripts.push(path);
+            }
+        }
+    }
+
+    /// Reads the script at `path`, or stdin when it's `-`.
+    fn read_script(path: &str) -> String {
+        let source = if path == "-" {
+            io::read_to_string(io::stdin())
+        } else {
+            fs::read_to_string(path)
+        };
+
+        match source {
+            Ok(source) => source,
+            Err(err) => {
+                eprintln!("{} {}: {}", "Error:".bold().red(), path.yellow(), err);
+                process::exit(EXIT_IO_ERROR);
+            }
+        }
+    }
+
+    fn exit_with(&self, diagnostics: &[Diagnostic], source: &str) -> ! {
+        diagnostics
+            .iter()
+            .for_each(|diagnostic| self.emit(diagnostic, Some(source)));
+        process::exit(EXIT_SYNTAX_ERROR);
+    }
+
+    /// Runs the script at `path`, then again whenever it or a module it
+    /// imported changes, until interrupted. Errors are reported without
+    /// stopping. The globals are kept across runs: functions and classes
+    /// are declared again in place of the old ones and variables the script
+    /// doesn't declare again keep their values, while modules are imported
+    /// afresh.
+    #[cfg(feature = "cli")]
+    pub fn watch_file(&mut self, path: &str) -> ! {
+        loop {
+            match fs::read_to_string(path) {
+                Ok(source) => {
+                    self.interpreter.enter_main_module(path);
+
+                    let _ = self.run(source);
+
+                    self.interpreter.leave_main_module();
+                }
+                Err(err) => eprintln!("{} {}: {}", "Error:".bold().red(), path.yellow(), err),
+            }
+
+            let mut files = self.interpreter.forget_modules();
+
+            files.push(PathBuf::from(path));
+
+            let modified = |files: &[PathBuf]| {
+                files
+                    .iter()
+                    .map(|file| {
+                        fs::metadata(file)
+                            .and_then(|metadata| metadata.modified())
+                            .ok()
+                    })
+                    .collect::<Vec<_>>()
+            };
+            let before = modified(&files);
+            let changed = loop {
+                thread::sleep(Duration::from_millis(200));
+
+                let now = modified(&files);
+
+                if let Some(index) = (0..files.len()).find(|&index| now[index] != before[index]) {
+                    break &files[index];
+                }
+            };
+
+            eprintln!(
+                "{} {} changed, running {} again",
+                "Watch:".bold().blue(),
+                changed.display(),
+                path
+            );
+        }
+    }
+
+    /// Reads and runs lines until Ctrl-D or `:quit`. `~/.typhoonrc` is run
+    /// first if it exists, so it can declare globals, and the lines entered
+    /// are kept in `~/.typhoon_history` across sessions. Lines starting with
+    /// `:` are commands, see `:help`.
+    #[cfg(feature = "cli")]
     pub fn run_prompt(&mut self) {
         println!("{}", VERSION);
 
-        let mut rl = DefaultEditor::new().expect("failed to create editor");
+        if let Some(rc) = Lib::home_file(".typhoonrc").filter(|rc| rc.is_file()) {
+            match fs::read_to_string(&rc) {
+                // Its errors are reported like any other, the prompt starts
+                // regardless.
+                Ok(source) => {
+                    let _ = self.run(source);
+                }
+                Err(err) => eprintln!("{} {}: {}", "Error:".bold().red(), rc.display(), err),
+            }
+        }
+
+        let mut rl = match Editor::<ReplHelper, DefaultHistory>::new() {
+            Ok(rl) => rl,
+            Err(err) => {
+                eprintln!("{} {}", "Error:".bold().red(), err);
+                process::exit(EXIT_IO_ERROR);
+            }
+        };
+        let history = Lib::home_file(".typhoon_history");
+
+        if let Some(history) = &history {
+            // There's no history yet on the first run.
+            let _ = rl.load_history(history);
+        }
+
+        rl.set_helper(Some(ReplHelper::default()));
 
         loop {
-            let input = rl.readline("> ").expect("input is read correctly");
-            rl.add_history_entry(&input)
-                .expect("input added to history");
-            self.run(input);
+            if let Some(helper) = rl.helper_mut() {
+                helper.refresh(&self.interpreter);
+            }
+
+            let input = match rl.readline("> ") {
+                Ok(input) => input,
+                // Ctrl-C drops the line being typed.
+                Err(ReadlineError::Interrupted) => continue,
+                Err(ReadlineError::Eof) => break,
+                Err(err) => {
+                    eprintln!("{} {}", "Error:".bold().red(), err);
+                    break;
+                }
+            };
+
+            if !input.trim().is_empty() {
+                let _ = rl.add_history_entry(&input);
+            }
+
+            if let Some(command) = input.trim().strip_prefix(':') {
+                if !self.run_command(command) {
+                    break;
+                }
+            } else if let Ok(Some(value)) = self.run_line(input) {
+                println!("{}", value);
+            }
 
-            unsafe {
-                HAD_ERROR = false;
+            if let Some(code) = self.exit_code() {
+                Lib::save_history(&mut rl, history.as_deref());
+                process::exit(code);
             }
         }
+
+        Lib::save_history(&mut rl, history.as_deref());
     }
 
-    fn run(&mut self, source: String) {
-        let scanner = Scanner::new(source);
-        let tokens = scanner.scan_tokens();
+    /// Runs a REPL command, the line without its `:`. Returns whether the REPL
+    /// should keep going.
+    #[cfg(feature = "cli")]
+    fn run_command(&mut self, command: &str) -> bool {
+        let (name, argument) = match command.split_once(char::is_whitespace) {
+            Some((name, argument)) => (name, argument.trim()),
+            None => (command, ""),
+        };
 
-        if unsafe { HAD_ERROR } {
-            return;
+        match name {
+            "help" => {
+                println!(":help          Show this help");
+                println!(":env           List the variables declared in the session");
+                println!(":load <path>   Run a script in the session");
+                println!(":type <expr>   Show the type of an expression");
+                #[cfg(feature = "serde")]
+                {
+                    println!(":save <path>   Save the variables declared in the session");
+                    println!(":restore <path> Declare the variables saved by :save");
+                }
+                println!(":reset         Forget every variable declared in the session");
+                println!(":quit          Exit the REPL");
+            }
+            "env" => {
+                let builtins = Interpreter::new().global_names();
+                let mut names = self
+                    .interpreter
+                    .global_names()
+                    .into_iter()
+                    .filter(|name| !builtins.contains(name))
+                    .collect::<Vec<_>>();
+
+                names.sort();
+
+                for name in names {
+                    if let Some(value) = self.interpreter.global(&name) {
+                        println!("{} = {}", name.bold(), value);
+                    }
+                }
+            }
+            "load" if !argument.is_empty() => match fs::read_to_string(argument) {
+                Ok(source) => {
+                    self.interpreter.enter_main_module(argument);
+
+                    let _ = self.run(source);
+
+                    self.interpreter.leave_main_module();
+                }
+                Err(err) => eprintln!("{} {}: {}", "Error:".bold().red(), argument.yellow(), err),
+            },
+            "type" if !argument.is_empty() => {
+                if let Ok(value) = self.eval(argument) {
+                    println!("{}", value.type_name());
+                }
+            }
+            #[cfg(feature = "serde")]
+            "save" if !argument.is_empty() => {
+                let snapshot = self.interpreter.snapshot();
+
+                match snapshot.write(argument) {
+                    Ok(()) => {
+                        for name in snapshot.skipped() {
+                            eprintln!("{} {} can't be saved", "Warning:".bold().yellow(), name);
+                        }
+                    }
+                    Err(err) => {
+                        eprintln!("{} {}: {}", "Error:".bold().red(), argument.yellow(), err)
+                    }
+                }
+            }
+            #[cfg(feature = "serde")]
+            "restore" if !argument.is_empty() => match Snapshot::read(argument) {
+                Ok(snapshot) => {
+                    if let Err(err) = self.restore(&snapshot) {
+                        eprintln!("{} {}: {}", "Error:".bold().red(), argument.yellow(), err);
+                    }
+                }
+                Err(err) => eprintln!("{} {}: {}", "Error:".bold().red(), argument.yellow(), err),
+            },
+            "reset" => self.interpreter.reset(),
+            "quit" => return false,
+            "load" => eprintln!("{} Usage: :load <path>", "Error:".bold().red()),
+            "type" => eprintln!("{} Usage: :type <expr>", "Error:".bold().red()),
+            #[cfg(feature = "serde")]
+            "save" => eprintln!("{} Usage: :save <path>", "Error:".bold().red()),
+            #[cfg(feature = "serde")]
+            "restore" => eprintln!("{} Usage: :restore <path>", "Error:".bold().red()),
+            _ => eprintln!(
+                "{} Unknown command ':{}', see :help",
+                "Error:".bold().red(),
+                name
+            ),
         }
 
-        let mut parser = Parser::new(tokens);
-        let statements = parser.parse();
+        true
+    }
 
-        if unsafe { HAD_ERROR } {
-            return;
+    #[cfg(feature = "cli")]
+    fn save_history(rl: &mut Editor<ReplHelper, DefaultHistory>, history: Option<&Path>) {
+        if let Some(history) = history {
+            if let Err(err) = rl.save_history(history) {
+                eprintln!("{} {}: {}", "Error:".bold().red(), history.display(), err);
+            }
         }
+    }
 
-        let mut resolver = Resolver::new(&mut self.interpreter);
+    /// The path of `name` in the user's home directory.
+    #[cfg(feature = "cli")]
+    fn home_file(name: &str) -> Option<PathBuf> {
+        std::env::var_os("HOME")
+            .or_else(|| std::env::var_os("USERPROFILE"))
+            .map(|home| Path::new(&home).join(name))
+    }
 
-        resolver.resolve_stmts(&statements);
+    /// Redirects what the program prints, see [`Interpreter::set_output`].
+    /// Diagnostics and runtime errors are still reported to stdout.
+    ///
+    /// ```
+    /// use typhoon::{interpreter::CapturedOutput, Lib};
+    ///
+    /// let output = CapturedOutput::new();
+    /// let mut lib = Lib::new();
+    ///
+    /// lib.set_output(output.clone());
+    /// lib.run(String::from("print 1 + 2;")).unwrap();
+    ///
+    /// assert_eq!(output.contents(), "3\n");
+    /// ```
+    pub fn set_output(&mut self, output: impl Write + 'static) {
+        self.interpreter.set_output(Box::new(output));
+    }
 
-        if unsafe { HAD_ERROR } {
-            return;
+    /// Chooses how diagnostics and runtime errors are reported, readable
+    /// messages by default.
+    pub fn set_error_format(&mut self, error_format: ErrorFormat) {
+        self.error_format = error_format;
+    }
+
+    /// The diagnostics reported by the last [`Lib::run`], [`Lib::run_line`]
+    /// or [`Lib::eval`], including its runtime error.
+    ///
+    /// ```
+    /// use typhoon::Lib;
+    ///
+    /// let mut lib = Lib::new();
+    ///
+    /// lib.set_output(std::io::sink());
+    /// assert!(lib.run(String::from("print 1 / 0;")).is_err());
+    /// assert_eq!(lib.diagnostics()[0].code, "runtime-error");
+    /// ```
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// The code passed to the `exit` statement that ended the last run,
+    /// `None` if it ran to the end. The run itself succeeds.
+    ///
+    /// ```
+    /// use typhoon::Lib;
+    ///
+    /// let mut lib = Lib::new();
+    ///
+    /// lib.set_output(std::io::sink());
+    /// lib.run(String::from("print 1; exit(3); print 2;")).unwrap();
+    ///
+    /// assert_eq!(lib.exit_code(), Some(3));
+    /// ```
+    pub fn exit_code(&self) -> Option<i32> {
+        self.interpreter.exit_code()
+    }
+
+    /// Bounds the work each following run may do, see [`Limits`]. A run going
+    /// over a limit fails with [`TyphoonError::LimitExceeded`].
+    ///
+    /// ```
+    /// use typhoon::{errors::Limit, interpreter::Limits, Lib, TyphoonError};
+    ///
+    /// let mut lib = Lib::new();
+    ///
+    /// lib.set_output(std::io::sink());
+    /// lib.set_limits(Limits {
+    ///     max_statements: Some(10_000),
+    ///     ..Limits::default()
+    /// });
+    ///
+    /// let error = lib.run(String::from("while (true) { try {} catch {} }"));
+    ///
+    /// assert!(matches!(
+    ///     error,
+    ///     Err(TyphoonError::LimitExceeded(Limit::Statements, _))
+    /// ));
+    /// ```
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.interpreter.set_limits(limits);
+    }
+
+    /// Logs every executed statement and evaluated expression to stderr, see
+    /// [`Interpreter::set_trace`].
+    pub fn set_trace(&mut self, trace: bool) {
+        self.interpreter.set_trace(trace);
+    }
+
+    /// Enables or disables constant folding, see
+    /// [`Interpreter::set_constant_folding`].
+    pub fn set_constant_folding(&mut self, fold: bool) {
+        self.interpreter.set_constant_folding(fold);
+    }
+
+    /// Sets the arguments scripts see in the `ARGS` array, see
+    /// [`Interpreter::set_args`].
+    ///
+    /// ```
+    /// use typhoon::{object::Object, Lib};
+    ///
+    /// let mut lib = Lib::new();
+    /// lib.set_args(vec![String::from("--verbose"), String::from("input.txt")]);
+    ///
+    /// let value = lib.eval("ARGS.length == 2 and ARGS[1]").unwrap();
+    ///
+    /// assert_eq!(value.to_string(), "input.txt");
+    /// ```
+    pub fn set_args(&mut self, args: Vec<String>) {
+        self.interpreter.set_args(args);
+    }
+
+    /// Makes division and remainder by zero give infinity or NaN instead of
+    /// an error, see [`Interpreter::set_ieee_division`].
+    ///
+    /// ```
+    /// use typhoon::{object::Object, Lib};
+    ///
+    /// let mut lib = Lib::new();
+    /// lib.set_ieee_division(true);
+    ///
+    /// let value = lib.eval("isNaN(0 / 0) and 1 / 0 == Number.INFINITY").unwrap();
+    ///
+    /// assert!(matches!(value, Object::Boolean(true)));
+    /// ```
+    pub fn set_ieee_division(&mut self, ieee: bool) {
+        self.interpreter.set_ieee_division(ieee);
+    }
+
+    /// Sets what's done with the warnings of `lint` when running scripts, see
+    /// [`LintLevel`]. Only unused variables are reported by default.
+    ///
+    /// ```
+    /// use typhoon::{
+    ///     errors::{Lint, LintLevel},
+    ///     Lib,
+    /// };
+    ///
+    /// let mut lib = Lib::new();
+    ///
+    /// lib.set_output(std::io::sink());
+    /// lib.set_lint_level(Lint::ShadowedVariable, LintLevel::Deny);
+    ///
+    /// assert!(lib.run(String::from("var x = 1; { var x = 2; print x; }")).is_err());
+    /// assert_eq!(lib.diagnostics()[0].code, "shadowed-variable");
+    /// ```
+    pub fn set_lint_level(&mut self, lint: Lint, level: LintLevel) {
+        self.interpreter.set_lint_level(lint, level);
+    }
+
+    /// Turns the warnings of every lint that's reported into errors, keeping
+    /// scripts that have any from running.
+    pub fn set_deny_warnings(&mut self, deny: bool) {
+        self.interpreter.set_deny_warnings(deny);
+    }
+
+    /// Collects call counts and timings per function while running a file,
+    /// the report is printed to stderr once it finishes.
+    pub fn enable_profiler(&mut self) {
+        self.interpreter.start_profiling();
+    }
+
+    /// Counts the statements run while running a file and the modules it
+    /// imports, a per file line coverage report is printed to stderr once it
+    /// finishes. With `listing` it's followed by their annotated source.
+    pub fn enable_coverage(&mut self, listing: bool) {
+        self.interpreter.start_coverage(listing);
+    }
+
+    /// Records the results of the clock, random numbers, `input`, `readLine`
+    /// and `Env.get` while running a file, the journal is written to `path`
+    /// once it finishes, even when it fails. See [`Lib::replay_journal`].
+    pub fn record_journal(&mut self, path: impl Into<PathBuf>) {
+        self.journal_path = Some(path.into());
+        self.interpreter.start_recording();
+    }
+
+    /// Runs with the results recorded in the journal at `path` by
+    /// [`Lib::record_journal`], reproducing the recorded run.
+    pub fn replay_journal(&mut self, path: &str) {
+        let journal = fs::read_to_string(path)
+            .map_err(|err| err.to_string())
+            .and_then(|source| source.parse());
+
+        match journal {
+            Ok(journal) => self.interpreter.replay(journal),
+            Err(err) => {
+                eprintln!("{} {}: {}", "Error:".bold().red(), path.yellow(), err);
+                process::exit(EXIT_IO_ERROR);
+            }
         }
+    }
+
+    /// Starts recording a [`Journal`] of the results of nondeterministic
+    /// natives, see [`Interpreter::start_recording`].
+    pub fn start_recording(&mut self) {
+        self.interpreter.start_recording();
+    }
+
+    /// Makes the runs from now on use the results recorded in `journal`, see
+    /// [`Interpreter::replay`].
+    pub fn replay(&mut self, journal: Journal) {
+        self.interpreter.replay(journal);
+    }
+
+    /// Stops recording or replaying and returns the journal, see
+    /// [`Interpreter::take_journal`].
+    pub fn take_journal(&mut self) -> Option<Journal> {
+        self.interpreter.take_journal()
+    }
 
-        self.interpreter.interpret(&statements);
+    /// Saves the globals declared by the scripts run so far, see
+    /// [`Interpreter::snapshot`].
+    #[cfg(feature = "serde")]
+    pub fn snapshot(&self) -> Snapshot {
+        self.interpreter.snapshot()
     }
 
-    pub fn error_message(line: usize, message: &str) {
-        Lib::report(line, "", message);
+    /// Declares the globals saved in `snapshot`, see
+    /// [`Interpreter::restore`].
+    #[cfg(feature = "serde")]
+    pub fn restore(&mut self, snapshot: &Snapshot) -> Result<(), TyphoonError> {
+        self.interpreter.restore(snapshot)
     }
 
-    pub fn error_token(token: &Token, message: &str) {
-        if token.token_type == TokenType::Eof {
-            Lib::report(token.line, "at end", message);
+    /// Registers a host function as a global, see [`Interpreter::register_native`].
+    ///
+    /// ```
+    /// use typhoon::{object::Object, Lib};
+    ///
+    /// let mut lib = Lib::new();
+    ///
+    /// lib.register_native("double", 1, |interpreter, args| match &args[0] {
+    ///     Object::Int(n) => Ok(Object::Int(n * 2)),
+    ///     _ => Err(interpreter.native_error("Expected a number")),
+    /// });
+    /// lib.run(String::from("print double(21);")).unwrap();
+    /// ```
+    pub fn register_native(
+        &mut self,
+        name: &str,
+        arity: usize,
+        function: impl Fn(&mut Interpreter, Vec<Object>) -> Result<Object, RuntimeError> + 'static,
+    ) {
+        self.interpreter.register_native(name, arity, function);
+    }
+
+    /// Registers a module of natives scripts can import, see
+    /// [`NativeModule`](object::NativeModule).
+    pub fn register_module(&mut self, module: impl object::NativeModule + 'static) {
+        self.interpreter.register_module(module);
+    }
+
+    /// Compiles and executes `source`, reporting diagnostics and runtime
+    /// errors as they're found. The returned [`TyphoonError`] works with `?`.
+    pub fn run(&mut self, source: String) -> Result<(), TyphoonError> {
+        let statements = self.prepare(&source, false)?;
+
+        self.interpreter.cover(&source, &statements);
+
+        let result = self.interpreter.interpret(&statements);
+
+        self.finish(result, Some(&source))
+    }
+
+    /// Runs a line typed in the REPL, if it's a lone expression statement its
+    /// value is returned so it can be shown without a `print`.
+    ///
+    /// ```
+    /// use typhoon::{object::Object, Lib};
+    ///
+    /// let mut lib = Lib::new();
+    ///
+    /// assert!(lib.run_line(String::from("var x = 20;")).unwrap().is_none());
+    /// assert!(matches!(
+    ///     lib.run_line(String::from("x + 1;")).unwrap(),
+    ///     Some(Object::Int(21))
+    /// ));
+    /// ```
+    pub fn run_line(&mut self, source: String) -> Result<Option<Object>, TyphoonError> {
+        let statements = self.prepare(&source, false)?;
+        let result = match statements.as_slice() {
+            [Stmt::Expression(expr, _)] => self.interpreter.interpret_expression(expr).map(Some),
+            _ => self.interpreter.interpret(&statements).map(|()| None),
+        };
+
+        self.finish(result, Some(&source))
+    }
+
+    /// Evaluates `source` and returns the value of its last statement if
+    /// that's an expression, `undefined` otherwise. The last expression
+    /// doesn't need a `;`, so a lone expression can be evaluated too, and a
+    /// `return` at the top level ends the script with its value.
+    ///
+    /// ```
+    /// use typhoon::{object::Object, Lib, TyphoonError};
+    ///
+    /// fn main() -> Result<(), TyphoonError> {
+    ///     let mut lib = Lib::new();
+    ///
+    ///     lib.eval("var limit = 100; fun over(n) { return n > limit; }")?;
+    ///
+    ///     assert!(matches!(lib.eval("over(120)")?, Object::Boolean(true)));
+    ///     assert!(matches!(lib.eval("limit * 2;")?, Object::Int(200)));
+    ///     assert!(matches!(lib.eval("var x = 1;")?, Object::Undefined));
+    ///     assert!(matches!(
+    ///         lib.eval("if (x > 0) return x + 1; x")?,
+    ///         Object::Int(2)
+    ///     ));
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn eval(&mut self, source: &str) -> Result<Object, TyphoonError> {
+        let statements = self.prepare(source, true)?;
+        let result = self
+            .interpreter
+            .interpret_last(&statements)
+            .map(|value| value.unwrap_or(Object::Undefined));
+
+        self.finish(result, Some(source))
+    }
+
+    /// Calls the script function held by `callback`, reporting a runtime
+    /// error like [`Lib::run`] does. The promises the call leaves are
+    /// settled before it returns.
+    ///
+    /// ```
+    /// use typhoon::{object::Object, Lib, TyphoonError};
+    ///
+    /// fn main() -> Result<(), TyphoonError> {
+    ///     let mut lib = Lib::new();
+    ///     let on_click = lib
+    ///         .eval("var clicks = 0; fun onClick(x, y) { clicks = clicks + 1; return x + y; } onClick")?
+    ///         .into_callback()
+    ///         .unwrap();
+    ///
+    ///     lib.call(&on_click, vec![Object::Int(3), Object::Int(4)])?;
+    ///
+    ///     let sum = lib.call(&on_click, vec![Object::Int(1), Object::Int(2)])?;
+    ///
+    ///     assert!(matches!(sum, Object::Int(3)));
+    ///     assert!(matches!(lib.eval("clicks")?, Object::Int(2)));
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn call(
+        &mut self,
+        callback: &object::Callback,
+        arguments: Vec<Object>,
+    ) -> Result<Object, TyphoonError> {
+        let result = self
+            .interpreter
+            .interpret_call(callback.callable(), arguments);
+
+        self.finish(result, None)
+    }
+
+    /// Compiles `source` and reports its diagnostics, along with the warnings
+    /// of the modules it imported. With `trailing_expression` the last
+    /// expression statement may leave out its `;`.
+    fn prepare(
+        &mut self,
+        source: &str,
+        trailing_expression: bool,
+    ) -> Result<Vec<Stmt>, TyphoonError> {
+        let mut diagnostics = vec![];
+        let statements = Lib::parse(String::from(source), trailing_expression, &mut diagnostics)
+            .and_then(|statements| {
+                Lib::resolve(&mut self.interpreter, statements, &mut diagnostics)
+            });
+
+        diagnostics
+            .iter()
+            .for_each(|diagnostic| self.emit(diagnostic, Some(source)));
+
+        let mut module_diagnostics = self.interpreter.take_diagnostics();
+
+        module_diagnostics
+            .iter()
+            .for_each(|diagnostic| self.emit(diagnostic, None));
+        diagnostics.append(&mut module_diagnostics);
+        self.diagnostics = diagnostics.clone();
+
+        statements.ok_or(TyphoonError::Syntax(diagnostics))
+    }
+
+    /// Reports the diagnostics and runtime error left over by execution.
+    fn finish<T>(
+        &mut self,
+        result: Result<T, RuntimeError>,
+        source: Option<&str>,
+    ) -> Result<T, TyphoonError> {
+        for diagnostic in self.interpreter.take_diagnostics() {
+            self.emit(&diagnostic, None);
+            self.diagnostics.push(diagnostic);
+        }
+
+        result.map_err(|runtime_error| {
+            let backtrace = self.interpreter.take_backtrace();
+            let exceeded_limit = self.interpreter.take_exceeded_limit();
+            let mut diagnostic = Diagnostic::from(&runtime_error);
+
+            if exceeded_limit.is_some() {
+                diagnostic.code = "limit-exceeded";
+            }
+
+            match self.error_format {
+                ErrorFormat::Human => Lib::runtime_error(&runtime_error, &backtrace, source),
+                ErrorFormat::Json => self.emit(&diagnostic, source),
+            }
+
+            self.diagnostics.push(diagnostic);
+
+            match exceeded_limit {
+                Some(limit) => TyphoonError::LimitExceeded(limit, runtime_error),
+                None => TyphoonError::Runtime(runtime_error),
+            }
+        })
+    }
+
+    /// Reports `diagnostic` in the chosen [`ErrorFormat`].
+    fn emit(&self, diagnostic: &Diagnostic, source: Option<&str>) {
+        match self.error_format {
+            ErrorFormat::Human => Lib::report(diagnostic, source),
+            ErrorFormat::Json => println!("{}", diagnostic.to_json()),
+        }
+    }
+
+    /// Runs the scanner, parser and resolver over `source`, collecting every
+    /// diagnostic into `diagnostics`. Returns `None` if any of them is an error.
+    pub(crate) fn compile(
+        interpreter: &mut Interpreter,
+        source: String,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) -> Option<Vec<Stmt>> {
+        let statements = Lib::parse(source, false, diagnostics)?;
+
+        Lib::resolve(interpreter, statements, diagnostics)
+    }
+
+    fn parse(
+        source: String,
+        trailing_expression: bool,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) -> Option<Vec<Stmt>> {
+        let tokens = Scanner::new(source)
+            .scan_tokens()
+            .map_err(|errors| diagnostics.extend(errors))
+            .ok()?;
+        let mut parser = Parser::new(tokens);
+
+        if trailing_expression {
+            parser = parser.allow_trailing_expression();
+        }
+
+        parser
+            .parse()
+            .map_err(|errors| diagnostics.extend(errors))
+            .ok()
+    }
+
+    fn resolve(
+        interpreter: &mut Interpreter,
+        statements: Vec<Stmt>,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) -> Option<Vec<Stmt>> {
+        let resolved = Resolver::new()
+            .resolve(&statements)
+            .into_iter()
+            .filter_map(|diagnostic| {
+                let level = diagnostic.lint.map(|lint| interpreter.lint_level(lint));
+
+                Lib::leveled(diagnostic, level)
+            })
+            .collect::<Vec<_>>();
+        let had_error = resolved.iter().any(Diagnostic::is_error);
+
+        diagnostics.extend(resolved);
+
+        if had_error {
+            None
+        } else if interpreter.folds_constants() {
+            Some(Optimizer::optimize(&statements))
         } else {
-            let wheres = format!("at '{}'", token.lexeme);
-            Lib::report(token.line, &wheres, message);
+            Some(statements)
+        }
+    }
+
+    /// `diagnostic` as its lint's `level` has it reported, `None` when the
+    /// lint is allowed.
+    fn leveled(mut diagnostic: Diagnostic, level: Option<LintLevel>) -> Option<Diagnostic> {
+        match level {
+            Some(LintLevel::Allow) => return None,
+            Some(LintLevel::Deny) => diagnostic.severity = Severity::Error,
+            Some(LintLevel::Warn) | None => {}
         }
+
+        Some(diagnostic)
     }
 
-    pub fn runtime_error(runtime_error: &RuntimeError) {
+    /// Prints `runtime_error` followed by the calls that led to it, innermost
+    /// first. The failing source line is shown if the error is located in
+    /// `source`, errors raised inside imported modules are not.
+    pub fn runtime_error(
+        runtime_error: &RuntimeError,
+        backtrace: &[CallFrame],
+        source: Option<&str>,
+    ) {
+        let token = &runtime_error.token;
+
         println!(
-            "[{}] {}",
-            runtime_error.token.line.to_string().bold().blue(),
+            "{} {}",
+            Lib::location(token.line, Some(token.span)),
             runtime_error.message.bright_red()
         );
 
-        unsafe {
-            HAD_RUNTIME_ERROR = true;
+        if let Some(source) = source {
+            if source.get(token.span.range()) == Some(&*token.lexeme) {
+                Lib::print_snippet(source, token.line, token.span);
+            }
+        }
+
+        for (frame, count) in CallFrame::collapse(backtrace) {
+            println!(
+                "    in {} called from {}",
+                frame.function.yellow(),
+                format!("[{}]", frame.call_site.line).bold().blue()
+            );
+
+            if count > 1 {
+                println!("    ... repeated {} more times", count - 1);
+            }
         }
     }
 
-    fn report(line: usize, wheres: &str, message: &str) {
+    /// Prints `diagnostic`, underlining its span in `source` when both are
+    /// known.
+    pub fn report(diagnostic: &Diagnostic, source: Option<&str>) {
+        let severity = match diagnostic.severity {
+            Severity::Error => "Error:".bold().red(),
+            Severity::Warning => "Warning".truecolor(199, 79, 25).bold(),
+        };
+
+        let lint = match diagnostic.lint {
+            Some(lint) => format!(" [{}]", lint.name()).dimmed(),
+            None => "".normal(),
+        };
+
         println!(
-            "{} {} {}: {}",
-            format!("[{}]", line).bold().blue(),
-            "Error:".bold().red(),
-            wheres.yellow(),
-            message.bright_white()
+            "{} {} {}: {}{}",
+            Lib::location(diagnostic.line, diagnostic.span),
+            severity,
+            diagnostic.wheres.yellow(),
+            diagnostic.message.bright_white(),
+            lint
         );
 
-        unsafe {
-            HAD_ERROR = true;
+        if let (Some(source), Some(span)) = (source, diagnostic.span) {
+            Lib::print_snippet(source, diagnostic.line, span);
         }
     }
 
-    pub fn warn_token(token: &Token, message: &str) {
-        if token.token_type == TokenType::Eof {
-            Lib::report_warning(token.line, "at end", message);
-        } else {
-            let wheres = format!("at '{}'", token.lexeme);
-            Lib::report_warning(token.line, &wheres, message);
+    fn location(line: usize, span: Option<Span>) -> ColoredString {
+        match span {
+            Some(span) if span.column > 0 => format!("[{}:{}]", line, span.column),
+            _ => format!("[{line}]"),
         }
+        .bold()
+        .blue()
     }
 
-    fn report_warning(line: usize, wheres: &str, message: &str) {
+    /// Prints the source line containing `span` with the span underlined.
+    fn print_snippet(source: &str, line: usize, span: Span) {
+        let Range { start, end } = span.range();
+        let Some(before) = source.get(..start) else {
+            return;
+        };
+        let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+        let line_end = source[start..]
+            .find('\n')
+            .map_or(source.len(), |newline| start + newline);
+        let text = &source[line_start..line_end];
+        let padding: String = source[line_start..start]
+            .chars()
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let width = source[start..end.clamp(start, line_end)]
+            .chars()
+            .count()
+            .max(1);
+        let gutter = " ".repeat(line.to_string().len());
+
+        println!(
+            "{} {} {}",
+            line.to_string().bold().blue(),
+            "|".bold().blue(),
+            text
+        );
         println!(
-            "{} {} {}: {}",
-            format!("[{}]", line).bold().blue(),
-            "Warning".truecolor(199, 79, 25).bold(),
-            wheres.yellow(),
-            message.bright_white()
+            "{} {} {}{}",
+            gutter,
+            "|".bold().blue(),
+            padding,
+            "^".repeat(width).bold().red()
         );
     }
 }
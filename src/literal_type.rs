@@ -1,5 +1,7 @@
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum LiteralType {
     String(String),
+    Int(i64),
     Number(f64),
 }
@@ -1,27 +1,374 @@
-use clap::{Arg, Command};
-use typhoon::Lib;
+use std::{process, thread};
+
+use clap::{builder::PossibleValuesParser, Arg, ArgAction, ArgMatches, Command};
+use typhoon::{
+    errors::{ErrorFormat, Lint, LintLevel},
+    interpreter::Limits,
+    Lib, EXIT_SYNTAX_ERROR,
+};
+
+/// Scripts run on a thread with this much stack, so they can recurse much
+/// deeper than the main thread would let them.
+const STACK_SIZE: usize = 256 << 20;
 
 fn main() {
-    let matches = Command::new("MyApp")
+    let runner = thread::Builder::new()
+        .name(String::from("main"))
+        .stack_size(STACK_SIZE)
+        .spawn(run)
+        .expect("failed to start the interpreter thread");
+
+    if runner.join().is_err() {
+        process::exit(101);
+    }
+}
+
+fn run() {
+    let command = Command::new("typhoon")
+        .args_conflicts_with_subcommands(true)
         .arg(
-            Arg::new("filenames")
-                .help("List of filenames (optional)")
-                .num_args(0..)
+            Arg::new("error-format")
+                .long("error-format")
+                .help("How to report errors and warnings, json prints one object per line")
+                .value_parser(["human", "json"])
+                .default_value("human")
+                .global(true),
+        )
+        .subcommand(
+            Command::new("fmt")
+                .about("Rewrite scripts in the canonical format")
+                .arg(
+                    Arg::new("files")
+                        .help("Scripts to format")
+                        .required(true)
+                        .num_args(1..),
+                )
+                .arg(
+                    Arg::new("check")
+                        .long("check")
+                        .help("Only report scripts that need formatting, failing if any does")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("lint")
+                .about("Report likely mistakes in scripts without running them")
+                .arg(
+                    Arg::new("files")
+                        .help("Scripts to lint")
+                        .required(true)
+                        .num_args(1..),
+                )
+                .args(lint_level_args()),
+        )
+        .subcommand(
+            Command::new("check")
+                .about("Report the errors and warnings of scripts without running them")
+                .arg(
+                    Arg::new("files")
+                        .help("Scripts to check, - reads one from stdin")
+                        .required(true)
+                        .num_args(1..),
+                )
+                .args(lint_level_args()),
+        )
+        .subcommand(
+            Command::new("watch")
+                .about("Run a script again whenever it or a module it imports changes")
+                .arg(
+                    Arg::new("script")
+                        .help("Script to run, its globals are kept across runs")
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            Command::new("test")
+                .about("Run every *_test.ty script, failing if any raises an uncaught error")
+                .arg(
+                    Arg::new("paths")
+                        .help("Scripts or directories to search, the current directory by default")
+                        .num_args(0..),
+                ),
+        )
+        .arg(
+            Arg::new("script")
+                .help("Script to run, - reads it from stdin, starts the REPL when omitted")
                 .required(false),
         )
-        .get_matches();
-
-    let filenames: Vec<_> = matches
-        .get_many::<String>("filenames")
-        .unwrap_or_default()
-        .map(|s| s.to_string())
-        .collect();
+        .arg(
+            Arg::new("eval")
+                .short('e')
+                .long("eval")
+                .value_name("SOURCE")
+                .help("Run SOURCE and print the value of its last expression")
+                .conflicts_with_all(["script", "tokens", "ast", "ast-dot"]),
+        )
+        .arg(
+            Arg::new("args")
+                .help("Arguments passed to the script in ARGS")
+                .num_args(0..)
+                .trailing_var_arg(true)
+                .allow_hyphen_values(true)
+                .requires("script"),
+        )
+        .arg(
+            Arg::new("tokens")
+                .long("tokens")
+                .help("Print the scanned tokens instead of running the script")
+                .action(ArgAction::SetTrue)
+                .requires("script"),
+        )
+        .arg(
+            Arg::new("ast")
+                .long("ast")
+                .help("Print the parsed syntax tree instead of running the script")
+                .action(ArgAction::SetTrue)
+                .requires("script")
+                .conflicts_with("tokens"),
+        )
+        .arg(
+            Arg::new("ast-dot")
+                .long("ast-dot")
+                .help("Print the parsed syntax tree as a Graphviz DOT graph instead of running the script")
+                .action(ArgAction::SetTrue)
+                .requires("script")
+                .conflicts_with_all(["tokens", "ast"]),
+        )
+        .arg(
+            Arg::new("trace")
+                .long("trace")
+                .help("Log every executed statement and evaluated expression to stderr")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["tokens", "ast", "ast-dot"]),
+        )
+        .arg(
+            Arg::new("no-fold")
+                .long("no-fold")
+                .help("Run the script as written, without folding constant expressions")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["tokens", "ast", "ast-dot"]),
+        )
+        .arg(
+            Arg::new("ieee-division")
+                .long("ieee-division")
+                .help("Make division by zero give Infinity or NaN instead of an error")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["tokens", "ast", "ast-dot"]),
+        )
+        .args(lint_level_args())
+        .arg(
+            Arg::new("profile")
+                .long("profile")
+                .help("Print call counts and time spent per function once the script finishes")
+                .action(ArgAction::SetTrue)
+                .requires("script")
+                .conflicts_with_all(["tokens", "ast", "ast-dot"]),
+        )
+        .arg(
+            Arg::new("coverage")
+                .long("coverage")
+                .help("Print the lines of the script and its modules that ran once it finishes")
+                .action(ArgAction::SetTrue)
+                .requires("script")
+                .conflicts_with_all(["tokens", "ast", "ast-dot"]),
+        )
+        .arg(
+            Arg::new("coverage-listing")
+                .long("coverage-listing")
+                .help("Follow the coverage report with the source annotated with run counts")
+                .action(ArgAction::SetTrue)
+                .requires("coverage"),
+        )
+        .arg(
+            Arg::new("record")
+                .long("record")
+                .value_name("JOURNAL")
+                .help("Record the clock, random numbers, input and environment the script sees to JOURNAL")
+                .requires("script")
+                .conflicts_with_all(["tokens", "ast", "ast-dot"]),
+        )
+        .arg(
+            Arg::new("replay")
+                .long("replay")
+                .value_name("JOURNAL")
+                .help("Run the script with the results recorded in JOURNAL by --record")
+                .requires("script")
+                .conflicts_with_all(["record", "tokens", "ast", "ast-dot"]),
+        );
+    #[cfg(feature = "serde")]
+    let command = command.arg(
+        Arg::new("ast-json")
+            .long("ast-json")
+            .help("Print the parsed syntax tree as JSON instead of running the script")
+            .action(ArgAction::SetTrue)
+            .requires("script")
+            .conflicts_with_all(["tokens", "ast", "ast-dot", "trace", "profile", "coverage"]),
+    );
+    let matches = command.get_matches();
 
     let mut compiler = Lib::new();
 
-    if filenames.is_empty() {
-        compiler.run_prompt();
-    } else {
-        println!("Filenames: {:?}", filenames);
+    compiler.set_limits(Limits {
+        max_call_depth: Some(Limits::call_depth_for_stack(STACK_SIZE)),
+        ..Limits::default()
+    });
+
+    if matches.get_one::<String>("error-format").unwrap() == "json" {
+        compiler.set_error_format(ErrorFormat::Json);
+    }
+
+    match matches.subcommand() {
+        Some(("fmt", fmt)) => {
+            let check = fmt.get_flag("check");
+            let mut formatted = true;
+
+            for file in fmt.get_many::<String>("files").unwrap() {
+                formatted &= compiler.format_file(file, check);
+            }
+
+            if check && !formatted {
+                process::exit(1);
+            }
+
+            return;
+        }
+        Some(("lint", lint)) => {
+            let mut clean = true;
+
+            set_lint_levels(&mut compiler, lint);
+
+            for file in lint.get_many::<String>("files").unwrap() {
+                clean &= compiler.lint_file(file);
+            }
+
+            if !clean {
+                process::exit(1);
+            }
+
+            return;
+        }
+        Some(("check", check)) => {
+            let mut valid = true;
+
+            set_lint_levels(&mut compiler, check);
+
+            for file in check.get_many::<String>("files").unwrap() {
+                valid &= compiler.check_file(file);
+            }
+
+            if !valid {
+                process::exit(EXIT_SYNTAX_ERROR);
+            }
+
+            return;
+        }
+        Some(("watch", watch)) => {
+            compiler.watch_file(watch.get_one::<String>("script").unwrap());
+        }
+        Some(("test", test)) => {
+            let paths = test
+                .get_many::<String>("paths")
+                .map(|paths| paths.map(String::as_str).collect::<Vec<_>>())
+                .unwrap_or_else(|| vec!["."]);
+
+            if !compiler.run_tests(&paths) {
+                process::exit(1);
+            }
+
+            return;
+        }
+        _ => {}
+    }
+
+    compiler.set_trace(matches.get_flag("trace"));
+    compiler.set_constant_folding(!matches.get_flag("no-fold"));
+    compiler.set_ieee_division(matches.get_flag("ieee-division"));
+    set_lint_levels(&mut compiler, &matches);
+    compiler.set_args(
+        matches
+            .get_many::<String>("args")
+            .unwrap_or_default()
+            .cloned()
+            .collect(),
+    );
+
+    if matches.get_flag("profile") {
+        compiler.enable_profiler();
+    }
+
+    if matches.get_flag("coverage") {
+        compiler.enable_coverage(matches.get_flag("coverage-listing"));
+    }
+
+    if let Some(journal) = matches.get_one::<String>("record") {
+        compiler.record_journal(journal);
+    }
+
+    if let Some(journal) = matches.get_one::<String>("replay") {
+        compiler.replay_journal(journal);
+    }
+
+    if let Some(source) = matches.get_one::<String>("eval") {
+        compiler.run_eval(source);
+        return;
+    }
+
+    match matches.get_one::<String>("script") {
+        Some(script) if matches.get_flag("tokens") => compiler.print_tokens(script),
+        Some(script) if matches.get_flag("ast") => compiler.print_ast(script),
+        Some(script) if matches.get_flag("ast-dot") => compiler.print_ast_dot(script),
+        #[cfg(feature = "serde")]
+        Some(script) if matches.get_flag("ast-json") => compiler.print_ast_json(script),
+        Some(script) => compiler.run_file(script),
+        None => compiler.run_prompt(),
+    }
+}
+
+/// The options setting what's done with the warnings of lints, shared by
+/// running a script, `check` and `lint`.
+fn lint_level_args() -> [Arg; 4] {
+    let lint = |name: &'static str, short, help: &'static str| {
+        Arg::new(name)
+            .long(name)
+            .short(short)
+            .value_name("LINT")
+            .help(help)
+            .value_parser(PossibleValuesParser::new(Lint::ALL.map(|lint| lint.name())))
+            .action(ArgAction::Append)
+    };
+
+    [
+        lint("allow", 'A', "Don't check a lint, can be repeated"),
+        lint(
+            "warn",
+            'W',
+            "Report the warnings of a lint, can be repeated",
+        ),
+        lint(
+            "deny",
+            'D',
+            "Report the warnings of a lint as errors, can be repeated",
+        ),
+        Arg::new("deny-warnings")
+            .long("deny-warnings")
+            .help("Report every warning as an error, failing on any")
+            .action(ArgAction::SetTrue),
+    ]
+}
+
+/// Sets the lint levels given with the options of [`lint_level_args`].
+fn set_lint_levels(compiler: &mut Lib, matches: &ArgMatches) {
+    compiler.set_deny_warnings(matches.get_flag("deny-warnings"));
+
+    for (flag, level) in [
+        ("allow", LintLevel::Allow),
+        ("warn", LintLevel::Warn),
+        ("deny", LintLevel::Deny),
+    ] {
+        for name in matches.get_many::<String>(flag).unwrap_or_default() {
+            if let Some(lint) = Lint::from_name(name) {
+                compiler.set_lint_level(lint, level);
+            }
+        }
     }
 }
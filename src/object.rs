@@ -1,28 +1,53 @@
 mod callable;
 mod callable_instance;
+mod callback;
 mod class;
 mod class_instance;
+mod convert;
 mod definition;
 mod function;
 mod instance;
+mod iter;
+mod namespace;
+mod native_function;
+mod native_module;
+mod plain;
 mod resolvable_function;
+#[cfg(feature = "serde")]
+mod serialize;
+mod user_data;
 
-use std::rc::Rc;
+use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
 
 pub use callable::Callable;
 pub use callable_instance::CallableInstance;
+pub use callback::Callback;
 pub use class::Class;
+pub use convert::{FromObject, IntoObject};
 pub use function::Function;
-pub use instance::Instance;
+pub use instance::{Instance, Mutability};
+pub use iter::ObjectIter;
+pub use namespace::Namespace;
+pub use native_function::{NativeFn, NativeFunction};
+pub use native_module::NativeModule;
+pub use plain::PlainValue;
 pub use resolvable_function::ResolvableFunction;
+pub use user_data::{UserData, UserDataType};
 
+/// Values are cloned on every evaluation step, so every variant is at most
+/// two words: strings are immutable and shared rather than copied.
 #[derive(Clone)]
 pub enum Object {
     Undefined,
     Boolean(bool),
+    Int(i64),
     Number(f64),
-    String(String),
+    String(Rc<str>),
+    Array(Rc<RefCell<Vec<Object>>>),
+    Map(Rc<RefCell<BTreeMap<String, Object>>>),
     Callable(Rc<dyn Callable>),
     Instance(Rc<dyn Instance>),
     CallableInstance(Rc<dyn CallableInstance>),
 }
+
+const _: () = assert!(std::mem::size_of::<Object>() == 8 + std::mem::size_of::<Rc<str>>());
@@ -1,17 +1,37 @@
-use crate::{errors::RuntimeError, interpreter::Interpreter};
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{environment::Environment, errors::VMException, interpreter::Interpreter};
 
 use super::Object;
 
 pub trait Callable {
     fn arity(&self) -> usize;
 
+    /// The most arguments the callable accepts, `None` if it's variadic.
+    fn max_arity(&self) -> Option<usize> {
+        Some(self.arity())
+    }
+
     fn call(
         &self,
         interpreter: &mut Interpreter,
         arguments: Vec<Object>,
-    ) -> Result<Object, RuntimeError>;
+    ) -> Result<Object, VMException>;
+
+    fn name(&self) -> String;
 
     fn to_string(&self) -> String;
 
-    fn bind(&self, _: Object) -> Object;
+    /// Binds `this` to `instance`, `None` for callables that aren't methods
+    /// and don't use `this`.
+    fn bind(&self, _instance: Object) -> Option<Rc<dyn Callable>> {
+        None
+    }
+
+    /// The source of a declaration giving the callable to the variable
+    /// `name` of `globals`. `None` for natives, and for the functions and
+    /// classes that use variables only where they were declared can see.
+    fn source(&self, _name: &str, _globals: &Rc<RefCell<Environment>>) -> Option<String> {
+        None
+    }
 }
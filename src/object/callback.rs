@@ -0,0 +1,67 @@
+use std::rc::Rc;
+
+use crate::{errors::TyphoonError, interpreter::Interpreter};
+
+use super::{Callable, Object};
+
+/// A script function or class held by the host to be called later, e.g. as
+/// the handler of an event. [`Object::into_callback`] makes one out of a
+/// value scripts returned or passed to a native.
+///
+/// Every call is a run of its own, the promises it leaves are settled before
+/// it returns. Hosts owning a [`Lib`](crate::Lib) call it with
+/// [`Lib::call`](crate::Lib::call), which also reports its errors.
+///
+/// ```
+/// use typhoon::{interpreter::Interpreter, object::Object, TyphoonError};
+///
+/// let mut interpreter = Interpreter::new();
+/// let type_of = interpreter.global("type").unwrap().into_callback().unwrap();
+///
+/// let name = type_of.call(&mut interpreter, vec![Object::Int(42)]).unwrap();
+///
+/// assert!(matches!(name, Object::String(_)));
+/// assert!(matches!(
+///     type_of.call(&mut interpreter, vec![]),
+///     Err(TyphoonError::Runtime(_))
+/// ));
+/// ```
+#[derive(Clone)]
+pub struct Callback {
+    callable: Rc<dyn Callable>,
+}
+
+impl Callback {
+    /// Calls the function with `arguments` in `interpreter`, which should be
+    /// the one it was declared in.
+    pub fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        arguments: Vec<Object>,
+    ) -> Result<Object, TyphoonError> {
+        interpreter
+            .interpret_call(self.callable(), arguments)
+            .map_err(|runtime_error| match interpreter.take_exceeded_limit() {
+                Some(limit) => TyphoonError::LimitExceeded(limit, runtime_error),
+                None => TyphoonError::Runtime(runtime_error),
+            })
+    }
+
+    pub(crate) fn callable(&self) -> Rc<dyn Callable> {
+        Rc::clone(&self.callable)
+    }
+}
+
+impl Object {
+    /// Turns a function, a native or a class into a [`Callback`] the host can
+    /// call, other values give `None`.
+    pub fn into_callback(self) -> Option<Callback> {
+        let callable = match self {
+            Object::Callable(callable) => callable,
+            Object::CallableInstance(class) => class,
+            _ => return None,
+        };
+
+        Some(Callback { callable })
+    }
+}
@@ -4,13 +4,28 @@ use super::{
     callable_instance::CallableInstance, class_instance::ClassInstance, Callable, Instance, Object,
 };
 
-use crate::{errors::RuntimeError, interpreter::Interpreter, token::Token};
+use crate::{
+    environment::Environment,
+    errors::{RuntimeError, VMException},
+    formatter,
+    interpreter::Interpreter,
+    stmt::{self, Stmt},
+    symbol::Symbol,
+    token::Token,
+};
+
+/// The declaration of a class declared at the top level of a script or
+/// module, and the globals it was declared in.
+pub type TopLevel = (Rc<stmt::Class>, Rc<RefCell<Environment>>);
 
 pub struct ClassInternal {
     pub name: String,
     super_class: Option<Rc<dyn CallableInstance>>,
-    methods: HashMap<String, Object>,
-    statics: RefCell<HashMap<String, Object>>,
+    methods: HashMap<Symbol, Object>,
+    statics: RefCell<HashMap<Symbol, Object>>,
+    /// Where the class can be declared again from, `None` for classes
+    /// declared inside a function or block.
+    top_level: Option<TopLevel>,
 }
 
 #[derive(Clone)]
@@ -22,8 +37,9 @@ impl Class {
     pub fn new(
         name: &str,
         super_class: Option<Rc<dyn CallableInstance>>,
-        statics: HashMap<String, Object>,
-        methods: HashMap<String, Object>,
+        statics: HashMap<Symbol, Object>,
+        methods: HashMap<Symbol, Object>,
+        top_level: Option<TopLevel>,
     ) -> Self {
         Self {
             internal: Rc::new(ClassInternal {
@@ -31,10 +47,72 @@ impl Class {
                 super_class,
                 methods,
                 statics: RefCell::new(statics),
+                top_level,
             }),
         }
     }
 
+    /// Whether this class is `other` or inherits from it.
+    pub fn is_subclass_of(&self, other: &Class) -> bool {
+        if Rc::ptr_eq(&self.internal, &other.internal) {
+            return true;
+        }
+
+        match &self.internal.super_class {
+            Some(super_class) => super_class
+                .as_any()
+                .downcast_ref::<Class>()
+                .is_some_and(|class| class.is_subclass_of(other)),
+            None => false,
+        }
+    }
+
+    /// The names of the methods of this class and the ones it inherits.
+    pub fn method_names(&self) -> Vec<String> {
+        let mut names = match &self.internal.super_class {
+            Some(super_class) => super_class
+                .as_any()
+                .downcast_ref::<Class>()
+                .map(Class::method_names)
+                .unwrap_or_default(),
+            None => vec![],
+        };
+
+        names.extend(self.internal.methods.keys().map(|name| name.to_string()));
+        names
+    }
+
+    /// Looks up a static field or method of this class or the ones it
+    /// inherits from.
+    pub fn find_static(&self, name: &str) -> Option<Object> {
+        if let Some(member) = self.internal.statics.borrow().get(name) {
+            return Some(member.clone());
+        }
+
+        self.super_class()?.find_static(name)
+    }
+
+    /// The static fields of this class itself and their values, the static
+    /// members that aren't methods.
+    pub fn static_fields(&self) -> Vec<(String, Object)> {
+        self.internal
+            .statics
+            .borrow()
+            .iter()
+            .filter(|(_, value)| !matches!(value, Object::Callable(_)))
+            .map(|(name, value)| (name.to_string(), value.clone()))
+            .collect()
+    }
+
+    /// The class this one inherits from, if it's a class of the script.
+    pub fn super_class(&self) -> Option<&Class> {
+        self.internal
+            .super_class
+            .as_ref()?
+            .as_any()
+            .downcast_ref::<Class>()
+    }
+
     pub fn find_method(&self, name: &str) -> Option<Object> {
         if let Some(method) = self.internal.methods.get(name) {
             return Some(method.clone());
@@ -59,38 +137,58 @@ impl Callable for Class {
         }
     }
 
+    fn max_arity(&self) -> Option<usize> {
+        if let Some(Object::Callable(callable)) = self.find_method("init") {
+            callable.max_arity()
+        } else {
+            Some(0)
+        }
+    }
+
     fn call(
         &self,
         interpreter: &mut Interpreter,
         arguments: Vec<Object>,
-    ) -> Result<Object, RuntimeError> {
+    ) -> Result<Object, VMException> {
         let class_instance = ClassInstance::new(self.clone());
         let instance = Object::Instance(Rc::new(class_instance));
 
         if let Some(Object::Callable(callable)) = self.find_method("init") {
-            let bound_callable = callable.bind(instance.clone());
-
-            if let Object::Callable(bound_callable) = bound_callable {
-                bound_callable.call(interpreter, arguments)?;
+            if let Some(initializer) = callable.bind(instance.clone()) {
+                initializer.call(interpreter, arguments)?;
             }
         }
 
         Ok(instance)
     }
 
+    fn name(&self) -> String {
+        String::clone(&self.internal.name)
+    }
+
     fn to_string(&self) -> String {
         format!("[Class: ({})]", self.internal.name)
     }
 
-    fn bind(&self, _: Object) -> Object {
-        unreachable!()
+    fn source(&self, name: &str, globals: &Rc<RefCell<Environment>>) -> Option<String> {
+        let (declaration, declared_in) = self.internal.top_level.as_ref()?;
+
+        if !Rc::ptr_eq(declared_in, globals) {
+            return None;
+        }
+
+        let mut declaration = stmt::Class::clone(declaration);
+
+        declaration.name.lexeme = Rc::from(name);
+
+        Some(formatter::format_stmt(&Stmt::Class(Rc::new(declaration))))
     }
 }
 
 impl Instance for Class {
     fn get(&self, _: Object, name: &Token) -> Result<Object, RuntimeError> {
-        if let Some(field) = self.internal.statics.borrow().get(&name.lexeme) {
-            return Ok(field.clone());
+        if let Some(member) = self.find_static(&name.lexeme) {
+            return Ok(member);
         }
 
         Err(RuntimeError {
@@ -103,11 +201,28 @@ impl Instance for Class {
         self.internal
             .statics
             .borrow_mut()
-            .insert(String::clone(&name.lexeme), value);
+            .insert(Rc::clone(&name.lexeme), value);
 
         Ok(())
     }
 
+    fn property_names(&self) -> Vec<String> {
+        let mut names = self
+            .super_class()
+            .map(Instance::property_names)
+            .unwrap_or_default();
+
+        names.extend(
+            self.internal
+                .statics
+                .borrow()
+                .keys()
+                .map(|name| name.to_string())
+                .filter(|name| !name.starts_with('_')),
+        );
+        names
+    }
+
     fn to_string(&self) -> String {
         format!("[Class Instance: ({})]", self.internal.name)
     }
@@ -1,12 +1,21 @@
-use std::{cell::RefCell, collections::HashMap};
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    rc::Rc,
+};
 
-use crate::{errors::RuntimeError, token::Token};
+use crate::{errors::RuntimeError, symbol::Symbol, token::Token};
 
-use super::{class::Class, instance::Instance, Object};
+use super::{
+    class::Class,
+    instance::{Instance, Mutability},
+    Object,
+};
 
 pub struct ClassInstance {
     class: Class,
-    fields: RefCell<HashMap<String, Object>>,
+    fields: RefCell<HashMap<Symbol, Object>>,
+    mutability: Cell<Mutability>,
 }
 
 impl ClassInstance {
@@ -14,6 +23,7 @@ impl ClassInstance {
         Self {
             class,
             fields: RefCell::new(HashMap::new()),
+            mutability: Cell::new(Mutability::Open),
         }
     }
 }
@@ -24,10 +34,8 @@ impl Instance for ClassInstance {
             return Ok(field.clone());
         }
 
-        if let Some(method) = self.class.find_method(&name.lexeme) {
-            if let Object::Callable(callable) = method {
-                return Ok(callable.bind(this));
-            };
+        if let Some(Object::Callable(callable)) = self.class.find_method(&name.lexeme) {
+            return Ok(Object::Callable(callable.bind(this).unwrap_or(callable)));
         }
 
         Err(RuntimeError {
@@ -36,14 +44,95 @@ impl Instance for ClassInstance {
         })
     }
 
+    fn get_private(
+        &self,
+        this: Object,
+        name: &Token,
+        class: &Class,
+    ) -> Result<Object, RuntimeError> {
+        if let Some(field) = self.fields.borrow().get(&name.lexeme) {
+            return Ok(field.clone());
+        }
+
+        // Like fields, private methods are reachable from subclasses, the
+        // one the accessing class sees first wins.
+        if let Some(Object::Callable(callable)) = class.find_method(&name.lexeme) {
+            return Ok(Object::Callable(callable.bind(this).unwrap_or(callable)));
+        }
+
+        let message = match self.class.find_method(&name.lexeme) {
+            Some(_) => format!(
+                "Private method '{}' is not accessible from class '{}'",
+                name.lexeme, class.internal.name
+            ),
+            None => format!("Undefined property '{}'", name.lexeme),
+        };
+
+        Err(RuntimeError {
+            token: name.clone(),
+            message,
+        })
+    }
+
     fn set(&self, name: &Token, value: Object) -> Result<(), RuntimeError> {
+        let message = match self.mutability.get() {
+            Mutability::Open => None,
+            Mutability::Sealed if self.fields.borrow().contains_key(&name.lexeme) => None,
+            Mutability::Sealed => Some(format!(
+                "Can't add field '{}' to a sealed instance",
+                name.lexeme
+            )),
+            Mutability::Frozen => Some(format!(
+                "Can't assign field '{}' of a frozen instance",
+                name.lexeme
+            )),
+        };
+
+        if let Some(message) = message {
+            return Err(RuntimeError {
+                token: name.clone(),
+                message,
+            });
+        }
+
         self.fields
             .borrow_mut()
-            .insert(String::clone(&name.lexeme), value);
+            .insert(Rc::clone(&name.lexeme), value);
 
         Ok(())
     }
 
+    fn restrict(&self, mutability: Mutability) -> bool {
+        if mutability > self.mutability.get() {
+            self.mutability.set(mutability);
+        }
+
+        true
+    }
+
+    fn class(&self) -> Option<Class> {
+        Some(self.class.clone())
+    }
+
+    fn property_names(&self) -> Vec<String> {
+        self.fields
+            .borrow()
+            .keys()
+            .map(|name| name.to_string())
+            .chain(self.class.method_names())
+            .filter(|name| !name.starts_with('_') && name != "init")
+            .collect()
+    }
+
+    fn field_names(&self) -> Vec<String> {
+        self.fields
+            .borrow()
+            .keys()
+            .map(|name| name.to_string())
+            .filter(|name| !name.starts_with('_'))
+            .collect()
+    }
+
     fn to_string(&self) -> String {
         format!("[Class Instance: ({})]", self.class.internal.name)
     }
@@ -0,0 +1,318 @@
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+    rc::Rc,
+};
+
+use crate::errors::ConversionError;
+
+use super::Object;
+
+/// Rust values that can be handed to scripts as an [`Object`].
+///
+/// Structs implement it by building a map with [`Object::from_fields`]:
+///
+/// ```
+/// use typhoon::object::{FromObject, IntoObject, Object};
+///
+/// #[derive(Debug)]
+/// struct Point {
+///     x: i64,
+///     y: i64,
+///     label: Option<String>,
+/// }
+///
+/// impl IntoObject for Point {
+///     fn into_object(self) -> Object {
+///         Object::from_fields([
+///             ("x", self.x.into_object()),
+///             ("y", self.y.into_object()),
+///             ("label", self.label.into_object()),
+///         ])
+///     }
+/// }
+///
+/// impl FromObject for Point {
+///     fn from_object(object: &Object) -> Result<Self, typhoon::errors::ConversionError> {
+///         Ok(Point {
+///             x: object.field("x")?,
+///             y: object.field("y")?,
+///             label: object.field("label")?,
+///         })
+///     }
+/// }
+///
+/// let object = vec![Point { x: 1, y: 2, label: None }].into_object();
+///
+/// assert_eq!(object.to_string(), r#"[{"label": undefined, "x": 1, "y": 2}]"#);
+///
+/// let points = Vec::<Point>::from_object(&object).unwrap();
+/// assert_eq!((points[0].x, points[0].y), (1, 2));
+///
+/// let moved = Object::from_fields([("x", 1.5.into_object()), ("y", 2.into_object())]);
+/// let error = Vec::<Point>::from_object(&vec![moved].into_object()).unwrap_err();
+///
+/// assert_eq!(error.to_string(), "Expected int, found number at [0].x");
+/// ```
+pub trait IntoObject {
+    fn into_object(self) -> Object;
+}
+
+/// Rust values that can be read from an [`Object`] a script handed over,
+/// see [`IntoObject`] for implementing it for a struct.
+///
+/// Integers are checked to fit the Rust type, floats accept integers too,
+/// and `Option` reads `undefined` as `None`.
+pub trait FromObject: Sized {
+    fn from_object(object: &Object) -> Result<Self, ConversionError>;
+}
+
+impl Object {
+    /// A map holding `fields`, for [`IntoObject`] implementations.
+    pub fn from_fields<'a>(fields: impl IntoIterator<Item = (&'a str, Object)>) -> Object {
+        let fields = fields
+            .into_iter()
+            .map(|(name, value)| (String::from(name), value))
+            .collect();
+
+        Object::Map(Rc::new(RefCell::new(fields)))
+    }
+
+    /// Converts the field `name` of a map, for [`FromObject`]
+    /// implementations. A missing field is `undefined`, so it converts to
+    /// an `Option` but is an error for anything else.
+    pub fn field<T: FromObject>(&self, name: &str) -> Result<T, ConversionError> {
+        let Object::Map(map) = self else {
+            return Err(ConversionError::expected("map", self));
+        };
+        let value = map.borrow().get(name).cloned().unwrap_or(Object::Undefined);
+
+        T::from_object(&value).map_err(|err| err.within(&format!(".{name}")))
+    }
+}
+
+impl IntoObject for Object {
+    fn into_object(self) -> Object {
+        self
+    }
+}
+
+impl FromObject for Object {
+    fn from_object(object: &Object) -> Result<Self, ConversionError> {
+        Ok(object.clone())
+    }
+}
+
+impl IntoObject for () {
+    fn into_object(self) -> Object {
+        Object::Undefined
+    }
+}
+
+impl FromObject for () {
+    fn from_object(object: &Object) -> Result<Self, ConversionError> {
+        match object {
+            Object::Undefined => Ok(()),
+            other => Err(ConversionError::expected("undefined", other)),
+        }
+    }
+}
+
+impl IntoObject for bool {
+    fn into_object(self) -> Object {
+        Object::Boolean(self)
+    }
+}
+
+impl FromObject for bool {
+    fn from_object(object: &Object) -> Result<Self, ConversionError> {
+        match object {
+            Object::Boolean(boolean) => Ok(*boolean),
+            other => Err(ConversionError::expected("boolean", other)),
+        }
+    }
+}
+
+/// Reading an integer checks that it fits the Rust type.
+macro_rules! integers {
+    ($($integer:ty),*) => {
+        $(
+            impl FromObject for $integer {
+                fn from_object(object: &Object) -> Result<Self, ConversionError> {
+                    match object {
+                        Object::Int(int) => <$integer>::try_from(*int).map_err(|_| {
+                            ConversionError::new(format!(
+                                "Expected {}, found {int} which is out of range",
+                                stringify!($integer)
+                            ))
+                        }),
+                        other => Err(ConversionError::expected("int", other)),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+integers!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+/// Only the integer types whose every value an `i64` holds become an `Int`.
+macro_rules! lossless_integers {
+    ($($integer:ty),*) => {
+        $(
+            impl IntoObject for $integer {
+                fn into_object(self) -> Object {
+                    Object::Int(i64::from(self))
+                }
+            }
+        )*
+    };
+}
+
+lossless_integers!(i8, i16, i32, i64, u8, u16, u32);
+
+impl IntoObject for f64 {
+    fn into_object(self) -> Object {
+        Object::Number(self)
+    }
+}
+
+impl FromObject for f64 {
+    fn from_object(object: &Object) -> Result<Self, ConversionError> {
+        match object {
+            Object::Number(number) => Ok(*number),
+            Object::Int(int) => Ok(*int as f64),
+            other => Err(ConversionError::expected("number", other)),
+        }
+    }
+}
+
+impl IntoObject for f32 {
+    fn into_object(self) -> Object {
+        Object::Number(f64::from(self))
+    }
+}
+
+impl FromObject for f32 {
+    fn from_object(object: &Object) -> Result<Self, ConversionError> {
+        f64::from_object(object).map(|number| number as f32)
+    }
+}
+
+impl IntoObject for String {
+    fn into_object(self) -> Object {
+        Object::String(Rc::from(self))
+    }
+}
+
+impl IntoObject for &str {
+    fn into_object(self) -> Object {
+        Object::String(Rc::from(self))
+    }
+}
+
+impl IntoObject for Rc<str> {
+    fn into_object(self) -> Object {
+        Object::String(self)
+    }
+}
+
+impl FromObject for String {
+    fn from_object(object: &Object) -> Result<Self, ConversionError> {
+        Rc::<str>::from_object(object).map(|string| string.to_string())
+    }
+}
+
+impl FromObject for Rc<str> {
+    fn from_object(object: &Object) -> Result<Self, ConversionError> {
+        match object {
+            Object::String(string) => Ok(Rc::clone(string)),
+            other => Err(ConversionError::expected("string", other)),
+        }
+    }
+}
+
+impl<T: IntoObject> IntoObject for Option<T> {
+    fn into_object(self) -> Object {
+        self.map_or(Object::Undefined, T::into_object)
+    }
+}
+
+impl<T: FromObject> FromObject for Option<T> {
+    fn from_object(object: &Object) -> Result<Self, ConversionError> {
+        match object {
+            Object::Undefined => Ok(None),
+            other => T::from_object(other).map(Some),
+        }
+    }
+}
+
+impl<T: IntoObject> IntoObject for Vec<T> {
+    fn into_object(self) -> Object {
+        let elements = self.into_iter().map(T::into_object).collect();
+
+        Object::Array(Rc::new(RefCell::new(elements)))
+    }
+}
+
+impl<T: FromObject> FromObject for Vec<T> {
+    fn from_object(object: &Object) -> Result<Self, ConversionError> {
+        let Object::Array(array) = object else {
+            return Err(ConversionError::expected("array", object));
+        };
+
+        array
+            .borrow()
+            .iter()
+            .enumerate()
+            .map(|(index, element)| {
+                T::from_object(element).map_err(|err| err.within(&format!("[{index}]")))
+            })
+            .collect()
+    }
+}
+
+impl<T: IntoObject> IntoObject for HashMap<String, T> {
+    fn into_object(self) -> Object {
+        let entries = self
+            .into_iter()
+            .map(|(key, value)| (key, value.into_object()))
+            .collect();
+
+        Object::Map(Rc::new(RefCell::new(entries)))
+    }
+}
+
+impl<T: FromObject> FromObject for HashMap<String, T> {
+    fn from_object(object: &Object) -> Result<Self, ConversionError> {
+        BTreeMap::<String, T>::from_object(object).map(|entries| entries.into_iter().collect())
+    }
+}
+
+impl<T: IntoObject> IntoObject for BTreeMap<String, T> {
+    fn into_object(self) -> Object {
+        let entries = self
+            .into_iter()
+            .map(|(key, value)| (key, value.into_object()))
+            .collect();
+
+        Object::Map(Rc::new(RefCell::new(entries)))
+    }
+}
+
+impl<T: FromObject> FromObject for BTreeMap<String, T> {
+    fn from_object(object: &Object) -> Result<Self, ConversionError> {
+        let Object::Map(map) = object else {
+            return Err(ConversionError::expected("map", object));
+        };
+
+        map.borrow()
+            .iter()
+            .map(|(key, value)| {
+                let value = T::from_object(value).map_err(|err| err.within(&format!(".{key}")))?;
+
+                Ok((key.clone(), value))
+            })
+            .collect()
+    }
+}
@@ -1,16 +1,73 @@
 use std::{fmt, rc::Rc};
 
-use crate::{object::Callable, utils::bool_to_number};
+use crate::{
+    object::Callable,
+    utils::{bool_to_number, format_number},
+};
 
 use super::Object;
 
+impl Object {
+    /// The name of the value's type as returned by the `type` global.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Object::Undefined => "undefined",
+            Object::Boolean(_) => "boolean",
+            Object::Int(_) => "int",
+            Object::Number(_) => "number",
+            Object::String(_) => "string",
+            Object::Array(_) => "array",
+            Object::Map(_) => "map",
+            Object::Callable(_) => "function",
+            Object::Instance(_) => "instance",
+            Object::CallableInstance(_) => "class",
+        }
+    }
+
+    /// Equality without coercion, what `===` compares. Values of different
+    /// types are never equal, except integers and floats as both are numbers.
+    pub fn strict_eq(&self, other: &Object) -> bool {
+        match (self, other) {
+            (Object::Int(_) | Object::Number(_), Object::Int(_) | Object::Number(_)) => {
+                self == other
+            }
+            _ => self.type_name() == other.type_name() && self == other,
+        }
+    }
+}
+
 impl fmt::Display for Object {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Object::Undefined => write!(f, "{}", "undefined"),
-            Object::Number(n) => write!(f, "{}", n),
+            Object::Undefined => write!(f, "undefined"),
+            Object::Int(int) => write!(f, "{int}"),
+            Object::Number(n) => write!(f, "{}", format_number(*n)),
             Object::String(s) => write!(f, "{}", s),
             Object::Boolean(b) => write!(f, "{}", b),
+            Object::Array(array) => {
+                let elements = array
+                    .borrow()
+                    .iter()
+                    .map(|element| match element {
+                        Object::String(s) => format!("\"{s}\""),
+                        _ => element.to_string(),
+                    })
+                    .collect::<Vec<_>>();
+
+                write!(f, "[{}]", elements.join(", "))
+            }
+            Object::Map(map) => {
+                let entries = map
+                    .borrow()
+                    .iter()
+                    .map(|(key, value)| match value {
+                        Object::String(s) => format!("\"{key}\": \"{s}\""),
+                        _ => format!("\"{key}\": {value}"),
+                    })
+                    .collect::<Vec<_>>();
+
+                write!(f, "{{{}}}", entries.join(", "))
+            }
             Object::Callable(callee) => write!(f, "{}", callee.to_string()),
             Object::Instance(class_instance) => {
                 write!(f, "{}", class_instance.to_string())
@@ -26,11 +83,20 @@ impl PartialEq for Object {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
             (Object::Undefined, Object::Undefined) => true,
+            (Object::Int(a), Object::Int(b)) => a == b,
+            (Object::Int(a), Object::Number(b)) | (Object::Number(b), Object::Int(a)) => {
+                *a as f64 == *b
+            }
+            (Object::Int(a), Object::Boolean(b)) | (Object::Boolean(b), Object::Int(a)) => {
+                *a == i64::from(*b)
+            }
             (Object::Number(a), Object::Number(b)) => a == b,
             (Object::Number(a), Object::Boolean(b)) => *a == bool_to_number(*b),
             (Object::Boolean(a), Object::Number(b)) => bool_to_number(*a) == *b,
             (Object::String(a), Object::String(b)) => a == b,
             (Object::Boolean(a), Object::Boolean(b)) => a == b,
+            (Object::Array(a), Object::Array(b)) => Rc::ptr_eq(a, b),
+            (Object::Map(a), Object::Map(b)) => Rc::ptr_eq(a, b),
             (Object::Callable(a), Object::Callable(b)) => Rc::ptr_eq(a, b),
             (Object::Instance(a), Object::Instance(b)) => Rc::ptr_eq(a, b),
             (Object::CallableInstance(a), Object::CallableInstance(b)) => Rc::ptr_eq(a, b),
@@ -1,9 +1,9 @@
 use std::{cell::RefCell, rc::Rc};
 
 use crate::{
-    environment::Environment,
-    errors::{RuntimeError, VMException},
-    interpreter::Interpreter,
+    environment::{Environment, THIS_SLOT},
+    errors::VMException,
+    interpreter::{generators, promises, Interpreter},
 };
 
 use super::{Callable, Object, ResolvableFunction};
@@ -11,19 +11,41 @@ use super::{Callable, Object, ResolvableFunction};
 pub struct Function<T: ResolvableFunction> {
     declaration: Rc<T>,
     closure: Rc<RefCell<Environment>>,
+    globals: Rc<RefCell<Environment>>,
     is_initializer: bool,
+    /// The class environment a method was declared in, `this` is always
+    /// bound on top of it so binding an already bound method replaces its
+    /// `this`. `None` for functions that aren't methods.
+    method_closure: Option<Rc<RefCell<Environment>>>,
 }
 
 impl<T: ResolvableFunction> Function<T> {
     pub fn new(
         declaration: Rc<T>,
         closure: Rc<RefCell<Environment>>,
+        globals: Rc<RefCell<Environment>>,
         is_initializer: bool,
     ) -> Self {
         Self {
             declaration,
             closure,
+            globals,
             is_initializer,
+            method_closure: None,
+        }
+    }
+
+    /// Creates a method of the class whose environment is `closure`, calling
+    /// it requires binding `this` first.
+    pub fn method(
+        declaration: Rc<T>,
+        closure: Rc<RefCell<Environment>>,
+        globals: Rc<RefCell<Environment>>,
+        is_initializer: bool,
+    ) -> Self {
+        Self {
+            method_closure: Some(Rc::clone(&closure)),
+            ..Self::new(declaration, closure, globals, is_initializer)
         }
     }
 }
@@ -33,47 +55,91 @@ impl<T: ResolvableFunction> Callable for Function<T> {
         self.declaration.params().len()
     }
 
+    fn max_arity(&self) -> Option<usize> {
+        match self.declaration.rest() {
+            Some(_) => None,
+            None => Some(self.arity()),
+        }
+    }
+
     fn call(
         &self,
         interpreter: &mut Interpreter,
         arguments: Vec<Object>,
-    ) -> Result<Object, RuntimeError> {
-        let mut env = Environment::new(Some(Rc::clone(&self.closure)));
+    ) -> Result<Object, VMException> {
+        // Parameters take the first slots in order, the rest parameter last.
+        let mut arguments = arguments;
+
+        if self.declaration.rest().is_some() {
+            let rest = arguments.split_off(self.declaration.params().len());
 
-        for (param, arg) in self.declaration.params().iter().zip(arguments) {
-            env.define(&param.lexeme, arg);
+            arguments.push(Object::Array(Rc::new(RefCell::new(rest))));
         }
 
-        if let Err(err) = interpreter.execute_block(&self.declaration.body(), env) {
+        let env = Environment::with_slots(Some(Rc::clone(&self.closure)), arguments);
+
+        if self.declaration.is_async() {
+            let declaration: Rc<dyn ResolvableFunction> = self.declaration.clone();
+
+            return promises::call_async(interpreter, declaration, env, Rc::clone(&self.globals));
+        }
+
+        if self.declaration.is_generator() {
+            let declaration: Rc<dyn ResolvableFunction> = self.declaration.clone();
+
+            return Ok(generators::generator(
+                declaration,
+                env,
+                Rc::clone(&self.globals),
+            ));
+        }
+
+        let result = interpreter.with_globals(Rc::clone(&self.globals), |interpreter| {
+            interpreter.execute_block(self.declaration.body(), env)
+        });
+
+        if let Err(err) = result {
             return match err {
-                VMException::RuntimeError(runtime_error) => Err(runtime_error),
                 VMException::ReturnException(object) => {
                     if self.is_initializer {
-                        return self.closure.borrow().get_at(0, "this");
+                        return Ok(self.closure.borrow().get_at(0, THIS_SLOT));
                     }
 
                     Ok(object)
                 }
-                _ => unreachable!(),
+                _ => Err(err),
             };
         }
 
         Ok(Object::Undefined)
     }
 
+    fn name(&self) -> String {
+        String::from(self.declaration.name())
+    }
+
     fn to_string(&self) -> String {
         format!("[Function: ({})]", self.declaration.name())
     }
 
-    fn bind(&self, instance: Object) -> Object {
-        let mut env = Environment::new(Some(Rc::clone(&self.closure)));
+    fn source(&self, name: &str, globals: &Rc<RefCell<Environment>>) -> Option<String> {
+        let redeclarable = Rc::ptr_eq(&self.globals, globals)
+            && self.method_closure.is_none()
+            && self.closure.borrow().is_empty();
+
+        redeclarable.then(|| self.declaration.source(name))
+    }
 
-        env.define("this", instance);
+    fn bind(&self, instance: Object) -> Option<Rc<dyn Callable>> {
+        let method_closure = self.method_closure.as_ref()?;
+        let env = Environment::with_slots(Some(Rc::clone(method_closure)), vec![instance]);
 
-        Object::Callable(Rc::new(Function::new(
-            Rc::clone(&self.declaration),
-            Rc::new(RefCell::new(env)),
-            self.is_initializer,
-        )))
+        Some(Rc::new(Function {
+            declaration: Rc::clone(&self.declaration),
+            closure: Rc::new(RefCell::new(env)),
+            globals: Rc::clone(&self.globals),
+            is_initializer: self.is_initializer,
+            method_closure: Some(Rc::clone(method_closure)),
+        }))
     }
 }
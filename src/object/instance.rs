@@ -1,11 +1,69 @@
-use crate::{errors::RuntimeError, token::Token};
+use std::rc::Rc;
 
-use super::Object;
+use crate::{errors::RuntimeError, interpreter::promises::Promise, token::Token};
+
+use super::{Callable, Class, Object, UserData};
+
+/// What can still change about an instance, see `seal` and `freeze`.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub enum Mutability {
+    Open,
+    /// Existing fields can be assigned, no new ones can be added.
+    Sealed,
+    /// No field can be added or assigned.
+    Frozen,
+}
 
 pub trait Instance {
     fn get(&self, this: Object, name: &Token) -> Result<Object, RuntimeError>;
 
+    /// Looks up a private member from inside a method declared by `class`.
+    fn get_private(&self, this: Object, name: &Token, _: &Class) -> Result<Object, RuntimeError> {
+        self.get(this, name)
+    }
+
     fn set(&self, name: &Token, value: Object) -> Result<(), RuntimeError>;
 
+    /// The names of the public members that can be read, for completion.
+    fn property_names(&self) -> Vec<String> {
+        vec![]
+    }
+
+    /// The names of the public fields, listed by `keys`. Only instances of
+    /// classes tell their fields apart from their methods.
+    fn field_names(&self) -> Vec<String> {
+        self.property_names()
+    }
+
+    /// Restricts what can change about the instance, returns `false` when it
+    /// doesn't support restrictions. Restrictions are never lifted, freezing
+    /// a sealed instance works but sealing a frozen one keeps it frozen.
+    fn restrict(&self, _: Mutability) -> bool {
+        false
+    }
+
+    /// The function returning the items of an instance that can be iterated
+    /// over natively, one `{value, done}` map per call, see `for-in`. The
+    /// iteration ends at the first one that's `done`.
+    fn iterator(&self) -> Option<Rc<dyn Callable>> {
+        None
+    }
+
+    /// The promise the instance stands for, what `await` waits for.
+    fn promise(&self) -> Option<Rc<Promise>> {
+        None
+    }
+
+    /// The Rust value the instance wraps, see
+    /// [`UserDataType`](super::UserDataType).
+    fn user_data(&self) -> Option<&UserData> {
+        None
+    }
+
+    /// The class the instance was created from, if any.
+    fn class(&self) -> Option<Class> {
+        None
+    }
+
     fn to_string(&self) -> String;
 }
@@ -0,0 +1,48 @@
+use std::{cell::RefCell, rc::Rc};
+
+use super::Object;
+
+/// Iteration over the built-in collections: array elements, map keys and
+/// string characters.
+pub enum ObjectIter {
+    Array(Rc<RefCell<Vec<Object>>>, usize),
+    Keys(std::vec::IntoIter<String>),
+    Chars(std::vec::IntoIter<char>),
+}
+
+impl Iterator for ObjectIter {
+    type Item = Object;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            ObjectIter::Array(array, index) => {
+                let element = array.borrow().get(*index).cloned();
+
+                *index += 1;
+
+                element
+            }
+            ObjectIter::Keys(keys) => keys.next().map(|key| Object::String(Rc::from(key))),
+            ObjectIter::Chars(chars) => chars
+                .next()
+                .map(|c| Object::String(Rc::from(c.to_string()))),
+        }
+    }
+}
+
+impl Object {
+    /// Returns an iterator for iterable objects, arrays are read live so
+    /// elements pushed while iterating are visited.
+    pub fn iter(&self) -> Option<ObjectIter> {
+        match self {
+            Object::Array(array) => Some(ObjectIter::Array(Rc::clone(array), 0)),
+            Object::Map(map) => Some(ObjectIter::Keys(
+                map.borrow().keys().cloned().collect::<Vec<_>>().into_iter(),
+            )),
+            Object::String(string) => Some(ObjectIter::Chars(
+                string.chars().collect::<Vec<_>>().into_iter(),
+            )),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use std::collections::HashMap;
+
+use crate::{errors::RuntimeError, token::Token};
+
+use super::{Instance, Object};
+
+/// A read-only bag of native members exposed to scripts as a global, such as `Math`.
+pub struct Namespace {
+    name: String,
+    members: HashMap<String, Object>,
+}
+
+impl Namespace {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: String::from(name),
+            members: HashMap::new(),
+        }
+    }
+
+    pub fn define(&mut self, name: &str, value: Object) -> &mut Self {
+        self.members.insert(String::from(name), value);
+        self
+    }
+}
+
+impl Instance for Namespace {
+    fn get(&self, _: Object, name: &Token) -> Result<Object, RuntimeError> {
+        match self.members.get(&*name.lexeme) {
+            Some(member) => Ok(member.clone()),
+            None => Err(RuntimeError {
+                token: name.clone(),
+                message: format!("Undefined property '{}' on '{}'", name.lexeme, self.name),
+            }),
+        }
+    }
+
+    fn set(&self, name: &Token, _: Object) -> Result<(), RuntimeError> {
+        Err(RuntimeError {
+            token: name.clone(),
+            message: format!("Can't modify native namespace '{}'", self.name),
+        })
+    }
+
+    fn property_names(&self) -> Vec<String> {
+        self.members.keys().cloned().collect()
+    }
+
+    fn to_string(&self) -> String {
+        format!("[Namespace: ({})]", self.name)
+    }
+}
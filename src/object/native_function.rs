@@ -0,0 +1,70 @@
+use std::rc::Rc;
+
+use crate::{
+    errors::{RuntimeError, VMException},
+    interpreter::Interpreter,
+};
+
+use super::{Callable, Object};
+
+pub type NativeFn = dyn Fn(&mut Interpreter, Vec<Object>) -> Result<Object, RuntimeError>;
+
+pub struct NativeFunction {
+    name: String,
+    arity: usize,
+    max_arity: Option<usize>,
+    function: Rc<NativeFn>,
+}
+
+impl NativeFunction {
+    pub fn new(
+        name: &str,
+        arity: usize,
+        function: impl Fn(&mut Interpreter, Vec<Object>) -> Result<Object, RuntimeError> + 'static,
+    ) -> Self {
+        Self {
+            name: String::from(name),
+            arity,
+            max_arity: Some(arity),
+            function: Rc::new(function),
+        }
+    }
+
+    /// Accepts up to `optional` arguments past the required ones.
+    pub fn optional(mut self, optional: usize) -> Self {
+        self.max_arity = Some(self.arity + optional);
+        self
+    }
+
+    /// Accepts any number of arguments past the required ones.
+    pub fn variadic(mut self) -> Self {
+        self.max_arity = None;
+        self
+    }
+}
+
+impl Callable for NativeFunction {
+    fn arity(&self) -> usize {
+        self.arity
+    }
+
+    fn max_arity(&self) -> Option<usize> {
+        self.max_arity
+    }
+
+    fn call(
+        &self,
+        interpreter: &mut Interpreter,
+        arguments: Vec<Object>,
+    ) -> Result<Object, VMException> {
+        Ok((self.function)(interpreter, arguments)?)
+    }
+
+    fn name(&self) -> String {
+        String::clone(&self.name)
+    }
+
+    fn to_string(&self) -> String {
+        format!("Native Function: ({})", self.name)
+    }
+}
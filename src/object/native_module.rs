@@ -0,0 +1,46 @@
+use super::NativeFunction;
+
+/// Natives bundled by the host as a module, scripts declare them with
+/// `import native "name";` once it's registered with
+/// [`Interpreter::register_module`](crate::interpreter::Interpreter::register_module).
+///
+/// ```
+/// use std::rc::Rc;
+///
+/// use typhoon::{
+///     interpreter::CapturedOutput,
+///     object::{NativeFunction, NativeModule, Object},
+///     Lib,
+/// };
+///
+/// struct Greetings;
+///
+/// impl NativeModule for Greetings {
+///     fn name(&self) -> &str {
+///         "greetings"
+///     }
+///
+///     fn natives(&self) -> Vec<NativeFunction> {
+///         vec![NativeFunction::new("hello", 1, |_, args| {
+///             Ok(Object::String(Rc::from(format!("Hello, {}!", args[0]))))
+///         })]
+///     }
+/// }
+///
+/// let output = CapturedOutput::new();
+/// let mut lib = Lib::new();
+///
+/// lib.set_output(output.clone());
+/// lib.register_module(Greetings);
+/// lib.run(String::from(r#"import native "greetings"; print hello("world");"#))
+///     .unwrap();
+///
+/// assert_eq!(output.contents(), "Hello, world!\n");
+/// ```
+pub trait NativeModule {
+    /// The name scripts import the module by.
+    fn name(&self) -> &str;
+
+    /// The natives declared in the importing script, each by its own name.
+    fn natives(&self) -> Vec<NativeFunction>;
+}
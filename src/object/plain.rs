@@ -0,0 +1,119 @@
+use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+
+use super::Object;
+
+/// A copy of a value made of booleans, numbers, strings, arrays and maps
+/// that owns its contents. Unlike [`Object`] it's `Send`, so script results
+/// can be handed to another thread and turned back into objects there.
+///
+/// ```
+/// use std::thread;
+///
+/// use typhoon::{object::Object, Lib};
+///
+/// let mut lib = Lib::new();
+///
+/// lib.run_line(String::from(r#"var result = {name: "typhoon", scores: [1, 2.5]};"#))
+///     .unwrap();
+///
+/// let result = lib.run_line(String::from("result;")).unwrap().unwrap();
+/// let plain = result.deep_clone_to_plain().unwrap();
+/// let printed = thread::spawn(move || Object::from(plain).to_string())
+///     .join()
+///     .unwrap();
+///
+/// assert_eq!(printed, r#"{"name": "typhoon", "scores": [1, 2.5]}"#);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub enum PlainValue {
+    Undefined,
+    Boolean(bool),
+    Int(i64),
+    Number(f64),
+    String(String),
+    Array(Vec<PlainValue>),
+    Map(BTreeMap<String, PlainValue>),
+}
+
+impl Object {
+    /// Copies the value and everything it contains into a [`PlainValue`].
+    ///
+    /// Returns `None` when it holds a function, class or instance, which
+    /// only exist in the interpreter that created them, or an array or map
+    /// that contains itself.
+    pub fn deep_clone_to_plain(&self) -> Option<PlainValue> {
+        self.to_plain(&mut vec![])
+    }
+
+    /// `containers` are the arrays and maps being copied, the value is
+    /// nested inside each of them.
+    fn to_plain(&self, containers: &mut Vec<*const ()>) -> Option<PlainValue> {
+        let plain = match self {
+            Object::Undefined => PlainValue::Undefined,
+            Object::Boolean(boolean) => PlainValue::Boolean(*boolean),
+            Object::Int(int) => PlainValue::Int(*int),
+            Object::Number(number) => PlainValue::Number(*number),
+            Object::String(string) => PlainValue::String(string.to_string()),
+            Object::Array(array) => {
+                let container = Rc::as_ptr(array).cast();
+
+                if containers.contains(&container) {
+                    return None;
+                }
+
+                containers.push(container);
+
+                let elements = array
+                    .borrow()
+                    .iter()
+                    .map(|element| element.to_plain(containers))
+                    .collect::<Option<_>>();
+
+                containers.pop();
+                PlainValue::Array(elements?)
+            }
+            Object::Map(map) => {
+                let container = Rc::as_ptr(map).cast();
+
+                if containers.contains(&container) {
+                    return None;
+                }
+
+                containers.push(container);
+
+                let entries = map
+                    .borrow()
+                    .iter()
+                    .map(|(key, value)| Some((key.clone(), value.to_plain(containers)?)))
+                    .collect::<Option<_>>();
+
+                containers.pop();
+                PlainValue::Map(entries?)
+            }
+            Object::Callable(_) | Object::Instance(_) | Object::CallableInstance(_) => return None,
+        };
+
+        Some(plain)
+    }
+}
+
+impl From<PlainValue> for Object {
+    fn from(value: PlainValue) -> Self {
+        match value {
+            PlainValue::Undefined => Object::Undefined,
+            PlainValue::Boolean(boolean) => Object::Boolean(boolean),
+            PlainValue::Int(int) => Object::Int(int),
+            PlainValue::Number(number) => Object::Number(number),
+            PlainValue::String(string) => Object::String(Rc::from(string)),
+            PlainValue::Array(elements) => Object::Array(Rc::new(RefCell::new(
+                elements.into_iter().map(Object::from).collect(),
+            ))),
+            PlainValue::Map(entries) => Object::Map(Rc::new(RefCell::new(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key, Object::from(value)))
+                    .collect(),
+            ))),
+        }
+    }
+}
@@ -1,5 +1,8 @@
+use std::{cell::RefCell, rc::Rc};
+
 use crate::{
-    expr,
+    expr::{self, Expr},
+    formatter,
     stmt::{self, Stmt},
     token::Token,
 };
@@ -7,15 +10,36 @@ use crate::{
 pub trait ResolvableFunction: 'static {
     fn params(&self) -> &Vec<Token>;
 
+    fn rest(&self) -> Option<&Token>;
+
     fn body(&self) -> &Vec<Stmt>;
 
     fn name(&self) -> &str;
+
+    /// Whether calling the function returns a generator running its body.
+    fn is_generator(&self) -> bool;
+
+    /// Whether calling the function returns a promise of what its body
+    /// returns.
+    fn is_async(&self) -> bool;
+
+    /// The locations of the variables the function captures, see
+    /// [`stmt::Function::captures`].
+    fn captures(&self) -> &RefCell<Vec<(usize, usize)>>;
+
+    /// The source of a declaration giving the function to the variable
+    /// `name`.
+    fn source(&self, name: &str) -> String;
 }
 
 impl ResolvableFunction for stmt::Function {
     fn params(&self) -> &Vec<Token> {
         &self.params
     }
+
+    fn rest(&self) -> Option<&Token> {
+        self.rest.as_ref()
+    }
     fn body(&self) -> &Vec<Stmt> {
         &self.body
     }
@@ -23,12 +47,36 @@ impl ResolvableFunction for stmt::Function {
     fn name(&self) -> &str {
         &self.name.lexeme
     }
+
+    fn is_generator(&self) -> bool {
+        self.is_generator
+    }
+
+    fn is_async(&self) -> bool {
+        self.is_async
+    }
+
+    fn captures(&self) -> &RefCell<Vec<(usize, usize)>> {
+        &self.captures
+    }
+
+    fn source(&self, name: &str) -> String {
+        let mut declaration = self.clone();
+
+        declaration.name.lexeme = Rc::from(name);
+
+        formatter::format_stmt(&Stmt::Function(Rc::new(declaration)))
+    }
 }
 
 impl ResolvableFunction for expr::Lambda {
     fn params(&self) -> &Vec<Token> {
         &self.params
     }
+
+    fn rest(&self) -> Option<&Token> {
+        self.rest.as_ref()
+    }
     fn body(&self) -> &Vec<Stmt> {
         &self.body
     }
@@ -36,4 +84,22 @@ impl ResolvableFunction for expr::Lambda {
     fn name(&self) -> &str {
         "anonymous"
     }
+
+    fn is_generator(&self) -> bool {
+        self.is_generator
+    }
+
+    fn is_async(&self) -> bool {
+        self.is_async
+    }
+
+    fn captures(&self) -> &RefCell<Vec<(usize, usize)>> {
+        &self.captures
+    }
+
+    fn source(&self, name: &str) -> String {
+        let lambda = formatter::format_expr(&Expr::Lambda(Rc::new(self.clone())));
+
+        format!("var {name} = {lambda};")
+    }
 }
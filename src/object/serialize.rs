@@ -0,0 +1,111 @@
+use std::{cell::RefCell, collections::BTreeMap, fmt, rc::Rc};
+
+use serde::{
+    de::{self, MapAccess, SeqAccess, Visitor},
+    ser::{self, SerializeMap, SerializeSeq},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use super::Object;
+
+/// Plain values map to their JSON counterparts, `undefined` becomes `null`.
+/// Functions, classes and instances only exist at runtime and can't be
+/// serialized.
+impl Serialize for Object {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Object::Undefined => serializer.serialize_unit(),
+            Object::Boolean(boolean) => serializer.serialize_bool(*boolean),
+            Object::Int(int) => serializer.serialize_i64(*int),
+            Object::Number(number) => serializer.serialize_f64(*number),
+            Object::String(string) => serializer.serialize_str(string),
+            Object::Array(array) => {
+                let array = array.borrow();
+                let mut seq = serializer.serialize_seq(Some(array.len()))?;
+
+                for element in array.iter() {
+                    seq.serialize_element(element)?;
+                }
+
+                seq.end()
+            }
+            Object::Map(map) => {
+                let map = map.borrow();
+                let mut entries = serializer.serialize_map(Some(map.len()))?;
+
+                for (key, value) in map.iter() {
+                    entries.serialize_entry(key, value)?;
+                }
+
+                entries.end()
+            }
+            Object::Callable(_) | Object::Instance(_) | Object::CallableInstance(_) => Err(
+                ser::Error::custom(format!("Can't serialize a {}", self.type_name())),
+            ),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Object {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ObjectVisitor)
+    }
+}
+
+struct ObjectVisitor;
+
+impl<'de> Visitor<'de> for ObjectVisitor {
+    type Value = Object;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a boolean, number, string, array, map or null")
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<Object, E> {
+        Ok(Object::Undefined)
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<Object, E> {
+        Ok(Object::Undefined)
+    }
+
+    fn visit_bool<E: de::Error>(self, value: bool) -> Result<Object, E> {
+        Ok(Object::Boolean(value))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Object, E> {
+        Ok(Object::Int(value))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Object, E> {
+        Ok(i64::try_from(value).map_or(Object::Number(value as f64), Object::Int))
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<Object, E> {
+        Ok(Object::Number(value))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Object, E> {
+        Ok(Object::String(Rc::from(value)))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Object, A::Error> {
+        let mut elements = vec![];
+
+        while let Some(element) = seq.next_element()? {
+            elements.push(element);
+        }
+
+        Ok(Object::Array(Rc::new(RefCell::new(elements))))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Object, A::Error> {
+        let mut entries = BTreeMap::new();
+
+        while let Some((key, value)) = map.next_entry()? {
+            entries.insert(key, value);
+        }
+
+        Ok(Object::Map(Rc::new(RefCell::new(entries))))
+    }
+}
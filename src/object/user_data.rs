@@ -0,0 +1,185 @@
+use std::{any::Any, collections::HashMap, marker::PhantomData, rc::Rc};
+
+use crate::{errors::RuntimeError, interpreter::Interpreter, token::Token};
+
+use super::{Instance, NativeFunction, Object};
+
+type UserMethod = dyn Fn(&mut Interpreter, &dyn Any, Vec<Object>) -> Result<Object, RuntimeError>;
+
+#[derive(Clone)]
+struct Method {
+    arity: usize,
+    function: Rc<UserMethod>,
+}
+
+/// The name and methods of the user data of one Rust type.
+#[derive(Clone)]
+struct Table {
+    name: String,
+    methods: HashMap<String, Method>,
+}
+
+/// Describes how scripts see values of the Rust type `T`: the name they're
+/// printed with and the methods they can call. [`UserDataType::wrap`] then
+/// hands values to scripts as instances, without copying them.
+///
+/// Methods get the value by reference, fields that change go in a `Cell` or
+/// `RefCell`. Natives get it back with [`Object::user_data`].
+///
+/// ```
+/// use std::cell::Cell;
+///
+/// use typhoon::{
+///     interpreter::CapturedOutput,
+///     object::{Object, UserDataType},
+///     Lib,
+/// };
+///
+/// struct Counter {
+///     count: Cell<i64>,
+/// }
+///
+/// let counters = UserDataType::<Counter>::new("Counter").method(
+///     "increment",
+///     0,
+///     |_, counter, _| {
+///         counter.count.set(counter.count.get() + 1);
+///
+///         Ok(Object::Int(counter.count.get()))
+///     },
+/// );
+/// let counter = counters.wrap(Counter { count: Cell::new(0) });
+/// let output = CapturedOutput::new();
+/// let mut lib = Lib::new();
+///
+/// lib.set_output(output.clone());
+/// lib.register_native("counter", 0, move |_, _| Ok(counter.clone()));
+/// lib.register_native("count", 1, |interpreter, args| {
+///     match args[0].user_data::<Counter>() {
+///         Some(counter) => Ok(Object::Int(counter.count.get())),
+///         None => Err(interpreter.native_error("Expected a counter")),
+///     }
+/// });
+/// lib.run(String::from(
+///     "var c = counter(); c.increment(); print c.increment(); print count(c); print c;",
+/// ))
+/// .unwrap();
+///
+/// assert_eq!(output.contents(), "2\n2\n[UserData: (Counter)]\n");
+/// ```
+pub struct UserDataType<T> {
+    table: Rc<Table>,
+    value: PhantomData<T>,
+}
+
+impl<T: 'static> UserDataType<T> {
+    pub fn new(name: &str) -> Self {
+        Self {
+            table: Rc::new(Table {
+                name: String::from(name),
+                methods: HashMap::new(),
+            }),
+            value: PhantomData,
+        }
+    }
+
+    /// Adds the method `name` taking exactly `arity` arguments, replacing
+    /// the method of the same name. Values wrapped before keep their
+    /// methods.
+    pub fn method(
+        mut self,
+        name: &str,
+        arity: usize,
+        method: impl Fn(&mut Interpreter, &T, Vec<Object>) -> Result<Object, RuntimeError> + 'static,
+    ) -> Self {
+        let function = move |interpreter: &mut Interpreter, value: &dyn Any, args| {
+            let value = value
+                .downcast_ref::<T>()
+                .expect("user data is only wrapped by its own type");
+
+            method(interpreter, value, args)
+        };
+
+        Rc::make_mut(&mut self.table).methods.insert(
+            String::from(name),
+            Method {
+                arity,
+                function: Rc::new(function),
+            },
+        );
+
+        self
+    }
+
+    /// Wraps `value` into an instance scripts can call the methods of.
+    pub fn wrap(&self, value: T) -> Object {
+        Object::Instance(Rc::new(UserData {
+            value: Rc::new(value),
+            table: Rc::clone(&self.table),
+        }))
+    }
+}
+
+/// A Rust value handed to scripts, see [`UserDataType`].
+pub struct UserData {
+    value: Rc<dyn Any>,
+    table: Rc<Table>,
+}
+
+impl UserData {
+    /// The wrapped value, if it's a `T`.
+    pub fn value<T: 'static>(&self) -> Option<Rc<T>> {
+        Rc::clone(&self.value).downcast().ok()
+    }
+}
+
+impl Object {
+    /// The Rust value wrapped by [`UserDataType::wrap`], if the object is
+    /// user data wrapping a `T`.
+    pub fn user_data<T: 'static>(&self) -> Option<Rc<T>> {
+        match self {
+            Object::Instance(instance) => instance.user_data()?.value(),
+            _ => None,
+        }
+    }
+}
+
+impl Instance for UserData {
+    fn get(&self, _: Object, name: &Token) -> Result<Object, RuntimeError> {
+        let Some(Method { arity, function }) = self.table.methods.get(&*name.lexeme) else {
+            return Err(RuntimeError {
+                token: name.clone(),
+                message: format!(
+                    "Undefined property '{}' on '{}'",
+                    name.lexeme, self.table.name
+                ),
+            });
+        };
+        let value = Rc::clone(&self.value);
+        let function = Rc::clone(function);
+        let method = NativeFunction::new(&name.lexeme, *arity, move |interpreter, args| {
+            function(interpreter, &*value, args)
+        });
+
+        Ok(Object::Callable(Rc::new(method)))
+    }
+
+    fn set(&self, name: &Token, _: Object) -> Result<(), RuntimeError> {
+        Err(RuntimeError {
+            token: name.clone(),
+            message: format!("Can't modify user data '{}'", self.table.name),
+        })
+    }
+
+    fn property_names(&self) -> Vec<String> {
+        self.table.methods.keys().cloned().collect()
+    }
+
+    fn user_data(&self) -> Option<&UserData> {
+        Some(self)
+    }
+
+    fn to_string(&self) -> String {
+        format!("[UserData: ({})]", self.table.name)
+    }
+}
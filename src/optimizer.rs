@@ -0,0 +1,460 @@
+use std::rc::Rc;
+
+use crate::{
+    expr::{self, Expr, ExprVisitor},
+    interpreter::operations,
+    object::Object,
+    stmt::{self, Stmt, StmtVisitor, VariableDeclaration},
+    token::{Span, Token},
+    token_type::TokenType,
+    utils::is_truthy,
+};
+
+/// Folds the subexpressions made of literals only, `2 * 3 + 1` becomes `7`,
+/// and drops the branches constant conditions never take.
+///
+/// It runs on resolved statements, so code it removes is still checked and
+/// the tokens it keeps still find their variables. Operations that would
+/// fail, like dividing by zero, are left for the interpreter to report.
+pub struct Optimizer;
+
+impl Optimizer {
+    pub fn optimize(statements: &[Stmt]) -> Vec<Stmt> {
+        Optimizer.statements(statements)
+    }
+
+    fn statements(&mut self, statements: &[Stmt]) -> Vec<Stmt> {
+        statements.iter().map(|stmt| stmt.accept(self)).collect()
+    }
+
+    fn expressions(&mut self, expressions: &[Expr]) -> Vec<Expr> {
+        expressions.iter().map(|expr| expr.accept(self)).collect()
+    }
+
+    fn declarations(&mut self, declarations: &[VariableDeclaration]) -> Vec<VariableDeclaration> {
+        declarations
+            .iter()
+            .map(|variable| VariableDeclaration {
+                name: variable.name.clone(),
+                initializer: variable
+                    .initializer
+                    .as_ref()
+                    .map(|initializer| initializer.accept(self)),
+                is_const: variable.is_const,
+            })
+            .collect()
+    }
+
+    fn literal(expr: &Expr) -> Option<&Object> {
+        match expr {
+            Expr::Literal(literal, _) => Some(literal),
+            _ => None,
+        }
+    }
+}
+
+impl ExprVisitor for Optimizer {
+    type Item = Expr;
+
+    fn visit_comma(&mut self, expr: &expr::Comma) -> Self::Item {
+        Expr::Comma(Box::new(expr::Comma {
+            left: expr.left.accept(self),
+            right: expr.right.accept(self),
+        }))
+    }
+
+    fn visit_lambda(&mut self, expr: &Rc<expr::Lambda>) -> Self::Item {
+        Expr::Lambda(Rc::new(expr::Lambda {
+            name: expr.name.clone(),
+            params: expr.params.clone(),
+            rest: expr.rest.clone(),
+            body: self.statements(&expr.body),
+            is_generator: expr.is_generator,
+            is_async: expr.is_async,
+            span: expr.span,
+            captures: expr.captures.clone(),
+        }))
+    }
+
+    fn visit_assignment(&mut self, expr: &expr::Assignment) -> Self::Item {
+        Expr::Assignment(Box::new(expr::Assignment {
+            name: expr.name.clone(),
+            value: expr.value.accept(self),
+        }))
+    }
+
+    fn visit_set(&mut self, expr: &expr::Set) -> Self::Item {
+        Expr::Set(Box::new(expr::Set {
+            object: expr.object.accept(self),
+            name: expr.name.clone(),
+            value: expr.value.accept(self),
+        }))
+    }
+
+    fn visit_index_set(&mut self, expr: &expr::IndexSet) -> Self::Item {
+        Expr::IndexSet(Box::new(expr::IndexSet {
+            object: expr.object.accept(self),
+            bracket: expr.bracket.clone(),
+            index: expr.index.accept(self),
+            value: expr.value.accept(self),
+        }))
+    }
+
+    fn visit_ternary(&mut self, expr: &expr::Ternary) -> Self::Item {
+        let condition = expr.condition.accept(self);
+
+        match Optimizer::literal(&condition) {
+            Some(literal) if is_truthy(literal) => expr.truth.accept(self),
+            Some(_) => expr.falsy.accept(self),
+            None => Expr::Ternary(Box::new(expr::Ternary {
+                condition,
+                truth: expr.truth.accept(self),
+                falsy: expr.falsy.accept(self),
+            })),
+        }
+    }
+
+    fn visit_logical(&mut self, expr: &expr::Logical) -> Self::Item {
+        let left = expr.left.accept(self);
+
+        let takes_left = match (Optimizer::literal(&left), &expr.operator.token_type) {
+            (Some(literal), TokenType::And) => Some(!is_truthy(literal)),
+            (Some(literal), TokenType::Or) => Some(is_truthy(literal)),
+            (Some(literal), TokenType::QuestionQuestion) => {
+                Some(!matches!(literal, Object::Undefined))
+            }
+            _ => None,
+        };
+
+        match takes_left {
+            Some(true) => left,
+            Some(false) => expr.right.accept(self),
+            None => Expr::Logical(Box::new(expr::Logical {
+                operator: expr.operator.clone(),
+                left,
+                right: expr.right.accept(self),
+            })),
+        }
+    }
+
+    fn visit_binary(&mut self, expr: &expr::Binary) -> Self::Item {
+        let left = expr.left.accept(self);
+        let right = expr.right.accept(self);
+
+        if let (Some(l), Some(r)) = (Optimizer::literal(&left), Optimizer::literal(&right)) {
+            if let Ok(value) = operations::handle_binary(l, r, &expr.operator) {
+                return Expr::Literal(Box::new(value), expr.left.span().to(expr.right.span()));
+            }
+        }
+
+        Expr::Binary(Box::new(expr::Binary {
+            left,
+            operator: expr.operator.clone(),
+            right,
+        }))
+    }
+
+    fn visit_unary(&mut self, expr: &expr::Unary) -> Self::Item {
+        let right = expr.right.accept(self);
+
+        if let Some(literal) = Optimizer::literal(&right) {
+            if let Ok(value) = operations::handle_unary(literal, &expr.operator) {
+                return Expr::Literal(Box::new(value), expr.operator.span.to(expr.right.span()));
+            }
+        }
+
+        Expr::Unary(Box::new(expr::Unary {
+            operator: expr.operator.clone(),
+            right,
+        }))
+    }
+
+    fn visit_await(&mut self, expr: &expr::Await) -> Self::Item {
+        Expr::Await(Box::new(expr::Await {
+            keyword: expr.keyword.clone(),
+            value: expr.value.accept(self),
+        }))
+    }
+
+    fn visit_call(&mut self, expr: &expr::Call) -> Self::Item {
+        Expr::Call(Box::new(expr::Call {
+            callee: expr.callee.accept(self),
+            arguments: self.expressions(&expr.arguments),
+            paren: expr.paren.clone(),
+        }))
+    }
+
+    fn visit_get(&mut self, expr: &expr::Get) -> Self::Item {
+        Expr::Get(Box::new(expr::Get {
+            object: expr.object.accept(self),
+            name: expr.name.clone(),
+            optional: expr.optional,
+        }))
+    }
+
+    fn visit_index(&mut self, expr: &expr::Index) -> Self::Item {
+        Expr::Index(Box::new(expr::Index {
+            object: expr.object.accept(self),
+            bracket: expr.bracket.clone(),
+            index: expr.index.accept(self),
+            span: expr.span,
+        }))
+    }
+
+    fn visit_slice(&mut self, expr: &expr::Slice) -> Self::Item {
+        Expr::Slice(Box::new(expr::Slice {
+            object: expr.object.accept(self),
+            bracket: expr.bracket.clone(),
+            start: expr.start.as_ref().map(|start| start.accept(self)),
+            end: expr.end.as_ref().map(|end| end.accept(self)),
+            span: expr.span,
+        }))
+    }
+
+    fn visit_grouping(&mut self, expr: &Expr, span: Span) -> Self::Item {
+        match expr.accept(self) {
+            Expr::Literal(literal, _) => Expr::Literal(literal, span),
+            expr => Expr::Grouping(Box::new(expr), span),
+        }
+    }
+
+    fn visit_variable(&mut self, expr: &Token) -> Self::Item {
+        Expr::Variable(Box::new(expr.clone()))
+    }
+
+    fn visit_this(&mut self, expr: &Token) -> Self::Item {
+        Expr::This(Box::new(expr.clone()))
+    }
+
+    fn visit_super(&mut self, expr: &expr::Super) -> Self::Item {
+        Expr::Super(Box::new(expr.clone()))
+    }
+
+    fn visit_array(&mut self, expr: &expr::Array) -> Self::Item {
+        Expr::Array(Box::new(expr::Array {
+            bracket: expr.bracket.clone(),
+            elements: self.expressions(&expr.elements),
+            span: expr.span,
+        }))
+    }
+
+    fn visit_map(&mut self, expr: &expr::Map) -> Self::Item {
+        Expr::Map(Box::new(expr::Map {
+            brace: expr.brace.clone(),
+            entries: expr
+                .entries
+                .iter()
+                .map(|(key, value)| (key.accept(self), value.accept(self)))
+                .collect(),
+            span: expr.span,
+        }))
+    }
+
+    fn visit_interpolation(&mut self, expr: &expr::Interpolation) -> Self::Item {
+        let parts = self.expressions(&expr.parts);
+        let literals = parts
+            .iter()
+            .map(Optimizer::literal)
+            .collect::<Option<Vec<_>>>();
+
+        match literals {
+            Some(literals) => {
+                let string = literals
+                    .iter()
+                    .map(|literal| literal.to_string())
+                    .collect::<String>();
+
+                Expr::Literal(Box::new(Object::String(Rc::from(string))), expr.span)
+            }
+            None => Expr::Interpolation(Box::new(expr::Interpolation {
+                parts,
+                span: expr.span,
+            })),
+        }
+    }
+
+    fn visit_spread(&mut self, expr: &expr::Spread) -> Self::Item {
+        Expr::Spread(Box::new(expr::Spread {
+            ellipsis: expr.ellipsis.clone(),
+            value: expr.value.accept(self),
+        }))
+    }
+
+    fn visit_literal(&mut self, expr: &Object, span: Span) -> Self::Item {
+        Expr::Literal(Box::new(expr.clone()), span)
+    }
+}
+
+impl StmtVisitor for Optimizer {
+    type Item = Stmt;
+
+    fn visit_empty_stmt(&mut self, span: Span) -> Self::Item {
+        Stmt::Empty(span)
+    }
+
+    fn visit_expression_stmt(&mut self, stmt: &Expr, span: Span) -> Self::Item {
+        Stmt::Expression(Box::new(stmt.accept(self)), span)
+    }
+
+    fn visit_print_stmt(&mut self, stmt: &Expr, span: Span) -> Self::Item {
+        Stmt::Print(Box::new(stmt.accept(self)), span)
+    }
+
+    fn visit_variable_stmt(&mut self, stmt: &[VariableDeclaration], span: Span) -> Self::Item {
+        Stmt::Variable(Box::new(self.declarations(stmt)), span)
+    }
+
+    fn visit_block_stmt(&mut self, stmt: &[Stmt], span: Span) -> Self::Item {
+        Stmt::Block(Box::new(self.statements(stmt)), span)
+    }
+
+    fn visit_if_stmt(&mut self, stmt: &stmt::If) -> Self::Item {
+        let condition = stmt.condition.accept(self);
+
+        match Optimizer::literal(&condition) {
+            Some(literal) if is_truthy(literal) => stmt.truth.accept(self),
+            Some(_) => match &stmt.falsy {
+                Some(falsy) => falsy.accept(self),
+                None => Stmt::Empty(stmt.keyword.span.to(stmt.truth.span())),
+            },
+            None => Stmt::If(Box::new(stmt::If {
+                keyword: stmt.keyword.clone(),
+                condition,
+                truth: stmt.truth.accept(self),
+                falsy: stmt.falsy.as_ref().map(|falsy| falsy.accept(self)),
+            })),
+        }
+    }
+
+    fn visit_while_stmt(&mut self, stmt: &stmt::While) -> Self::Item {
+        Stmt::While(Box::new(stmt::While {
+            keyword: stmt.keyword.clone(),
+            condition: stmt.condition.accept(self),
+            body: stmt.body.accept(self),
+        }))
+    }
+
+    fn visit_for_stmt(&mut self, stmt: &stmt::For) -> Self::Item {
+        Stmt::For(Box::new(stmt::For {
+            keyword: stmt.keyword.clone(),
+            initializer: stmt
+                .initializer
+                .as_ref()
+                .map(|initializer| initializer.accept(self)),
+            condition: stmt
+                .condition
+                .as_ref()
+                .map(|condition| condition.accept(self)),
+            increment: stmt
+                .increment
+                .as_ref()
+                .map(|increment| increment.accept(self)),
+            body: stmt.body.accept(self),
+        }))
+    }
+
+    fn visit_for_in_stmt(&mut self, stmt: &stmt::ForIn) -> Self::Item {
+        Stmt::ForIn(Box::new(stmt::ForIn {
+            name: stmt.name.clone(),
+            keyword: stmt.keyword.clone(),
+            iterable: stmt.iterable.accept(self),
+            body: stmt.body.accept(self),
+            span: stmt.span,
+        }))
+    }
+
+    fn visit_break_stmt(&mut self, keyword: &Token, span: Span) -> Self::Item {
+        Stmt::Break(keyword.clone(), span)
+    }
+
+    fn visit_continue_stmt(&mut self, keyword: &Token, span: Span) -> Self::Item {
+        Stmt::Continue(keyword.clone(), span)
+    }
+
+    fn visit_function_stmt(&mut self, stmt: &Rc<stmt::Function>) -> Self::Item {
+        Stmt::Function(Rc::new(stmt::Function {
+            name: stmt.name.clone(),
+            params: stmt.params.clone(),
+            rest: stmt.rest.clone(),
+            body: self.statements(&stmt.body),
+            is_generator: stmt.is_generator,
+            is_async: stmt.is_async,
+            span: stmt.span,
+            captures: stmt.captures.clone(),
+        }))
+    }
+
+    fn visit_return_stmt(&mut self, stmt: &stmt::Return) -> Self::Item {
+        Stmt::Return(Box::new(stmt::Return {
+            keyword: stmt.keyword.clone(),
+            value: stmt.value.as_ref().map(|value| value.accept(self)),
+            span: stmt.span,
+        }))
+    }
+
+    fn visit_class_stmt(&mut self, stmt: &Rc<stmt::Class>) -> Self::Item {
+        Stmt::Class(Rc::new(stmt::Class {
+            name: stmt.name.clone(),
+            super_class: stmt
+                .super_class
+                .as_ref()
+                .map(|super_class| super_class.accept(self)),
+            methods: self.statements(&stmt.methods),
+            statics: self.statements(&stmt.statics),
+            static_fields: self.declarations(&stmt.static_fields),
+            span: stmt.span,
+        }))
+    }
+
+    fn visit_try_stmt(&mut self, stmt: &stmt::Try) -> Self::Item {
+        Stmt::Try(Box::new(stmt::Try {
+            body: self.statements(&stmt.body),
+            body_span: stmt.body_span,
+            catch: stmt.catch.as_ref().map(|catch| stmt::Catch {
+                name: catch.name.clone(),
+                body: self.statements(&catch.body),
+                span: catch.span,
+            }),
+            finally: stmt
+                .finally
+                .as_ref()
+                .map(|finally| self.statements(finally)),
+            span: stmt.span,
+        }))
+    }
+
+    fn visit_throw_stmt(&mut self, stmt: &stmt::Throw) -> Self::Item {
+        Stmt::Throw(Box::new(stmt::Throw {
+            keyword: stmt.keyword.clone(),
+            value: stmt.value.accept(self),
+            span: stmt.span,
+        }))
+    }
+
+    fn visit_yield_stmt(&mut self, stmt: &stmt::Yield) -> Self::Item {
+        Stmt::Yield(Box::new(stmt::Yield {
+            keyword: stmt.keyword.clone(),
+            value: stmt.value.as_ref().map(|value| value.accept(self)),
+            span: stmt.span,
+        }))
+    }
+
+    fn visit_exit_stmt(&mut self, stmt: &stmt::Exit) -> Self::Item {
+        Stmt::Exit(Box::new(stmt::Exit {
+            keyword: stmt.keyword.clone(),
+            code: stmt.code.as_ref().map(|code| code.accept(self)),
+            span: stmt.span,
+        }))
+    }
+
+    fn visit_import_stmt(&mut self, stmt: &stmt::Import) -> Self::Item {
+        Stmt::Import(Box::new(stmt.clone()))
+    }
+
+    fn visit_export_stmt(&mut self, stmt: &stmt::Export) -> Self::Item {
+        Stmt::Export(Box::new(stmt::Export {
+            keyword: stmt.keyword.clone(),
+            declaration: stmt.declaration.accept(self),
+        }))
+    }
+}
@@ -1,25 +1,49 @@
+use std::{cell::RefCell, rc::Rc};
+
 use crate::{
-    errors::SyntaxError,
+    errors::{Diagnostic, Severity, SyntaxError},
     expr::{self, Expr, Super},
     literal_type::LiteralType,
     object::Object,
     stmt::{self, Stmt},
-    token::Token,
+    token::{Span, Token},
     token_type::TokenType,
-    Lib,
 };
 
 pub struct Parser {
     tokens: Vec<Token>,
     current: usize,
+    diagnostics: RefCell<Vec<Diagnostic>>,
+    /// Whether the last expression statement may leave out its `;`.
+    trailing_expression: bool,
+    /// How many destructuring patterns were parsed, numbering the hidden
+    /// variable holding the value of each.
+    patterns: usize,
+    /// Whether each function whose body is being parsed yields, innermost
+    /// last.
+    generators: Vec<bool>,
 }
 
 impl Parser {
     pub fn new(tokens: Vec<Token>) -> Self {
-        Self { tokens, current: 0 }
+        Self {
+            tokens,
+            current: 0,
+            diagnostics: RefCell::new(vec![]),
+            trailing_expression: false,
+            patterns: 0,
+            generators: vec![],
+        }
+    }
+
+    /// Lets the program end with an expression without a `;`, so `1 + 2`
+    /// parses on its own.
+    pub fn allow_trailing_expression(mut self) -> Self {
+        self.trailing_expression = true;
+        self
     }
 
-    pub fn parse(&mut self) -> Vec<Stmt> {
+    pub fn parse(&mut self) -> Result<Vec<Stmt>, Vec<Diagnostic>> {
         let mut statements = vec![];
 
         while !self.is_at_end() {
@@ -28,17 +52,25 @@ impl Parser {
             };
         }
 
-        statements
+        let diagnostics = self.diagnostics.take();
+
+        if diagnostics.is_empty() {
+            Ok(statements)
+        } else {
+            Err(diagnostics)
+        }
     }
 
     fn declaration_stmt(&mut self) -> Option<Stmt> {
-        let stmt = if self.matches(&[TokenType::Var]) {
+        let stmt = if self.matches(&[TokenType::Var, TokenType::Const]) {
             self.variable_stmt()
+        } else if self.matches(&[TokenType::Export]) {
+            self.export_stmt()
         } else {
             self.stmt()
         };
 
-        if let Err(_) = stmt {
+        if stmt.is_err() {
             self.synchronize();
         }
 
@@ -47,11 +79,14 @@ impl Parser {
 
     fn stmt(&mut self) -> Result<Stmt, SyntaxError> {
         if self.matches(&[TokenType::SemiColon]) {
-            Ok(Stmt::Empty)
+            Ok(Stmt::Empty(self.previous().span))
         } else if self.matches(&[TokenType::Print]) {
             self.print_stmt()
         } else if self.matches(&[TokenType::LeftBraces]) {
-            Ok(Stmt::Block(Box::new(self.block_stmt()?)))
+            let start = self.previous().span;
+            let stmts = self.block_stmt()?;
+
+            Ok(Stmt::Block(Box::new(stmts), self.span_from(start)))
         } else if self.matches(&[TokenType::If]) {
             self.if_stmt()
         } else if self.matches(&[TokenType::While]) {
@@ -61,67 +96,380 @@ impl Parser {
         } else if self.matches(&[TokenType::Break, TokenType::Continue]) {
             self.loop_control()
         } else if self.matches(&[TokenType::Function]) {
-            self.function_stmt("function")
+            self.function_stmt("function", false)
+        } else if self.matches(&[TokenType::Async]) {
+            self.consume(&TokenType::Function, "Expect 'fun' after 'async'")?;
+            self.function_stmt("function", true)
         } else if self.matches(&[TokenType::Return]) {
             self.return_stmt()
         } else if self.matches(&[TokenType::Class]) {
             self.class_stmt()
+        } else if self.matches(&[TokenType::Try]) {
+            self.try_stmt()
+        } else if self.matches(&[TokenType::Throw]) {
+            self.throw_stmt()
+        } else if self.matches(&[TokenType::Yield]) {
+            self.yield_stmt()
+        } else if self.matches(&[TokenType::Exit]) {
+            self.exit_stmt()
+        } else if self.matches(&[TokenType::Import]) {
+            self.import_stmt()
         } else {
             self.expr_stmt()
         }
     }
 
     fn expr_stmt(&mut self) -> Result<Stmt, SyntaxError> {
+        if self.is_destructuring_assignment() {
+            return self.destructuring_assignment();
+        }
+
+        let start = self.peek().span;
         let value = self.expression()?;
 
+        if self.trailing_expression && self.is_at_end() {
+            return Ok(Stmt::Expression(Box::new(value), self.span_from(start)));
+        }
+
         self.consume(
             &TokenType::SemiColon,
             "Expect a ';' at the end of expression",
         )?;
 
-        Ok(Stmt::Expression(Box::new(value)))
+        Ok(Stmt::Expression(Box::new(value), self.span_from(start)))
     }
 
     fn print_stmt(&mut self) -> Result<Stmt, SyntaxError> {
+        let start = self.previous().span;
         let value = self.expression()?;
 
         self.consume(&TokenType::SemiColon, "Expect a ';' at the end of print")?;
 
-        Ok(Stmt::Print(Box::new(value)))
+        Ok(Stmt::Print(Box::new(value), self.span_from(start)))
     }
 
     fn variable_stmt(&mut self) -> Result<Stmt, SyntaxError> {
+        let is_const = self.previous().token_type == TokenType::Const;
+        let start = self.previous().span;
         let mut stmts = vec![];
+
+        loop {
+            let declared = if self.matches(&[TokenType::LeftBracket, TokenType::LeftBraces]) {
+                self.destructuring_declaration(is_const)
+                    .map(|declarations| stmts.extend(declarations))
+            } else {
+                self.variable_declaration(is_const)
+                    .map(|declaration| stmts.push(declaration))
+            };
+
+            // The declarations after a broken one are still checked, the
+            // statement has an error either way.
+            if let Err(error) = declared {
+                if self.skip_list_element() {
+                    continue;
+                }
+
+                if !self.check(&TokenType::SemiColon) {
+                    return Err(error);
+                }
+            }
+
+            if !self.matches(&[TokenType::Comma]) {
+                break;
+            }
+        }
+
+        self.consume(
+            &TokenType::SemiColon,
+            "Expect a ';' at the end of variable declaration",
+        )?;
+
+        Ok(Stmt::Variable(Box::new(stmts), self.span_from(start)))
+    }
+
+    fn variable_declaration(
+        &mut self,
+        is_const: bool,
+    ) -> Result<stmt::VariableDeclaration, SyntaxError> {
         let name = self
             .consume(&TokenType::Identifier, "Expect an identifier")?
             .clone();
         let initializer = if self.matches(&[TokenType::Equal]) {
             Some(self.assignment()?)
+        } else if is_const {
+            return Err(self.error(self.peek(), "Expect an initializer for constant"));
         } else {
             None
         };
 
-        stmts.push(stmt::VariableDeclaration { name, initializer });
+        Ok(stmt::VariableDeclaration {
+            name,
+            initializer,
+            is_const,
+        })
+    }
+
+    /// `[a, b, ...rest] = value` or `{x, y} = value` once past the opening
+    /// bracket, desugared into a hidden variable holding the value followed
+    /// by a variable per name reading its element or property.
+    fn destructuring_declaration(
+        &mut self,
+        is_const: bool,
+    ) -> Result<Vec<stmt::VariableDeclaration>, SyntaxError> {
+        let bracket = self.previous().clone();
+        let closing = match bracket.token_type {
+            TokenType::LeftBracket => TokenType::RightBracket,
+            _ => TokenType::RightBraces,
+        };
+        let mut names = vec![];
+        let mut rest = None;
 
-        while self.matches(&[TokenType::Comma]) {
-            let name = self
-                .consume(&TokenType::Identifier, "Expect an identifier")?
-                .clone();
-            let initializer = if self.matches(&[TokenType::Equal]) {
-                Some(self.assignment()?)
+        loop {
+            if bracket.token_type == TokenType::LeftBracket && self.matches(&[TokenType::Ellipsis])
+            {
+                rest = Some(
+                    self.consume(&TokenType::Identifier, "Expect a name after '...'")?
+                        .clone(),
+                );
+                break;
+            }
+
+            names.push(
+                self.consume(
+                    &TokenType::Identifier,
+                    "Expect a name in destructuring pattern",
+                )?
+                .clone(),
+            );
+
+            if !self.matches(&[TokenType::Comma]) {
+                break;
+            }
+        }
+
+        self.consume(&closing, "Expect the end of destructuring pattern")?;
+        self.consume(&TokenType::Equal, "Expect an initializer for destructuring")?;
+
+        let value = self.assignment()?;
+        let hidden = self.hidden_variable(&bracket);
+        let pattern = Parser::counted(&bracket, names.len());
+        let mut declarations = vec![stmt::VariableDeclaration {
+            name: hidden.clone(),
+            initializer: Some(value),
+            is_const,
+        }];
+
+        for (i, name) in names.into_iter().enumerate() {
+            declarations.push(stmt::VariableDeclaration {
+                initializer: Some(Parser::destructured(&hidden, &pattern, &name, i)),
+                name,
+                is_const,
+            });
+        }
+
+        if let Some(rest) = rest {
+            let start = declarations.len() - 1;
+
+            declarations.push(stmt::VariableDeclaration {
+                initializer: Some(Parser::destructured_rest(&hidden, &pattern, start)),
+                name: rest,
+                is_const,
+            });
+        }
+
+        Ok(declarations)
+    }
+
+    /// Whether the current tokens start a destructuring assignment, an
+    /// array pattern or an object one in parentheses, rather than an array
+    /// or a block.
+    fn is_destructuring_assignment(&self) -> bool {
+        let start = if self.check(&TokenType::LeftBracket) {
+            0
+        } else if self.check(&TokenType::LeftParenthesis)
+            && self.check_ahead(1, &TokenType::LeftBraces)
+        {
+            1
+        } else {
+            return false;
+        };
+
+        let mut depth = 0;
+
+        for (distance, token) in self.tokens[self.current + start..].iter().enumerate() {
+            match token.token_type {
+                TokenType::LeftBracket | TokenType::LeftParenthesis | TokenType::LeftBraces => {
+                    depth += 1
+                }
+                TokenType::RightParenthesis | TokenType::RightBraces | TokenType::RightBracket => {
+                    depth -= 1;
+
+                    if depth == 0 {
+                        return self.check_ahead(start + distance + 1, &TokenType::Equal);
+                    }
+                }
+                TokenType::SemiColon | TokenType::Eof => return false,
+                _ => {}
+            }
+        }
+
+        false
+    }
+
+    /// `[a, obj.b, ...rest] = value;` or `({x, y} = value);` desugared into
+    /// a block declaring a hidden variable holding the value, followed by an
+    /// assignment per target reading its element or property.
+    fn destructuring_assignment(&mut self) -> Result<Stmt, SyntaxError> {
+        let start = self.peek().span;
+        let parenthesis = self.matches(&[TokenType::LeftParenthesis]);
+        let bracket = self.advance().clone();
+        let mut targets = vec![];
+        let mut rest = None;
+
+        loop {
+            if parenthesis {
+                let name = self.consume(
+                    &TokenType::Identifier,
+                    "Expect a name in destructuring pattern",
+                )?;
+
+                targets.push((Expr::Variable(Box::new(name.clone())), name.clone()));
+            } else if self.matches(&[TokenType::Ellipsis]) {
+                rest = Some(self.call()?);
+                break;
             } else {
-                None
-            };
+                targets.push((self.call()?, bracket.clone()));
+            }
+
+            if !self.matches(&[TokenType::Comma]) {
+                break;
+            }
+        }
+
+        self.consume(
+            if parenthesis {
+                &TokenType::RightBraces
+            } else {
+                &TokenType::RightBracket
+            },
+            "Expect the end of destructuring pattern",
+        )?;
 
-            stmts.push(stmt::VariableDeclaration { name, initializer });
+        let equal = self.advance().clone();
+        let value = self.assignment()?;
+
+        if parenthesis {
+            self.consume(
+                &TokenType::RightParenthesis,
+                "Expect ')' after destructuring assignment",
+            )?;
         }
 
         self.consume(
             &TokenType::SemiColon,
-            "Expect a ';' at the end of variable declaration",
+            "Expect a ';' at the end of expression",
         )?;
 
-        Ok(Stmt::Variable(Box::new(stmts)))
+        // The statements it's desugared into all span the whole assignment.
+        let span = self.span_from(start);
+        let hidden = self.hidden_variable(&bracket);
+        let pattern = Parser::counted(&bracket, targets.len());
+        let mut stmts = vec![Stmt::Variable(
+            Box::new(vec![stmt::VariableDeclaration {
+                name: hidden.clone(),
+                initializer: Some(value),
+                is_const: true,
+            }]),
+            span,
+        )];
+
+        for (i, (target, name)) in targets.into_iter().enumerate() {
+            let value = Parser::destructured(&hidden, &pattern, &name, i);
+
+            stmts.push(Stmt::Expression(
+                Box::new(self.assignment_to(target, value, &equal)?),
+                span,
+            ));
+        }
+
+        if let Some(rest) = rest {
+            let value = Parser::destructured_rest(&hidden, &pattern, stmts.len() - 1);
+
+            stmts.push(Stmt::Expression(
+                Box::new(self.assignment_to(rest, value, &equal)?),
+                span,
+            ));
+        }
+
+        Ok(Stmt::Block(Box::new(stmts), span))
+    }
+
+    /// The variable a destructured value is kept in. It's named after the
+    /// pattern's bracket, which also keeps scripts from referring to it, and
+    /// its token type tells the formatter to print the pattern back.
+    fn hidden_variable(&mut self, bracket: &Token) -> Token {
+        let name = match bracket.token_type {
+            TokenType::LeftBracket => format!("[{}]", self.patterns),
+            _ => format!("{{{}}}", self.patterns),
+        };
+
+        self.patterns += 1;
+
+        Token::new(
+            bracket.token_type.clone(),
+            &name,
+            None,
+            bracket.line,
+            bracket.span,
+        )
+    }
+
+    /// The opening bracket of a pattern naming `count` elements, which the
+    /// reads of its elements report errors at. The count is kept as its
+    /// literal so a value too short for the pattern says so.
+    fn counted(bracket: &Token, count: usize) -> Token {
+        Token {
+            literal: Some(LiteralType::Int(count as i64)),
+            ..bracket.clone()
+        }
+    }
+
+    /// Reads the `i`th element of an array pattern's value, or the property
+    /// named by `name` in an object pattern.
+    fn destructured(hidden: &Token, pattern: &Token, name: &Token, i: usize) -> Expr {
+        let variable = hidden.clone();
+
+        let object = Expr::Variable(Box::new(variable));
+
+        match hidden.token_type {
+            TokenType::LeftBracket => Expr::Index(Box::new(expr::Index {
+                object,
+                bracket: pattern.clone(),
+                index: Expr::Literal(Box::new(Object::Int(i as i64)), hidden.span),
+                span: hidden.span,
+            })),
+            _ => Expr::Get(Box::new(expr::Get {
+                object,
+                name: name.clone(),
+                optional: false,
+            })),
+        }
+    }
+
+    /// The elements of an array pattern's value from `start` on.
+    fn destructured_rest(hidden: &Token, pattern: &Token, start: usize) -> Expr {
+        let variable = hidden.clone();
+
+        Expr::Slice(Box::new(expr::Slice {
+            object: Expr::Variable(Box::new(variable)),
+            bracket: pattern.clone(),
+            start: Some(Expr::Literal(
+                Box::new(Object::Int(start as i64)),
+                hidden.span,
+            )),
+            end: None,
+            span: hidden.span,
+        }))
     }
 
     fn block_stmt(&mut self) -> Result<Vec<Stmt>, SyntaxError> {
@@ -139,6 +487,8 @@ impl Parser {
     }
 
     fn if_stmt(&mut self) -> Result<Stmt, SyntaxError> {
+        let keyword = self.previous().clone();
+
         self.consume(&TokenType::LeftParenthesis, "Expect a '(' after if")?;
 
         let condition = self.expression()?;
@@ -153,6 +503,7 @@ impl Parser {
         };
 
         Ok(Stmt::If(Box::new(stmt::If {
+            keyword,
             condition,
             truth,
             falsy,
@@ -160,6 +511,8 @@ impl Parser {
     }
 
     fn while_stmt(&mut self) -> Result<Stmt, SyntaxError> {
+        let keyword = self.previous().clone();
+
         self.consume(&TokenType::LeftParenthesis, "Expect a '(' after while")?;
 
         let condition = self.expression()?;
@@ -171,12 +524,25 @@ impl Parser {
 
         let body = self.stmt()?;
 
-        Ok(Stmt::While(Box::new(stmt::While { condition, body })))
+        Ok(Stmt::While(Box::new(stmt::While {
+            keyword,
+            condition,
+            body,
+        })))
     }
 
     fn for_stmt(&mut self) -> Result<Stmt, SyntaxError> {
+        let keyword = self.previous().clone();
+
         self.consume(&TokenType::LeftParenthesis, "Expect a '(' after for")?;
 
+        if self.check(&TokenType::Var)
+            && self.check_ahead(1, &TokenType::Identifier)
+            && self.check_ahead(2, &TokenType::In)
+        {
+            return self.for_in_stmt(keyword.span);
+        }
+
         let initializer = if self.matches(&[TokenType::SemiColon]) {
             None
         } else if self.matches(&[TokenType::Var]) {
@@ -186,9 +552,9 @@ impl Parser {
         };
 
         let condition = if self.check(&TokenType::SemiColon) {
-            Expr::Literal(Box::new(Object::Boolean(true)))
+            None
         } else {
-            self.expression()?
+            Some(self.expression()?)
         };
 
         self.consume(
@@ -204,36 +570,67 @@ impl Parser {
 
         self.consume(&TokenType::RightParenthesis, "Expect a ')' before for body")?;
 
-        let mut body = self.stmt()?;
+        let body = self.stmt()?;
 
-        if let Some(value) = increment {
-            body = Stmt::Block(Box::new(vec![body, Stmt::Expression(Box::new(value))]));
-        }
+        Ok(Stmt::For(Box::new(stmt::For {
+            keyword,
+            initializer,
+            condition,
+            increment,
+            body,
+        })))
+    }
+
+    fn for_in_stmt(&mut self, start: Span) -> Result<Stmt, SyntaxError> {
+        self.advance();
 
-        body = Stmt::While(Box::new(stmt::While { condition, body }));
+        let name = self.advance().clone();
+        let keyword = self.advance().clone();
+        let iterable = self.expression()?;
 
-        if let Some(initializer) = initializer {
-            body = Stmt::Block(Box::new(vec![initializer, body]));
-        }
+        self.consume(&TokenType::RightParenthesis, "Expect a ')' before for body")?;
+
+        let body = self.stmt()?;
 
-        Ok(body)
+        Ok(Stmt::ForIn(Box::new(stmt::ForIn {
+            name,
+            keyword,
+            iterable,
+            body,
+            span: self.span_from(start),
+        })))
     }
 
     fn loop_control(&mut self) -> Result<Stmt, SyntaxError> {
         let token = self.previous().clone();
 
-        let result = if token.token_type == TokenType::Continue {
-            Ok(Stmt::Continue(token))
-        } else {
-            Ok(Stmt::Break(token))
-        };
-
         self.consume(&TokenType::SemiColon, "Expected ';' at end of loop control")?;
 
-        result
+        let span = self.span_from(token.span);
+
+        if token.token_type == TokenType::Continue {
+            Ok(Stmt::Continue(token, span))
+        } else {
+            Ok(Stmt::Break(token, span))
+        }
     }
 
-    fn function_stmt(&mut self, kind: &str) -> Result<Stmt, SyntaxError> {
+    fn function_stmt(&mut self, kind: &str, is_async: bool) -> Result<Stmt, SyntaxError> {
+        // The keywords before the name, `fun` after `async` or a static
+        // method's `class` before it, start the function. Methods without
+        // any start at the name.
+        let start = self.tokens[..self.current]
+            .iter()
+            .rev()
+            .take(2)
+            .take_while(|token| {
+                matches!(
+                    token.token_type,
+                    TokenType::Function | TokenType::Class | TokenType::Async
+                )
+            })
+            .last()
+            .map_or(self.peek().span, |token| token.span);
         let name = self
             .consume(&TokenType::Identifier, &format!("Expect {kind} name"))?
             .clone();
@@ -243,12 +640,64 @@ impl Parser {
             &format!("Expect '(' after {kind} name"),
         )?;
 
+        let (params, rest) = self.params(kind)?;
+
+        self.consume(
+            &TokenType::LeftBraces,
+            &format!("Expect '{{' after {kind} params"),
+        )?;
+
+        let (body, is_generator) = self.function_body()?;
+
+        Ok(Stmt::Function(Rc::new(stmt::Function {
+            name,
+            params,
+            rest,
+            body,
+            is_generator,
+            is_async,
+            span: self.span_from(start),
+            captures: RefCell::default(),
+        })))
+    }
+
+    /// Parses the block of a function after its `{`, along with whether it
+    /// yields. The yields of the functions nested in it are theirs.
+    fn function_body(&mut self) -> Result<(Vec<Stmt>, bool), SyntaxError> {
+        self.generators.push(false);
+
+        let body = self.block_stmt();
+        let is_generator = self.generators.pop().unwrap();
+
+        Ok((body?, is_generator))
+    }
+
+    /// Parses a parameter list up to and including its closing parenthesis,
+    /// a trailing `...name` is returned as the rest parameter.
+    fn params(&mut self, kind: &str) -> Result<(Vec<Token>, Option<Token>), SyntaxError> {
         let mut params = vec![];
+        let mut rest = None;
 
         if !self.check(&TokenType::RightParenthesis) {
             loop {
                 if params.len() >= 255 {
-                    Self::error(self.peek(), "Can't have more than 255 parameters");
+                    self.error(self.peek(), "Can't have more than 255 parameters");
+                }
+
+                if self.matches(&[TokenType::Ellipsis]) {
+                    rest = Some(
+                        self.consume(
+                            &TokenType::Identifier,
+                            "Expect rest parameter name after '...'",
+                        )?
+                        .clone(),
+                    );
+
+                    if self.check(&TokenType::Comma) {
+                        return Err(self.error(self.peek(), "Rest parameter must be the last one"));
+                    }
+
+                    break;
                 }
 
                 let param = self
@@ -271,21 +720,31 @@ impl Parser {
             &format!("Expect ')' after {kind} params"),
         )?;
 
-        self.consume(
-            &TokenType::LeftBraces,
-            &format!("Expect '{{' after {kind} params"),
-        )?;
+        Ok((params, rest))
+    }
 
-        let body = self.block_stmt()?;
+    fn return_stmt(&mut self) -> Result<Stmt, SyntaxError> {
+        let keyword = self.previous().clone();
+        let value = if !self.check(&TokenType::SemiColon) {
+            Some(self.expression()?)
+        } else {
+            None
+        };
 
-        Ok(Stmt::Function(Box::new(stmt::Function {
-            name,
-            params,
-            body,
+        self.consume(&TokenType::SemiColon, "Expect ';' at the end of return")?;
+
+        let span = self.span_from(keyword.span);
+
+        Ok(Stmt::Return(Box::new(stmt::Return {
+            keyword,
+            value,
+            span,
         })))
     }
 
-    fn return_stmt(&mut self) -> Result<Stmt, SyntaxError> {
+    /// Parses `yield value;`, which turns the function it's in into a
+    /// generator. One outside a function is reported by the resolver.
+    fn yield_stmt(&mut self) -> Result<Stmt, SyntaxError> {
         let keyword = self.previous().clone();
         let value = if !self.check(&TokenType::SemiColon) {
             Some(self.expression()?)
@@ -293,15 +752,46 @@ impl Parser {
             None
         };
 
-        self.consume(
-            &TokenType::SemiColon,
-            &format!("Expect ';' at the end of return"),
-        )?;
+        self.consume(&TokenType::SemiColon, "Expect ';' at the end of yield")?;
+
+        if let Some(is_generator) = self.generators.last_mut() {
+            *is_generator = true;
+        }
+
+        let span = self.span_from(keyword.span);
+
+        Ok(Stmt::Yield(Box::new(stmt::Yield {
+            keyword,
+            value,
+            span,
+        })))
+    }
+
+    fn exit_stmt(&mut self) -> Result<Stmt, SyntaxError> {
+        let keyword = self.previous().clone();
+        let code = if self.matches(&[TokenType::LeftParenthesis]) {
+            let code = self.expression()?;
 
-        Ok(Stmt::Return(Box::new(stmt::Return { keyword, value })))
+            self.consume(&TokenType::RightParenthesis, "Expect ')' after exit code")?;
+
+            Some(code)
+        } else {
+            None
+        };
+
+        self.consume(&TokenType::SemiColon, "Expect ';' at the end of exit")?;
+
+        let span = self.span_from(keyword.span);
+
+        Ok(Stmt::Exit(Box::new(stmt::Exit {
+            keyword,
+            code,
+            span,
+        })))
     }
 
     fn class_stmt(&mut self) -> Result<Stmt, SyntaxError> {
+        let start = self.previous().span;
         let name = self
             .consume(&TokenType::Identifier, "Expected an identifier after class")?
             .clone();
@@ -319,12 +809,27 @@ impl Parser {
 
         let mut methods = vec![];
         let mut statics = vec![];
+        let mut static_fields = vec![];
 
         while !self.check(&TokenType::RightBraces) {
             if self.matches(&[TokenType::Class]) {
-                statics.push(self.function_stmt("static")?);
+                if self.check_ahead(1, &TokenType::Equal)
+                    || self.check_ahead(1, &TokenType::SemiColon)
+                {
+                    static_fields.push(self.variable_declaration(false)?);
+                    self.consume(
+                        &TokenType::SemiColon,
+                        "Expect a ';' at the end of static field declaration",
+                    )?;
+                } else {
+                    let is_async = self.matches(&[TokenType::Async]);
+
+                    statics.push(self.function_stmt("static", is_async)?);
+                }
             } else {
-                methods.push(self.function_stmt("method")?);
+                let is_async = self.matches(&[TokenType::Async]);
+
+                methods.push(self.function_stmt("method", is_async)?);
             }
         }
 
@@ -333,11 +838,137 @@ impl Parser {
             "Expected '}' at the end of class body",
         )?;
 
-        Ok(Stmt::Class(Box::new(stmt::Class {
+        Ok(Stmt::Class(Rc::new(stmt::Class {
             name,
             super_class,
             methods,
             statics,
+            static_fields,
+            span: self.span_from(start),
+        })))
+    }
+
+    fn try_stmt(&mut self) -> Result<Stmt, SyntaxError> {
+        let start = self.previous().span;
+
+        let body_start = self
+            .consume(&TokenType::LeftBraces, "Expect '{' after try")?
+            .span;
+        let body = self.block_stmt()?;
+        let body_span = self.span_from(body_start);
+        let catch = if self.matches(&[TokenType::Catch]) {
+            let catch_start = self.previous().span;
+            let name = if self.matches(&[TokenType::LeftParenthesis]) {
+                let name = self
+                    .consume(&TokenType::Identifier, "Expect an identifier after catch")?
+                    .clone();
+
+                self.consume(
+                    &TokenType::RightParenthesis,
+                    "Expect ')' after catch identifier",
+                )?;
+
+                Some(name)
+            } else {
+                None
+            };
+
+            self.consume(&TokenType::LeftBraces, "Expect '{' before catch body")?;
+
+            let body = self.block_stmt()?;
+
+            Some(stmt::Catch {
+                name,
+                body,
+                span: self.span_from(catch_start),
+            })
+        } else {
+            None
+        };
+        let finally = if self.matches(&[TokenType::Finally]) {
+            self.consume(&TokenType::LeftBraces, "Expect '{' after finally")?;
+
+            Some(self.block_stmt()?)
+        } else {
+            None
+        };
+
+        if catch.is_none() && finally.is_none() {
+            return Err(self.error(self.peek(), "Expect 'catch' or 'finally' after try block"));
+        }
+
+        Ok(Stmt::Try(Box::new(stmt::Try {
+            body,
+            body_span,
+            catch,
+            finally,
+            span: self.span_from(start),
+        })))
+    }
+
+    fn throw_stmt(&mut self) -> Result<Stmt, SyntaxError> {
+        let keyword = self.previous().clone();
+        let value = self.expression()?;
+
+        self.consume(&TokenType::SemiColon, "Expect ';' at the end of throw")?;
+
+        let span = self.span_from(keyword.span);
+
+        Ok(Stmt::Throw(Box::new(stmt::Throw {
+            keyword,
+            value,
+            span,
+        })))
+    }
+
+    fn import_stmt(&mut self) -> Result<Stmt, SyntaxError> {
+        let keyword = self.previous().clone();
+        // `native` is only special here, it can still name variables.
+        let native = self.check(&TokenType::Identifier) && &*self.peek().lexeme == "native";
+
+        if native {
+            self.advance();
+        }
+
+        let message = match native {
+            true => "Expect a native module name after 'import native'",
+            false => "Expect a module path after import",
+        };
+        let path = self.consume(&TokenType::StringLiteral, message)?.clone();
+
+        self.consume(&TokenType::SemiColon, "Expect ';' at the end of import")?;
+
+        let span = self.span_from(keyword.span);
+
+        Ok(Stmt::Import(Box::new(stmt::Import {
+            keyword,
+            path,
+            native,
+            span,
+        })))
+    }
+
+    fn export_stmt(&mut self) -> Result<Stmt, SyntaxError> {
+        let keyword = self.previous().clone();
+        let declaration = if self.matches(&[TokenType::Var, TokenType::Const]) {
+            self.variable_stmt()?
+        } else if self.matches(&[TokenType::Function]) {
+            self.function_stmt("function", false)?
+        } else if self.matches(&[TokenType::Async]) {
+            self.consume(&TokenType::Function, "Expect 'fun' after 'async'")?;
+            self.function_stmt("function", true)?
+        } else if self.matches(&[TokenType::Class]) {
+            self.class_stmt()?
+        } else {
+            return Err(self.error(
+                self.peek(),
+                "Expect a variable, function or class declaration after export",
+            ));
+        };
+
+        Ok(Stmt::Export(Box::new(stmt::Export {
+            keyword,
+            declaration,
         })))
     }
 
@@ -356,97 +987,183 @@ impl Parser {
         Ok(left)
     }
 
-    fn lambda(&mut self) -> Result<Expr, SyntaxError> {
+    fn lambda(&mut self, is_async: bool) -> Result<Expr, SyntaxError> {
         let name = self.previous().clone();
+        // An async one starts at the `async` before its `fun`.
+        let start = match is_async {
+            true => self.tokens[self.current - 2].span,
+            false => name.span,
+        };
 
         self.consume(
             &TokenType::LeftParenthesis,
-            &format!("Expect '(' after anonymous function name"),
+            "Expect '(' after anonymous function name",
         )?;
 
-        let mut params = vec![];
+        let (params, rest) = self.params("anonymous function")?;
 
-        if !self.check(&TokenType::RightParenthesis) {
-            loop {
-                if params.len() >= 255 {
-                    Self::error(self.peek(), "Can't have more than 255 parameters");
-                }
+        self.consume(
+            &TokenType::LeftBraces,
+            "Expect '{' after anonymous function params",
+        )?;
 
-                let param = self
-                    .consume(
-                        &TokenType::Identifier,
-                        &format!("Expect identifier after anonymous function name"),
-                    )?
-                    .clone();
+        let (body, is_generator) = self.function_body()?;
+        let span = self.span_from(start);
 
-                params.push(param);
+        Ok(Expr::Lambda(Rc::new(expr::Lambda {
+            name,
+            params,
+            rest,
+            body,
+            is_generator,
+            is_async,
+            span,
+            captures: RefCell::default(),
+        })))
+    }
 
-                if !self.matches(&[TokenType::Comma]) {
-                    break;
-                }
-            }
-        }
+    /// Parses `(params) => expression` or `(params) => { ... }`, an expression
+    /// body is desugared to a block returning it.
+    fn arrow_function(&mut self, is_async: bool) -> Result<Expr, SyntaxError> {
+        let start = match is_async {
+            true => self.previous().span,
+            false => self.peek().span,
+        };
 
         self.consume(
-            &TokenType::RightParenthesis,
-            &format!("Expect ')' after anonymous function params"),
+            &TokenType::LeftParenthesis,
+            "Expect '(' before arrow function params",
         )?;
 
-        self.consume(
-            &TokenType::LeftBraces,
-            &format!("Expect '{{' after anonymous function params"),
-        )?;
+        let (params, rest) = self.params("arrow function")?;
+        let name = self
+            .consume(&TokenType::Arrow, "Expect '=>' after arrow function params")?
+            .clone();
 
-        let body = self.block_stmt()?;
+        let (body, is_generator) = if self.matches(&[TokenType::LeftBraces]) {
+            self.function_body()?
+        } else {
+            let value = self.assignment()?;
+            let body = vec![Stmt::Return(Box::new(stmt::Return {
+                keyword: name.clone(),
+                span: value.span(),
+                value: Some(value),
+            }))];
+
+            (body, false)
+        };
+
+        Ok(Expr::Lambda(Rc::new(expr::Lambda {
+            name,
+            params,
+            rest,
+            body,
+            is_generator,
+            is_async,
+            span: self.span_from(start),
+            captures: RefCell::default(),
+        })))
+    }
+
+    /// Whether the parenthesis at the current token opens the params of an
+    /// arrow function rather than a grouping.
+    fn is_arrow_function(&self) -> bool {
+        if !self.check(&TokenType::LeftParenthesis) {
+            return false;
+        }
 
-        Ok(Expr::Lambda(Box::new(expr::Lambda { name, params, body })))
+        let mut distance = 1;
+
+        while let Some(token) = self.tokens.get(self.current + distance) {
+            match token.token_type {
+                TokenType::Identifier | TokenType::Comma | TokenType::Ellipsis => distance += 1,
+                TokenType::RightParenthesis => {
+                    return self.check_ahead(distance + 1, &TokenType::Arrow)
+                }
+                _ => return false,
+            }
+        }
+
+        false
     }
 
     fn assignment(&mut self) -> Result<Expr, SyntaxError> {
         if self.matches(&[TokenType::Function]) {
-            return self.lambda();
+            return self.lambda(false);
+        }
+
+        if self.is_arrow_function() {
+            return self.arrow_function(false);
+        }
+
+        if self.matches(&[TokenType::Async]) {
+            if self.matches(&[TokenType::Function]) {
+                return self.lambda(true);
+            }
+
+            if self.is_arrow_function() {
+                return self.arrow_function(true);
+            }
+
+            return Err(self.error(
+                self.peek(),
+                "Expect 'fun' or an arrow function after 'async'",
+            ));
         }
 
         let variable = self.ternary()?;
 
         if self.matches(&[TokenType::Equal]) {
-            match variable {
-                Expr::Variable(variable) => {
-                    let value = self.assignment()?;
-
-                    Ok(Expr::Assignment(Box::new(expr::Assignment {
-                        name: *variable,
-                        value,
-                    })))
-                }
-                Expr::Get(get) => {
-                    let value = self.assignment()?;
-
-                    Ok(Expr::Set(Box::new(expr::Set {
-                        object: get.object,
-                        name: get.name,
-                        value,
-                    })))
-                }
-                _ => Err(Self::error(
-                    self.previous(),
-                    "Invalid left hand side in assignment",
-                )),
-            }
+            let equal = self.previous().clone();
+            let value = self.assignment()?;
+
+            self.assignment_to(variable, value, &equal)
         } else {
             Ok(variable)
         }
     }
 
+    /// Assigns `value` to `target`, errors are reported at the `equal` sign
+    /// when `target` can't be assigned to.
+    fn assignment_to(&self, target: Expr, value: Expr, equal: &Token) -> Result<Expr, SyntaxError> {
+        match target {
+            Expr::Variable(variable) => Ok(Expr::Assignment(Box::new(expr::Assignment {
+                name: *variable,
+                value,
+            }))),
+            Expr::Get(get) if get.optional => {
+                Err(self.error(equal, "Can't assign to an optional property access"))
+            }
+            Expr::Get(get) => Ok(Expr::Set(Box::new(expr::Set {
+                object: get.object,
+                name: get.name,
+                value,
+            }))),
+            Expr::Index(index) => Ok(Expr::IndexSet(Box::new(expr::IndexSet {
+                object: index.object,
+                bracket: index.bracket,
+                index: index.index,
+                value,
+            }))),
+            _ => Err(self.error(equal, "Invalid left hand side in assignment")),
+        }
+    }
+
     fn ternary(&mut self) -> Result<Expr, SyntaxError> {
-        let mut condition = self.or()?;
+        let mut condition = self.nullish()?;
 
+        // Like in C, the branches bind looser than anything but the comma, so
+        // `a ? b : c, d` is a comma expression and `a ? b : c ? d : e` nests
+        // to the right.
         if self.matches(&[TokenType::Question]) {
-            let truth = self.expression()?;
+            let truth = self.assignment()?;
 
-            self.consume(&TokenType::Colon, "Expect a ':' a falsy expression")?;
+            self.consume(
+                &TokenType::Colon,
+                "Expect a ':' before the falsy expression",
+            )?;
 
-            let falsy = self.expression()?;
+            let falsy = self.assignment()?;
 
             condition = Expr::Ternary(Box::new(expr::Ternary {
                 condition,
@@ -458,6 +1175,22 @@ impl Parser {
         Ok(condition)
     }
 
+    fn nullish(&mut self) -> Result<Expr, SyntaxError> {
+        let mut left = self.or()?;
+
+        while self.matches(&[TokenType::QuestionQuestion]) {
+            let operator = self.previous().clone();
+            let right = self.or()?;
+            left = Expr::Logical(Box::new(expr::Logical {
+                operator,
+                left,
+                right,
+            }))
+        }
+
+        Ok(left)
+    }
+
     fn or(&mut self) -> Result<Expr, SyntaxError> {
         let mut left = self.and()?;
 
@@ -493,7 +1226,12 @@ impl Parser {
     fn equality(&mut self) -> Result<Expr, SyntaxError> {
         let mut left = self.comparison()?;
 
-        while self.matches(&[TokenType::BangEqual, TokenType::EqualEqual]) {
+        while self.matches(&[
+            TokenType::BangEqual,
+            TokenType::EqualEqual,
+            TokenType::BangEqualEqual,
+            TokenType::EqualEqualEqual,
+        ]) {
             let operator = self.previous().clone();
             let right = self.comparison()?;
             left = Expr::Binary(Box::new(expr::Binary {
@@ -514,6 +1252,7 @@ impl Parser {
             TokenType::GreaterEqual,
             TokenType::Less,
             TokenType::Greater,
+            TokenType::InstanceOf,
         ]) {
             let operator = self.previous().clone();
             let right = self.term()?;
@@ -565,6 +1304,11 @@ impl Parser {
             let right = self.unary()?;
 
             Ok(Expr::Unary(Box::new(expr::Unary { operator, right })))
+        } else if self.matches(&[TokenType::Await]) {
+            let keyword = self.previous().clone();
+            let value = self.unary()?;
+
+            Ok(Expr::Await(Box::new(expr::Await { keyword, value })))
         } else {
             self.call()
         }
@@ -576,10 +1320,24 @@ impl Parser {
         if !self.check(&TokenType::RightParenthesis) {
             loop {
                 if arguments.len() >= 255 {
-                    Self::error(self.peek(), "Can't have more than 255 arguments.");
+                    self.error(self.peek(), "Can't have more than 255 arguments.");
                 }
 
-                arguments.push(self.assignment()?);
+                let argument = if self.matches(&[TokenType::Ellipsis]) {
+                    let ellipsis = self.previous().clone();
+
+                    self.assignment()
+                        .map(|value| Expr::Spread(Box::new(expr::Spread { ellipsis, value })))
+                } else {
+                    self.assignment()
+                };
+
+                match argument {
+                    Ok(argument) => arguments.push(argument),
+                    Err(_) if self.skip_list_element() => continue,
+                    Err(error) if !self.check(&TokenType::RightParenthesis) => return Err(error),
+                    Err(_) => {}
+                }
 
                 if !self.matches(&[TokenType::Comma]) {
                     break;
@@ -604,14 +1362,18 @@ impl Parser {
         loop {
             if self.matches(&[TokenType::LeftParenthesis]) {
                 callee = self.finish_call(callee)?;
-            } else if self.matches(&[TokenType::Dot]) {
+            } else if self.matches(&[TokenType::Dot, TokenType::QuestionDot]) {
+                let optional = self.previous().token_type == TokenType::QuestionDot;
                 let name = self
                     .consume(&TokenType::Identifier, "Expect property name")?
                     .clone();
                 callee = Expr::Get(Box::new(expr::Get {
                     object: callee,
                     name,
+                    optional,
                 }))
+            } else if self.matches(&[TokenType::LeftBracket]) {
+                callee = self.index(callee)?;
             } else {
                 break;
             }
@@ -620,13 +1382,149 @@ impl Parser {
         Ok(callee)
     }
 
+    /// `object[index]` or the slice `object[start:end]` once past the `[`.
+    fn index(&mut self, object: Expr) -> Result<Expr, SyntaxError> {
+        let bracket = self.previous().clone();
+        let start = if self.check(&TokenType::Colon) {
+            None
+        } else {
+            Some(self.expression()?)
+        };
+
+        if !self.matches(&[TokenType::Colon]) {
+            self.consume(&TokenType::RightBracket, "Expect ']' after index")?;
+
+            return Ok(Expr::Index(Box::new(expr::Index {
+                span: self.span_from(object.span()),
+                object,
+                bracket,
+                index: start.unwrap(),
+            })));
+        }
+
+        let end = if self.check(&TokenType::RightBracket) {
+            None
+        } else {
+            Some(self.expression()?)
+        };
+
+        self.consume(&TokenType::RightBracket, "Expect ']' after slice")?;
+
+        Ok(Expr::Slice(Box::new(expr::Slice {
+            span: self.span_from(object.span()),
+            object,
+            bracket,
+            start,
+            end,
+        })))
+    }
+
+    fn interpolation(&mut self) -> Result<Expr, SyntaxError> {
+        let start = self.previous().span;
+        let mut parts = vec![];
+
+        loop {
+            if let Some(LiteralType::String(text)) = &self.previous().literal {
+                if !text.is_empty() {
+                    parts.push(Expr::Literal(
+                        Box::new(Object::String(Rc::from(text.as_str()))),
+                        self.previous().span,
+                    ));
+                }
+            }
+
+            if self.previous().token_type == TokenType::StringLiteral {
+                break;
+            }
+
+            parts.push(self.expression()?);
+
+            if !self.matches(&[TokenType::Interpolation]) {
+                self.consume(
+                    &TokenType::StringLiteral,
+                    "Expect '}' after interpolated expression",
+                )?;
+            }
+        }
+
+        Ok(Expr::Interpolation(Box::new(expr::Interpolation {
+            parts,
+            span: self.span_from(start),
+        })))
+    }
+
+    fn array(&mut self) -> Result<Expr, SyntaxError> {
+        let bracket = self.previous().clone();
+        let mut elements = vec![];
+
+        while !self.check(&TokenType::RightBracket) {
+            elements.push(self.assignment()?);
+
+            if !self.matches(&[TokenType::Comma]) {
+                break;
+            }
+        }
+
+        self.consume(&TokenType::RightBracket, "Expect ']' after array elements")?;
+
+        let span = self.span_from(bracket.span);
+
+        Ok(Expr::Array(Box::new(expr::Array {
+            bracket,
+            elements,
+            span,
+        })))
+    }
+
+    fn map(&mut self) -> Result<Expr, SyntaxError> {
+        let brace = self.previous().clone();
+        let mut entries = vec![];
+
+        while !self.check(&TokenType::RightBraces) {
+            let key = if self.matches(&[TokenType::Identifier]) {
+                let key = Rc::from(&*self.previous().lexeme);
+
+                Expr::Literal(Box::new(Object::String(key)), self.previous().span)
+            } else {
+                self.assignment()?
+            };
+
+            self.consume(&TokenType::Colon, "Expect ':' after map key")?;
+
+            entries.push((key, self.assignment()?));
+
+            if !self.matches(&[TokenType::Comma]) {
+                break;
+            }
+        }
+
+        self.consume(&TokenType::RightBraces, "Expect '}' after map entries")?;
+
+        let span = self.span_from(brace.span);
+
+        Ok(Expr::Map(Box::new(expr::Map {
+            brace,
+            entries,
+            span,
+        })))
+    }
+
     fn primary(&mut self) -> Result<Expr, SyntaxError> {
         if self.matches(&[TokenType::LeftParenthesis]) {
+            let start = self.previous().span;
             let expression = self.expression()?;
 
             self.consume(&TokenType::RightParenthesis, "Expect a ')'")?;
 
-            return Ok(Expr::Grouping(Box::new(expression)));
+            return Ok(Expr::Grouping(Box::new(expression), self.span_from(start)));
+        }
+
+        if self.matches(&[TokenType::LeftBracket]) {
+            return self.array();
+        }
+
+        if self.matches(&[TokenType::LeftBraces]) {
+            return self.map();
         }
 
         if self.matches(&[TokenType::This]) {
@@ -636,7 +1534,19 @@ impl Parser {
         if self.matches(&[TokenType::Super]) {
             let keyword = self.previous().clone();
 
-            self.consume(&TokenType::Dot, "Expect a '.' after 'super'")?;
+            if self.check(&TokenType::LeftParenthesis) {
+                let method = Token::new(
+                    TokenType::Identifier,
+                    "init",
+                    None,
+                    keyword.line,
+                    keyword.span,
+                );
+
+                return Ok(Expr::Super(Box::new(Super { keyword, method })));
+            }
+
+            self.consume(&TokenType::Dot, "Expect a '.' or '(' after 'super'")?;
 
             let method = self
                 .consume(&TokenType::Identifier, "Expect an super class method name")?
@@ -650,22 +1560,24 @@ impl Parser {
         }
 
         if self.matches(&[TokenType::Undefined]) {
-            return Ok(Expr::Literal(Box::new(Object::Undefined)));
+            return Ok(self.literal(Object::Undefined));
         }
 
         if self.matches(&[TokenType::False]) {
-            return Ok(Expr::Literal(Box::new(Object::Boolean(false))));
+            return Ok(self.literal(Object::Boolean(false)));
         }
 
         if self.matches(&[TokenType::True]) {
-            return Ok(Expr::Literal(Box::new(Object::Boolean(true))));
+            return Ok(self.literal(Object::Boolean(true)));
         }
 
         if self.matches(&[TokenType::NumberLiteral]) {
             let number = self.previous().literal.as_ref().unwrap();
 
-            if let LiteralType::Number(value) = number {
-                return Ok(Expr::Literal(Box::new(Object::Number(*value))));
+            match number {
+                LiteralType::Int(value) => return Ok(self.literal(Object::Int(*value))),
+                LiteralType::Number(value) => return Ok(self.literal(Object::Number(*value))),
+                LiteralType::String(_) => {}
             }
         }
 
@@ -673,22 +1585,29 @@ impl Parser {
             let string = self.previous().literal.as_ref().unwrap();
 
             if let LiteralType::String(value) = string {
-                return Ok(Expr::Literal(Box::new(Object::String(String::from(value)))));
+                return Ok(self.literal(Object::String(Rc::from(value.as_str()))));
             }
         }
 
+        if self.matches(&[TokenType::Interpolation]) {
+            return self.interpolation();
+        }
+
         if self.matches(&[
             TokenType::EqualEqual,
             TokenType::BangEqual,
+            TokenType::EqualEqualEqual,
+            TokenType::BangEqualEqual,
             TokenType::LessEqual,
             TokenType::Less,
             TokenType::GreaterEqual,
             TokenType::Greater,
+            TokenType::InstanceOf,
             TokenType::Plus,
             TokenType::Star,
             TokenType::Slash,
         ]) {
-            Self::error(
+            self.error(
                 self.previous(),
                 "Expect expression on left side of binary expression",
             );
@@ -696,7 +1615,17 @@ impl Parser {
             return self.expression();
         }
 
-        Err(Self::error(&self.peek(), "Expect an expression"))
+        Err(self.error(self.peek(), "Expect an expression"))
+    }
+
+    /// A literal spanning the last token consumed.
+    fn literal(&self, value: Object) -> Expr {
+        Expr::Literal(Box::new(value), self.previous().span)
+    }
+
+    /// The span from `start` to the end of the last token consumed.
+    fn span_from(&self, start: Span) -> Span {
+        start.to(self.previous().span)
     }
 
     fn matches(&mut self, tokens: &[TokenType]) -> bool {
@@ -716,7 +1645,14 @@ impl Parser {
             return Ok(self.advance());
         }
 
-        Err(Self::error(self.peek(), message))
+        Err(self.error(self.peek(), message))
+    }
+
+    fn check_ahead(&self, distance: usize, token: &TokenType) -> bool {
+        match self.tokens.get(self.current + distance) {
+            Some(next) => &next.token_type == token,
+            None => false,
+        }
     }
 
     fn check(&self, token: &TokenType) -> bool {
@@ -747,12 +1683,52 @@ impl Parser {
         &self.tokens[self.current - 1]
     }
 
-    fn error(token: &Token, message: &str) -> SyntaxError {
-        Lib::error_token(token, message);
+    fn error(&self, token: &Token, message: &str) -> SyntaxError {
+        self.diagnostics.borrow_mut().push(Diagnostic::at_token(
+            Severity::Error,
+            "syntax-error",
+            token,
+            message,
+        ));
 
         SyntaxError
     }
 
+    /// Skips what's left of an element of a comma separated list that failed
+    /// to parse, so the following ones are checked too. Returns whether the
+    /// comma before the next element was consumed, otherwise it stops before
+    /// whatever ends the list.
+    fn skip_list_element(&mut self) -> bool {
+        let mut depth = 0usize;
+
+        while !self.is_at_end() {
+            match self.peek().token_type {
+                TokenType::LeftParenthesis | TokenType::LeftBraces | TokenType::LeftBracket => {
+                    depth += 1;
+                }
+                TokenType::RightParenthesis | TokenType::RightBraces | TokenType::RightBracket
+                    if depth > 0 =>
+                {
+                    depth -= 1;
+                }
+                TokenType::Comma if depth == 0 => {
+                    self.advance();
+
+                    return true;
+                }
+                TokenType::RightParenthesis | TokenType::RightBraces | TokenType::RightBracket => {
+                    return false;
+                }
+                TokenType::SemiColon if depth == 0 => return false,
+                _ => {}
+            }
+
+            self.advance();
+        }
+
+        false
+    }
+
     fn synchronize(&mut self) {
         self.advance();
 
@@ -765,13 +1741,21 @@ impl Parser {
                 TokenType::Class
                 | TokenType::Function
                 | TokenType::Var
+                | TokenType::Const
                 | TokenType::For
                 | TokenType::If
                 | TokenType::While
                 | TokenType::Print
                 | TokenType::Return
                 | TokenType::Continue
-                | TokenType::Break => {
+                | TokenType::Break
+                | TokenType::Try
+                | TokenType::Throw
+                | TokenType::Yield
+                | TokenType::Async
+                | TokenType::Exit
+                | TokenType::Import
+                | TokenType::Export => {
                     return;
                 }
                 _ => {
@@ -1,11 +1,17 @@
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
 
 use crate::{
+    environment::{CLASS_SLOT, SUPER_SLOT, THIS_SLOT},
+    errors::{Diagnostic, Lint, Severity},
     expr::{self, Expr, ExprVisitor},
     object::{Object, ResolvableFunction},
     stmt::{self, Stmt, StmtVisitor},
-    token::Token,
-    Interpreter, Lib,
+    token::{Span, Token},
+    token_type::TokenType,
+    utils::is_truthy,
 };
 
 #[derive(Clone)]
@@ -17,6 +23,24 @@ enum FunctionType {
     None,
 }
 
+/// A variable declared in a scope, stored in `slot` of the environment the
+/// scope runs in.
+struct Local {
+    is_defined: bool,
+    slot: usize,
+}
+
+/// A function being resolved. The scopes from `boundary` on are its own,
+/// variables declared further out are captured into the slots of its
+/// closure, in order, along with where they are from the environment the
+/// function is declared in.
+struct FunctionScope {
+    boundary: usize,
+    captures: Vec<(String, (usize, usize))>,
+    /// Whether it's declared `async`, only those can `await`.
+    is_async: bool,
+}
+
 #[derive(Clone)]
 enum ClassType {
     None,
@@ -24,40 +48,250 @@ enum ClassType {
     SubClass,
 }
 
-pub struct Resolver<'a> {
-    interpreter: &'a mut Interpreter,
-    scopes: Vec<HashMap<String, bool>>,
-    unused_variables: Vec<HashMap<String, Token>>,
+pub struct Resolver {
+    scopes: Vec<HashMap<String, Local>>,
+    /// The number of slots used by each scope.
+    slots: Vec<usize>,
+    functions: Vec<FunctionScope>,
+    unused_variables: Vec<HashMap<String, (Token, Lint)>>,
+    globals: HashSet<String>,
+    global_functions: Vec<Token>,
+    global_references: HashSet<String>,
+    constants: Vec<HashSet<String>>,
+    global_constants: HashSet<String>,
     function_type: FunctionType,
     class_type: ClassType,
+    class_name: Option<String>,
+    /// The names of the classes declared so far, their private statics are
+    /// only reachable from inside them.
+    classes: HashSet<String>,
     loop_depth: usize,
     function_depth: usize,
+    /// Whether the initializer being resolved calls the super class's one.
+    calls_super_init: bool,
+    /// Where the `await` the statement being resolved starts with is, the
+    /// only place one can be, see [`Stmt::awaited`].
+    placed_await: Option<Span>,
+    diagnostics: Vec<Diagnostic>,
 }
 
-impl<'a> Resolver<'a> {
-    pub fn new(interpreter: &'a mut Interpreter) -> Self {
+impl Resolver {
+    pub fn new() -> Self {
         Self {
-            interpreter,
             scopes: vec![],
+            slots: vec![],
+            functions: vec![],
             unused_variables: vec![],
+            globals: HashSet::new(),
+            global_functions: vec![],
+            global_references: HashSet::new(),
+            constants: vec![],
+            global_constants: HashSet::new(),
             function_type: FunctionType::None,
             class_type: ClassType::None,
+            class_name: None,
+            classes: HashSet::new(),
             loop_depth: 0,
             function_depth: 0,
+            calls_super_init: false,
+            placed_await: None,
+            diagnostics: vec![],
         }
     }
 
+    /// Resolves a whole program, returning every error and warning found.
+    pub fn resolve(mut self, stmts: &[Stmt]) -> Vec<Diagnostic> {
+        self.resolve_stmts(stmts);
+
+        // Top level functions can be referenced before they're declared, so
+        // they're only known to be unused once the whole program is resolved.
+        for function in std::mem::take(&mut self.global_functions) {
+            if !self.global_references.contains(&*function.lexeme) {
+                self.lint(Lint::UnusedFunction, &function, "Unused function");
+            }
+        }
+
+        self.diagnostics
+    }
+
+    fn error(&mut self, token: &Token, message: &str) {
+        self.diagnostics.push(Diagnostic::at_token(
+            Severity::Error,
+            "resolve-error",
+            token,
+            message,
+        ));
+    }
+
+    fn lint(&mut self, lint: Lint, token: &Token, message: &str) {
+        self.diagnostics.push(
+            Diagnostic::at_token(Severity::Warning, lint.name(), token, message).with_lint(lint),
+        );
+    }
+
     fn resolve_expression(&mut self, expr: &Expr) {
         expr.accept(self)
     }
 
     fn resolve_stmt(&mut self, stmt: &Stmt) {
+        self.placed_await = stmt.awaited().map(|awaited| awaited.keyword.span);
+
         stmt.accept(self)
     }
 
-    pub fn resolve_stmts(&mut self, stmts: &Vec<Stmt>) {
+    fn resolve_stmts(&mut self, stmts: &[Stmt]) {
+        let jump = stmts.iter().enumerate().find_map(|(i, stmt)| match stmt {
+            Stmt::Return(stmt) => Some((i, &stmt.keyword)),
+            Stmt::Throw(stmt) => Some((i, &stmt.keyword)),
+            Stmt::Exit(stmt) => Some((i, &stmt.keyword)),
+            Stmt::Break(keyword, _) | Stmt::Continue(keyword, _) => Some((i, keyword)),
+            Stmt::While(stmt)
+                if Resolver::is_always_true(&stmt.condition) && !Resolver::breaks(&stmt.body) =>
+            {
+                Some((i, &stmt.keyword))
+            }
+            Stmt::For(stmt)
+                if stmt.condition.as_ref().is_none_or(Resolver::is_always_true)
+                    && !Resolver::breaks(&stmt.body) =>
+            {
+                Some((i, &stmt.keyword))
+            }
+            _ => None,
+        });
+
+        if let Some((i, keyword)) = jump {
+            if stmts[i + 1..]
+                .iter()
+                .any(|stmt| !matches!(stmt, Stmt::Empty(_)))
+            {
+                let message = match keyword.token_type {
+                    TokenType::While | TokenType::For => {
+                        format!(
+                            "Unreachable code after a '{}' loop that never ends",
+                            keyword.lexeme
+                        )
+                    }
+                    _ => format!("Unreachable code after '{}'", keyword.lexeme),
+                };
+
+                self.lint(Lint::UnreachableCode, keyword, &message);
+            }
+        }
+
         for stmt in stmts {
-            stmt.accept(self)
+            self.resolve_stmt(stmt)
+        }
+    }
+
+    fn is_always_true(condition: &Expr) -> bool {
+        match condition {
+            Expr::Grouping(inner, _) => Resolver::is_always_true(inner),
+            Expr::Literal(value, _) => is_truthy(value),
+            _ => false,
+        }
+    }
+
+    /// Whether the loop body `stmt` has a `break` ending the loop, the ones
+    /// of nested loops end those instead.
+    fn breaks(stmt: &Stmt) -> bool {
+        match stmt {
+            Stmt::Break(..) => true,
+            Stmt::Block(stmts, _) => stmts.iter().any(Resolver::breaks),
+            Stmt::If(stmt) => {
+                Resolver::breaks(&stmt.truth) || stmt.falsy.as_ref().is_some_and(Resolver::breaks)
+            }
+            Stmt::Try(stmt) => stmt
+                .body
+                .iter()
+                .chain(stmt.catch.iter().flat_map(|catch| &catch.body))
+                .chain(stmt.finally.iter().flatten())
+                .any(Resolver::breaks),
+            _ => false,
+        }
+    }
+
+    /// Reports a branch or loop body that does nothing.
+    fn check_body(&mut self, keyword: &Token, body: &Stmt) {
+        if matches!(body, Stmt::Block(stmts, _) if stmts.is_empty()) {
+            self.lint(Lint::EmptyBlock, keyword, "Empty block");
+        }
+    }
+
+    /// Reports a condition that's a literal, so always takes the same branch.
+    fn check_condition(&mut self, keyword: &Token, condition: &Expr) {
+        let mut condition = condition;
+
+        while let Expr::Grouping(inner, _) = condition {
+            condition = inner;
+        }
+
+        let Expr::Literal(value, _) = condition else {
+            return;
+        };
+
+        // `while (true)` is the usual way of writing an endless loop.
+        if keyword.token_type != TokenType::If && matches!(**value, Object::Boolean(true)) {
+            return;
+        }
+
+        let message = if is_truthy(value) {
+            "Condition is always true"
+        } else {
+            "Condition is always false"
+        };
+
+        self.lint(Lint::ConstantCondition, keyword, message);
+    }
+
+    /// Reports `==` or `!=` between operands known to be of different types,
+    /// which either coerce a boolean to a number or are never equal.
+    fn check_equality(&mut self, expr: &expr::Binary) {
+        let (Some(left), Some(right)) = (
+            Resolver::static_type(&expr.left),
+            Resolver::static_type(&expr.right),
+        ) else {
+            return;
+        };
+
+        if left != right {
+            let message = format!(
+                "Loose comparison of {left} and {right}, use '{}=' to compare without coercion",
+                expr.operator.lexeme
+            );
+
+            self.lint(Lint::LooseEquality, &expr.operator, &message);
+        }
+    }
+
+    /// The type `expr` always evaluates to, if it can be told without
+    /// running it. Integers and floats are both numbers.
+    fn static_type(expr: &Expr) -> Option<&'static str> {
+        match expr {
+            Expr::Grouping(inner, _) => Resolver::static_type(inner),
+            Expr::Literal(value, _) => match **value {
+                Object::Int(_) | Object::Number(_) => Some("a number"),
+                Object::Boolean(_) => Some("a boolean"),
+                Object::String(_) => Some("a string"),
+                Object::Undefined => Some("undefined"),
+                _ => None,
+            },
+            Expr::Interpolation(_) => Some("a string"),
+            Expr::Array(_) => Some("an array"),
+            Expr::Map(_) => Some("a map"),
+            Expr::Unary(unary) if unary.operator.token_type == TokenType::Bang => Some("a boolean"),
+            Expr::Binary(binary) => match binary.operator.token_type {
+                TokenType::EqualEqual
+                | TokenType::BangEqual
+                | TokenType::EqualEqualEqual
+                | TokenType::BangEqualEqual
+                | TokenType::Less
+                | TokenType::LessEqual
+                | TokenType::Greater
+                | TokenType::GreaterEqual
+                | TokenType::InstanceOf => Some("a boolean"),
+                _ => None,
+            },
+            _ => None,
         }
     }
 
@@ -65,79 +299,258 @@ impl<'a> Resolver<'a> {
         &mut self,
         function: &T,
         function_type: FunctionType,
+    ) {
+        self.resolve_function_from(function, function_type, self.scopes.len());
+    }
+
+    /// Resolves a function whose own scopes start at `boundary`, methods
+    /// include the scope binding `this`.
+    fn resolve_function_from<T: ResolvableFunction>(
+        &mut self,
+        function: &T,
+        function_type: FunctionType,
+        boundary: usize,
     ) {
         let enclosing = self.function_type.clone();
         self.function_type = function_type;
         self.function_depth += 1;
+        self.functions.push(FunctionScope {
+            boundary,
+            captures: vec![],
+            is_async: function.is_async(),
+        });
         self.begin_scope();
 
-        for param in function.params() {
-            self.declare(param);
+        for param in function.params().iter().chain(function.rest()) {
+            if self.scopes.last().unwrap().contains_key(&*param.lexeme) {
+                self.error(param, &format!("Duplicate parameter '{}'", param.lexeme));
+                self.add_local(param);
+            } else {
+                self.declare(param);
+            }
+
+            if let Some((_, lint)) = self
+                .unused_variables
+                .last_mut()
+                .unwrap()
+                .get_mut(&*param.lexeme)
+            {
+                *lint = Lint::UnusedParameter;
+            }
+
             self.define(param);
         }
 
         self.resolve_stmts(function.body());
         self.end_scope();
+
+        let scope = self.functions.pop().unwrap();
+
+        *function.captures().borrow_mut() = scope
+            .captures
+            .into_iter()
+            .map(|(_, location)| location)
+            .collect();
         self.function_depth -= 1;
         self.function_type = enclosing;
     }
 
     fn resolve_local(&mut self, name: &Token) {
-        for i in (0..self.scopes.len()).rev() {
-            if self.scopes[i].contains_key(&name.lexeme) {
-                self.unused_variables[i].remove(&name.lexeme);
-                self.interpreter.resolve(
-                    &name.identifier_hash.as_ref().unwrap(),
-                    self.scopes.len() - 1 - i,
-                );
+        self.resolve_name(&name.lexeme, name);
+    }
+
+    /// Resolves the variable `name` for the expression `token` belongs to.
+    fn resolve_name(&mut self, name: &str, token: &Token) {
+        let Some(scope) = self
+            .scopes
+            .iter()
+            .rposition(|scope| scope.contains_key(name))
+        else {
+            self.global_references.insert(String::from(name));
+
+            return;
+        };
+
+        self.unused_variables[scope].remove(name);
+
+        let (distance, slot) = self.locate(name, scope, self.functions.len(), self.scopes.len());
+
+        token.location.set(Some((distance, slot)));
+    }
+
+    /// The distance from the innermost of the first `top` scopes to the
+    /// variable `name` declared in `scope` and its slot there, looking
+    /// through the first `functions` functions. A variable declared outside
+    /// the innermost one is in its closure, right past its own scopes, and is
+    /// captured by every function it's declared outside of.
+    fn locate(&mut self, name: &str, scope: usize, functions: usize, top: usize) -> (usize, usize) {
+        let Some(function) = functions.checked_sub(1) else {
+            return (top - 1 - scope, self.scopes[scope][name].slot);
+        };
+        let boundary = self.functions[function].boundary;
+
+        if scope >= boundary {
+            return (top - 1 - scope, self.scopes[scope][name].slot);
+        }
+
+        let captures = &self.functions[function].captures;
+        let slot = match captures.iter().position(|(captured, _)| captured == name) {
+            Some(slot) => slot,
+            None => {
+                let location = self.locate(name, scope, function, boundary);
+                let captures = &mut self.functions[function].captures;
+
+                captures.push((String::from(name), location));
+                captures.len() - 1
             }
+        };
+
+        (top - boundary, slot)
+    }
+
+    /// The private statics of a class can only be reached through its name
+    /// from inside it. Any other object may be a map, whose keys can start
+    /// with an underscore too, so instances are checked when running.
+    fn check_private(&mut self, object: &Expr, name: &Token) {
+        let Expr::Variable(class) = object else {
+            return;
+        };
+
+        if name.is_private()
+            && self.classes.contains(&*class.lexeme)
+            && self.class_name.as_deref() != Some(&*class.lexeme)
+        {
+            self.error(name, "Can't access a private member outside of its class");
+        }
+    }
+
+    fn is_const(&self, name: &Token) -> bool {
+        match self
+            .scopes
+            .iter()
+            .rposition(|scope| scope.contains_key(&*name.lexeme))
+        {
+            Some(i) => self.constants[i].contains(&*name.lexeme),
+            None => self.global_constants.contains(&*name.lexeme),
         }
     }
 
     fn begin_scope(&mut self) {
         self.unused_variables.push(HashMap::new());
+        self.constants.push(HashSet::new());
         self.scopes.push(HashMap::new());
+        self.slots.push(0);
     }
 
     fn end_scope(&mut self) {
         if let Some(unused_vars) = self.unused_variables.pop() {
-            for unused in unused_vars.into_values() {
-                Lib::warn_token(&unused, "Unused variable");
+            let mut unused_vars = unused_vars.into_values().collect::<Vec<_>>();
+
+            unused_vars.sort_by_key(|(token, _)| token.span.start);
+
+            for (token, lint) in unused_vars {
+                let message = match lint {
+                    Lint::UnusedFunction => "Unused function",
+                    Lint::UnusedParameter => "Unused parameter",
+                    _ => "Unused variable",
+                };
+
+                self.lint(lint, &token, message);
             }
         }
 
+        self.constants.pop();
         self.scopes.pop();
+        self.slots.pop();
     }
 
     fn declare(&mut self, name: &Token) {
         if self.scopes.is_empty() {
+            self.global_constants.remove(&*name.lexeme);
+            self.globals.insert(name.lexeme.to_string());
+
             return;
         }
 
-        self.unused_variables
-            .last_mut()
-            .unwrap()
-            .insert(String::from(&name.lexeme), name.clone());
+        let (scope, outer_scopes) = self.scopes.split_last().unwrap();
+
+        if scope.contains_key(&*name.lexeme) {
+            let message = format!("'{}' is already declared in this scope", name.lexeme);
+
+            self.error(name, &message);
+        } else if self.globals.contains(&*name.lexeme)
+            || outer_scopes
+                .iter()
+                .any(|scope| scope.contains_key(&*name.lexeme))
+        {
+            self.lint(
+                Lint::ShadowedVariable,
+                name,
+                "Shadows a variable declared in an outer scope",
+            );
+        }
 
-        self.scopes
-            .last_mut()
-            .unwrap()
-            .insert(String::from(&name.lexeme), false);
+        self.add_local(name);
     }
 
-    fn define(&mut self, name: &Token) {
-        if self.scopes.is_empty() {
-            return;
+    /// Declares `name` in the innermost scope without checking it against
+    /// the other declarations.
+    fn add_local(&mut self, name: &Token) {
+        self.constants.last_mut().unwrap().remove(&*name.lexeme);
+
+        // Names starting with an underscore are meant to go unused.
+        if !name.lexeme.starts_with('_') {
+            self.unused_variables.last_mut().unwrap().insert(
+                name.lexeme.to_string(),
+                (name.clone(), Lint::UnusedVariable),
+            );
         }
 
-        self.scopes
+        // Every declaration gets a slot of its own, even an erroneous one
+        // redeclaring a variable of the same scope.
+        let slots = self.slots.last_mut().unwrap();
+        let slot = *slots;
+
+        *slots += 1;
+
+        self.scopes.last_mut().unwrap().insert(
+            name.lexeme.to_string(),
+            Local {
+                is_defined: false,
+                slot,
+            },
+        );
+        name.location.set(Some((0, slot)));
+    }
+
+    fn define(&mut self, name: &Token) {
+        if let Some(local) = self
+            .scopes
             .last_mut()
-            .unwrap()
-            .insert(String::from(&name.lexeme), true);
+            .and_then(|scope| scope.get_mut(&*name.lexeme))
+        {
+            local.is_defined = true;
+        }
+    }
+
+    /// Declares a variable the interpreter defines in `slot` itself, such as
+    /// `this`.
+    fn declare_implicit(&mut self, name: &str, slot: usize) {
+        let slots = self.slots.last_mut().unwrap();
+
+        *slots = (*slots).max(slot + 1);
+
+        self.scopes.last_mut().unwrap().insert(
+            String::from(name),
+            Local {
+                is_defined: true,
+                slot,
+            },
+        );
     }
 }
 
-impl<'a> ExprVisitor for Resolver<'a> {
+impl ExprVisitor for Resolver {
     type Item = ();
 
     fn visit_comma(&mut self, expr: &expr::Comma) -> Self::Item {
@@ -145,18 +558,30 @@ impl<'a> ExprVisitor for Resolver<'a> {
         self.resolve_expression(&expr.right);
     }
 
-    fn visit_lambda(&mut self, expr: &expr::Lambda) -> Self::Item {
-        self.resolve_function(expr, FunctionType::Function);
+    fn visit_lambda(&mut self, expr: &Rc<expr::Lambda>) -> Self::Item {
+        self.resolve_function(expr.as_ref(), FunctionType::Function);
     }
 
     fn visit_assignment(&mut self, expr: &expr::Assignment) -> Self::Item {
         self.resolve_expression(&expr.value);
+
+        if self.is_const(&expr.name) {
+            self.error(&expr.name, "Can't assign to a constant");
+        }
+
         self.resolve_local(&expr.name);
     }
 
     fn visit_set(&mut self, expr: &expr::Set) -> Self::Item {
         self.resolve_expression(&expr.value);
         self.resolve_expression(&expr.object);
+        self.check_private(&expr.object, &expr.name);
+    }
+
+    fn visit_index_set(&mut self, expr: &expr::IndexSet) -> Self::Item {
+        self.resolve_expression(&expr.value);
+        self.resolve_expression(&expr.index);
+        self.resolve_expression(&expr.object);
     }
 
     fn visit_ternary(&mut self, expr: &expr::Ternary) -> Self::Item {
@@ -171,6 +596,13 @@ impl<'a> ExprVisitor for Resolver<'a> {
     }
 
     fn visit_binary(&mut self, expr: &expr::Binary) -> Self::Item {
+        if matches!(
+            expr.operator.token_type,
+            TokenType::EqualEqual | TokenType::BangEqual
+        ) {
+            self.check_equality(expr);
+        }
+
         self.resolve_expression(&expr.left);
         self.resolve_expression(&expr.right);
     }
@@ -179,26 +611,64 @@ impl<'a> ExprVisitor for Resolver<'a> {
         self.resolve_expression(&expr.right);
     }
 
+    fn visit_await(&mut self, expr: &expr::Await) -> Self::Item {
+        match self.functions.last() {
+            Some(function) if function.is_async => {
+                if self.placed_await != Some(expr.keyword.span) {
+                    self.error(
+                        &expr.keyword,
+                        "An 'await' can only be a statement's whole expression, the value printed, assigned or returned, or a variable's initializer",
+                    );
+                }
+            }
+            _ => self.error(&expr.keyword, "Can't use 'await' outside an async function"),
+        }
+
+        self.resolve_expression(&expr.value);
+    }
+
     fn visit_call(&mut self, expr: &expr::Call) -> Self::Item {
         self.resolve_expression(&expr.callee);
 
         for arg in &expr.arguments {
-            self.resolve_expression(&arg);
+            self.resolve_expression(arg);
         }
     }
 
     fn visit_get(&mut self, expr: &expr::Get) -> Self::Item {
         self.resolve_expression(&expr.object);
+        self.check_private(&expr.object, &expr.name);
+
+        // Private methods are looked up in the class declaring the method.
+        if matches!(expr.object, Expr::This(_)) && expr.name.is_private() {
+            self.resolve_name("class", &expr.name);
+        }
+    }
+
+    fn visit_index(&mut self, expr: &expr::Index) -> Self::Item {
+        self.resolve_expression(&expr.object);
+        self.resolve_expression(&expr.index);
+    }
+
+    fn visit_slice(&mut self, expr: &expr::Slice) -> Self::Item {
+        self.resolve_expression(&expr.object);
+
+        for bound in [&expr.start, &expr.end].into_iter().flatten() {
+            self.resolve_expression(bound);
+        }
     }
 
-    fn visit_grouping(&mut self, expr: &Expr) -> Self::Item {
+    fn visit_grouping(&mut self, expr: &Expr, _span: Span) -> Self::Item {
         self.resolve_expression(expr);
     }
 
     fn visit_variable(&mut self, expr: &Token) -> Self::Item {
         if !self.scopes.is_empty() {
-            if let Some(&false) = self.scopes.last().unwrap().get(&expr.lexeme) {
-                Lib::error_token(expr, "Can't read local variable in its own initializer.");
+            if let Some(Local {
+                is_defined: false, ..
+            }) = self.scopes.last().unwrap().get(&*expr.lexeme)
+            {
+                self.error(expr, "Can't read local variable in its own initializer.");
             }
         }
 
@@ -207,11 +677,11 @@ impl<'a> ExprVisitor for Resolver<'a> {
 
     fn visit_this(&mut self, expr: &Token) -> Self::Item {
         if matches!(self.class_type, ClassType::None) {
-            Lib::error_token(&expr, "Can't use 'this' outside a class method");
+            self.error(expr, "Can't use 'this' outside a class method");
         }
 
         if matches!(self.function_type, FunctionType::Static) {
-            Lib::error_token(&expr, "Can't use 'this' inside a static method");
+            self.error(expr, "Can't use 'this' inside a static method");
         }
 
         self.resolve_local(expr);
@@ -219,40 +689,93 @@ impl<'a> ExprVisitor for Resolver<'a> {
 
     fn visit_super(&mut self, expr: &expr::Super) -> Self::Item {
         if matches!(self.class_type, ClassType::None) {
-            Lib::error_token(&expr.keyword, "Can't use 'super' outside a class method");
+            self.error(&expr.keyword, "Can't use 'super' outside a class method");
         }
 
         if matches!(self.class_type, ClassType::Class) {
-            Lib::error_token(
+            self.error(
                 &expr.keyword,
                 "Can't use 'super' inside a class with no super class",
             );
         }
 
-        if matches!(self.function_type, FunctionType::Static) {
-            Lib::error_token(&expr.keyword, "Can't use 'super' inside a static method");
+        if expr.method.is_private() {
+            self.error(
+                &expr.method,
+                "Can't access a private method through 'super'",
+            );
+        }
+
+        if expr.is_shorthand() && !matches!(self.function_type, FunctionType::Initializer) {
+            self.error(&expr.keyword, "Can't call 'super()' outside an initializer");
+        }
+
+        if &*expr.method.lexeme == "init" {
+            self.calls_super_init = true;
         }
 
         self.resolve_local(&expr.keyword);
+
+        // Statics look the member up among the super class's statics, the
+        // method token is resolved against the class instead of `this`.
+        if matches!(self.function_type, FunctionType::Static) {
+            if !self.scopes.iter().any(|scope| scope.contains_key("super")) {
+                self.error(
+                    &expr.keyword,
+                    "Can't use 'super' in a static field initializer",
+                );
+            }
+
+            self.resolve_name("class", &expr.method);
+        } else {
+            self.resolve_name("this", &expr.method);
+        }
+    }
+
+    fn visit_array(&mut self, expr: &expr::Array) -> Self::Item {
+        for element in &expr.elements {
+            self.resolve_expression(element);
+        }
+    }
+
+    fn visit_map(&mut self, expr: &expr::Map) -> Self::Item {
+        for (key, value) in &expr.entries {
+            self.resolve_expression(key);
+            self.resolve_expression(value);
+        }
     }
 
-    fn visit_literal(&mut self, _: &Object) -> Self::Item {}
+    fn visit_spread(&mut self, expr: &expr::Spread) -> Self::Item {
+        self.resolve_expression(&expr.value);
+    }
+
+    fn visit_interpolation(&mut self, expr: &expr::Interpolation) -> Self::Item {
+        for part in &expr.parts {
+            self.resolve_expression(part);
+        }
+    }
+
+    fn visit_literal(&mut self, _: &Object, _span: Span) -> Self::Item {}
 }
 
-impl<'a> StmtVisitor for Resolver<'a> {
+impl StmtVisitor for Resolver {
     type Item = ();
 
-    fn visit_empty_stmt(&mut self) -> Self::Item {}
+    fn visit_empty_stmt(&mut self, _span: Span) -> Self::Item {}
 
-    fn visit_expression_stmt(&mut self, stmt: &Expr) -> Self::Item {
+    fn visit_expression_stmt(&mut self, stmt: &Expr, _span: Span) -> Self::Item {
         self.resolve_expression(stmt);
     }
 
-    fn visit_print_stmt(&mut self, stmt: &Expr) -> Self::Item {
+    fn visit_print_stmt(&mut self, stmt: &Expr, _span: Span) -> Self::Item {
         self.resolve_expression(stmt);
     }
 
-    fn visit_variable_stmt(&mut self, stmt: &Vec<stmt::VariableDeclaration>) -> Self::Item {
+    fn visit_variable_stmt(
+        &mut self,
+        stmt: &[stmt::VariableDeclaration],
+        _span: Span,
+    ) -> Self::Item {
         for variable in stmt {
             self.declare(&variable.name);
 
@@ -261,69 +784,132 @@ impl<'a> StmtVisitor for Resolver<'a> {
             }
 
             self.define(&variable.name);
+
+            if variable.is_const {
+                match self.constants.last_mut() {
+                    Some(constants) => constants.insert(variable.name.lexeme.to_string()),
+                    None => self
+                        .global_constants
+                        .insert(variable.name.lexeme.to_string()),
+                };
+            }
         }
     }
 
-    fn visit_block_stmt(&mut self, stmt: &Vec<Stmt>) -> Self::Item {
+    fn visit_block_stmt(&mut self, stmt: &[Stmt], _span: Span) -> Self::Item {
         self.begin_scope();
         self.resolve_stmts(stmt);
         self.end_scope();
     }
 
     fn visit_if_stmt(&mut self, stmt: &stmt::If) -> Self::Item {
+        self.check_condition(&stmt.keyword, &stmt.condition);
+        self.check_body(&stmt.keyword, &stmt.truth);
         self.resolve_expression(&stmt.condition);
         self.resolve_stmt(&stmt.truth);
 
         if let Some(falsy) = &stmt.falsy {
+            self.check_body(&stmt.keyword, falsy);
             self.resolve_stmt(falsy);
         }
     }
 
     fn visit_while_stmt(&mut self, stmt: &stmt::While) -> Self::Item {
+        self.check_condition(&stmt.keyword, &stmt.condition);
+        self.check_body(&stmt.keyword, &stmt.body);
         self.loop_depth += 1;
         self.resolve_expression(&stmt.condition);
         self.resolve_stmt(&stmt.body);
         self.loop_depth -= 1;
     }
 
-    fn visit_break_stmt(&mut self, keyword: &Token) -> Self::Item {
+    fn visit_for_stmt(&mut self, stmt: &stmt::For) -> Self::Item {
+        if let Some(condition) = &stmt.condition {
+            self.check_condition(&stmt.keyword, condition);
+        }
+
+        self.check_body(&stmt.keyword, &stmt.body);
+        self.begin_scope();
+
+        if let Some(initializer) = &stmt.initializer {
+            self.resolve_stmt(initializer);
+        }
+
+        self.loop_depth += 1;
+
+        if let Some(condition) = &stmt.condition {
+            self.resolve_expression(condition);
+        }
+
+        if let Some(increment) = &stmt.increment {
+            self.resolve_expression(increment);
+        }
+
+        self.resolve_stmt(&stmt.body);
+        self.loop_depth -= 1;
+        self.end_scope();
+    }
+
+    fn visit_for_in_stmt(&mut self, stmt: &stmt::ForIn) -> Self::Item {
+        self.check_body(&stmt.keyword, &stmt.body);
+        self.resolve_expression(&stmt.iterable);
+        self.loop_depth += 1;
+        self.begin_scope();
+        self.declare(&stmt.name);
+        self.define(&stmt.name);
+        self.resolve_stmt(&stmt.body);
+        self.end_scope();
+        self.loop_depth -= 1;
+    }
+
+    fn visit_break_stmt(&mut self, keyword: &Token, _span: Span) -> Self::Item {
         if self.loop_depth == 0 {
-            Lib::error_token(keyword, "Can't use 'break' outside a loop");
+            self.error(keyword, "Can't use 'break' outside a loop");
         } else if self.function_depth >= self.loop_depth {
-            Lib::error_token(keyword, "Jump target 'cannot' cross function boundary");
+            self.error(keyword, "Jump target 'cannot' cross function boundary");
         }
     }
 
-    fn visit_continue_stmt(&mut self, keyword: &Token) -> Self::Item {
+    fn visit_continue_stmt(&mut self, keyword: &Token, _span: Span) -> Self::Item {
         if self.loop_depth == 0 {
-            Lib::error_token(keyword, "Can't use 'continue' outside a loop");
+            self.error(keyword, "Can't use 'continue' outside a loop");
         } else if self.function_depth >= self.loop_depth {
-            Lib::error_token(keyword, "Jump target cannot cross function boundary");
+            self.error(keyword, "Jump target cannot cross function boundary");
         }
     }
 
-    fn visit_function_stmt(&mut self, stmt: &stmt::Function) -> Self::Item {
+    fn visit_function_stmt(&mut self, stmt: &Rc<stmt::Function>) -> Self::Item {
         self.declare(&stmt.name);
         self.define(&stmt.name);
-        self.resolve_function(stmt, FunctionType::Function);
-    }
 
-    fn visit_return_stmt(&mut self, stmt: &stmt::Return) -> Self::Item {
-        if self.function_depth == 0 {
-            Lib::error_token(&stmt.keyword, "Can't use 'return' outside a function");
+        match self.unused_variables.last_mut() {
+            _ if stmt.name.lexeme.starts_with('_') => {}
+            Some(unused) => {
+                unused.insert(
+                    stmt.name.lexeme.to_string(),
+                    (stmt.name.clone(), Lint::UnusedFunction),
+                );
+            }
+            None => self.global_functions.push(stmt.name.clone()),
         }
 
+        self.resolve_function(stmt.as_ref(), FunctionType::Function);
+    }
+
+    fn visit_return_stmt(&mut self, stmt: &stmt::Return) -> Self::Item {
         if let Some(value) = &stmt.value {
             if matches!(self.function_type, FunctionType::Initializer) {
-                Lib::error_token(&stmt.keyword, "Can't return a value from initializer");
+                self.error(&stmt.keyword, "Can't return a value from initializer");
             }
 
             self.resolve_expression(value);
         }
     }
 
-    fn visit_class_stmt(&mut self, stmt: &stmt::Class) -> Self::Item {
+    fn visit_class_stmt(&mut self, stmt: &Rc<stmt::Class>) -> Self::Item {
         let enclosing = self.class_type.clone();
+        let enclosing_name = self.class_name.replace(stmt.name.lexeme.to_string());
+        self.classes.insert(stmt.name.lexeme.to_string());
         self.class_type = ClassType::Class;
 
         self.declare(&stmt.name);
@@ -332,54 +918,179 @@ impl<'a> StmtVisitor for Resolver<'a> {
         if let Some(super_class) = &stmt.super_class {
             self.class_type = ClassType::SubClass;
             self.resolve_expression(super_class);
+        }
 
-            self.begin_scope();
+        // Static fields are initialized when the class is defined, there's no
+        // `this` to refer to yet.
+        let enclosing_function = self.function_type.clone();
+        self.function_type = FunctionType::Static;
 
-            self.scopes
-                .last_mut()
-                .unwrap()
-                .insert(String::from("super"), true);
+        let mut statics = HashSet::new();
+
+        for field in &stmt.static_fields {
+            if !statics.insert(&*field.name.lexeme) {
+                let message = format!("Duplicate class member '{}'", field.name.lexeme);
+
+                self.error(&field.name, &message);
+            }
+
+            if let Some(initializer) = &field.initializer {
+                self.resolve_expression(initializer);
+            }
+        }
+
+        self.function_type = enclosing_function;
+
+        // Mirrors the environment holding the class and its super class that
+        // methods close over.
+        self.begin_scope();
+
+        self.declare_implicit("class", CLASS_SLOT);
+
+        if let Some(super_class) = &stmt.super_class {
+            self.declare_implicit("super", SUPER_SLOT);
 
             if let Expr::Variable(super_class) = super_class {
                 if super_class.lexeme == stmt.name.lexeme {
-                    Lib::error_token(&super_class, "A class can't inherit from itself");
+                    self.error(super_class, "A class can't inherit from itself");
                 }
             }
         }
 
-        self.begin_scope();
-
         for method in &stmt.statics {
             let declaration = FunctionType::Static;
 
             if let Stmt::Function(function_stmt) = method {
+                if !statics.insert(&*function_stmt.name.lexeme) {
+                    let message = format!("Duplicate class member '{}'", function_stmt.name.lexeme);
+
+                    self.error(&function_stmt.name, &message);
+                }
+
                 self.resolve_function(&**function_stmt, declaration);
             }
         }
 
-        self.scopes
-            .last_mut()
-            .unwrap()
-            .insert(String::from("this"), true);
+        // Mirrors the environment binding `this`, which is part of each
+        // method rather than of the closure they share.
+        self.begin_scope();
+
+        self.declare_implicit("this", THIS_SLOT);
+
+        let mut methods = HashSet::new();
 
         for method in &stmt.methods {
             let mut declaration = FunctionType::Method;
 
             if let Stmt::Function(function_stmt) = method {
-                if function_stmt.name.lexeme.eq("init") {
+                if !methods.insert(&*function_stmt.name.lexeme) {
+                    let message = format!("Duplicate method '{}'", function_stmt.name.lexeme);
+
+                    self.error(&function_stmt.name, &message);
+                }
+
+                if &*function_stmt.name.lexeme == "init" {
                     declaration = FunctionType::Initializer;
+
+                    if function_stmt.is_async {
+                        self.error(&function_stmt.name, "An initializer can't be async");
+                    }
                 }
 
-                self.resolve_function(&**function_stmt, declaration);
+                let enclosing_calls = std::mem::replace(&mut self.calls_super_init, false);
+
+                self.resolve_function_from(&**function_stmt, declaration, self.scopes.len() - 1);
+
+                if &*function_stmt.name.lexeme == "init"
+                    && matches!(self.class_type, ClassType::SubClass)
+                    && !self.calls_super_init
+                {
+                    self.lint(
+                        Lint::MissingSuperInit,
+                        &function_stmt.name,
+                        "Initializer doesn't call the super class's 'init'",
+                    );
+                }
+
+                self.calls_super_init = enclosing_calls;
             }
         }
 
+        self.end_scope();
+        self.end_scope();
+
+        self.class_type = enclosing;
+        self.class_name = enclosing_name;
+    }
+
+    fn visit_try_stmt(&mut self, stmt: &stmt::Try) -> Self::Item {
+        self.begin_scope();
+        self.resolve_stmts(&stmt.body);
         self.end_scope();
 
-        if let Some(_) = &stmt.super_class {
+        if let Some(catch) = &stmt.catch {
+            self.begin_scope();
+
+            if let Some(name) = &catch.name {
+                self.declare(name);
+                self.define(name);
+            }
+
+            self.resolve_stmts(&catch.body);
             self.end_scope();
         }
 
-        self.class_type = enclosing;
+        if let Some(finally) = &stmt.finally {
+            self.begin_scope();
+            self.resolve_stmts(finally);
+            self.end_scope();
+        }
+    }
+
+    fn visit_throw_stmt(&mut self, stmt: &stmt::Throw) -> Self::Item {
+        self.resolve_expression(&stmt.value);
+    }
+
+    fn visit_yield_stmt(&mut self, stmt: &stmt::Yield) -> Self::Item {
+        match self.functions.last() {
+            None => self.error(&stmt.keyword, "Can't use 'yield' outside a function"),
+            Some(function) if function.is_async => {
+                self.error(&stmt.keyword, "Can't use 'yield' inside an async function")
+            }
+            Some(_) if matches!(self.function_type, FunctionType::Initializer) => {
+                self.error(&stmt.keyword, "Can't use 'yield' inside an initializer")
+            }
+            Some(_) => {}
+        }
+
+        if let Some(value) = &stmt.value {
+            self.resolve_expression(value);
+        }
+    }
+
+    fn visit_exit_stmt(&mut self, stmt: &stmt::Exit) -> Self::Item {
+        if let Some(code) = &stmt.code {
+            self.resolve_expression(code);
+        }
+    }
+
+    fn visit_import_stmt(&mut self, stmt: &stmt::Import) -> Self::Item {
+        if !self.scopes.is_empty() {
+            self.error(&stmt.keyword, "Can only import at the top level");
+        }
+    }
+
+    fn visit_export_stmt(&mut self, stmt: &stmt::Export) -> Self::Item {
+        if !self.scopes.is_empty() {
+            self.error(&stmt.keyword, "Can only export top level declarations");
+        }
+
+        self.resolve_stmt(&stmt.declaration);
+
+        // Exported functions are called by the importing modules.
+        if let Stmt::Function(function) = &stmt.declaration {
+            self.global_references
+                .insert(function.name.lexeme.to_string());
+        }
     }
 }
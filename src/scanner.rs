@@ -1,9 +1,13 @@
 use phf::phf_map;
-use uuid::Uuid;
 
-use crate::{literal_type::LiteralType, token::Token, token_type::TokenType, Lib};
+use crate::{
+    errors::{Diagnostic, Severity},
+    literal_type::LiteralType,
+    token::{Span, Token},
+    token_type::TokenType,
+};
 
-static KEYWORDS: phf::Map<&'static str, TokenType> = phf_map! {
+pub(crate) static KEYWORDS: phf::Map<&'static str, TokenType> = phf_map! {
     "and" => TokenType::And,
     "or" => TokenType::Or,
     "class" => TokenType::Class,
@@ -13,24 +17,49 @@ static KEYWORDS: phf::Map<&'static str, TokenType> = phf_map! {
     "false" => TokenType::False,
     "while" => TokenType::While,
     "for" => TokenType::For,
+    "in" => TokenType::In,
+    "instanceof" => TokenType::InstanceOf,
     "return" => TokenType::Return,
     "super" => TokenType::Super,
     "this" => TokenType::This,
     "var" => TokenType::Var,
+    "const" => TokenType::Const,
     "undefined" => TokenType::Undefined,
     "fun" => TokenType::Function,
     "print" => TokenType::Print,
     "exit" => TokenType::Exit,
     "break" => TokenType::Break,
     "continue" => TokenType::Continue,
+    "try" => TokenType::Try,
+    "catch" => TokenType::Catch,
+    "finally" => TokenType::Finally,
+    "throw" => TokenType::Throw,
+    "yield" => TokenType::Yield,
+    "async" => TokenType::Async,
+    "await" => TokenType::Await,
+    "import" => TokenType::Import,
+    "export" => TokenType::Export,
 };
 
+/// A comment skipped while scanning, kept for the formatter.
+#[derive(Clone, Debug)]
+pub struct Comment {
+    pub line: usize,
+    pub text: String,
+    /// Whether code precedes the comment on its line.
+    pub trailing: bool,
+}
+
 pub struct Scanner {
     source: String,
     tokens: Vec<Token>,
     current: usize,
     start: usize,
     line: usize,
+    diagnostics: Vec<Diagnostic>,
+    /// Open `${` segments, each entry counts the braces opened inside it.
+    interpolations: Vec<usize>,
+    comments: Vec<Comment>,
 }
 
 impl Scanner {
@@ -41,18 +70,36 @@ impl Scanner {
             current: 0,
             start: 0,
             line: 1,
+            diagnostics: vec![],
+            interpolations: vec![],
+            comments: vec![],
         }
     }
 
-    pub fn scan_tokens(mut self) -> Vec<Token> {
+    pub fn scan_tokens(self) -> Result<Vec<Token>, Vec<Diagnostic>> {
+        self.scan_with_comments().map(|(tokens, _)| tokens)
+    }
+
+    /// Scans the tokens along with the comments found between them.
+    pub fn scan_with_comments(mut self) -> Result<(Vec<Token>, Vec<Comment>), Vec<Diagnostic>> {
         while !self.is_at_end() {
             self.start = self.current;
 
             self.scan_token();
         }
 
+        if !self.interpolations.is_empty() {
+            self.error("Unterminated string interpolation");
+        }
+
+        self.start = self.current;
         self.add_token(TokenType::Eof);
-        self.tokens
+
+        if self.diagnostics.is_empty() {
+            Ok((self.tokens, self.comments))
+        } else {
+            Err(self.diagnostics)
+        }
     }
 
     fn scan_token(&mut self) {
@@ -63,13 +110,37 @@ impl Scanner {
         } else if c == ')' {
             self.add_token(TokenType::RightParenthesis);
         } else if c == '{' {
+            if let Some(depth) = self.interpolations.last_mut() {
+                *depth += 1;
+            }
+
             self.add_token(TokenType::LeftBraces);
         } else if c == '}' {
-            self.add_token(TokenType::RightBraces);
+            match self.interpolations.last_mut() {
+                Some(0) => {
+                    self.interpolations.pop();
+                    self.string_literal();
+                }
+                Some(depth) => {
+                    *depth -= 1;
+                    self.add_token(TokenType::RightBraces);
+                }
+                None => self.add_token(TokenType::RightBraces),
+            }
+        } else if c == '[' {
+            self.add_token(TokenType::LeftBracket);
+        } else if c == ']' {
+            self.add_token(TokenType::RightBracket);
         } else if c == ',' {
             self.add_token(TokenType::Comma);
         } else if c == '.' {
-            self.add_token(TokenType::Dot);
+            if self.peek() == '.' && self.peek_next() == '.' {
+                self.advance();
+                self.advance();
+                self.add_token(TokenType::Ellipsis);
+            } else {
+                self.add_token(TokenType::Dot);
+            }
         } else if c == '-' {
             self.add_token(TokenType::Minus);
         } else if c == '+' {
@@ -79,21 +150,40 @@ impl Scanner {
         } else if c == ';' {
             self.add_token(TokenType::SemiColon);
         } else if c == '?' {
-            self.add_token(TokenType::Question);
+            if self.matches('.') {
+                self.add_token(TokenType::QuestionDot);
+            } else if self.matches('?') {
+                self.add_token(TokenType::QuestionQuestion);
+            } else {
+                self.add_token(TokenType::Question);
+            }
         } else if c == ':' {
             self.add_token(TokenType::Colon);
         } else if c == '%' {
             self.add_token(TokenType::Percentage);
         } else if c == '!' {
             let token_type = if self.matches('=') {
-                TokenType::BangEqual
+                if self.matches('=') {
+                    TokenType::BangEqualEqual
+                } else {
+                    TokenType::BangEqual
+                }
             } else {
                 TokenType::Bang
             };
             self.add_token(token_type);
         } else if c == '=' {
+            if self.matches('>') {
+                self.add_token(TokenType::Arrow);
+                return;
+            }
+
             let token_type = if self.matches('=') {
-                TokenType::EqualEqual
+                if self.matches('=') {
+                    TokenType::EqualEqualEqual
+                } else {
+                    TokenType::EqualEqual
+                }
             } else {
                 TokenType::Equal
             };
@@ -114,28 +204,30 @@ impl Scanner {
             self.add_token(token_type);
         } else if c == '/' {
             self.slash()
+        } else if c == '#' {
+            // Shell style comments, which also skip the `#!` line that makes
+            // a script executable.
+            self.line_comment();
         } else if c == '\n' {
             self.line += 1;
         } else if c == '"' {
             self.string_literal();
-        } else if c.is_digit(10) {
+        } else if c.is_ascii_digit() {
             self.number_literal();
         } else if Self::is_alphabetic(c) {
             self.identifier();
         } else if c == ' ' || c == '\r' || c == '\t' {
         } else {
-            Lib::error_message(self.line, "Unexpected character");
+            self.error("Unexpected character");
         }
     }
 
     fn slash(&mut self) {
         match self.peek() {
-            '/' => {
-                while self.peek() != '\n' && !self.is_at_end() {
-                    self.advance();
-                }
-            }
+            '/' => self.line_comment(),
             '*' => {
+                let line = self.line;
+
                 self.advance();
 
                 while !self.is_at_end() {
@@ -146,6 +238,7 @@ impl Scanner {
                     if self.peek() == '*' && self.peek_next() == '/' {
                         self.advance();
                         self.advance();
+                        self.add_comment(line);
 
                         return;
                     }
@@ -153,7 +246,7 @@ impl Scanner {
                     self.advance();
                 }
 
-                Lib::error_message(self.line, "Expect a '*/'");
+                self.error("Expect a '*/'");
             }
             _ => {
                 self.add_token(TokenType::Slash);
@@ -161,6 +254,27 @@ impl Scanner {
         }
     }
 
+    fn line_comment(&mut self) {
+        while self.peek() != '\n' && !self.is_at_end() {
+            self.advance();
+        }
+
+        self.add_comment(self.line);
+    }
+
+    fn add_comment(&mut self, line: usize) {
+        let before = self.source[..self.start].rsplit('\n').next();
+
+        self.comments.push(Comment {
+            line,
+            text: String::from(&self.source[self.start..self.current]),
+            trailing: before.is_some_and(|before| !before.trim().is_empty()),
+        });
+    }
+
+    /// Scans a string up to its closing quote, or up to the next `${` in which
+    /// case an [`TokenType::Interpolation`] token is emitted and scanning goes
+    /// back to regular tokens until the matching `}` resumes the string.
     fn string_literal(&mut self) {
         while !self.is_at_end() {
             match self.peek() {
@@ -172,11 +286,23 @@ impl Scanner {
                     self.add_token_with_literal(
                         TokenType::StringLiteral,
                         Some(LiteralType::String(String::from(literal))),
-                        None,
                     );
 
                     return;
                 }
+                '$' if self.peek_next() == '{' => {
+                    let literal = &self.source[self.start + 1..self.current];
+
+                    self.add_token_with_literal(
+                        TokenType::Interpolation,
+                        Some(LiteralType::String(String::from(literal))),
+                    );
+                    self.advance();
+                    self.advance();
+                    self.interpolations.push(0);
+
+                    return;
+                }
                 '\n' => {
                     break;
                 }
@@ -186,35 +312,43 @@ impl Scanner {
             }
         }
 
-        Lib::error_message(self.line, "Unterminated string literal");
+        self.error("Unterminated string literal");
     }
 
     fn number_literal(&mut self) {
-        while self.peek().is_digit(10) {
+        while self.peek().is_ascii_digit() {
             self.advance();
         }
 
-        if self.peek() == '.' && self.peek_next().is_digit(10) {
+        if self.peek() == '.' && self.peek_next().is_ascii_digit() {
             self.advance();
 
-            while self.peek().is_digit(10) {
+            while self.peek().is_ascii_digit() {
                 self.advance();
             }
-        }
 
-        let number = self.source[self.start..self.current]
-            .parse()
-            .expect("Valid number literal");
+            let number = self.source[self.start..self.current]
+                .parse()
+                .expect("Valid number literal");
 
-        self.add_token_with_literal(
-            TokenType::NumberLiteral,
-            Some(LiteralType::Number(number)),
-            None,
-        );
+            self.add_token_with_literal(
+                TokenType::NumberLiteral,
+                Some(LiteralType::Number(number)),
+            );
+
+            return;
+        }
+
+        match self.source[self.start..self.current].parse() {
+            Ok(int) => {
+                self.add_token_with_literal(TokenType::NumberLiteral, Some(LiteralType::Int(int)));
+            }
+            Err(_) => self.error("Integer literal is too large"),
+        }
     }
 
     fn identifier(&mut self) {
-        while Self::is_alphabetic(self.peek()) || self.peek().is_digit(10) {
+        while Self::is_alphabetic(self.peek()) || self.peek().is_ascii_digit() {
             self.advance();
         }
 
@@ -224,27 +358,33 @@ impl Scanner {
         } else {
             TokenType::Identifier
         };
-        let uuid = Uuid::new_v4().to_string();
 
-        self.add_token_with_hash(token_type, Some(uuid));
+        self.add_token(token_type);
+    }
+
+    fn error(&mut self, message: &str) {
+        self.diagnostics.push(
+            Diagnostic::at_line(Severity::Error, "syntax-error", self.line, message)
+                .with_span(self.span()),
+        );
     }
 
     fn matches(&mut self, expected: char) -> bool {
         if self.is_at_end() || self.peek() != expected {
             false
         } else {
-            self.current += 1;
+            self.current += expected.len_utf8();
 
             true
         }
     }
 
     fn peek(&self) -> char {
-        self.source.chars().nth(self.current).unwrap_or('\0')
+        self.source[self.current..].chars().next().unwrap_or('\0')
     }
 
     fn peek_next(&self) -> char {
-        self.source.chars().nth(self.current + 1).unwrap_or('\0')
+        self.source[self.current..].chars().nth(1).unwrap_or('\0')
     }
 
     fn is_alphabetic(c: char) -> bool {
@@ -256,35 +396,34 @@ impl Scanner {
     }
 
     fn advance(&mut self) -> char {
-        let c = self.source.chars().nth(self.current).unwrap();
+        let c = self.source[self.current..].chars().next().unwrap();
 
-        self.current += 1;
+        self.current += c.len_utf8();
 
         c
     }
 
-    fn add_token(&mut self, token_type: TokenType) {
-        self.add_token_with_literal(token_type, None, None);
+    /// The span of the lexeme being scanned, `start` and `current` are byte
+    /// offsets into `source`.
+    fn span(&self) -> Span {
+        let line_start = self.source[..self.start]
+            .rfind('\n')
+            .map_or(0, |newline| newline + 1);
+
+        Span {
+            start: self.start as u32,
+            end: self.current as u32,
+            column: self.source[line_start..self.start].chars().count() as u32 + 1,
+        }
     }
 
-    fn add_token_with_hash(&mut self, token_type: TokenType, identifier_hash: Option<String>) {
-        self.add_token_with_literal(token_type, None, identifier_hash);
+    fn add_token(&mut self, token_type: TokenType) {
+        self.add_token_with_literal(token_type, None);
     }
 
-    fn add_token_with_literal(
-        &mut self,
-        token_type: TokenType,
-        literal: Option<LiteralType>,
-        identifier_hash: Option<String>,
-    ) {
+    fn add_token_with_literal(&mut self, token_type: TokenType, literal: Option<LiteralType>) {
         let lexeme = &self.source[self.start..self.current];
-        let token = Token::new(
-            token_type,
-            String::from(lexeme),
-            literal,
-            self.line,
-            identifier_hash,
-        );
+        let token = Token::new(token_type, lexeme, literal, self.line, self.span());
         self.tokens.push(token);
     }
 }
@@ -1,67 +1,196 @@
+mod _for;
 mod _if;
 mod _return;
+mod _try;
 mod _while;
+mod _yield;
 mod class;
+mod exit;
+mod export;
+mod for_in;
 mod function;
+mod import;
+mod throw;
 mod variable;
 
+pub use _for::For;
 pub use _if::If;
 pub use _return::Return;
+pub use _try::{Catch, Try};
 pub use _while::While;
+pub use _yield::Yield;
 pub use class::Class;
+pub use exit::Exit;
+pub use export::Export;
+pub use for_in::ForIn;
 pub use function::Function;
+pub use import::Import;
+pub use throw::Throw;
 pub use variable::VariableDeclaration;
 
-use crate::{expr::Expr, token::Token};
+use std::rc::Rc;
 
+use crate::{
+    expr::{Await, Expr},
+    token::{Span, Token},
+};
+
+/// Every statement knows its span in the source, see [`Stmt::span`]. The
+/// variants whose tokens don't reach both of its ends keep it alongside.
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Stmt {
-    Empty,
-    Expression(Box<Expr>),
-    Print(Box<Expr>),
-    Variable(Box<Vec<VariableDeclaration>>),
-    Block(Box<Vec<Stmt>>),
+    Empty(Span),
+    Expression(Box<Expr>, Span),
+    Print(Box<Expr>, Span),
+    Variable(Box<Vec<VariableDeclaration>>, Span),
+    Block(Box<Vec<Stmt>>, Span),
     If(Box<If>),
     While(Box<While>),
-    Break(Token),
-    Continue(Token),
-    Function(Box<Function>),
+    For(Box<For>),
+    ForIn(Box<ForIn>),
+    Break(Token, Span),
+    Continue(Token, Span),
+    /// Shared with the functions declared from it, declaring one doesn't
+    /// copy its body.
+    Function(Rc<Function>),
     Return(Box<Return>),
-    Class(Box<Class>),
+    /// Shared with the classes declared from it, like functions.
+    Class(Rc<Class>),
+    Try(Box<Try>),
+    Throw(Box<Throw>),
+    Yield(Box<Yield>),
+    Exit(Box<Exit>),
+    Import(Box<Import>),
+    Export(Box<Export>),
 }
 
 pub trait StmtVisitor {
     type Item;
 
-    fn visit_empty_stmt(&mut self) -> Self::Item;
-    fn visit_expression_stmt(&mut self, stmt: &Expr) -> Self::Item;
-    fn visit_print_stmt(&mut self, stmt: &Expr) -> Self::Item;
-    fn visit_variable_stmt(&mut self, stmt: &Vec<VariableDeclaration>) -> Self::Item;
-    fn visit_block_stmt(&mut self, stmt: &Vec<Stmt>) -> Self::Item;
+    fn visit_empty_stmt(&mut self, span: Span) -> Self::Item;
+    fn visit_expression_stmt(&mut self, stmt: &Expr, span: Span) -> Self::Item;
+    fn visit_print_stmt(&mut self, stmt: &Expr, span: Span) -> Self::Item;
+    fn visit_variable_stmt(&mut self, stmt: &[VariableDeclaration], span: Span) -> Self::Item;
+    fn visit_block_stmt(&mut self, stmt: &[Stmt], span: Span) -> Self::Item;
     fn visit_if_stmt(&mut self, stmt: &If) -> Self::Item;
     fn visit_while_stmt(&mut self, stmt: &While) -> Self::Item;
-    fn visit_break_stmt(&mut self, keyword: &Token) -> Self::Item;
-    fn visit_continue_stmt(&mut self, keyword: &Token) -> Self::Item;
-    fn visit_function_stmt(&mut self, stmt: &Function) -> Self::Item;
+    fn visit_for_stmt(&mut self, stmt: &For) -> Self::Item;
+    fn visit_for_in_stmt(&mut self, stmt: &ForIn) -> Self::Item;
+    fn visit_break_stmt(&mut self, keyword: &Token, span: Span) -> Self::Item;
+    fn visit_continue_stmt(&mut self, keyword: &Token, span: Span) -> Self::Item;
+    fn visit_function_stmt(&mut self, stmt: &Rc<Function>) -> Self::Item;
     fn visit_return_stmt(&mut self, stmt: &Return) -> Self::Item;
-    fn visit_class_stmt(&mut self, stmt: &Class) -> Self::Item;
+    fn visit_class_stmt(&mut self, stmt: &Rc<Class>) -> Self::Item;
+    fn visit_try_stmt(&mut self, stmt: &Try) -> Self::Item;
+    fn visit_throw_stmt(&mut self, stmt: &Throw) -> Self::Item;
+    fn visit_yield_stmt(&mut self, stmt: &Yield) -> Self::Item;
+    fn visit_exit_stmt(&mut self, stmt: &Exit) -> Self::Item;
+    fn visit_import_stmt(&mut self, stmt: &Import) -> Self::Item;
+    fn visit_export_stmt(&mut self, stmt: &Export) -> Self::Item;
 }
 
 impl Stmt {
     pub fn accept<V: StmtVisitor>(&self, visitor: &mut V) -> V::Item {
         match self {
-            Stmt::Empty => visitor.visit_empty_stmt(),
-            Stmt::Expression(stmt) => visitor.visit_expression_stmt(stmt),
-            Stmt::Print(stmt) => visitor.visit_print_stmt(stmt),
-            Stmt::Variable(stmt) => visitor.visit_variable_stmt(stmt),
-            Stmt::Block(stmt) => visitor.visit_block_stmt(stmt),
+            Stmt::Empty(span) => visitor.visit_empty_stmt(*span),
+            Stmt::Expression(stmt, span) => visitor.visit_expression_stmt(stmt, *span),
+            Stmt::Print(stmt, span) => visitor.visit_print_stmt(stmt, *span),
+            Stmt::Variable(stmt, span) => visitor.visit_variable_stmt(stmt, *span),
+            Stmt::Block(stmt, span) => visitor.visit_block_stmt(stmt, *span),
             Stmt::If(stmt) => visitor.visit_if_stmt(stmt),
             Stmt::While(stmt) => visitor.visit_while_stmt(stmt),
-            Stmt::Break(stmt) => visitor.visit_break_stmt(stmt),
-            Stmt::Continue(stmt) => visitor.visit_continue_stmt(stmt),
+            Stmt::For(stmt) => visitor.visit_for_stmt(stmt),
+            Stmt::ForIn(stmt) => visitor.visit_for_in_stmt(stmt),
+            Stmt::Break(stmt, span) => visitor.visit_break_stmt(stmt, *span),
+            Stmt::Continue(stmt, span) => visitor.visit_continue_stmt(stmt, *span),
             Stmt::Function(stmt) => visitor.visit_function_stmt(stmt),
             Stmt::Return(stmt) => visitor.visit_return_stmt(stmt),
             Stmt::Class(stmt) => visitor.visit_class_stmt(stmt),
+            Stmt::Try(stmt) => visitor.visit_try_stmt(stmt),
+            Stmt::Throw(stmt) => visitor.visit_throw_stmt(stmt),
+            Stmt::Yield(stmt) => visitor.visit_yield_stmt(stmt),
+            Stmt::Exit(stmt) => visitor.visit_exit_stmt(stmt),
+            Stmt::Import(stmt) => visitor.visit_import_stmt(stmt),
+            Stmt::Export(stmt) => visitor.visit_export_stmt(stmt),
+        }
+    }
+
+    /// The line the statement starts on, as far as its tokens tell.
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            Stmt::Empty(_) => None,
+            Stmt::Expression(expr, _) | Stmt::Print(expr, _) => expr.line(),
+            Stmt::Variable(declarations, _) => declarations.first().map(|var| var.name.line),
+            Stmt::Block(stmts, _) => stmts.first().and_then(Stmt::line),
+            Stmt::If(stmt) => Some(stmt.keyword.line),
+            Stmt::While(stmt) => Some(stmt.keyword.line),
+            Stmt::For(stmt) => Some(stmt.keyword.line),
+            Stmt::ForIn(stmt) => Some(stmt.keyword.line),
+            Stmt::Break(token, _) | Stmt::Continue(token, _) => Some(token.line),
+            Stmt::Function(stmt) => Some(stmt.name.line),
+            Stmt::Return(stmt) => Some(stmt.keyword.line),
+            Stmt::Class(stmt) => Some(stmt.name.line),
+            Stmt::Try(stmt) => stmt.body.first().and_then(Stmt::line),
+            Stmt::Throw(stmt) => Some(stmt.keyword.line),
+            Stmt::Yield(stmt) => Some(stmt.keyword.line),
+            Stmt::Exit(stmt) => Some(stmt.keyword.line),
+            Stmt::Import(stmt) => Some(stmt.keyword.line),
+            Stmt::Export(stmt) => Some(stmt.keyword.line),
+        }
+    }
+
+    /// Where the statement is in its source, including its closing `;` or
+    /// `}`.
+    pub fn span(&self) -> Span {
+        match self {
+            Stmt::Empty(span)
+            | Stmt::Expression(_, span)
+            | Stmt::Print(_, span)
+            | Stmt::Variable(_, span)
+            | Stmt::Block(_, span)
+            | Stmt::Break(_, span)
+            | Stmt::Continue(_, span) => *span,
+            Stmt::If(stmt) => {
+                let last = stmt.falsy.as_ref().unwrap_or(&stmt.truth);
+
+                stmt.keyword.span.to(last.span())
+            }
+            Stmt::While(stmt) => stmt.keyword.span.to(stmt.body.span()),
+            Stmt::For(stmt) => stmt.keyword.span.to(stmt.body.span()),
+            Stmt::ForIn(stmt) => stmt.span,
+            Stmt::Function(stmt) => stmt.span,
+            Stmt::Return(stmt) => stmt.span,
+            Stmt::Class(stmt) => stmt.span,
+            Stmt::Try(stmt) => stmt.span,
+            Stmt::Throw(stmt) => stmt.span,
+            Stmt::Yield(stmt) => stmt.span,
+            Stmt::Exit(stmt) => stmt.span,
+            Stmt::Import(stmt) => stmt.span,
+            Stmt::Export(stmt) => stmt.keyword.span.to(stmt.declaration.span()),
+        }
+    }
+
+    /// The `await` the statement starts with: its whole expression, the
+    /// initializer of its first variable, or the value it prints, assigns to
+    /// a variable or returns. Nothing runs before it, so an async function
+    /// resumed there runs the statement again with the `await` evaluating
+    /// to the outcome of the promise. It can't be anywhere else.
+    pub fn awaited(&self) -> Option<&Await> {
+        let expr = match self {
+            Stmt::Expression(expr, _) => match &**expr {
+                Expr::Assignment(assignment) => &assignment.value,
+                expr => expr,
+            },
+            Stmt::Print(expr, _) => expr,
+            Stmt::Variable(declarations, _) => declarations.first()?.initializer.as_ref()?,
+            Stmt::Return(stmt) => stmt.value.as_ref()?,
+            _ => return None,
+        };
+
+        match expr {
+            Expr::Await(expr) => Some(expr),
+            _ => None,
         }
     }
 }
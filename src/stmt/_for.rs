@@ -0,0 +1,13 @@
+use crate::{expr::Expr, token::Token};
+
+use super::Stmt;
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct For {
+    pub keyword: Token,
+    pub initializer: Option<Stmt>,
+    pub condition: Option<Expr>,
+    pub increment: Option<Expr>,
+    pub body: Stmt,
+}
@@ -1,9 +1,11 @@
-use crate::expr::Expr;
+use crate::{expr::Expr, token::Token};
 
 use super::Stmt;
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct If {
+    pub keyword: Token,
     pub condition: Expr,
     pub truth: Stmt,
     pub falsy: Option<Stmt>,
@@ -1,7 +1,12 @@
-use crate::{expr::Expr, token::Token};
+use crate::{
+    expr::Expr,
+    token::{Span, Token},
+};
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Return {
     pub keyword: Token,
     pub value: Option<Expr>,
+    pub span: Span,
 }
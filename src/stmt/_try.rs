@@ -0,0 +1,23 @@
+use crate::token::{Span, Token};
+
+use super::Stmt;
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Catch {
+    pub name: Option<Token>,
+    pub body: Vec<Stmt>,
+    /// From `catch` to the `}` closing its body.
+    pub span: Span,
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Try {
+    pub body: Vec<Stmt>,
+    /// The braces around `body`, `finally` ends where the statement does.
+    pub body_span: Span,
+    pub catch: Option<Catch>,
+    pub finally: Option<Vec<Stmt>>,
+    pub span: Span,
+}
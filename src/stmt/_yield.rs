@@ -0,0 +1,13 @@
+use crate::{
+    expr::Expr,
+    token::{Span, Token},
+};
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Yield {
+    pub keyword: Token,
+    /// The value handed to `next()`, `undefined` when left out.
+    pub value: Option<Expr>,
+    pub span: Span,
+}
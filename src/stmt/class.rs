@@ -1,11 +1,17 @@
-use crate::{expr::Expr, token::Token};
+use crate::{
+    expr::Expr,
+    token::{Span, Token},
+};
 
-use super::Stmt;
+use super::{Stmt, VariableDeclaration};
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Class {
     pub name: Token,
     pub super_class: Option<Expr>,
     pub methods: Vec<Stmt>,
     pub statics: Vec<Stmt>,
+    pub static_fields: Vec<VariableDeclaration>,
+    pub span: Span,
 }
@@ -0,0 +1,13 @@
+use crate::{
+    expr::Expr,
+    token::{Span, Token},
+};
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Exit {
+    pub keyword: Token,
+    /// The exit code, `0` when left out.
+    pub code: Option<Expr>,
+    pub span: Span,
+}
@@ -0,0 +1,21 @@
+use crate::token::Token;
+
+use super::Stmt;
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Export {
+    pub keyword: Token,
+    pub declaration: Stmt,
+}
+
+impl Export {
+    pub fn names(&self) -> Vec<&Token> {
+        match &self.declaration {
+            Stmt::Variable(declarations, _) => declarations.iter().map(|var| &var.name).collect(),
+            Stmt::Function(function) => vec![&function.name],
+            Stmt::Class(class) => vec![&class.name],
+            _ => vec![],
+        }
+    }
+}
@@ -0,0 +1,16 @@
+use crate::{
+    expr::Expr,
+    token::{Span, Token},
+};
+
+use super::Stmt;
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ForIn {
+    pub name: Token,
+    pub keyword: Token,
+    pub iterable: Expr,
+    pub body: Stmt,
+    pub span: Span,
+}
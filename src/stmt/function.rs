@@ -1,10 +1,23 @@
-use crate::token::Token;
+use std::cell::RefCell;
+
+use crate::token::{Span, Token};
 
 use super::Stmt;
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Function {
     pub name: Token,
     pub params: Vec<Token>,
+    pub rest: Option<Token>,
     pub body: Vec<Stmt>,
+    /// Whether its body yields, calling it then returns a generator.
+    pub is_generator: bool,
+    /// Whether it's declared `async`, calling it then returns a promise.
+    pub is_async: bool,
+    pub span: Span,
+    /// Where the variables the function captures are, as their distance
+    /// from where it's declared and their slot, filled in by the resolver.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub captures: RefCell<Vec<(usize, usize)>>,
 }
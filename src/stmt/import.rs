@@ -0,0 +1,12 @@
+use crate::token::{Span, Token};
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Import {
+    pub keyword: Token,
+    pub path: Token,
+    /// Whether it's `import native "name";`, importing a module the host
+    /// registered rather than a file.
+    pub native: bool,
+    pub span: Span,
+}
@@ -0,0 +1,12 @@
+use crate::{
+    expr::Expr,
+    token::{Span, Token},
+};
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Throw {
+    pub keyword: Token,
+    pub value: Expr,
+    pub span: Span,
+}
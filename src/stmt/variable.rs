@@ -1,7 +1,9 @@
 use crate::{expr::Expr, token::Token};
 
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VariableDeclaration {
     pub name: Token,
     pub initializer: Option<Expr>,
+    pub is_const: bool,
 }
@@ -0,0 +1,54 @@
+use std::{cell::RefCell, collections::HashSet, rc::Rc};
+
+/// An interned string, cloning one only bumps a reference count.
+pub type Symbol = Rc<str>;
+
+thread_local! {
+    // Symbols are never shared between threads, each interpreter only sees
+    // the table of the thread it runs on.
+    static SYMBOLS: RefCell<Symbols> = RefCell::new(Symbols {
+        table: HashSet::new(),
+        limit: Symbols::MIN_LIMIT,
+    });
+}
+
+struct Symbols {
+    table: HashSet<Symbol>,
+    /// The size the table can reach before the symbols nothing else refers
+    /// to anymore are dropped, so a thread running script after script only
+    /// keeps the names still in use.
+    limit: usize,
+}
+
+impl Symbols {
+    const MIN_LIMIT: usize = 1024;
+}
+
+/// Returns the symbol for `text`, every identifier and lexeme spelled the
+/// same shares a single allocation.
+///
+/// ```
+/// use std::rc::Rc;
+/// use typhoon::symbol;
+///
+/// assert!(Rc::ptr_eq(&symbol::intern("name"), &symbol::intern("name")));
+/// ```
+pub fn intern(text: &str) -> Symbol {
+    SYMBOLS.with(|symbols| {
+        let mut symbols = symbols.borrow_mut();
+
+        if let Some(symbol) = symbols.table.get(text) {
+            return Rc::clone(symbol);
+        }
+
+        if symbols.table.len() >= symbols.limit {
+            symbols.table.retain(|symbol| Rc::strong_count(symbol) > 1);
+            symbols.limit = Symbols::MIN_LIMIT.max(symbols.table.len() * 2);
+        }
+
+        let symbol = Symbol::from(text);
+
+        symbols.table.insert(Rc::clone(&symbol));
+        symbol
+    })
+}
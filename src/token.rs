@@ -1,28 +1,71 @@
-use crate::{literal_type::LiteralType, token_type::TokenType};
+use std::{cell::Cell, ops::Range};
+
+use crate::{
+    literal_type::LiteralType,
+    symbol::{self, Symbol},
+    token_type::TokenType,
+};
+
+/// Where a token is in its source, `start` and `end` are byte offsets and
+/// `column` is the 1-based column of `start` counted in characters.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Span {
+    pub start: u32,
+    pub end: u32,
+    pub column: u32,
+}
+
+impl Span {
+    /// The byte range of the span, to slice the source with.
+    pub fn range(&self) -> Range<usize> {
+        self.start as usize..self.end as usize
+    }
+
+    /// The span from the start of this one to the end of `end`.
+    pub fn to(self, end: Span) -> Span {
+        Span {
+            end: end.end,
+            ..self
+        }
+    }
+}
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Token {
     pub token_type: TokenType,
-    pub lexeme: String,
+    pub lexeme: Symbol,
     pub literal: Option<LiteralType>,
     pub line: usize,
-    pub identifier_hash: Option<String>,
+    pub span: Span,
+    /// Where the resolver found the local variable the token names, as the
+    /// distance to its environment and its slot there. `None` for globals
+    /// and tokens that don't name a variable.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub location: Cell<Option<(usize, usize)>>,
 }
 
 impl Token {
     pub fn new(
         token_type: TokenType,
-        lexeme: String,
+        lexeme: &str,
         literal: Option<LiteralType>,
         line: usize,
-        identifier_hash: Option<String>,
+        span: Span,
     ) -> Self {
         Self {
             token_type,
-            lexeme,
+            lexeme: symbol::intern(lexeme),
             literal,
             line,
-            identifier_hash,
+            span,
+            location: Cell::new(None),
         }
     }
+
+    /// Class members whose name starts with an underscore are private.
+    pub fn is_private(&self) -> bool {
+        self.lexeme.starts_with('_')
+    }
 }
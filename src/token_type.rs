@@ -1,29 +1,39 @@
 #[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TokenType {
     LeftParenthesis,
     RightParenthesis,
     LeftBraces,
     RightBraces,
+    LeftBracket,
+    RightBracket,
     Comma,
     Dot,
+    Ellipsis,
     Minus,
     Plus,
     Percentage,
     SemiColon,
     Question,
+    QuestionDot,
+    QuestionQuestion,
     Colon,
     Slash,
     Star,
     Bang,
     BangEqual,
+    BangEqualEqual,
     Equal,
     EqualEqual,
+    EqualEqualEqual,
+    Arrow,
     Greater,
     GreaterEqual,
     Less,
     LessEqual,
     Identifier,
     StringLiteral,
+    Interpolation,
     NumberLiteral,
     And,
     Or,
@@ -34,10 +44,13 @@ pub enum TokenType {
     False,
     While,
     For,
+    In,
+    InstanceOf,
     Return,
     Super,
     This,
     Var,
+    Const,
     Undefined,
     Function,
     Print,
@@ -45,5 +58,14 @@ pub enum TokenType {
     NewLine,
     Break,
     Continue,
+    Try,
+    Catch,
+    Finally,
+    Throw,
+    Yield,
+    Async,
+    Await,
+    Import,
+    Export,
     Eof,
 }
@@ -8,9 +8,43 @@ pub fn bool_to_number(boolean: bool) -> f64 {
     }
 }
 
+/// Formats a number the way it's printed: integral values without a fraction,
+/// otherwise the shortest digits that read back as the same value. Very large
+/// and very small magnitudes use exponent notation.
+pub fn format_number(number: f64) -> String {
+    if number.is_nan() {
+        return String::from("NaN");
+    }
+
+    if number.is_infinite() {
+        let sign = if number < 0.0 { "-" } else { "" };
+
+        return format!("{sign}Infinity");
+    }
+
+    // Also drops the sign of negative zero.
+    if number == 0.0 {
+        return String::from("0");
+    }
+
+    if (1e-6..1e21).contains(&number.abs()) {
+        return number.to_string();
+    }
+
+    let formatted = format!("{number:e}");
+
+    match formatted.split_once('e') {
+        Some((mantissa, exponent)) if !exponent.starts_with('-') => {
+            format!("{mantissa}e+{exponent}")
+        }
+        _ => formatted,
+    }
+}
+
 pub fn is_truthy(literal: &Object) -> bool {
     match literal {
         Object::Undefined => false,
+        Object::Int(int) => *int != 0,
         Object::Number(number) => *number != 0.0,
         Object::String(string) => !string.is_empty(),
         Object::Boolean(boolean) => *boolean,
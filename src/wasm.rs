@@ -0,0 +1,57 @@
+//! Entry point for running typhoon in the browser, e.g. behind an online
+//! playground. Built with the `wasm` feature.
+
+use std::io::Write;
+
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::{
+    errors::CallFrame,
+    interpreter::{CapturedOutput, Interpreter},
+    Lib,
+};
+
+/// Runs `source` in a fresh interpreter and returns what it printed, followed
+/// by its diagnostics and runtime error if any, without colors.
+#[wasm_bindgen]
+pub fn run_source(source: &str) -> String {
+    let mut output = CapturedOutput::new();
+    let mut interpreter = Interpreter::new();
+    let mut diagnostics = vec![];
+
+    interpreter.set_output(Box::new(output.clone()));
+
+    let statements = Lib::compile(&mut interpreter, String::from(source), &mut diagnostics);
+
+    for diagnostic in &diagnostics {
+        let _ = writeln!(output, "{diagnostic}");
+    }
+
+    if let Some(statements) = statements {
+        let result = interpreter.interpret(&statements);
+
+        for diagnostic in interpreter.take_diagnostics() {
+            let _ = writeln!(output, "{diagnostic}");
+        }
+
+        if let Err(runtime_error) = result {
+            let _ = writeln!(output, "{runtime_error}");
+
+            let backtrace = interpreter.take_backtrace();
+
+            for (frame, count) in CallFrame::collapse(&backtrace) {
+                let _ = writeln!(
+                    output,
+                    "    in {} called from [{}]",
+                    frame.function, frame.call_site.line
+                );
+
+                if count > 1 {
+                    let _ = writeln!(output, "    ... repeated {} more times", count - 1);
+                }
+            }
+        }
+    }
+
+    output.contents()
+}
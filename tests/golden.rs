@@ -0,0 +1,147 @@
+//! Runs every script in `tests/fixtures` and compares its warnings and what
+//! it printed, followed by its errors or exit code, with the `.expected` file
+//! next to it. Comments like `// warn: loose-equality, empty-block` heading a
+//! script set the level of lints for it, like the `--allow`, `--warn` and
+//! `--deny` flags. The scripts in `tests/format` are formatted instead, the `.expected` file
+//! holding the result.
+//!
+//! Each script runs on a spawned thread, whose stack the default call depth
+//! is fitted to, so a fixture can recurse until it goes over it.
+//!
+//! Set `TYPHOON_BLESS=1` to rewrite the expected files from the current
+//! output instead.
+
+use std::{env, fs, path::Path, thread};
+
+use typhoon::{
+    errors::{Lint, LintLevel},
+    formatter,
+    interpreter::CapturedOutput,
+    Lib, TyphoonError,
+};
+
+/// The lint levels set by the comments heading `source`.
+fn lint_levels(source: &str) -> Vec<(Lint, LintLevel)> {
+    source
+        .lines()
+        .map_while(|line| line.strip_prefix("// "))
+        .filter_map(|comment| {
+            let (level, lints) = comment.split_once(": ")?;
+            let level = match level {
+                "allow" => LintLevel::Allow,
+                "warn" => LintLevel::Warn,
+                "deny" => LintLevel::Deny,
+                _ => return None,
+            };
+
+            Some(lints.split(", ").map(move |name| {
+                let lint = Lint::from_name(name).unwrap_or_else(|| panic!("no lint '{name}'"));
+
+                (lint, level)
+            }))
+        })
+        .flatten()
+        .collect()
+}
+
+fn run(source: String) -> String {
+    let output = CapturedOutput::new();
+    let mut lib = Lib::new();
+
+    lib.set_output(output.clone());
+
+    for (lint, level) in lint_levels(&source) {
+        lib.set_lint_level(lint, level);
+    }
+
+    let result = lib.run(source);
+    let mut contents = String::new();
+
+    // A script that doesn't compile reports its warnings with its errors.
+    if !matches!(result, Err(TyphoonError::Syntax(_))) {
+        for warning in lib
+            .diagnostics()
+            .iter()
+            .filter(|diagnostic| !diagnostic.is_error())
+        {
+            contents += &format!("{warning}\n");
+        }
+    }
+
+    contents += &output.contents();
+
+    match result {
+        Ok(()) => {
+            if let Some(code) = lib.exit_code() {
+                contents += &format!("exit {code}\n");
+            }
+        }
+        Err(TyphoonError::Syntax(diagnostics)) => {
+            for diagnostic in diagnostics {
+                contents += &format!("{diagnostic}\n");
+            }
+        }
+        Err(error) => contents += &format!("{error}\n"),
+    }
+
+    contents
+}
+
+/// Compares what `produce` makes of every script in `directory` with the
+/// `.expected` file next to it.
+fn compare(directory: &str, produce: impl Fn(String) -> String) {
+    let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join(directory);
+    let bless = env::var_os("TYPHOON_BLESS").is_some();
+    let mut scripts = fs::read_dir(&directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "ty"))
+        .collect::<Vec<_>>();
+    let mut failures = vec![];
+
+    scripts.sort();
+
+    for script in &scripts {
+        let actual = produce(fs::read_to_string(script).unwrap());
+        let expected_path = script.with_extension("expected");
+
+        if bless {
+            fs::write(&expected_path, &actual).unwrap();
+            continue;
+        }
+
+        let expected = fs::read_to_string(&expected_path).unwrap_or_default();
+
+        if actual != expected {
+            failures.push(format!(
+                "{}\n--- expected\n{expected}--- actual\n{actual}",
+                script.display()
+            ));
+        }
+    }
+
+    assert!(!scripts.is_empty(), "no fixtures found");
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+#[test]
+fn fixtures() {
+    compare("tests/fixtures", |source| {
+        thread::spawn(|| run(source)).join().unwrap()
+    });
+}
+
+#[test]
+fn formatting() {
+    compare("tests/format", |source| {
+        let formatted = formatter::format(&source).unwrap();
+
+        assert_eq!(
+            formatter::format(&formatted).unwrap(),
+            formatted,
+            "formatting isn't stable"
+        );
+
+        formatted
+    });
+}